@@ -25,6 +25,7 @@ use std::io::{BufReader, BufRead};
 use std::time::{Instant, Duration};
 use std::thread::sleep;
 use std::sync::Arc;
+use std::path::PathBuf;
 use rustc_hex::FromHex;
 use bytes::ToPretty;
 use rlp::PayloadInfo;
@@ -32,6 +33,7 @@ use acore::service::ClientService;
 use acore::client::{DatabaseCompactionProfile, VMType, BlockImportError, BlockChainClient, BlockId};
 use acore::ImportError;
 use acore::miner::Miner;
+use acore::snapshot::{LooseReader, LooseWriter, SnapshotReader};
 use acore::verification::queue::VerifierSettings;
 use cache::CacheConfig;
 use params::{SpecType, Pruning, Switch, fatdb_switch_to_bool};
@@ -80,6 +82,8 @@ pub enum BlockchainCmd {
     Import(ImportBlockchain),
     Export(ExportBlockchain),
     Revert(RevertBlockchain),
+    Snapshot(SnapshotBlockchain),
+    Restore(RestoreBlockchain),
 }
 
 #[derive(Debug, PartialEq)]
@@ -124,6 +128,35 @@ pub struct ExportBlockchain {
     pub to_block: BlockId,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct SnapshotBlockchain {
+    pub spec: SpecType,
+    pub cache_config: CacheConfig,
+    pub dirs: Directories,
+    pub file_path: Option<String>,
+    pub pruning: Pruning,
+    pub pruning_history: u64,
+    pub pruning_memory: usize,
+    pub compaction: DatabaseCompactionProfile,
+    pub wal: bool,
+    pub fat_db: Switch,
+    pub block_at: BlockId,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RestoreBlockchain {
+    pub spec: SpecType,
+    pub cache_config: CacheConfig,
+    pub dirs: Directories,
+    pub file_path: Option<String>,
+    pub pruning: Pruning,
+    pub pruning_history: u64,
+    pub pruning_memory: usize,
+    pub compaction: DatabaseCompactionProfile,
+    pub wal: bool,
+    pub fat_db: Switch,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct RevertBlockchain {
     pub spec: SpecType,
@@ -144,6 +177,8 @@ pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
         BlockchainCmd::Import(import_cmd) => execute_import(import_cmd),
         BlockchainCmd::Export(export_cmd) => execute_export(export_cmd),
         BlockchainCmd::Revert(revert_cmd) => execute_revert(revert_cmd),
+        BlockchainCmd::Snapshot(snapshot_cmd) => execute_snapshot(snapshot_cmd),
+        BlockchainCmd::Restore(restore_cmd) => execute_restore(restore_cmd),
     }
 }
 
@@ -519,6 +554,118 @@ fn execute_revert(cmd: RevertBlockchain) -> Result<(), String> {
 
     Ok(())
 }
+
+/// Snapshot directory given on the command line, or the local one served to peers.
+fn snapshot_path(
+    spec: &SpecType,
+    dirs: &Directories,
+    file_path: Option<String>,
+) -> Result<(PathBuf, bool), String>
+{
+    match file_path {
+        Some(file_path) => Ok((PathBuf::from(file_path), false)),
+        None => {
+            let spec = spec.spec()?;
+            let genesis_hash = spec.genesis_header().hash();
+            let db_dirs = dirs.database(genesis_hash, None, spec.data_dir.clone());
+            Ok((db_dirs.snapshot_path(), true))
+        }
+    }
+}
+
+fn execute_snapshot(cmd: SnapshotBlockchain) -> Result<(), String> {
+    let timer = Instant::now();
+    let (path, is_local) = snapshot_path(&cmd.spec, &cmd.dirs, cmd.file_path)?;
+    let service = start_client(
+        cmd.dirs,
+        cmd.spec,
+        cmd.pruning,
+        cmd.pruning_history,
+        cmd.pruning_memory,
+        cmd.fat_db,
+        cmd.compaction,
+        cmd.wal,
+        cmd.cache_config,
+        false,
+    )?;
+    let client = service.client();
+
+    // the local snapshot is replaced rather than mixed with stale chunks.
+    if is_local && path.exists() {
+        fs::remove_dir_all(&path)
+            .map_err(|e| format!("Error removing old snapshot {:?}: {:?}", &path, e))?;
+    }
+    let writer = LooseWriter::new(path.clone())
+        .map_err(|e| format!("Failed to open snapshot writer: {}", e))?;
+
+    let manifest = client
+        .take_snapshot(writer, cmd.block_at)
+        .map_err(|e| format!("Encountered fatal error while creating snapshot: {}", e))?;
+
+    let ms = timer.elapsed().as_milliseconds();
+    info!(
+        target: "snapshot",
+        "Snapshot of block #{} written to {:?} in {} ms",
+        manifest.block_number, path, ms
+    );
+    Ok(())
+}
+
+fn execute_restore(cmd: RestoreBlockchain) -> Result<(), String> {
+    let timer = Instant::now();
+    let (path, _) = snapshot_path(&cmd.spec, &cmd.dirs, cmd.file_path)?;
+    let reader = LooseReader::new(path.clone())
+        .map_err(|e| format!("Couldn't read snapshot from {:?}: {}", &path, e))?;
+    let service = start_client(
+        cmd.dirs,
+        cmd.spec,
+        cmd.pruning,
+        cmd.pruning_history,
+        cmd.pruning_memory,
+        cmd.fat_db,
+        cmd.compaction,
+        cmd.wal,
+        cmd.cache_config,
+        false,
+    )?;
+    let client = service.client();
+
+    let manifest = reader.manifest().clone();
+    let mut restoration = client
+        .begin_restoration(manifest.clone())
+        .map_err(|e| format!("Failed to begin restoration: {}", e))?;
+
+    let chunks: Vec<_> = manifest
+        .state_hashes
+        .iter()
+        .chain(manifest.block_hashes.iter())
+        .cloned()
+        .collect();
+    for (i, hash) in chunks.iter().enumerate() {
+        let chunk = reader
+            .chunk(*hash)
+            .map_err(|e| format!("Couldn't read snapshot chunk {}: {}", hash, e))?;
+        restoration
+            .feed(*hash, &chunk)
+            .map_err(|e| format!("Failed to restore chunk {}: {}", hash, e))?;
+        if (i + 1) % 100 == 0 {
+            info!(target: "snapshot", "Restored {}/{} chunks", i + 1, chunks.len());
+        }
+    }
+
+    client
+        .finish_restoration(restoration)
+        .map_err(|e| format!("Failed to finalize restoration: {}", e))?;
+
+    let ms = timer.elapsed().as_milliseconds();
+    info!(
+        target: "snapshot",
+        "Restored snapshot of block #{} in {} ms",
+        manifest.block_number, ms
+    );
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::DataFormat;
@@ -32,6 +32,7 @@ pub enum Group {
     Ipc,
     Wallet,
     Stratum,
+    Metrics,
     Mining,
     Database,
     Log,
@@ -51,6 +52,7 @@ impl str::FromStr for Group {
             "ipc" | "ipc options" => Ok(Group::Ipc),
             "wallet" | "wallet options" => Ok(Group::Wallet),
             "stratum" | "stratum options" => Ok(Group::Stratum),
+            "metrics" | "metrics options" => Ok(Group::Metrics),
             "mining" | "sealing/mining options" => Ok(Group::Mining),
             "db" | "database" | "database options" => Ok(Group::Database),
             "log" | "log options" => Ok(Group::Log),
@@ -72,6 +74,7 @@ impl<'a> From<Group> for &'a str {
             Group::Ipc => "ipc",
             Group::Wallet => "wallet",
             Group::Stratum => "stratum",
+            Group::Metrics => "metrics",
             Group::Mining => "mining",
             Group::Database => "db",
             Group::Log => "log",
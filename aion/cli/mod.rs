@@ -112,6 +112,28 @@ usage! {
             "Revert Database to (including) block BLOCK, which may be an index, hash.",
         }
 
+        CMD cmd_snapshot
+        {
+            "Make a snapshot of the database",
+
+            ARG arg_snapshot_at: (String) = "latest",
+            "--at=[BLOCK]",
+            "Take a snapshot at the given block, which may be an index, hash, or latest. Note that taking snapshots at non-recent blocks will only work with --pruning archive",
+
+            ARG arg_snapshot_file: (Option<String>) = None,
+            "[DIR]",
+            "Path to the snapshot directory. (default: the local snapshot directory, which is served to peers)",
+        }
+
+        CMD cmd_restore
+        {
+            "Restore the database from a snapshot",
+
+            ARG arg_restore_file: (Option<String>) = None,
+            "[DIR]",
+            "Path to the snapshot directory to restore from. (default: the local snapshot directory)",
+        }
+
         CMD cmd_db
         {
             "Manage the database representing the state of the blockchain on this system",
@@ -192,10 +214,38 @@ usage! {
             "--sync-boot-nodes-only",
             "Indicates if only sync from bootnodes.",
 
+            FLAG flag_no_discovery: (bool) = false, or |c: &Config| c.network.as_ref()?.no_discovery.clone(),
+            "--no-discovery",
+            "Disable udp node discovery, learning about nodes from connected peers instead.",
+
+            FLAG flag_snapshot_sync: (bool) = false, or |c: &Config| c.network.as_ref()?.snapshot_sync.clone(),
+            "--snapshot-sync",
+            "Bootstrap an empty database from a snapshot offered by peers before syncing blocks.",
+
+            FLAG flag_warp_sync: (bool) = false, or |c: &Config| c.network.as_ref()?.warp_sync.clone(),
+            "--warp-sync",
+            "Bootstrap an empty database by downloading the state of a recent block from peers, then sync blocks from there.",
+
+            ARG arg_sync_checkpoint: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.sync_checkpoint.clone(),
+            "--sync-checkpoint=[HASH]",
+            "Trust a snapshot or warp sync pivot at block HASH without waiting for several peers to offer it.",
+
+            FLAG flag_light: (bool) = false, or |c: &Config| c.network.as_ref()?.light.clone(),
+            "--light",
+            "Run as a light client: sync headers only and fetch state and receipts from peers on demand.",
+
             ARG arg_max_peers: (u32) = 64u32, or |c: &Config| c.network.as_ref()?.max_peers.clone(),
             "--max-peers=[NUM]",
             "Allow up to NUM peers.",
 
+            ARG arg_max_inbound_peers: (Option<u32>) = None, or |c: &Config| c.network.as_ref()?.max_inbound_peers,
+            "--max-inbound-peers=[NUM]",
+            "Accept up to NUM of the peers as inbound connections. Defaults to max peers.",
+
+            ARG arg_max_outbound_peers: (Option<u32>) = None, or |c: &Config| c.network.as_ref()?.max_outbound_peers,
+            "--max-outbound-peers=[NUM]",
+            "Dial up to NUM of the peers as outbound connections. Defaults to max peers.",
+
             ARG arg_net_id: (u32) = 256u32, or |c: &Config| c.network.as_ref()?.net_id.clone(),
             "--net-id=[INDEX]",
             "Override the network identifier from the chain we are on.",
@@ -221,11 +271,47 @@ usage! {
             "--black_ip_list=[IPs]",
             "IP list whose connecting requests are to be rejected.",
 
+            ARG arg_reserved_nodes: (Vec<String>) = Vec::new(), or |c: &Config| c.network.as_ref()?.reserved_nodes.clone(),
+            "--reserved-nodes=[NODES]...",
+            "Nodes always kept connected and not counted against the peer limits. NODES should be p2p nodes.",
+
+            FLAG flag_reserved_only: (bool) = false, or |c: &Config| c.network.as_ref()?.reserved_only.clone(),
+            "--reserved-only",
+            "Only connect to and accept reserved nodes.",
+
+            ARG arg_max_upload_rate: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.max_upload_rate,
+            "--max-upload-rate=[KBPS]",
+            "Limit the upload to all peers to KBPS kilobytes per second. Not limited by default.",
+
+            ARG arg_max_download_rate: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.max_download_rate,
+            "--max-download-rate=[KBPS]",
+            "Limit the download from all peers to KBPS kilobytes per second. Not limited by default.",
+
+            ARG arg_request_quota_window: (u64) = 10u64, or |c: &Config| c.network.as_ref()?.request_quota_window,
+            "--request-quota-window=[SECS]",
+            "Limit the headers and bodies served to each peer over windows of SECS seconds.",
+
+            ARG arg_max_headers_per_window: (u64) = 1000u64, or |c: &Config| c.network.as_ref()?.max_headers_per_window,
+            "--max-headers-per-window=[NUM]",
+            "Serve at most NUM headers to each peer within a request quota window.",
+
+            ARG arg_max_bodies_per_window: (u64) = 1000u64, or |c: &Config| c.network.as_ref()?.max_bodies_per_window,
+            "--max-bodies-per-window=[NUM]",
+            "Serve at most NUM bodies to each peer within a request quota window.",
+
         ["Rpc Options"]
             ARG arg_rpc_processing_threads: (Option<usize>) = None, or |c: &Config| c.rpc.as_ref()?.processing_threads,
             "--rpc--processing-threads=[NUM]",
             "Turn on additional processing threads for JSON-RPC servers (for all severs http, websocket and ipc). Setting this to a non-zero value allows parallel execution of cpu-heavy queries.",
 
+            ARG arg_trace_filter_max_blocks: (u64) = 1000u64, or |c: &Config| c.rpc.as_ref()?.trace_filter_max_blocks.clone(),
+            "--trace-filter-max-blocks=[NUM]",
+            "Maximal number of blocks a single trace_filter request may search.",
+
+            ARG arg_trace_filter_max_results: (usize) = 10000usize, or |c: &Config| c.rpc.as_ref()?.trace_filter_max_results.clone(),
+            "--trace-filter-max-results=[NUM]",
+            "Maximal number of traces a single trace_filter request may return. Larger results have to be paged with after and count.",
+
         ["Http Options"]
             FLAG flag_no_http: (bool) = false, or |c: &Config| c.http.as_ref()?.disable.clone(),
             "--no-http",
@@ -241,7 +327,7 @@ usage! {
 
             ARG arg_http_apis: (Vec<String>) = vec!["all".into()], or |c: &Config| c.http.as_ref()?.apis.clone(),
             "--http-apis=[APIS]...",
-            "Specify the APIs available through the HTTP interface. APIS is a comma-delimited list of API name. Possible name are all, web3, eth, stratum, net, personal, rpc. You can also disable a specific API by putting '-' in the front: all,-personal.",
+            "Specify the APIs available through the HTTP interface. APIS is a comma-delimited list of API name. Possible name are all, web3, eth, stratum, net, personal, rpc, trace, debug, admin, txpool. The unsafe debug, admin and txpool apis are not part of all and have to be listed explicitly: all,debug,admin,txpool. You can also disable a specific API by putting '-' in the front: all,-personal.",
 
             ARG arg_http_hosts: (Vec<String>) = vec!["none".into()], or |c: &Config| c.http.as_ref()?.hosts.clone(),
             "--http-hosts=[HOSTS]...",
@@ -270,7 +356,7 @@ usage! {
 
             ARG arg_ws_apis: (Vec<String>) = vec!["all".into()], or |c: &Config| c.websockets.as_ref()?.apis.clone(),
             "--ws-apis=[APIS]...",
-            "Specify the APIs available through the WebSockets interface. APIS is a comma-delimited list of API name. Possible name are web3, eth, stratum, net, personal, rpc.",
+            "Specify the APIs available through the WebSockets interface. APIS is a comma-delimited list of API name. Possible name are web3, eth, stratum, net, personal, rpc, trace, debug, admin, txpool.",
 
             ARG arg_ws_origins: (Vec<String>) = vec!["none".into()], or |c: &Config| c.websockets.as_ref()?.origins.clone(),
             "--ws-origins=[URL]...",
@@ -295,7 +381,7 @@ usage! {
 
             ARG arg_ipc_apis: (Vec<String>) = vec!["all".into()], or |c: &Config| c.ipc.as_ref()?.apis.clone(),
             "--ipc-apis=[APIS]...",
-            "Specify custom API set available via JSON-RPC over IPC. Possible name are web3, eth, stratum, net, personal, rpc.",
+            "Specify custom API set available via JSON-RPC over IPC. Possible name are web3, eth, stratum, net, personal, rpc, trace, debug, admin, txpool.",
 
         ["Wallet Options"]
             FLAG flag_enable_wallet: (bool) = false, or |c: &Config| c.wallet.as_ref()?.disable.clone().map(|a| !a),
@@ -335,6 +421,19 @@ usage! {
             "--stratum-secret=[STRING]",
             "Secret for authorizing Stratum server for peers.",
 
+        ["Metrics Options"]
+            FLAG flag_metrics: (bool) = false, or |c: &Config| c.metrics.as_ref()?.enable.clone(),
+            "--metrics",
+            "Run the metrics server exporting node statistics in Prometheus text format at /metrics.",
+
+            ARG arg_metrics_interface: (String) = "local", or |c: &Config| c.metrics.as_ref()?.interface.clone(),
+            "--metrics-interface=[IP]",
+            "Specify the hostname portion of the metrics server, IP should be an interface's IP address, or all (all interfaces) or local.",
+
+            ARG arg_metrics_port: (u16) = 8548u16, or |c: &Config| c.metrics.as_ref()?.port.clone(),
+            "--metrics-port=[PORT]",
+            "Specify the port portion of the metrics server.",
+
         ["Sealing/Mining Options"]
             FLAG flag_force_sealing: (bool) = false, or |c: &Config| c.mining.as_ref()?.force_sealing.clone(),
             "--force-sealing",
@@ -378,7 +477,27 @@ usage! {
 
             ARG arg_tx_queue_strategy: (String) = "gas_price", or |c: &Config| c.mining.as_ref()?.tx_queue_strategy.clone(),
             "--tx-queue-strategy=[S]",
-            "Prioritization strategy used to order transactions in the queue. S may be: gas - Prioritize txs with low gas limit; gas_price - Prioritize txs with high gas price; gas_factor - Prioritize txs using gas price and gas limit ratio.",
+            "Policy used to order and admit transactions in the queue. S may be: gas - Prioritize txs with low gas limit; gas_price - Prioritize txs with high gas price; gas_factor - Prioritize txs using gas price and gas limit ratio; priority_senders - Prioritize txs of --tx-queue-priority-senders, then txs with high gas price; min_effective_price - Reject txs whose gas price minus the gas limit factor is below --min-gas-price and prioritize the rest by that price.",
+
+            ARG arg_tx_queue_priority_senders: (Vec<String>) = Vec::new(), or |c: &Config| c.mining.as_ref()?.tx_queue_priority_senders.clone(),
+            "--tx-queue-priority-senders=[ACCOUNTS]...",
+            "Senders whose transactions are processed first when using the priority_senders strategy. ACCOUNTS is a comma-delimited list of addresses.",
+
+            ARG arg_tx_queue_per_sender: (usize) = 0usize, or |c: &Config| c.mining.as_ref()?.tx_queue_per_sender.clone(),
+            "--tx-queue-per-sender=[N]",
+            "Maximum number of pending transactions a single sender can keep in the queue. Lowest priority transactions are dropped first. Local transactions are not limited. Setting this parameter to 0 disables limiting.",
+
+            ARG arg_tx_queue_future_per_sender: (usize) = 0usize, or |c: &Config| c.mining.as_ref()?.tx_queue_future_per_sender.clone(),
+            "--tx-queue-future-per-sender=[N]",
+            "Maximum number of future transactions (waiting for a nonce gap to be filled) a single sender can keep in the queue. Setting this parameter to 0 disables limiting.",
+
+            ARG arg_tx_queue_max_nonce_distance: (u64) = 0u64, or |c: &Config| c.mining.as_ref()?.tx_queue_max_nonce_distance.clone(),
+            "--tx-queue-max-nonce-distance=[N]",
+            "Reject transactions whose nonce is more than N ahead of the sender's current nonce. Setting this parameter to 0 disables limiting.",
+
+            ARG arg_tx_queue_per_contract: (usize) = 0usize, or |c: &Config| c.mining.as_ref()?.tx_queue_per_contract.clone(),
+            "--tx-queue-per-contract=[N]",
+            "Maximum number of pending and of future transactions calling a single address. Lowest priority transactions are dropped first. Setting this parameter to 0 disables limiting.",
 
             ARG arg_tx_queue_ban_count: (u16) = 1u16, or |c: &Config| c.mining.as_ref()?.tx_queue_ban_count.clone(),
             "--tx-queue-ban-count=[C]",
@@ -442,6 +561,10 @@ usage! {
             "--scale-verifiers",
             "Automatically scale amount of verifier threads based on workload. Not guaranteed to be faster.",
 
+            FLAG flag_tracing: (bool) = false, or |c: &Config| c.db.as_ref()?.tracing.clone(),
+            "--tracing",
+            "Record call traces of imported blocks, as served by the trace RPC api. Only blocks imported while enabled are traced.",
+
             ARG arg_pruning: (String) = "archive", or |c: &Config| c.db.as_ref()?.pruning.clone(),
             "--pruning=[METHOD]",
             "Configure pruning of the state/storage trie. METHOD may be one of auto, archive, fast: archive - keep all state trie data. No pruning. fast - maintain journal overlay. Fast but 50MB used. auto - use the method most recently synced or default to fast if none synced.",
@@ -521,6 +644,7 @@ struct Config {
     mining: Option<Mining>,
     db: Option<Database>,
     stratum: Option<Stratum>,
+    metrics: Option<Metrics>,
     log: Option<Log>,
 }
 
@@ -548,17 +672,33 @@ struct Account {
 #[serde(deny_unknown_fields)]
 struct Network {
     sync_from_boot_nodes_only: Option<bool>,
+    no_discovery: Option<bool>,
+    snapshot_sync: Option<bool>,
+    warp_sync: Option<bool>,
+    sync_checkpoint: Option<String>,
+    light: Option<bool>,
     max_peers: Option<u32>,
+    max_inbound_peers: Option<u32>,
+    max_outbound_peers: Option<u32>,
     net_id: Option<u32>,
     local_node: Option<String>,
     boot_nodes: Option<Vec<String>>,
     ip_black_list: Option<Vec<String>>,
+    reserved_nodes: Option<Vec<String>>,
+    reserved_only: Option<bool>,
+    max_upload_rate: Option<u64>,
+    max_download_rate: Option<u64>,
+    request_quota_window: Option<u64>,
+    max_headers_per_window: Option<u64>,
+    max_bodies_per_window: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct Rpc {
     processing_threads: Option<usize>,
+    trace_filter_max_blocks: Option<u64>,
+    trace_filter_max_results: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -621,6 +761,11 @@ struct Mining {
     extra_data: Option<String>,
     tx_queue_mem_limit: Option<u32>,
     tx_queue_strategy: Option<String>,
+    tx_queue_priority_senders: Option<Vec<String>>,
+    tx_queue_per_sender: Option<usize>,
+    tx_queue_future_per_sender: Option<usize>,
+    tx_queue_max_nonce_distance: Option<u64>,
+    tx_queue_per_contract: Option<usize>,
     tx_queue_ban_count: Option<u16>,
     tx_queue_ban_time: Option<u64>,
     remove_solved: Option<bool>,
@@ -641,6 +786,14 @@ struct Stratum {
     secret: Option<String>,
 }
 
+#[derive(Default, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Metrics {
+    enable: Option<bool>,
+    interface: Option<String>,
+    port: Option<u16>,
+}
+
 #[derive(Default, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct Database {
@@ -657,6 +810,7 @@ struct Database {
     fat_db: Option<String>,
     scale_verifiers: Option<bool>,
     num_verifiers: Option<usize>,
+    tracing: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -813,6 +967,8 @@ mod tests {
             cmd_db: false,
             cmd_db_kill: false,
             cmd_revert: false,
+            cmd_snapshot: false,
+            cmd_restore: false,
 
             // Arguments
             arg_import_file: None,
@@ -825,6 +981,9 @@ mod tests {
             arg_account_private_key: None,
             arg_account_address: None,
             arg_revert_blocks_to: "0".into(),
+            arg_snapshot_at: "latest".into(),
+            arg_snapshot_file: None,
+            arg_restore_file: None,
 
             // -- Operating Options
             arg_chain: "xyz".into(),
@@ -842,6 +1001,8 @@ mod tests {
 
             // -- Networking Options
             arg_max_peers: 50u32,
+            arg_max_inbound_peers: Some(20u32),
+            arg_max_outbound_peers: None,
             arg_boot_nodes: vec![
                 "p2p://22345678-9abc-def0-1234-56789abcdef0@3.4.4.4:4444".into(),
                 "p2p://32345678-9abc-def0-1234-56789abcdef0@4.5.5.5:5555".into(),
@@ -849,11 +1010,29 @@ mod tests {
             arg_local_node: "p2p://12345678-9abc-def0-1234-56789abcdef0@2.3.3.3:3333".into(),
             arg_net_id: 128u32,
             flag_sync_from_boot_nodes_only: true,
+            flag_no_discovery: false,
+            flag_snapshot_sync: true,
+            flag_warp_sync: false,
+            arg_sync_checkpoint: Some(
+                "0x0000000000000000000000000000000000000000000000000000000000000001".into(),
+            ),
+            flag_light: false,
             arg_ip_black_list: vec!["ip1".into(), "ip2".into()],
+            arg_reserved_nodes: vec![
+                "p2p://42345678-9abc-def0-1234-56789abcdef0@5.6.6.6:6666".into(),
+            ],
+            flag_reserved_only: true,
+            arg_max_upload_rate: Some(512u64),
+            arg_max_download_rate: None,
+            arg_request_quota_window: 20u64,
+            arg_max_headers_per_window: 2000u64,
+            arg_max_bodies_per_window: 500u64,
 
             // -- API and Console Options
             // RPC
             arg_rpc_processing_threads: Some(3usize),
+            arg_trace_filter_max_blocks: 2000u64,
+            arg_trace_filter_max_results: 20000usize,
 
             // Http
             flag_no_http: true,
@@ -901,6 +1080,11 @@ mod tests {
             arg_extra_data: Some("Aion".into()),
             arg_tx_queue_mem_limit: 2u32,
             arg_tx_queue_strategy: "gas_factor".into(),
+            arg_tx_queue_priority_senders: Vec::new(),
+            arg_tx_queue_per_sender: 0usize,
+            arg_tx_queue_future_per_sender: 0usize,
+            arg_tx_queue_max_nonce_distance: 0u64,
+            arg_tx_queue_per_contract: 0usize,
             arg_tx_queue_ban_count: 1u16,
             arg_tx_queue_ban_time: 180u64,
             flag_remove_solved: true,
@@ -917,6 +1101,11 @@ mod tests {
             arg_stratum_port: 8089u16,
             arg_stratum_secret: Some("secret".into()),
 
+            // -- Metrics Options
+            flag_metrics: true,
+            arg_metrics_interface: "all".to_owned(),
+            arg_metrics_port: 9548u16,
+
             // -- Database Options
             arg_pruning: "auto".into(),
             arg_pruning_history: 64u64,
@@ -931,6 +1120,7 @@ mod tests {
             arg_fat_db: "auto".into(),
             flag_scale_verifiers: true,
             arg_num_verifiers: Some(6),
+            flag_tracing: true,
 
             // -- Miscellaneous Options
             flag_no_config: false,
@@ -1000,11 +1190,25 @@ mod tests {
                 }),
                 network: Some(Network {
                     max_peers: Some(20),
+                    max_inbound_peers: None,
+                    max_outbound_peers: None,
                     net_id: None,
                     local_node: None,
                     boot_nodes: None,
                     sync_from_boot_nodes_only: None,
+                    no_discovery: None,
+                    snapshot_sync: None,
+                    warp_sync: None,
+                    sync_checkpoint: None,
+                    light: None,
                     ip_black_list: None,
+                    reserved_nodes: None,
+                    reserved_only: None,
+                    max_upload_rate: None,
+                    max_download_rate: None,
+                    request_quota_window: None,
+                    max_headers_per_window: None,
+                    max_bodies_per_window: None,
                 }),
                 websockets: Some(Ws {
                     disable: Some(true),
@@ -1050,6 +1254,11 @@ mod tests {
                     gas_cap: None,
                     tx_queue_mem_limit: None,
                     tx_queue_strategy: None,
+                    tx_queue_priority_senders: None,
+                    tx_queue_per_sender: None,
+                    tx_queue_future_per_sender: None,
+                    tx_queue_max_nonce_distance: None,
+                    tx_queue_per_contract: None,
                     tx_queue_ban_count: None,
                     tx_queue_ban_time: None,
                     tx_gas_limit: None,
@@ -1077,8 +1286,10 @@ mod tests {
                     fat_db: Some("off".into()),
                     scale_verifiers: Some(false),
                     num_verifiers: None,
+                    tracing: None,
                 }),
                 stratum: None,
+                metrics: None,
                 log: None,
             }
         );
@@ -537,6 +537,7 @@ macro_rules! usage {
                             let mut add_flag = &stringify!($flag)[5..];
                             add_flag = match (title,add_flag){
                                 ("http","no_http") | ("websockets","no_ws") | ("ipc","no_ipc") | ("stratum","no_stratum") | ("wallet", "enable_wallet") => "disable",
+                                ("metrics","metrics") => "enable",
                                 (_,_) => add_flag,
                             };
                             let add_default=match (title,add_flag) {
@@ -549,6 +550,7 @@ macro_rules! usage {
                             let mut add_arg = &stringify!($arg)[4..];
                             add_arg = match title{
                                 "stratum" => &add_arg[8..],
+                                "metrics" => &add_arg[8..],
                                 "websockets" => &add_arg[3..],
                                 "rpc" | "ipc" => &add_arg[4..],
                                 "http" => &add_arg[5..],
@@ -21,19 +21,22 @@
 
 use std::time::Duration;
 use cli::{Args, ArgsError};
-use aion_types::{U256, Address};
+use aion_types::{U256, Address, H256};
 use bytes::Bytes;
 use p2p::Config;
 use acore::client::{VMType};
 use acore::miner::{MinerOptions, Banning};
+use acore::transaction::transaction_queue::QueueQuotas;
 use acore::verification::queue::VerifierSettings;
 
 use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration};
+use metrics::MetricsConfiguration;
 use aion_rpc::dispatch::DynamicGasPrice;
+use aion_rpc::impls::TraceFilterLimits;
 use cache::CacheConfig;
 use helpers::{
-    to_block_id, to_u256, to_pending_set, aion_ipc_path, parse_log_target, to_addresses,
-    to_address, to_queue_strategy, validate_log_level,
+    to_block_id, to_u256, to_h256, to_pending_set, aion_ipc_path, parse_log_target,
+    to_addresses, to_address, to_queue_strategy, validate_log_level,
 };
 use dir::helpers::{replace_home, replace_home_and_local, absolute};
 use params::{AccountsConfig, StakeConfig, MinerExtras, SpecType};
@@ -42,6 +45,7 @@ use dir::{self, Directories, default_local_path, default_data_path};
 use run::RunCmd;
 use blockchain::{
     BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, RevertBlockchain, DataFormat,
+    SnapshotBlockchain, RestoreBlockchain,
 };
 use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportAccount, ExportAccount};
 
@@ -85,6 +89,7 @@ impl Configuration {
         let ws_conf = self.ws_config()?;
         let http_conf = self.http_config()?;
         let ipc_conf = self.ipc_config()?;
+        let metrics_conf = self.metrics_config();
         let net_conf = self.net_config()?;
         let cache_config = self.cache_config();
         let fat_db = self.args.arg_fat_db.parse()?;
@@ -199,6 +204,35 @@ impl Configuration {
                 to_block: to_block_id(&self.args.arg_revert_blocks_to)?,
             };
             Cmd::Blockchain(BlockchainCmd::Revert(revert_cmd))
+        } else if self.args.cmd_snapshot {
+            let snapshot_cmd = SnapshotBlockchain {
+                spec,
+                cache_config,
+                dirs,
+                file_path: self.args.arg_snapshot_file.clone(),
+                pruning,
+                pruning_history,
+                pruning_memory,
+                compaction,
+                wal,
+                fat_db,
+                block_at: to_block_id(&self.args.arg_snapshot_at)?,
+            };
+            Cmd::Blockchain(BlockchainCmd::Snapshot(snapshot_cmd))
+        } else if self.args.cmd_restore {
+            let restore_cmd = RestoreBlockchain {
+                spec,
+                cache_config,
+                dirs,
+                file_path: self.args.arg_restore_file.clone(),
+                pruning,
+                pruning_history,
+                pruning_memory,
+                compaction,
+                wal,
+                fat_db,
+            };
+            Cmd::Blockchain(BlockchainCmd::Restore(restore_cmd))
         } else {
             let verifier_settings = self.verifier_settings();
 
@@ -214,6 +248,7 @@ impl Configuration {
                 ws_conf,
                 http_conf,
                 ipc_conf,
+                metrics_conf,
                 net_conf,
                 acc_conf: self.accounts_config()?,
                 stake_conf: self.stake_config()?,
@@ -221,6 +256,15 @@ impl Configuration {
                 fat_db,
                 compaction,
                 wal,
+                tracing: self.args.flag_tracing,
+                trace_filter_limits: TraceFilterLimits {
+                    max_blocks: self.args.arg_trace_filter_max_blocks,
+                    max_results: self.args.arg_trace_filter_max_results,
+                },
+                snapshot_sync: self.args.flag_snapshot_sync,
+                warp_sync: self.args.flag_warp_sync,
+                sync_checkpoint: self.sync_checkpoint()?,
+                light: self.args.flag_light,
                 vm_type,
                 verifier_settings,
             };
@@ -313,6 +357,13 @@ impl Configuration {
         Ok(cfg)
     }
 
+    fn sync_checkpoint(&self) -> Result<Option<H256>, String> {
+        match self.args.arg_sync_checkpoint {
+            Some(ref hash) => to_h256(hash).map(Some),
+            None => Ok(None),
+        }
+    }
+
     fn accounts_config(&self) -> Result<AccountsConfig, String> {
         let cfg = AccountsConfig {
             iterations: self.args.arg_keys_iterations,
@@ -342,7 +393,12 @@ impl Configuration {
             } else {
                 None
             },
-            tx_queue_strategy: to_queue_strategy(&self.args.arg_tx_queue_strategy)?,
+            tx_queue_strategy: to_queue_strategy(
+                &self.args.arg_tx_queue_strategy,
+                &self.args.arg_tx_queue_priority_senders,
+                U256::from(self.args.arg_min_gas_price),
+            )?,
+            tx_queue_quotas: self.tx_queue_quotas(),
             pending_set: to_pending_set(&self.args.arg_relay_set)?,
             reseal_min_period: Duration::from_millis(self.args.arg_reseal_min_period),
             prepare_block_interval: Duration::from_millis(self.args.arg_reseal_min_period),
@@ -368,6 +424,19 @@ impl Configuration {
         Ok(options)
     }
 
+    fn tx_queue_quotas(&self) -> QueueQuotas {
+        let limit = |n: usize| if n > 0 { n } else { usize::max_value() };
+        QueueQuotas {
+            pending_per_sender: limit(self.args.arg_tx_queue_per_sender),
+            future_per_sender: limit(self.args.arg_tx_queue_future_per_sender),
+            max_nonce_distance: match self.args.arg_tx_queue_max_nonce_distance {
+                0 => U256::max_value(),
+                distance => distance.into(),
+            },
+            per_recipient: limit(self.args.arg_tx_queue_per_contract),
+        }
+    }
+
     fn dynamic_gas_price(&self) -> Result<Option<DynamicGasPrice>, String> {
         if !self.args.flag_dynamic_gas_price {
             return Ok(None);
@@ -392,11 +461,24 @@ impl Configuration {
     fn net_config(&self) -> Result<Config, String> {
         let mut ret = Config::new();
         ret.max_peers = self.max_peers();
+        ret.max_inbound = self.args.arg_max_inbound_peers.unwrap_or(ret.max_peers);
+        ret.max_outbound = self.args.arg_max_outbound_peers.unwrap_or(ret.max_peers);
         ret.local_node = self.args.arg_local_node.clone();
         ret.boot_nodes = self.args.arg_boot_nodes.clone();
         ret.sync_from_boot_nodes_only = self.args.flag_sync_from_boot_nodes_only;
+        ret.discovery = !self.args.flag_no_discovery;
         ret.net_id = self.args.arg_net_id.clone();
         ret.ip_black_list = self.args.arg_ip_black_list.clone();
+        ret.reserved_nodes = self.args.arg_reserved_nodes.clone();
+        ret.reserved_only = self.args.flag_reserved_only;
+        ret.max_upload = self.args.arg_max_upload_rate.map(|rate| rate * 1024);
+        ret.max_download = self.args.arg_max_download_rate.map(|rate| rate * 1024);
+        ret.request_quota_window = self.args.arg_request_quota_window;
+        ret.max_headers_per_window = self.args.arg_max_headers_per_window;
+        ret.max_bodies_per_window = self.args.arg_max_bodies_per_window;
+        ret.node_key_path = Some(self.directories().node_key_path());
+        ret.bans_path = Some(self.directories().bans_path());
+        ret.peers_path = Some(self.directories().peers_path());
         Ok(ret)
     }
 
@@ -495,6 +577,14 @@ impl Configuration {
         Ok(conf)
     }
 
+    fn metrics_config(&self) -> MetricsConfiguration {
+        MetricsConfiguration {
+            enabled: self.args.flag_metrics,
+            interface: self.interface(&self.args.arg_metrics_interface),
+            port: self.args.arg_metrics_port,
+        }
+    }
+
     fn directories(&self) -> Directories {
         let local_path = default_local_path();
         let base_path = self
@@ -609,6 +699,7 @@ impl Configuration {
 #[cfg(test)]
 mod tests {
     use acore::client::{BlockId};
+    use acore::transaction::queue_policy::QueuePolicy;
     use acore::transaction::transaction_queue::PrioritizationStrategy;
     use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
     use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat};
@@ -639,10 +730,23 @@ mod tests {
                 "p2p://c36d4208-fe4b-41fa-989b-c7eeafdffe72@35.208.215.219:30303".into(),
             ],
             max_peers: 64,
+            max_inbound: 64,
+            max_outbound: 64,
             local_node: "p2p://00000000-0000-0000-0000-000000000000@0.0.0.0:30303".to_string(),
             net_id: 256,
             sync_from_boot_nodes_only: false,
             ip_black_list: Vec::new(),
+            reserved_nodes: Vec::new(),
+            reserved_only: false,
+            node_key_path: Some(Directories::default().node_key_path()),
+            bans_path: Some(Directories::default().bans_path()),
+            peers_path: Some(Directories::default().peers_path()),
+            discovery: true,
+            max_upload: None,
+            max_download: None,
+            request_quota_window: 10,
+            max_headers_per_window: 1000,
+            max_bodies_per_window: 1000,
         }
     }
 
@@ -784,12 +888,19 @@ mod tests {
             ws_conf: Default::default(),
             http_conf: Default::default(),
             ipc_conf: Default::default(),
+            metrics_conf: Default::default(),
             net_conf: default_network_config(),
             acc_conf: Default::default(),
             stake_conf: Default::default(),
             miner_extras: Default::default(),
             compaction: Default::default(),
             wal: true,
+            tracing: false,
+            trace_filter_limits: Default::default(),
+            snapshot_sync: false,
+            warp_sync: false,
+            sync_checkpoint: None,
+            light: false,
             vm_type: Default::default(),
             fat_db: Default::default(),
             verifier_settings: Default::default(),
@@ -807,15 +918,129 @@ mod tests {
         let conf1 = parse(&["aion", "--tx-queue-strategy", "gas_factor"]);
         let conf2 = parse(&["aion", "--tx-queue-strategy", "gas_price"]);
         let conf3 = parse(&["aion", "--tx-queue-strategy", "gas"]);
+        let conf4 = parse(&[
+            "aion",
+            "--tx-queue-strategy",
+            "priority_senders",
+            "--tx-queue-priority-senders",
+            "0xa00000000000000000000000000000000000000000000000000000000000000b",
+        ]);
+        let conf5 = parse(&["aion", "--tx-queue-strategy", "min_effective_price"]);
+        let conf6 = parse(&["aion", "--tx-queue-strategy", "unknown"]);
 
         // then
         assert_eq!(conf0.miner_options().unwrap(), mining_options);
-        mining_options.tx_queue_strategy = PrioritizationStrategy::GasFactorAndGasPrice;
+        mining_options.tx_queue_strategy = PrioritizationStrategy::GasFactorAndGasPrice.into();
         assert_eq!(conf1.miner_options().unwrap(), mining_options);
-        mining_options.tx_queue_strategy = PrioritizationStrategy::GasPriceOnly;
+        mining_options.tx_queue_strategy = PrioritizationStrategy::GasPriceOnly.into();
         assert_eq!(conf2.miner_options().unwrap(), mining_options);
-        mining_options.tx_queue_strategy = PrioritizationStrategy::GasAndGasPrice;
+        mining_options.tx_queue_strategy = PrioritizationStrategy::GasAndGasPrice.into();
         assert_eq!(conf3.miner_options().unwrap(), mining_options);
+        mining_options.tx_queue_strategy = QueuePolicy::PrioritySenders(vec![
+            "a00000000000000000000000000000000000000000000000000000000000000b"
+                .parse()
+                .unwrap(),
+        ]);
+        assert_eq!(conf4.miner_options().unwrap(), mining_options);
+        mining_options.tx_queue_strategy = QueuePolicy::MinEffectivePrice(10_000_000_000u64.into());
+        assert_eq!(conf5.miner_options().unwrap(), mining_options);
+        assert!(conf6.miner_options().is_err());
+    }
+
+    #[test]
+    fn should_parse_tx_queue_quotas() {
+        // given
+        let mut mining_options = MinerOptions::default();
+
+        // when
+        let conf = parse(&[
+            "aion",
+            "--tx-queue-per-sender",
+            "16",
+            "--tx-queue-future-per-sender",
+            "8",
+            "--tx-queue-max-nonce-distance",
+            "64",
+            "--tx-queue-per-contract",
+            "128",
+        ]);
+
+        // then
+        mining_options.tx_queue_quotas = QueueQuotas {
+            pending_per_sender: 16,
+            future_per_sender: 8,
+            max_nonce_distance: 64.into(),
+            per_recipient: 128,
+        };
+        assert_eq!(conf.miner_options().unwrap(), mining_options);
+        assert_eq!(
+            parse(&["aion"]).miner_options().unwrap().tx_queue_quotas,
+            QueueQuotas::default()
+        );
+    }
+
+    #[test]
+    fn should_parse_peer_limits() {
+        let reserved = "p2p://42345678-9abc-def0-1234-56789abcdef0@5.6.6.6:6666";
+
+        let conf0 = parse(&["aion", "--max-peers", "32"]);
+        let conf1 = parse(&[
+            "aion",
+            "--max-peers",
+            "32",
+            "--max-inbound-peers",
+            "8",
+            "--reserved-nodes",
+            reserved,
+            "--reserved-only",
+        ]);
+
+        let net0 = conf0.net_config().unwrap();
+        assert_eq!((net0.max_inbound, net0.max_outbound), (32, 32));
+        assert!(net0.reserved_nodes.is_empty());
+        assert!(!net0.reserved_only);
+        let net1 = conf1.net_config().unwrap();
+        assert_eq!((net1.max_inbound, net1.max_outbound), (8, 32));
+        assert_eq!(net1.reserved_nodes, vec![reserved.to_string()]);
+        assert!(net1.reserved_only);
+    }
+
+    #[test]
+    fn should_parse_rate_limits() {
+        let conf0 = parse(&["aion"]);
+        let conf1 = parse(&["aion", "--max-upload-rate", "256"]);
+
+        let net0 = conf0.net_config().unwrap();
+        assert_eq!((net0.max_upload, net0.max_download), (None, None));
+        let net1 = conf1.net_config().unwrap();
+        assert_eq!((net1.max_upload, net1.max_download), (Some(256 * 1024), None));
+    }
+
+    #[test]
+    fn should_parse_request_quotas() {
+        let conf0 = parse(&["aion"]);
+        let conf1 = parse(&[
+            "aion",
+            "--request-quota-window",
+            "30",
+            "--max-headers-per-window",
+            "500",
+            "--max-bodies-per-window",
+            "200",
+        ]);
+
+        let net0 = conf0.net_config().unwrap();
+        assert_eq!(net0.request_quota_window, 10);
+        assert_eq!(
+            (net0.max_headers_per_window, net0.max_bodies_per_window),
+            (1000, 1000)
+        );
+        let net1 = conf1.net_config().unwrap();
+        assert_eq!(net1.request_quota_window, 30);
+        assert_eq!(
+            (net1.max_headers_per_window, net1.max_bodies_per_window),
+            (500, 200)
+        );
     }
 
     #[test]
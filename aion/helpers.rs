@@ -22,10 +22,11 @@
 use std::io;
 use std::io::{Write, BufReader, BufRead};
 use std::fs::File;
-use aion_types::{U256, clean_0x, Address};
+use aion_types::{U256, H256, clean_0x, Address};
 use journaldb::Algorithm;
 use acore::client::{BlockId, VMType, DatabaseCompactionProfile, ClientConfig};
 use acore::miner::PendingSet;
+use acore::transaction::queue_policy::QueuePolicy;
 use acore::transaction::transaction_queue::PrioritizationStrategy;
 use cache::CacheConfig;
 use dir::helpers::replace_home;
@@ -52,6 +53,12 @@ pub fn to_u256(s: &str) -> Result<U256, String> {
     }
 }
 
+pub fn to_h256(s: &str) -> Result<H256, String> {
+    clean_0x(s)
+        .parse()
+        .map_err(|_| format!("Invalid hash: {:?}", s))
+}
+
 pub fn to_pending_set(s: &str) -> Result<PendingSet, String> {
     match s {
         "cheap" => Ok(PendingSet::AlwaysQueue),
@@ -61,11 +68,18 @@ pub fn to_pending_set(s: &str) -> Result<PendingSet, String> {
     }
 }
 
-pub fn to_queue_strategy(s: &str) -> Result<PrioritizationStrategy, String> {
+pub fn to_queue_strategy(
+    s: &str,
+    priority_senders: &Vec<String>,
+    min_gas_price: U256,
+) -> Result<QueuePolicy, String>
+{
     match s {
-        "gas" => Ok(PrioritizationStrategy::GasAndGasPrice),
-        "gas_price" => Ok(PrioritizationStrategy::GasPriceOnly),
-        "gas_factor" => Ok(PrioritizationStrategy::GasFactorAndGasPrice),
+        "gas" => Ok(PrioritizationStrategy::GasAndGasPrice.into()),
+        "gas_price" => Ok(PrioritizationStrategy::GasPriceOnly.into()),
+        "gas_factor" => Ok(PrioritizationStrategy::GasFactorAndGasPrice.into()),
+        "priority_senders" => Ok(QueuePolicy::PrioritySenders(to_addresses(priority_senders)?)),
+        "min_effective_price" => Ok(QueuePolicy::MinEffectivePrice(min_gas_price)),
         other => Err(format!("Invalid queue strategy: {}", other)),
     }
 }
@@ -253,12 +267,12 @@ mod tests {
     use std::fs::File;
     use std::io::Write;
     use tempdir::TempDir;
-    use aion_types::U256;
+    use aion_types::{U256, H256};
     use acore::client::BlockId;
     use acore::miner::PendingSet;
     use super::{
-        to_block_id, to_u256, to_pending_set, to_address, to_addresses, password_from_file,
-        parse_log_target,
+        to_block_id, to_u256, to_h256, to_pending_set, to_address, to_addresses,
+        password_from_file, parse_log_target,
 };
 
     #[test]
@@ -314,6 +328,18 @@ mod tests {
         assert!(to_u256("u").is_err())
     }
 
+    #[test]
+    fn test_to_h256() {
+        let hash: H256 = "0000000000000000000000000000000000000000000000000000000000000011"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            to_h256("0x0000000000000000000000000000000000000000000000000000000000000011").unwrap(),
+            hash
+        );
+        assert!(to_h256("0x11").is_err());
+    }
+
     #[test]
     fn test_pending_set() {
         assert_eq!(to_pending_set("cheap").unwrap(), PendingSet::AlwaysQueue);
@@ -27,6 +27,7 @@ extern crate ctrlc;
 extern crate clap;
 extern crate dir;
 extern crate fdlimit;
+extern crate futures;
 extern crate jsonrpc_core;
 extern crate num_cpus;
 extern crate parking_lot;
@@ -69,6 +70,7 @@ mod cache;
 mod cli;
 mod configuration;
 mod helpers;
+mod metrics;
 mod params;
 mod rpc;
 mod rpc_apis;
@@ -0,0 +1,389 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Node statistics exported in the Prometheus text format.
+
+use std::fmt::{Display, Write};
+use std::sync::Arc;
+
+use acore::client::{BlockChainClient, Client};
+use acore::miner::{Miner, MinerService};
+use acore::sync::{Sync, SyncProvider};
+use aion_rpc::hyper::{Body, Method, Request, Response, Server, StatusCode};
+use aion_rpc::hyper::header::CONTENT_TYPE;
+use aion_rpc::hyper::service::service_fn_ok;
+use futures::sync::oneshot;
+use tokio::prelude::Future;
+use tokio::runtime::TaskExecutor;
+
+const CONTENT_TYPE_TEXT: &'static str = "text/plain; version=0.0.4";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsConfiguration {
+    pub enabled: bool,
+    pub interface: String,
+    pub port: u16,
+}
+
+impl Default for MetricsConfiguration {
+    fn default() -> Self {
+        MetricsConfiguration {
+            enabled: false,
+            interface: "127.0.0.1".into(),
+            port: 8548,
+        }
+    }
+}
+
+pub struct Dependencies {
+    pub client: Arc<Client>,
+    pub miner: Arc<Miner>,
+    pub sync: Arc<Sync>,
+}
+
+/// Start the metrics server, returns the hook to shut it down
+pub fn start(
+    conf: MetricsConfiguration,
+    deps: Dependencies,
+    executor: TaskExecutor,
+) -> Result<Option<oneshot::Sender<()>>, String>
+{
+    if !conf.enabled {
+        return Ok(None);
+    }
+
+    let url = format!("{}:{}", conf.interface, conf.port);
+    let addr = url
+        .parse()
+        .map_err(|_| format!("Invalid metrics listen host/port given: {}", url))?;
+    let builder = Server::try_bind(&addr)
+        .map_err(|e| format!("Metrics io error: {} (address: {})", e, url))?;
+
+    let deps = Arc::new(deps);
+    let (close, shutdown) = oneshot::channel::<()>();
+    let server = builder
+        .serve(move || {
+            let deps = deps.clone();
+            service_fn_ok(move |req: Request<Body>| respond(&deps, &req))
+        })
+        .with_graceful_shutdown(shutdown.map_err(|_| ()))
+        .map_err(|e| error!(target: "metrics", "metrics server error: {}", e));
+    executor.spawn(server);
+
+    Ok(Some(close))
+}
+
+fn respond(deps: &Dependencies, req: &Request<Body>) -> Response<Body> {
+    let mut response = Response::builder();
+    if req.method() != &Method::GET || req.uri().path() != "/metrics" {
+        return response
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .expect("response with empty body is valid");
+    }
+    response
+        .header(CONTENT_TYPE, CONTENT_TYPE_TEXT)
+        .body(Body::from(collect(deps)))
+        .expect("response with text body is valid")
+}
+
+/// Gather the node statistics into a text exposition
+fn collect(deps: &Dependencies) -> String {
+    let mut out = Exposition::default();
+
+    let chain = deps.client.chain_info();
+    out.family(
+        "aion_chain_head_block_number",
+        "gauge",
+        "Number of the best block.",
+    );
+    out.sample("aion_chain_head_block_number", &[], chain.best_block_number);
+    out.family(
+        "aion_chain_total_difficulty",
+        "gauge",
+        "Total difficulty of the best chain.",
+    );
+    out.sample("aion_chain_total_difficulty", &[], chain.total_difficulty);
+
+    let report = deps.client.report();
+    out.family(
+        "aion_blocks_imported_total",
+        "counter",
+        "Blocks imported since start.",
+    );
+    out.sample("aion_blocks_imported_total", &[], report.blocks_imported);
+    out.family(
+        "aion_transactions_applied_total",
+        "counter",
+        "Transactions applied since start.",
+    );
+    out.sample(
+        "aion_transactions_applied_total",
+        &[],
+        report.transactions_applied,
+    );
+    out.family(
+        "aion_block_import_seconds_total",
+        "counter",
+        "Time spent importing blocks since start.",
+    );
+    out.sample(
+        "aion_block_import_seconds_total",
+        &[],
+        report.import_time_ns as f64 / 1_000_000_000f64,
+    );
+
+    let queue = deps.client.queue_info();
+    out.family(
+        "aion_verification_queue_size",
+        "gauge",
+        "Blocks in the verification queue.",
+    );
+    out.sample(
+        "aion_verification_queue_size",
+        &[("queue", "unverified")],
+        queue.unverified_queue_size,
+    );
+    out.sample(
+        "aion_verification_queue_size",
+        &[("queue", "verifying")],
+        queue.verifying_queue_size,
+    );
+    out.sample(
+        "aion_verification_queue_size",
+        &[("queue", "verified")],
+        queue.verified_queue_size,
+    );
+    out.family(
+        "aion_verification_queue_bytes",
+        "gauge",
+        "Heap memory used by the verification queue.",
+    );
+    out.sample("aion_verification_queue_bytes", &[], queue.mem_used);
+
+    let status = deps.miner.status();
+    out.family(
+        "aion_txqueue_transactions",
+        "gauge",
+        "Transactions in the transaction queue.",
+    );
+    out.sample(
+        "aion_txqueue_transactions",
+        &[("queue", "pending")],
+        status.transactions_in_pending_queue,
+    );
+    out.sample(
+        "aion_txqueue_transactions",
+        &[("queue", "future")],
+        status.transactions_in_future_queue,
+    );
+    out.family(
+        "aion_pending_block_transactions",
+        "gauge",
+        "Transactions included in the block being sealed.",
+    );
+    out.sample(
+        "aion_pending_block_transactions",
+        &[],
+        status.transactions_in_pending_block,
+    );
+
+    let peers = deps.sync.peers();
+    let inbound = peers
+        .iter()
+        .filter(|peer| peer.direction == "inbound")
+        .count();
+    out.family("aion_peers", "gauge", "Active peers.");
+    out.sample("aion_peers", &[("direction", "inbound")], inbound);
+    out.sample(
+        "aion_peers",
+        &[("direction", "outbound")],
+        peers.len() - inbound,
+    );
+
+    let statics = deps.sync.storage_statics();
+    out.family(
+        "aion_sync_storage_size",
+        "gauge",
+        "Items held by the sync storage.",
+    );
+    out.sample(
+        "aion_sync_storage_size",
+        &[("storage", "downloaded_headers")],
+        statics.downloaded_headers,
+    );
+    out.sample(
+        "aion_sync_storage_size",
+        &[("storage", "downloaded_blocks")],
+        statics.downloaded_blocks,
+    );
+    out.sample(
+        "aion_sync_storage_size",
+        &[("storage", "downloaded_blocks_hashes")],
+        statics.downloaded_blocks_hashes.0,
+    );
+    out.sample(
+        "aion_sync_storage_size",
+        &[("storage", "staged_blocks")],
+        statics.staged_blocks.0,
+    );
+    out.sample(
+        "aion_sync_storage_size",
+        &[("storage", "received_transactions")],
+        statics.received_transactions,
+    );
+    out.family(
+        "aion_sync_storage_capacity",
+        "gauge",
+        "Capacity of the bounded sync storage caches.",
+    );
+    out.sample(
+        "aion_sync_storage_capacity",
+        &[("storage", "downloaded_blocks_hashes")],
+        statics.downloaded_blocks_hashes.1,
+    );
+    out.sample(
+        "aion_sync_storage_capacity",
+        &[("storage", "staged_blocks")],
+        statics.staged_blocks.1,
+    );
+
+    let dbs = deps.client.db_io_stats();
+    out.family("aion_db_reads_total", "counter", "Database reads.");
+    for &(ref db, ref stats) in &dbs {
+        out.sample("aion_db_reads_total", &[("db", &db[..])], stats.reads);
+    }
+    out.family("aion_db_read_bytes_total", "counter", "Bytes read from the database.");
+    for &(ref db, ref stats) in &dbs {
+        out.sample("aion_db_read_bytes_total", &[("db", &db[..])], stats.bytes_read);
+    }
+    out.family("aion_db_writes_total", "counter", "Database writes.");
+    for &(ref db, ref stats) in &dbs {
+        out.sample("aion_db_writes_total", &[("db", &db[..])], stats.writes);
+    }
+    out.family(
+        "aion_db_written_bytes_total",
+        "counter",
+        "Bytes written to the database.",
+    );
+    for &(ref db, ref stats) in &dbs {
+        out.sample(
+            "aion_db_written_bytes_total",
+            &[("db", &db[..])],
+            stats.bytes_written,
+        );
+    }
+    out.family(
+        "aion_db_pending_writes",
+        "gauge",
+        "Writes buffered and not yet flushed to the database.",
+    );
+    for &(ref db, ref stats) in &dbs {
+        out.sample("aion_db_pending_writes", &[("db", &db[..])], stats.pending_writes);
+    }
+
+    let cache = deps.client.blockchain_cache_info();
+    out.family("aion_cache_bytes", "gauge", "Memory used by the caches.");
+    out.sample("aion_cache_bytes", &[("cache", "blocks")], cache.blocks);
+    out.sample(
+        "aion_cache_bytes",
+        &[("cache", "block_details")],
+        cache.block_details,
+    );
+    out.sample(
+        "aion_cache_bytes",
+        &[("cache", "transaction_addresses")],
+        cache.transaction_addresses,
+    );
+    out.sample(
+        "aion_cache_bytes",
+        &[("cache", "blocks_blooms")],
+        cache.blocks_blooms,
+    );
+    out.sample(
+        "aion_cache_bytes",
+        &[("cache", "block_receipts")],
+        cache.block_receipts,
+    );
+    out.sample(
+        "aion_cache_bytes",
+        &[("cache", "state_db")],
+        report.state_db_mem,
+    );
+
+    out.into_string()
+}
+
+/// Prometheus text format writer
+#[derive(Default)]
+struct Exposition {
+    text: String,
+}
+
+impl Exposition {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {} {}", name, help);
+        let _ = writeln!(self.text, "# TYPE {} {}", name, kind);
+    }
+
+    fn sample<V: Display>(&mut self, name: &str, labels: &[(&str, &str)], value: V) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|&(key, value)| format!("{}=\"{}\"", key, escape(value)))
+                .collect();
+            let _ = write!(self.text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.text, " {}", value);
+    }
+
+    fn into_string(self) -> String { self.text }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Exposition;
+
+    #[test]
+    fn should_write_text_format() {
+        let mut out = Exposition::default();
+        out.family("aion_peers", "gauge", "Active peers.");
+        out.sample("aion_peers", &[("direction", "inbound")], 3);
+        out.sample("aion_chain_head_block_number", &[], 42u64);
+        out.sample("aion_db_reads_total", &[("db", "a\"b")], 1);
+
+        assert_eq!(
+            out.into_string(),
+            "# HELP aion_peers Active peers.\n# TYPE aion_peers gauge\n\
+             aion_peers{direction=\"inbound\"} 3\n\
+             aion_chain_head_block_number 42\n\
+             aion_db_reads_total{db=\"a\\\"b\"} 1\n"
+        );
+    }
+}
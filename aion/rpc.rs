@@ -126,7 +126,7 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
         .parse()
         .map_err(|_| format!("Invalid WebSockets listen host/port given: {}", url))?;
 
-    let handler = setup_apis(conf.apis, deps);
+    let handler = setup_apis(conf.apis, deps, true);
 
     let allowed_origins = into_domains(with_domain(conf.origins));
     let allowed_hosts = into_domains(with_domain(conf.hosts));
@@ -170,7 +170,7 @@ pub fn new_http<D: rpc_apis::Dependencies>(
     let addr = url
         .parse()
         .map_err(|_| format!("Invalid {} listen host/port given: {}", id, url))?;
-    let handler = setup_apis(conf.apis, deps);
+    let handler = setup_apis(conf.apis, deps, false);
 
     let cors_domains = into_domains(conf.cors);
     let allowed_hosts = into_domains(with_domain(conf.hosts));
@@ -209,7 +209,7 @@ pub fn new_ipc<D: rpc_apis::Dependencies>(
         return Ok(None);
     }
 
-    let handler = setup_apis(conf.apis, dependencies);
+    let handler = setup_apis(conf.apis, dependencies, true);
     let path = PathBuf::from(&conf.socket_addr);
     // Make sure socket file can be created on unix-like OS.
     // Windows pipe paths are not on the FS.
@@ -255,6 +255,7 @@ fn with_domain(items: Option<Vec<String>>) -> Option<Vec<String>> {
 fn setup_apis<D>(
     apis: ApiSet,
     deps: &Dependencies<D>,
+    subscriptions: bool,
 ) -> MetaIoHandler<Metadata, Middleware<D::Notifier>>
 where
     D: rpc_apis::Dependencies,
@@ -264,7 +265,7 @@ where
         deps.apis.activity_notifier(),
     ));
     let apis = apis.list_apis();
-    deps.apis.extend_with_set(&mut handler, &apis);
+    deps.apis.extend_with_set(&mut handler, &apis, subscriptions);
 
     handler
 }
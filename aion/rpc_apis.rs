@@ -26,10 +26,12 @@ use std::sync::Arc;
 
 use acore::account_provider::AccountProvider;
 use acore::client::Client;
+use acore::light::LightClient;
 use acore::miner::external::ExternalMiner;
 use acore::miner::Miner;
-use acore::sync::SyncProvider;
+use acore::sync::{ManageNetwork, SyncProvider};
 use aion_rpc::dispatch::{DynamicGasPrice, FullDispatcher};
+use aion_rpc::impls::TraceFilterLimits;
 use aion_rpc::informant::{ActivityNotifier, ClientNotifier};
 use aion_rpc::Metadata;
 use jsonrpc_core::{self as core, MetaIoHandler};
@@ -52,6 +54,14 @@ pub enum Api {
     Rpc,
     /// Ping (Safe)
     Ping,
+    /// Traces (Safe)
+    Trace,
+    /// Debug (Unsafe)
+    Debug,
+    /// Admin (Unsafe)
+    Admin,
+    /// TxPool (Unsafe)
+    TxPool,
 }
 
 impl FromStr for Api {
@@ -68,6 +78,10 @@ impl FromStr for Api {
             "personal" => Ok(Personal),
             "rpc" => Ok(Rpc),
             "ping" => Ok(Ping),
+            "trace" => Ok(Trace),
+            "debug" => Ok(Debug),
+            "admin" => Ok(Admin),
+            "txpool" => Ok(TxPool),
             api => Err(format!("Unknown api: {}", api)),
         }
     }
@@ -131,6 +145,10 @@ fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
             Api::Personal => ("personal", "1.0"),
             Api::Rpc => ("rpc", "1.0"),
             Api::Ping => ("ping", "1.0"),
+            Api::Trace => ("trace", "1.0"),
+            Api::Debug => ("debug", "1.0"),
+            Api::Admin => ("admin", "1.0"),
+            Api::TxPool => ("txpool", "1.0"),
         };
         modules.insert(name.into(), version.into());
     }
@@ -144,19 +162,28 @@ pub trait Dependencies {
     /// Create the activity notifier.
     fn activity_notifier(&self) -> Self::Notifier;
 
-    /// Extend the given I/O handler with endpoints for each API.
-    fn extend_with_set<S>(&self, handler: &mut MetaIoHandler<Metadata, S>, apis: &HashSet<Api>)
-    where S: core::Middleware<Metadata>;
+    /// Extend the given I/O handler with endpoints for each API. Subscriptions are only
+    /// served with `subscriptions`, by transports able to push notifications.
+    fn extend_with_set<S>(
+        &self,
+        handler: &mut MetaIoHandler<Metadata, S>,
+        apis: &HashSet<Api>,
+        subscriptions: bool,
+    ) where
+        S: core::Middleware<Metadata>;
 }
 
 /// RPC dependencies for a full node.
 pub struct FullDependencies {
     pub client: Arc<Client>,
     pub sync: Arc<SyncProvider>,
+    pub net: Arc<ManageNetwork>,
+    pub light: Option<Arc<LightClient>>,
     pub account_store: Option<Arc<AccountProvider>>,
     pub miner: Arc<Miner>,
     pub external_miner: Arc<ExternalMiner>,
     pub dynamic_gas_price: Option<DynamicGasPrice>,
+    pub trace_filter_limits: TraceFilterLimits,
     pub executor: TaskExecutor,
 }
 
@@ -166,6 +193,7 @@ impl FullDependencies {
         handler: &mut MetaIoHandler<Metadata, S>,
         apis: &HashSet<Api>,
         for_generic_pubsub: bool,
+        subscriptions: bool,
     ) where
         S: core::Middleware<Metadata>,
     {
@@ -213,6 +241,7 @@ impl FullDependencies {
                         &self.miner,
                         &self.external_miner,
                         self.dynamic_gas_price.clone(),
+                        &self.light,
                     );
                     handler.extend_with(client.to_delegate());
 
@@ -223,6 +252,17 @@ impl FullDependencies {
 
                         add_signing_methods!(EthSigning, handler, self, nonces.clone());
                     }
+
+                    // eth_subscribe can not deliver anything over http
+                    if !for_generic_pubsub && subscriptions {
+                        let pubsub_client = EthPubSubClient::new(
+                            self.client.clone(),
+                            self.sync.clone(),
+                            self.executor.clone(),
+                        );
+                        self.client.add_notify(pubsub_client.handler());
+                        handler.extend_with(pubsub_client.to_delegate());
+                    }
                 }
                 Api::Stratum => {
                     let client = StratumClient::new(
@@ -249,6 +289,21 @@ impl FullDependencies {
                 Api::Ping => {
                     handler.extend_with(PingClient::new().to_delegate());
                 }
+                Api::Trace => {
+                    handler.extend_with(
+                        TracesClient::new(&self.client, self.trace_filter_limits.clone())
+                            .to_delegate(),
+                    );
+                }
+                Api::Debug => {
+                    handler.extend_with(DebugClient::new(&self.client).to_delegate());
+                }
+                Api::Admin => {
+                    handler.extend_with(AdminClient::new(&self.sync, &self.net).to_delegate());
+                }
+                Api::TxPool => {
+                    handler.extend_with(TxPoolClient::new(&self.miner).to_delegate());
+                }
             }
         }
     }
@@ -263,14 +318,21 @@ impl Dependencies for FullDependencies {
         }
     }
 
-    fn extend_with_set<S>(&self, handler: &mut MetaIoHandler<Metadata, S>, apis: &HashSet<Api>)
-    where S: core::Middleware<Metadata> {
-        self.extend_api(handler, apis, false)
+    fn extend_with_set<S>(
+        &self,
+        handler: &mut MetaIoHandler<Metadata, S>,
+        apis: &HashSet<Api>,
+        subscriptions: bool,
+    ) where
+        S: core::Middleware<Metadata>,
+    {
+        self.extend_api(handler, apis, false, subscriptions)
     }
 }
 
 impl ApiSet {
     pub fn list_apis(&self) -> HashSet<Api> {
+        // unsafe apis have to be listed explicitly
         let all = [
             Api::Web3,
             Api::Net,
@@ -279,6 +341,7 @@ impl ApiSet {
             Api::Rpc,
             Api::Personal,
             Api::Ping,
+            Api::Trace,
         ]
             .into_iter()
             .cloned()
@@ -318,6 +381,10 @@ mod test {
         assert_eq!(Api::Stratum, "stratum".parse().unwrap());
         assert_eq!(Api::Personal, "personal".parse().unwrap());
         assert_eq!(Api::Rpc, "rpc".parse().unwrap());
+        assert_eq!(Api::Trace, "trace".parse().unwrap());
+        assert_eq!(Api::Debug, "debug".parse().unwrap());
+        assert_eq!(Api::Admin, "admin".parse().unwrap());
+        assert_eq!(Api::TxPool, "txpool".parse().unwrap());
         assert!("rp".parse::<Api>().is_err());
     }
 
@@ -359,6 +426,7 @@ mod test {
                     Api::Rpc,
                     Api::Personal,
                     Api::Ping,
+                    Api::Trace,
                 ]
                 .into_iter()
                 .collect()
@@ -378,6 +446,7 @@ mod test {
                     Api::Stratum,
                     Api::Rpc,
                     Api::Ping,
+                    Api::Trace,
                 ]
                 .into_iter()
                 .collect()
@@ -385,6 +454,16 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_all_excludes_unsafe_apis() {
+        assert!(!ApiSet::All.list_apis().contains(&Api::Admin));
+        assert!(!ApiSet::All.list_apis().contains(&Api::TxPool));
+        assert!(!ApiSet::All.list_apis().contains(&Api::Debug));
+        assert!("all,admin".parse::<ApiSet>().unwrap().list_apis().contains(&Api::Admin));
+        assert!("all,txpool".parse::<ApiSet>().unwrap().list_apis().contains(&Api::TxPool));
+        assert!("all,debug".parse::<ApiSet>().unwrap().list_apis().contains(&Api::Debug));
+    }
+
     /*
     #[test]
     fn test_safe_parsing() {
@@ -31,7 +31,10 @@ use acore::miner::{Miner, MinerOptions, MinerService};
 use acore::service::{ClientService, run_miner, run_staker, pos_sealing, run_transaction_pool};
 use acore::verification::queue::VerifierSettings;
 use acore::sync::Sync;
-use aion_rpc::{dispatch::DynamicGasPrice, informant};
+use acore::snapshot::SnapshotService;
+use acore::light::LightClient;
+use aion_types::H256;
+use aion_rpc::{dispatch::DynamicGasPrice, impls::TraceFilterLimits, informant};
 use aion_version::version;
 use ansi_term::Colour;
 use cache::CacheConfig;
@@ -41,6 +44,7 @@ use fdlimit::raise_fd_limit;
 use helpers::{passwords_from_files, to_client_config};
 use dir::helpers::absolute;
 use io::IoChannel;
+use metrics;
 use tokio;
 use tokio::prelude::*;
 use num_cpus;
@@ -69,6 +73,7 @@ pub struct RunCmd {
     pub ws_conf: rpc::WsConfiguration,
     pub http_conf: rpc::HttpConfiguration,
     pub ipc_conf: rpc::IpcConfiguration,
+    pub metrics_conf: metrics::MetricsConfiguration,
     pub net_conf: Config,
     pub acc_conf: AccountsConfig,
     pub stake_conf: StakeConfig,
@@ -76,6 +81,12 @@ pub struct RunCmd {
     pub fat_db: Switch,
     pub compaction: DatabaseCompactionProfile,
     pub wal: bool,
+    pub tracing: bool,
+    pub trace_filter_limits: TraceFilterLimits,
+    pub snapshot_sync: bool,
+    pub warp_sync: bool,
+    pub sync_checkpoint: Option<H256>,
+    pub light: bool,
     pub vm_type: VMType,
     pub verifier_settings: VerifierSettings,
 }
@@ -154,10 +165,11 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
 
     client_config.queue.verifier_settings = cmd.verifier_settings;
     client_config.stake_contract = cmd.stake_conf.contract;
+    client_config.tracing = cmd.tracing;
 
-    let (id, binding) = &cmd.net_conf.get_id_and_binding();
+    // the node id is derived from the node key when the network starts
+    let (_, binding) = &cmd.net_conf.get_id_and_binding();
 
-    info!(target: "run","          id: {}", &id);
     info!(target: "run","     binding: {}", &binding);
 
     // create client service.
@@ -190,6 +202,16 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
 
     let client = service.client();
 
+    // create light client
+    let light = if cmd.light {
+        let light = LightClient::new(service.db(), &spec)
+            .map_err(|e| format!("Light client error: {}", e))?;
+        info!(target: "run","        mode: light");
+        Some(Arc::new(light))
+    } else {
+        None
+    };
+
     // drop the spec to free up genesis state.
     drop(spec);
 
@@ -202,8 +224,29 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
           if cmd.ws_conf.enabled { "y" } else { "n" },
           if cmd.ipc_conf.enabled { "y" } else { "n" },
     );
+    if cmd.metrics_conf.enabled {
+        info!(
+            target: "run",
+            "     metrics: {}:{}",
+            cmd.metrics_conf.interface,
+            cmd.metrics_conf.port
+        );
+    }
 
-    let sync = Arc::new(Sync::new(cmd.net_conf.clone(), client.clone()));
+    let snapshot_service = Arc::new(SnapshotService::new(
+        client.clone(),
+        db_dirs.snapshot_path(),
+    ));
+    let sync = Arc::new(Sync::new(
+        cmd.net_conf.clone(),
+        client.clone(),
+        client.clone(),
+        snapshot_service,
+        cmd.snapshot_sync,
+        cmd.warp_sync,
+        cmd.sync_checkpoint,
+        light.clone(),
+    ));
     let weak_sync = Arc::downgrade(&sync);
     sync.register_callback(weak_sync);
     let sync_notify = sync.clone() as Arc<ChainNotify>;
@@ -232,10 +275,13 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
     let deps_for_rpc_apis = Arc::new(rpc_apis::FullDependencies {
         client: client.clone(),
         sync: sync.clone(),
+        net: sync.clone(),
+        light: light.clone(),
         account_store,
         miner: miner.clone(),
         external_miner: external_miner.clone(),
         dynamic_gas_price: cmd.dynamic_gas_price.clone(),
+        trace_filter_limits: cmd.trace_filter_limits.clone(),
         executor: runtime_rpc.executor(),
     });
 
@@ -271,6 +317,17 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
         executor_jsonrpc.clone(),
     )?;
 
+    // start metrics server
+    let close_metrics = metrics::start(
+        cmd.metrics_conf.clone(),
+        metrics::Dependencies {
+            client: client.clone(),
+            miner: miner.clone(),
+            sync: sync.clone(),
+        },
+        runtime_rpc.executor(),
+    )?;
+
     // save user defaults
     user_defaults.is_first_launch = false;
     user_defaults.pruning = algorithm;
@@ -294,7 +351,11 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
         .build()
         .expect("seal block runtime loop init failed");
     let executor_miner = runtime_miner.executor();
-    let close_miner = run_miner(executor_miner.clone(), client.clone());
+    // light clients have no state to seal blocks on
+    let close_miner = match light {
+        Some(_) => None,
+        None => Some(run_miner(executor_miner.clone(), client.clone())),
+    };
 
     // start internal staker module
     let runtime_staker = tokio::runtime::Builder::new()
@@ -303,7 +364,10 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
         .build()
         .expect("internal staker runtime loop init failed");
     let executor_internal_staker = runtime_staker.executor();
-    let close_staker = run_staker(executor_internal_staker.clone(), client.clone());
+    let close_staker = match light {
+        Some(_) => None,
+        None => Some(run_staker(executor_internal_staker.clone(), client.clone())),
+    };
 
     // start PoS invoker
     let pos_invoker = tokio::runtime::Builder::new()
@@ -312,7 +376,10 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
         .build()
         .expect("internal staker runtime loop init failed");
     let executor_external_staker = pos_invoker.executor();
-    let close_pos_invoker = pos_sealing(executor_external_staker.clone(), client.clone());
+    let close_pos_invoker = match light {
+        Some(_) => None,
+        None => Some(pos_sealing(executor_external_staker.clone(), client.clone())),
+    };
 
     // Create a weak reference to the client so that we can wait on shutdown until it is dropped
     let weak_client = Arc::downgrade(&client);
@@ -324,9 +391,12 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
 
     // close pool
     let _ = close_transaction_pool.send(());
-    let _ = close_miner.send(());
-    let _ = close_staker.send(());
-    let _ = close_pos_invoker.send(());
+    client.journal_local_transactions();
+    for close in vec![close_miner, close_staker, close_pos_invoker] {
+        if let Some(close) = close {
+            let _ = close.send(());
+        }
+    }
 
     // close rpc
     if ws_server.is_some() {
@@ -338,6 +408,9 @@ pub fn execute_impl(cmd: RunCmd) -> Result<(Weak<Client>), String> {
     if ipc_server.is_some() {
         ipc_server.unwrap().close();
     }
+    if let Some(close) = close_metrics {
+        let _ = close.send(());
+    }
 
     sync.shutdown();
 
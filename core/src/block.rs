@@ -41,6 +41,7 @@ use header::{Header, Seal, SealType};
 use receipt::Receipt;
 use state::State;
 use db::StateDB;
+use trace::FlatTrace;
 use transaction::{
     UnverifiedTransaction, SignedTransaction, Error as TransactionError, AVM_TRANSACTION_TYPE,
     Action,
@@ -96,18 +97,20 @@ pub struct ExecutedBlock {
     receipts: Vec<Receipt>,
     transactions_set: HashSet<H256>,
     state: State<StateDB>,
+    traces: Option<Vec<Vec<FlatTrace>>>,
     last_hashes: Arc<LastHashes>,
 }
 
 impl ExecutedBlock {
     /// Create a new block from the given `state`.
-    fn new(state: State<StateDB>, last_hashes: Arc<LastHashes>) -> ExecutedBlock {
+    fn new(state: State<StateDB>, last_hashes: Arc<LastHashes>, tracing: bool) -> ExecutedBlock {
         ExecutedBlock {
             header: Default::default(),
             transactions: Default::default(),
             receipts: Default::default(),
             transactions_set: Default::default(),
             state,
+            traces: if tracing { Some(Vec::new()) } else { None },
             last_hashes,
         }
     }
@@ -165,6 +168,9 @@ pub trait IsBlock {
 
     /// Get all information on receipts in this block.
     fn receipts(&self) -> &[Receipt] { &self.block().receipts }
+
+    /// Get all information concerning transaction tracing in this block.
+    fn traces(&self) -> &Option<Vec<Vec<FlatTrace>>> { &self.block().traces }
 }
 
 /// Trait for a object that has a state database.
@@ -223,6 +229,7 @@ impl<'x> OpenBlock<'x> {
     pub fn new(
         engine: &'x Engine,
         factories: Factories,
+        tracing: bool,
         db: StateDB,
         parent: &Header,
         seal_type: SealType,
@@ -248,7 +255,7 @@ impl<'x> OpenBlock<'x> {
         )?;
 
         let mut r = OpenBlock {
-            block: ExecutedBlock::new(state, last_hashes),
+            block: ExecutedBlock::new(state, last_hashes, tracing),
             engine,
         };
 
@@ -351,13 +358,14 @@ impl<'x> OpenBlock<'x> {
             return vec![Err(From::from(TransactionError::AlreadyImported))];
         }
         let env_info = self.env_info();
+        let tracing = self.block.traces.is_some();
         let mut idx = 0;
         let mut receipts_results = Vec::new();
         // avm should deal with exceptions correctly
         for apply_result in
             self.block
                 .state
-                .apply_batch(&env_info, self.engine.machine(), txs, check_gas)
+                .apply_batch(&env_info, self.engine.machine(), txs, check_gas, tracing)
         {
             let result = match apply_result {
                 Ok(outcome) => {
@@ -369,6 +377,9 @@ impl<'x> OpenBlock<'x> {
                         .header
                         .add_transaction_fee(&outcome.receipt.transaction_fee);
                     self.block.receipts.push(outcome.receipt.clone());
+                    if let Some(ref mut traces) = self.block.traces {
+                        traces.push(outcome.trace);
+                    }
                     idx += 1;
                     Ok(outcome.receipt)
                 }
@@ -401,6 +412,7 @@ impl<'x> OpenBlock<'x> {
 
         let mut result = Vec::new();
         let env_info = self.env_info();
+        let tracing = self.block.traces.is_some();
         debug!(target: "vm", "tx type = {:?}", t.tx_type());
 
         let aion040fork = self
@@ -416,6 +428,7 @@ impl<'x> OpenBlock<'x> {
                     self.engine.machine(),
                     &[t.clone()],
                     check_gas,
+                    tracing,
                 ));
             } else {
                 result.push(self.block.state.apply(
@@ -423,14 +436,17 @@ impl<'x> OpenBlock<'x> {
                     self.engine.machine(),
                     &t,
                     check_gas,
+                    tracing,
                 ));
             }
         } else {
-            result.push(
-                self.block
-                    .state
-                    .apply(&env_info, self.engine.machine(), &t, check_gas),
-            );
+            result.push(self.block.state.apply(
+                &env_info,
+                self.engine.machine(),
+                &t,
+                check_gas,
+                tracing,
+            ));
         }
 
         match result.pop().unwrap() {
@@ -443,6 +459,9 @@ impl<'x> OpenBlock<'x> {
                     .header
                     .add_transaction_fee(&outcome.receipt.transaction_fee);
                 self.block.receipts.push(outcome.receipt);
+                if let Some(ref mut traces) = self.block.traces {
+                    traces.push(outcome.trace);
+                }
                 Ok(self
                     .block
                     .receipts
@@ -712,6 +731,7 @@ fn enact(
     great_grand_parent: Option<&Header>,
     last_hashes: Arc<LastHashes>,
     factories: Factories,
+    tracing: bool,
     kvdb: Arc<KeyValueDB>,
     client: &BlockChainClient,
 ) -> Result<LockedBlock, Error>
@@ -733,6 +753,7 @@ fn enact(
     let mut b = OpenBlock::new(
         engine,
         factories,
+        tracing,
         db,
         parent,
         header.seal_type().to_owned().unwrap_or_default(),
@@ -873,6 +894,7 @@ pub fn enact_verified(
     great_grand_parent: Option<&Header>,
     last_hashes: Arc<LastHashes>,
     factories: Factories,
+    tracing: bool,
     kvdb: Arc<KeyValueDB>,
     client: &BlockChainClient,
 ) -> Result<LockedBlock, Error>
@@ -887,6 +909,7 @@ pub fn enact_verified(
         great_grand_parent,
         last_hashes,
         factories,
+        tracing,
         kvdb,
         client,
     )
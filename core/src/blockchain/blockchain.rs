@@ -818,7 +818,6 @@ impl BlockChain {
         })
     }
 
-    #[cfg(test)]
     /// Inserts a verified, known block from the canonical chain.
     ///
     /// Can be performed out-of-order, but care must be taken that the final chain is in a correct state.
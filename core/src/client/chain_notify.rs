@@ -49,4 +49,7 @@ pub trait ChainNotify: Send + Sync {
 
     /// fires when new transactions are received from a peer
     fn transactions_received(&self, _transactions: &[Bytes]) {}
+
+    /// fires when new transactions are added to the transaction pool
+    fn transactions_imported(&self, _hashes: &[H256]) {}
 }
@@ -31,7 +31,7 @@ use time::precise_time_ns;
 use blake2b::blake2b;
 use acore_bytes::Bytes;
 use journaldb;
-use kvdb::{DBTransaction, KeyValueDB};
+use kvdb::{AsHashStore, DBTransaction, DBValue, IoStats, KeyValueDB};
 use trie::{Trie, TrieFactory, TrieSpec};
 use ansi_term::Colour;
 
@@ -60,9 +60,14 @@ use parking_lot::{Mutex, RwLock, Condvar};
 use receipt::{LocalizedReceipt, Receipt};
 use rlp::*;
 use service::ClientIoMessage;
+use snapshot::{
+    self, Error as SnapshotError, ManifestData, NodeRequest, Pivot, Restoration, SnapshotWriter,
+    WarpRestoration,
+};
 use spec::Spec;
-use state::{State};
+use state::{self, State};
 use db::StateDB;
+use trace::{self, BlockTraces, LocalizedTrace, TraceDB, VMTraceOptions};
 use transaction::{
     Transaction,
     Action,
@@ -72,6 +77,7 @@ use transaction::{
     AVM_TRANSACTION_TYPE,
     DEFAULT_TRANSACTION_TYPE
 };
+use transaction::local_transactions::{decode_journal, encode_journal};
 use types::filter::Filter;
 use vms::{EnvInfo, LastHashes};
 use verification::queue::BlockQueue;
@@ -89,14 +95,16 @@ use num::Zero;
 use num_bigint::{BigUint};
 
 // re-export
-#[cfg(test)]
-use types::blockchain::cache::CacheSize as BlockChainCacheSize;
+pub use types::blockchain::cache::CacheSize as BlockChainCacheSize;
 pub use types::block::status::BlockStatus;
 pub use types::blockchain::info::BlockChainInfo;
 pub use verification::queue::QueueInfo as BlockQueueInfo;
 
 const MIN_HISTORY_SIZE: u64 = 8;
 
+/// Key of the local transactions journal in the node info column
+const LOCAL_TRANSACTIONS_KEY: &'static [u8] = b"local_transactions";
+
 lazy_static! {
     static ref DB_CAN_STOP: AtomicBool = AtomicBool::new(false);
 }
@@ -112,6 +120,8 @@ pub struct ClientReport {
     pub gas_processed: U256,
     /// Memory used by state DB
     pub state_db_mem: usize,
+    /// Time spent importing blocks so far, in nanoseconds.
+    pub import_time_ns: u64,
 }
 
 impl ClientReport {
@@ -134,6 +144,7 @@ impl<'a> ::std::ops::Sub<&'a ClientReport> for ClientReport {
         self.transactions_applied -= other.transactions_applied;
         self.gas_processed = self.gas_processed - other.gas_processed;
         self.state_db_mem = higher_mem - lower_mem;
+        self.import_time_ns -= other.import_time_ns;
 
         self
     }
@@ -148,6 +159,7 @@ pub struct Client {
     config: ClientConfig,
     db: RwLock<Arc<KeyValueDB>>,
     state_db: RwLock<StateDB>,
+    tracedb: TraceDB,
     block_queue: BlockQueue,
     report: RwLock<ClientReport>,
     import_lock: Mutex<()>,
@@ -157,6 +169,8 @@ pub struct Client {
     last_hashes: RwLock<VecDeque<H256>>,
     factories: Factories,
     history: u64,
+    /// Hashes of the local transactions in the journal
+    journaled_transactions: Mutex<Vec<H256>>,
 }
 
 impl Client {
@@ -195,6 +209,7 @@ impl Client {
         let gb = spec.genesis_block();
         let engine = spec.engine.clone();
         let chain = Arc::new(BlockChain::new(config.blockchain.clone(), &gb, db.clone()));
+        let tracedb = TraceDB::new(config.tracing, db.clone());
 
         trace!(
             target: "client",
@@ -237,6 +252,7 @@ impl Client {
             config,
             db: RwLock::new(db),
             state_db: RwLock::new(state_db),
+            tracedb,
             block_queue,
             report: RwLock::new(Default::default()),
             import_lock: Mutex::new(()),
@@ -246,6 +262,7 @@ impl Client {
             last_hashes: RwLock::new(VecDeque::new()),
             factories,
             history,
+            journaled_transactions: Mutex::new(Vec::new()),
         });
 
         // prune old states.
@@ -260,6 +277,46 @@ impl Client {
         Ok(client)
     }
 
+    /// Re-inject the local transactions journaled by the last run into the queue.
+    pub fn restore_local_transactions(&self) {
+        let journal = match self.db.read().get(::db::COL_NODE_INFO, LOCAL_TRANSACTIONS_KEY) {
+            Ok(Some(journal)) => journal,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(target: "own_tx", "Failed to read local transactions journal: {:?}", e);
+                return;
+            }
+        };
+        let transactions = decode_journal(&journal);
+        info!(target: "own_tx", "Restoring {} journaled local transactions", transactions.len());
+        let mut hashes: Vec<H256> = transactions.iter().map(|tx| tx.hash().clone()).collect();
+        hashes.sort();
+        *self.journaled_transactions.lock() = hashes;
+        self.miner.restore_local_transactions(self, transactions);
+    }
+
+    /// Journal the local transactions waiting in the queue so they survive a restart.
+    /// Mined or dropped transactions leave the journal.
+    pub fn journal_local_transactions(&self) {
+        let transactions = self.miner.local_pending_transactions();
+        let mut hashes: Vec<H256> = transactions.iter().map(|tx| tx.hash().clone()).collect();
+        hashes.sort();
+        let mut journaled = self.journaled_transactions.lock();
+        if *journaled == hashes {
+            return;
+        }
+        let mut batch = DBTransaction::new();
+        batch.put(
+            ::db::COL_NODE_INFO,
+            LOCAL_TRANSACTIONS_KEY,
+            &encode_journal(&transactions),
+        );
+        match self.db.read().write(batch) {
+            Ok(_) => *journaled = hashes,
+            Err(e) => warn!(target: "own_tx", "Failed to journal local transactions: {:?}", e),
+        }
+    }
+
     /// Adds an actor to be notified on certain events
     pub fn add_notify(&self, target: Arc<ChainNotify>) {
         self.notify.write().push(Arc::downgrade(&target));
@@ -410,6 +467,7 @@ impl Client {
             great_grand_parent.map(|header| header.decode()).as_ref(),
             last_hashes,
             self.factories.clone(),
+            self.tracedb.tracing_enabled(),
             self.db.read().clone(),
             self,
         );
@@ -522,6 +580,7 @@ impl Client {
             }
             let _is_empty = self.block_queue.mark_as_good(&imported_blocks);
             let duration_ns = precise_time_ns() - start;
+            self.report.write().import_time_ns += duration_ns;
             (
                 imported_blocks,
                 import_results,
@@ -596,6 +655,7 @@ impl Client {
 
         // Commit results
         let receipts = block.receipts().to_owned();
+        let traces = block.traces().clone();
 
         assert_eq!(
             header.hash(),
@@ -611,6 +671,9 @@ impl Client {
             .expect("DB commit failed");
         trace!(target: "block", "insert block number: {:?}", number);
         let route = chain.insert_block(&mut batch, block_data, receipts.clone());
+        if let Some(traces) = traces {
+            self.tracedb.import(&mut batch, hash, &BlockTraces(traces));
+        }
 
         let is_canon = route.enacted.last().map_or(false, |h| h == hash);
         state.sync_cache(&route.enacted, &route.retracted, is_canon);
@@ -755,10 +818,12 @@ impl Client {
         .expect("State root of best block header always valid.")
     }
 
-    #[cfg(test)]
     /// Get info on the cache.
     pub fn blockchain_cache_info(&self) -> BlockChainCacheSize { self.chain.read().cache_size() }
 
+    /// Get the io statistics of each database.
+    pub fn db_io_stats(&self) -> Vec<(String, IoStats)> { self.db.read().io_stats() }
+
     /// Get the report.
     pub fn report(&self) -> ClientReport {
         let mut report = self.report.read().clone();
@@ -806,6 +871,15 @@ impl Client {
         }
     }
 
+    fn localized_block_traces(&self, hash: &H256) -> Option<Vec<LocalizedTrace>> {
+        let chain = self.chain.read();
+        let number = chain.block_number(hash)?;
+        let body = chain.block_body(hash)?;
+        self.tracedb
+            .block_traces(hash)
+            .map(|traces| traces.localize(number, hash.clone(), &body.transaction_hashes()))
+    }
+
     fn do_virtual_call(
         machine: &::machine::EthereumMachine,
         env_info: &EnvInfo,
@@ -832,6 +906,8 @@ impl Client {
             env_info: &EnvInfo,
             machine: &::machine::EthereumMachine,
             state_diff: bool,
+            tracing: bool,
+            vm_tracing: Option<VMTraceOptions>,
             transaction: &SignedTransaction,
         ) -> Result<Executed, CallError>
         {
@@ -846,16 +922,25 @@ impl Client {
                 .params()
                 .monetary_policy_update
                 .map_or(false, |v| env_info.number >= v);
-            if aion040fork && for_local_avm(state, transaction) {
-                let avm_result = Executive::new(state, env_info, machine)
-                    .transact_virtual_bulk(&[transaction.clone()], false);
-                match avm_result[0].clone() {
-                    Err(x) => return Err(x.into()),
-                    Ok(_) => ret = avm_result[0].clone().unwrap(),
+            let is_avm = aion040fork && for_local_avm(state, transaction);
+            {
+                let mut executive = if let Some(options) = vm_tracing {
+                    Executive::new_with_vm_tracing(state, env_info, machine, options)
+                } else if tracing {
+                    Executive::new_with_tracing(state, env_info, machine)
+                } else {
+                    Executive::new(state, env_info, machine)
+                };
+                if is_avm {
+                    let avm_result =
+                        executive.transact_virtual_bulk(&[transaction.clone()], false);
+                    match avm_result[0].clone() {
+                        Err(x) => return Err(x.into()),
+                        Ok(_) => ret = avm_result[0].clone().unwrap(),
+                    }
+                } else {
+                    ret = executive.transact_virtual(transaction, false)?;
                 }
-            } else {
-                ret = Executive::new(state, env_info, machine)
-                    .transact_virtual(transaction, false)?;
             }
 
             debug!(target: "vm", "local call result = {:?}", ret);
@@ -867,8 +952,10 @@ impl Client {
         }
 
         let state_diff = analytics.state_diffing;
+        let tracing = analytics.transaction_tracing;
+        let vm_tracing = analytics.vm_tracing;
 
-        call(state, env_info, machine, state_diff, t)
+        call(state, env_info, machine, state_diff, tracing, vm_tracing, t)
     }
 
     fn block_number_ref(&self, id: &BlockId) -> Option<BlockNumber> {
@@ -1041,6 +1128,126 @@ impl Client {
         Ok(new_block)
     }
 
+    /// Take a snapshot of the state at the given block and of the blocks leading up to it.
+    pub fn take_snapshot<W: SnapshotWriter>(
+        &self,
+        writer: W,
+        at: BlockId,
+    ) -> Result<ManifestData, SnapshotError>
+    {
+        let block_hash = self
+            .block_hash(at)
+            .ok_or(SnapshotError::InvalidStartingBlock(H256::zero()))?;
+        let block_number = self
+            .block_number(BlockId::Hash(block_hash))
+            .ok_or(SnapshotError::BlockNotFound(block_hash))?;
+
+        let state_db = self.state_db.read().boxed_clone();
+        if state_db.is_pruned() && self.pruning_info().earliest_state > block_number {
+            return Err(SnapshotError::OldBlockPrunedDB);
+        }
+
+        let chain = self.chain.read().clone();
+        let db = self.db.read().clone();
+        snapshot::take_snapshot(&chain, block_hash, state_db.as_hashstore(), &*db, writer)
+    }
+
+    /// Start restoring a snapshot into this client's database.
+    /// Only possible while the chain holds nothing but the genesis block.
+    pub fn begin_restoration(&self, manifest: ManifestData) -> Result<Restoration, SnapshotError> {
+        if self.chain.read().best_block_number() != 0 {
+            return Err(SnapshotError::NonEmptyDatabase);
+        }
+
+        let db = self.db.read().clone();
+        let journal_db = journaldb::new(db.clone(), self.config.pruning, ::db::COL_STATE);
+        let chain = self.chain.read().clone();
+        Ok(Restoration::new(
+            manifest,
+            journal_db,
+            db,
+            chain,
+            self.engine.clone(),
+        ))
+    }
+
+    /// Finalize a restoration and reload the chain and the state from the database.
+    pub fn finish_restoration(&self, restoration: Restoration) -> Result<(), SnapshotError> {
+        let block_number = restoration.manifest().block_number;
+        restoration.finalize()?;
+        self.reload_chain();
+
+        info!(target: "snapshot", "Snapshot restored at block #{}", block_number);
+        Ok(())
+    }
+
+    /// Start a warp sync of the state at the given pivot into this client's database.
+    /// Only possible while the chain holds nothing but the genesis block.
+    pub fn begin_warp(&self, pivot: Pivot) -> Result<WarpRestoration, SnapshotError> {
+        if self.chain.read().best_block_number() != 0 {
+            return Err(SnapshotError::NonEmptyDatabase);
+        }
+
+        let db = self.db.read().clone();
+        let journal_db = journaldb::new(db.clone(), self.config.pruning, ::db::COL_STATE);
+        let chain = self.chain.read().clone();
+        WarpRestoration::new(pivot, journal_db, db, chain, self.engine.clone())
+    }
+
+    /// Finalize a warp sync and reload the chain and the state from the database.
+    pub fn finish_warp(&self, warp: WarpRestoration) -> Result<(), SnapshotError> {
+        let block_number = warp.pivot().number;
+        warp.finalize()?;
+        self.reload_chain();
+
+        info!(target: "snapshot", "Warp synced state at block #{}", block_number);
+        Ok(())
+    }
+
+    /// Pivot offered to warp syncing peers: the blocks leading up to a block slightly
+    /// behind the best one, whose state is still available.
+    pub fn warp_pivot(&self) -> Option<Bytes> {
+        let best_number = self.chain.read().best_block_number();
+        if best_number <= snapshot::PIVOT_DISTANCE {
+            return None;
+        }
+        let hash = self.block_hash(BlockId::Number(best_number - snapshot::PIVOT_DISTANCE))?;
+        let state_root = self.block_header(BlockId::Hash(hash))?.state_root();
+        if !self.state_db.read().as_hashstore().contains(&state_root) {
+            return None;
+        }
+        let chain = self.chain.read().clone();
+        snapshot::pivot_chunk(&chain, hash).ok()
+    }
+
+    /// Item of the current state requested by a warp syncing peer.
+    pub fn state_node(&self, request: &NodeRequest) -> Option<Bytes> {
+        let state_db = self.state_db.read();
+        let db = self.db.read();
+        snapshot::state_node(state_db.as_hashstore(), &**db, request)
+    }
+
+    // reopen the chain and the state after they were written from outside the client.
+    fn reload_chain(&self) {
+        let db = self.db.read().clone();
+        let genesis = {
+            let chain = self.chain.read();
+            chain
+                .block(&chain.genesis_hash())
+                .expect("genesis is always stored; qed")
+                .into_inner()
+        };
+        let chain = Arc::new(BlockChain::new(
+            self.config.blockchain.clone(),
+            &genesis,
+            db.clone(),
+        ));
+        let journal_db = journaldb::new(db, self.config.pruning, ::db::COL_STATE);
+        *self.state_db.write() = StateDB::new(journal_db, self.config.state_cache_size);
+        *self.chain.write() = chain;
+        self.last_hashes.write().clear();
+    }
+
     fn build_fake_transaction(&self, call_data: Vec<u8>, call_type: Action) -> SignedTransaction {
         Transaction::new(
             0.into(),
@@ -1663,6 +1870,79 @@ impl BlockChainClient for Client {
             .logs(blocks, |entry| filter.matches(entry), filter.limit)
     }
 
+    fn block_traces(&self, id: BlockId) -> Option<Vec<LocalizedTrace>> {
+        if !self.tracedb.tracing_enabled() {
+            return None;
+        }
+
+        let hash = Self::block_hash(&self.chain.read(), &self.miner, id)?;
+        self.localized_block_traces(&hash)
+    }
+
+    fn transaction_traces(&self, id: TransactionId) -> Option<Vec<LocalizedTrace>> {
+        if !self.tracedb.tracing_enabled() {
+            return None;
+        }
+
+        let address = self.transaction_address(id)?;
+        self.localized_block_traces(&address.block_hash)
+            .map(|traces| {
+                traces
+                    .into_iter()
+                    .filter(|trace| trace.transaction_number == address.index)
+                    .collect()
+            })
+    }
+
+    fn filter_traces(&self, filter: trace::Filter) -> Option<Vec<LocalizedTrace>> {
+        if !self.tracedb.tracing_enabled() {
+            return None;
+        }
+
+        let from = self.block_number_ref(&filter.from_block)?;
+        let to = self.block_number_ref(&filter.to_block)?;
+
+        let after = filter.after.unwrap_or(0);
+        let wanted = filter.count.map(|count| after.saturating_add(count));
+
+        let mut traces = Vec::new();
+        for number in from..(to + 1) {
+            if wanted.map_or(false, |wanted| traces.len() >= wanted) {
+                break;
+            }
+            let hash = match self.chain.read().block_hash(number) {
+                Some(hash) => hash,
+                None => break,
+            };
+            let block_traces = match self.tracedb.block_traces(&hash) {
+                Some(block_traces) => block_traces,
+                None => continue,
+            };
+            let transaction_hashes = match self.chain.read().block_body(&hash) {
+                Some(body) => body.transaction_hashes(),
+                None => continue,
+            };
+            let matching = BlockTraces(
+                block_traces
+                    .0
+                    .into_iter()
+                    .map(|tx_traces| {
+                        tx_traces
+                            .into_iter()
+                            .filter(|trace| filter.matches(trace))
+                            .collect()
+                    })
+                    .collect(),
+            );
+            traces.extend(matching.localize(number, hash, &transaction_hashes));
+        }
+        let traces = traces.into_iter().skip(after);
+        Some(match filter.count {
+            Some(count) => traces.take(count).collect(),
+            None => traces.collect(),
+        })
+    }
+
     fn last_hashes(&self) -> LastHashes {
         (*self.build_last_hashes(self.chain.read().best_block_hash())).clone()
     }
@@ -1729,6 +2009,7 @@ impl MiningBlockChainClient for Client {
         let open_block = OpenBlock::new(
             engine,
             self.factories.clone(),
+            self.tracedb.tracing_enabled(),
             self.state_db.read().boxed_clone_canon(&h),
             best_header,
             seal_type.unwrap_or_default(),
@@ -1764,6 +2045,15 @@ impl MiningBlockChainClient for Client {
         });
     }
 
+    fn notify_transactions_imported(&self, hashes: Vec<H256>) {
+        if hashes.is_empty() {
+            return;
+        }
+        self.notify(|notify| {
+            notify.transactions_imported(&hashes);
+        });
+    }
+
     fn broadcast_proposal_block(&self, block: SealedBlock) {
         self.notify(|notify| {
             notify.new_blocks(
@@ -1854,7 +2144,7 @@ fn utc_from_secs(secs: i64) -> (u64, u64, u64, u64, u64, u64) {
 }
 
 impl ProvingBlockChainClient for Client {
-    fn prove_storage(&self, key1: H256, key2: H256, id: BlockId) -> Option<(Vec<Bytes>, H256)> {
+    fn prove_storage(&self, key1: H256, key2: H256, id: BlockId) -> Option<(Vec<Bytes>, Bytes)> {
         self.state_at(id)
             .and_then(move |state| state.prove_storage(key1, key2).ok())
     }
@@ -1863,11 +2153,36 @@ impl ProvingBlockChainClient for Client {
         self.state_at(id)
             .and_then(move |state| state.prove_account(key1).ok())
     }
+
+    fn prove_transaction(
+        &self,
+        transaction: SignedTransaction,
+        id: BlockId,
+    ) -> Option<(Bytes, Vec<DBValue>)>
+    {
+        let (header, mut env_info) = match (self.block_header(id), self.env_info(id)) {
+            (Some(header), Some(env_info)) => (header, env_info),
+            _ => return None,
+        };
+
+        env_info.gas_limit = U256::max_value();
+        let mut jdb = self.state_db.read().journal_db().boxed_clone();
+
+        state::prove_transaction_virtual(
+            jdb.as_hashstore_mut(),
+            header.state_root(),
+            &transaction,
+            self.engine.machine(),
+            &env_info,
+            self.factories.clone(),
+            self.db.read().clone(),
+        )
+    }
 }
 
 /// Returns `LocalizedReceipt` given `LocalizedTransaction`
 /// and a vector of receipts from given block up to transaction index.
-fn transaction_receipt(
+pub fn transaction_receipt(
     mut tx: LocalizedTransaction,
     mut receipts: Vec<Receipt>,
 ) -> LocalizedReceipt
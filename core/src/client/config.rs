@@ -82,6 +82,8 @@ pub struct ClientConfig {
     pub vm_type: VMType,
     /// Fat DB enabled?
     pub fat_db: bool,
+    /// Record call traces of imported blocks?
+    pub tracing: bool,
     /// The JournalDB ("pruning") algorithm to use.
     pub pruning: journaldb::Algorithm,
     /// RocksDB column cache-size if not default
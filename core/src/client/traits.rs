@@ -23,6 +23,7 @@
 use std::time::Duration;
 
 use state::BasicAccount;
+use kvdb::DBValue;
 use block::{OpenBlock, SealedBlock, ClosedBlock};
 use blockchain::TreeRoute;
 use encoded;
@@ -33,6 +34,7 @@ use filter::Filter;
 use header::{BlockNumber, SealType, Header};
 use log_entry::LocalizedLogEntry;
 use receipt::LocalizedReceipt;
+use trace::{Filter as TraceFilter, LocalizedTrace};
 use transaction::{LocalizedTransaction, PendingTransaction, SignedTransaction};
 use verification::queue::QueueInfo as BlockQueueInfo;
 use aion_types::{H256, H128, U256, Address};
@@ -200,6 +202,18 @@ pub trait BlockChainClient: Sync + Send {
     /// Returns logs matching given filter.
     fn logs(&self, filter: Filter) -> Vec<LocalizedLogEntry>;
 
+    /// Returns traces of all transactions in the given block.
+    /// `None` if tracing is disabled or the block is unknown.
+    fn block_traces(&self, id: BlockId) -> Option<Vec<LocalizedTrace>>;
+
+    /// Returns traces of the given transaction.
+    /// `None` if tracing is disabled or the transaction is unknown.
+    fn transaction_traces(&self, id: TransactionId) -> Option<Vec<LocalizedTrace>>;
+
+    /// Returns traces matching given filter.
+    /// `None` if tracing is disabled or the block range is unknown.
+    fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
+
     /// Makes a non-persistent transaction call.
     fn call(
         &self,
@@ -330,6 +344,9 @@ pub trait MiningBlockChainClient: BlockChainClient {
     /// Broadcast a new transation
     fn broadcast_transaction(&self, transactions: Bytes);
 
+    /// Notify listeners of transactions added to the transaction pool.
+    fn notify_transactions_imported(&self, hashes: Vec<H256>);
+
     /// Broadcast a block proposal.
     fn broadcast_proposal_block(&self, block: SealedBlock);
 
@@ -373,10 +390,18 @@ pub trait ProvingBlockChainClient: BlockChainClient {
     ///
     /// Both provided keys assume a secure trie.
     /// Returns a vector of raw trie nodes (in order from the root) proving the storage query.
-    fn prove_storage(&self, key1: H256, key2: H256, id: BlockId) -> Option<(Vec<Bytes>, H256)>;
+    fn prove_storage(&self, key1: H256, key2: H256, id: BlockId) -> Option<(Vec<Bytes>, Bytes)>;
 
     /// Prove account existence at a specific block id.
     /// The key is the blake2b hash of the account's address.
     /// Returns a vector of raw trie nodes (in order from the root) proving the query.
     fn prove_account(&self, key1: H256, id: BlockId) -> Option<(Vec<Bytes>, BasicAccount)>;
+
+    /// Prove execution of a virtual call at the given block.
+    /// Returns the output of the call and a vector of all state items read during it.
+    fn prove_transaction(
+        &self,
+        transaction: SignedTransaction,
+        id: BlockId,
+    ) -> Option<(Bytes, Vec<DBValue>)>;
 }
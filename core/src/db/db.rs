@@ -42,11 +42,15 @@ pub const COL_EXTRA: &'static str = "extra";
 /// Column for the empty accounts bloom filter.
 pub const COL_ACCOUNT_BLOOM: &'static str = "account_bloom";
 /// Column for general information from the local node which can persist.
-// pub const COL_NODE_INFO: &'static str = "node_info";
+pub const COL_NODE_INFO: &'static str = "node_info";
 /// Column for avm object graph
 pub const COL_AVM_GRAPH: &'static str = "avm_graph";
+/// Column for call traces
+pub const COL_TRACE: &'static str = "trace";
+/// Column for the header chain of a light client
+pub const COL_LIGHT_CHAIN: &'static str = "light_chain";
 
-pub const DB_NAMES: [&'static str; 7] = [
+pub const DB_NAMES: [&'static str; 9] = [
     "headers",
     "bodies",
     "state",
@@ -54,6 +58,8 @@ pub const DB_NAMES: [&'static str; 7] = [
     "account_bloom",
     "node_info",
     "avm_graph",
+    "trace",
+    "light_chain",
 ];
 /// Modes for updating caches.
 #[derive(Clone, Copy)]
@@ -194,6 +194,18 @@ pub trait Engine: Sync + Send {
         Ok(())
     }
 
+    /// Verify a header received without its state, such as the blocks of a snapshot or a
+    /// warp sync pivot. Checks the header, its seal and the rules depending on its parent.
+    fn verify_header_seal(
+        &self,
+        _header: &<EthereumMachine as Machine>::Header,
+        _parent: &<EthereumMachine as Machine>::Header,
+        _grand_parent: Option<&<EthereumMachine as Machine>::Header>,
+    ) -> Result<(), Error>
+    {
+        Ok(())
+    }
+
     /// Populate a header's difficulty based on its parent's header.
     /// Usually implements the chain scoring rule based on weight.
     fn set_difficulty_from_parent(
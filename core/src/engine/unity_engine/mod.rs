@@ -455,6 +455,44 @@ impl UnityEngine {
 
         Ok(())
     }
+
+    /// Verify a header without access to the state, as done by light clients.
+    /// Checks the header itself, its PoW solution or PoS seal and the rules depending on
+    /// its parent. PoS timestamps and difficulties depend on stakes and are not verified.
+    pub fn validate_light_header(
+        header: &Header,
+        parent: &Header,
+        grand_parent: Option<&Header>,
+        unity_update: Option<BlockNumber>,
+    ) -> Result<(), Error>
+    {
+        Self::validate_block_header(header)?;
+
+        let mut parent_validators: Vec<Box<DependentHeaderValidator>> = Vec::with_capacity(3);
+        parent_validators.push(Box::new(NumberValidator {}));
+        parent_validators.push(Box::new(TimestampValidator {}));
+        if unity_update.map_or(false, |fork_number| header.number() > fork_number) {
+            parent_validators.push(Box::new(SealTypeValidator {}));
+        }
+        for v in parent_validators.iter() {
+            v.validate(header, parent)?;
+        }
+
+        match header.seal_type() {
+            Some(SealType::PoS) => {
+                if unity_update.map_or(true, |fork_number| header.number() <= fork_number) {
+                    return Err(BlockError::InvalidPoSBlockNumber.into());
+                }
+                PoSValidator::validate_seal(header, grand_parent)
+            }
+            _ => {
+                EquihashSolutionValidator {
+                    solution_validator: EquihashValidator::new(210, 9),
+                }
+                .validate(header)
+            }
+        }
+    }
 }
 
 impl Engine for Arc<UnityEngine> {
@@ -508,6 +546,21 @@ impl Engine for Arc<UnityEngine> {
         Ok(())
     }
 
+    fn verify_header_seal(
+        &self,
+        header: &Header,
+        parent: &Header,
+        grand_parent: Option<&Header>,
+    ) -> Result<(), Error>
+    {
+        UnityEngine::validate_light_header(
+            header,
+            parent,
+            grand_parent,
+            self.machine.params().unity_update,
+        )
+    }
+
     fn verify_local_seal_pow(&self, header: &Header) -> Result<(), Error> {
         self.verify_block_basic(header)
             .and_then(|_| self.verify_block_unordered(header))
@@ -49,6 +49,30 @@ impl PoSValidator {
             return Err(BlockError::NullStake.into());
         }
 
+        Self::validate_seal(header, grand_parent_header)?;
+
+        // Verify timestamp
+        let seed = &header.seal()[0];
+        let difficulty = header.difficulty().clone();
+        let timestamp = header.timestamp();
+        let parent_timestamp = parent_header.timestamp();
+
+        let delta_uint = calculate_delta(difficulty, &seed, stake.clone());
+
+        if timestamp - parent_timestamp != delta_uint {
+            Err(BlockError::InvalidPoSTimestamp(timestamp, parent_timestamp, delta_uint).into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Verify the seed and the signature of a PoS seal. Unlike `validate`, this does not
+    /// depend on the producer's stake.
+    pub fn validate_seal(
+        header: &Header,
+        grand_parent_header: Option<&Header>,
+    ) -> Result<(), Error>
+    {
         // Get seal, check seal length
         let seal = header.seal();
         if seal.len() != 3 {
@@ -92,18 +116,7 @@ impl PoSValidator {
         //     return Err(BlockError::InvalidPoSAuthor.into());
         // }
 
-        // Verify timestamp
-        let difficulty = header.difficulty().clone();
-        let timestamp = header.timestamp();
-        let parent_timestamp = parent_header.timestamp();
-
-        let delta_uint = calculate_delta(difficulty, &seed, stake.clone());
-
-        if timestamp - parent_timestamp != delta_uint {
-            Err(BlockError::InvalidPoSTimestamp(timestamp, parent_timestamp, delta_uint).into())
-        } else {
-            Ok(())
-        }
+        Ok(())
     }
 }
 
@@ -40,6 +40,7 @@ use transaction::{Action, SignedTransaction};
 use crossbeam;
 pub use types::executed::Executed;
 use precompiled::builtin::{BuiltinExtImpl, BuiltinContext};
+use trace::{self, AvmTracer, ExecutiveTracer, FlatTrace, StructLog, VMTraceOptions, VMTracer};
 
 use kvdb::{DBTransaction};
 
@@ -83,6 +84,7 @@ pub struct Executive<'a, B: 'a + StateBackend> {
     info: &'a EnvInfo,
     machine: &'a Machine,
     depth: usize,
+    tracer: Option<ExecutiveTracer>,
 }
 
 impl<'a, B: 'a + StateBackend> Executive<'a, B> {
@@ -93,6 +95,41 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
             info: info,
             machine: machine,
             depth: 0,
+            tracer: None,
+        }
+    }
+
+    /// Basic constructor which records a call trace of the executed transactions.
+    pub fn new_with_tracing(
+        state: &'a mut State<B>,
+        info: &'a EnvInfo,
+        machine: &'a Machine,
+    ) -> Self
+    {
+        Executive {
+            state: state,
+            info: info,
+            machine: machine,
+            depth: 0,
+            tracer: Some(ExecutiveTracer::default()),
+        }
+    }
+
+    /// Basic constructor which also records every instruction executed by the vm,
+    /// together with what `options` ask for.
+    pub fn new_with_vm_tracing(
+        state: &'a mut State<B>,
+        info: &'a EnvInfo,
+        machine: &'a Machine,
+        options: VMTraceOptions,
+    ) -> Self
+    {
+        Executive {
+            state: state,
+            info: info,
+            machine: machine,
+            depth: 0,
+            tracer: Some(ExecutiveTracer::with_vm_tracer(VMTracer::new(options))),
         }
     }
 
@@ -102,6 +139,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
         info: &'a EnvInfo,
         machine: &'a Machine,
         parent_depth: usize,
+        tracer: Option<ExecutiveTracer>,
     ) -> Self
     {
         Executive {
@@ -109,14 +147,32 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
             info: info,
             machine: machine,
             depth: parent_depth + 1,
+            tracer: tracer,
         }
     }
 
+    /// Takes the traces recorded so far. Empty if tracing is disabled.
+    pub fn drain_traces(&mut self) -> Vec<FlatTrace> {
+        self.tracer
+            .take()
+            .map_or_else(Vec::new, ExecutiveTracer::drain)
+    }
+
+    /// Takes the instructions recorded so far and whether some were dropped. Empty if
+    /// vm tracing is disabled.
+    pub fn drain_struct_logs(&self) -> (Vec<StructLog>, bool) {
+        self.tracer
+            .as_ref()
+            .and_then(ExecutiveTracer::vm_tracer)
+            .map_or_else(|| (Vec::new(), false), VMTracer::drain)
+    }
+
     /// Creates `Externalities` from `Executive`.
     pub fn as_externalities<'any>(
         &'any mut self,
         origin_info: Vec<OriginInfo>,
         substate: &'any mut Substate,
+        tracer: Option<&'any mut ExecutiveTracer>,
     ) -> Externalities<'any, B>
     {
         let kvdb = self.state.export_kvdb().clone();
@@ -128,6 +184,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
             origin_info,
             substate,
             kvdb,
+            tracer,
         )
     }
 
@@ -144,6 +201,10 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
             self.depth,
             substates,
             tx_chnnl,
+            self.tracer
+                .as_ref()
+                .and_then(ExecutiveTracer::avm_tracer)
+                .cloned(),
         )
     }
 
@@ -493,6 +554,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
         &mut self,
         params: ActionParams,
         unconfirmed_substate: &mut Substate,
+        tracer: Option<&mut ExecutiveTracer>,
     ) -> ExecutionResult
     {
         let local_stack_size = ::io::LOCAL_STACK_SIZE.with(|sz| sz.get());
@@ -504,7 +566,11 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
         if self.depth != depth_threshold {
             let mut vm_factory = self.state.vm_factory();
             // consider put global callback in ext
-            let mut ext = self.as_externalities(OriginInfo::from(&[&params]), unconfirmed_substate);
+            let mut ext = self.as_externalities(
+                OriginInfo::from(&[&params]),
+                unconfirmed_substate,
+                tracer,
+            );
             //TODO: make create/exec compatible with fastvm
             let vm = vm_factory.create(VMType::FastVM);
             // fastvm local call flag is unused
@@ -522,6 +588,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
             let mut ext = self.as_externalities(
                 OriginInfo::from(&[&params as &ActionParams]),
                 unconfirmed_substate,
+                tracer,
             );
 
             scope
@@ -548,6 +615,25 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
     /// Modifies the substate.
     /// Returns either gas_left or `vm::Error`.
     pub fn call(&mut self, params: ActionParams, substate: &mut Substate) -> ExecutionResult {
+        let mut subtracer = match self.tracer.as_ref().map(ExecutiveTracer::subtracer) {
+            Some(subtracer) => subtracer,
+            None => return self.exec_call(params, substate, None),
+        };
+        let trace_call = trace::Call::from(&params);
+        let result = self.exec_call(params, substate, Some(&mut subtracer));
+        if let Some(ref mut tracer) = self.tracer {
+            tracer.trace_call_result(trace_call, &result, subtracer.drain());
+        }
+        result
+    }
+
+    fn exec_call(
+        &mut self,
+        params: ActionParams,
+        substate: &mut Substate,
+        tracer: Option<&mut ExecutiveTracer>,
+    ) -> ExecutionResult
+    {
         trace!(
             target: "executive",
             "Executive::call(params={:?}) self.env_info={:?}",
@@ -653,7 +739,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
                 // part of substate that may be reverted
                 let mut unconfirmed_substate = Substate::new();
 
-                let result = self.exec_vm(params.clone(), &mut unconfirmed_substate);
+                let result = self.exec_vm(params.clone(), &mut unconfirmed_substate, tracer);
 
                 debug!(target: "vm", "result={:?}", result);
                 // Handle state and substates
@@ -741,6 +827,35 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
     /// NOTE. It does not finalize the transaction (doesn't do refunds, nor suicides).
     /// Modifies the substate.
     pub fn create(&mut self, params: ActionParams, substate: &mut Substate) -> ExecutionResult {
+        let mut subtracer = match self.tracer.as_ref().map(ExecutiveTracer::subtracer) {
+            Some(subtracer) => subtracer,
+            None => return self.exec_create(params, substate, None),
+        };
+        let trace_create = trace::Create::from(&params);
+        let address = params.address.clone();
+        let result = self.exec_create(params, substate, Some(&mut subtracer));
+        let code = match result.status_code {
+            ExecStatus::Success => {
+                self.state
+                    .code(&address)
+                    .unwrap_or(None)
+                    .map_or_else(Vec::new, |code| (*code).clone())
+            }
+            _ => Vec::new(),
+        };
+        if let Some(ref mut tracer) = self.tracer {
+            tracer.trace_create_result(trace_create, address, code, &result, subtracer.drain());
+        }
+        result
+    }
+
+    fn exec_create(
+        &mut self,
+        params: ActionParams,
+        substate: &mut Substate,
+        tracer: Option<&mut ExecutiveTracer>,
+    ) -> ExecutionResult
+    {
         // EIP-684: If a contract creation is attempted, due to either a creation transaction or the
         // CREATE (or future CREATE2) opcode, and the destination address already has either
         // nonzero nonce, or nonempty code, then the creation throws immediately, with exactly
@@ -816,7 +931,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
                 .new_contract(&params.address, prev_bal, nonce_offset);
         }
 
-        let res = self.exec_vm(params, &mut unconfirmed_substate);
+        let res = self.exec_vm(params, &mut unconfirmed_substate, tracer);
 
         self.enact_result(&res, substate, unconfirmed_substate);
         debug!(target: "vm", "create res = {:?}", res);
@@ -834,6 +949,12 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
         let mut final_results = Vec::new();
 
+        let avm_traces = self
+            .tracer
+            .as_ref()
+            .and_then(ExecutiveTracer::avm_tracer)
+            .map(AvmTracer::drain);
+
         let mut total_gas_used: U256 = U256::from(0);
         let mut multiple_sets: HashMap<H256, HashSet<H256>> = HashMap::new();
         for idx in 0..txs.len() {
@@ -859,6 +980,11 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
                 touched.insert(account);
             }
 
+            let trace = match self.tracer {
+                Some(_) => self.avm_trace(&t, &result),
+                None => Vec::new(),
+            };
+
             total_gas_used = total_gas_used + gas_used;
             if total_gas_used + self.info.gas_used > self.info.gas_limit {
                 final_results.push(Err(ExecutionError::BlockGasLimitReached {
@@ -876,6 +1002,12 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
                     logs: substate.logs,
                     contracts_created: substate.contracts_created,
                     output: result.return_data.to_vec(),
+                    trace: trace,
+                    struct_logs: Vec::new(),
+                    struct_logs_truncated: false,
+                    avm_trace: avm_traces
+                        .as_ref()
+                        .map(|traces| traces.get(idx).cloned().unwrap_or_default()),
                     state_diff: None,
                     transaction_fee: fees_value,
                     touched: touched,
@@ -932,6 +1064,43 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
         return final_results;
     }
 
+    /// Traces the top level frame of an avm transaction. Calls made inside the avm
+    /// are reported through the callback bridge and recorded by the `AvmTracer`
+    /// in `Executed::avm_trace` instead.
+    fn avm_trace(&self, t: &SignedTransaction, result: &ExecutionResult) -> Vec<FlatTrace> {
+        let sender = t.sender();
+        let mut tracer = ExecutiveTracer::default();
+        match t.action {
+            Action::Create => {
+                let create = trace::Create {
+                    from: sender.clone(),
+                    value: t.value,
+                    gas: t.gas,
+                    init: t.data.clone(),
+                };
+                let address = contract_address(&sender, &t.nonce).0;
+                let code = self
+                    .state
+                    .code(&address)
+                    .unwrap_or(None)
+                    .map_or_else(Vec::new, |code| (*code).clone());
+                tracer.trace_create_result(create, address, code, result, Vec::new());
+            }
+            Action::Call(ref address) => {
+                let call = trace::Call {
+                    from: sender.clone(),
+                    to: address.clone(),
+                    value: t.value,
+                    gas: t.gas,
+                    input: t.data.clone(),
+                    call_type: CallType::Call,
+                };
+                tracer.trace_call_result(call, result, Vec::new());
+            }
+        }
+        tracer.drain()
+    }
+
     fn decode_alias_and_set(raw_set: &[u8], set: &mut HashSet<H256>) {
         assert!(raw_set.len() >= 5);
         let mut index = 5;
@@ -1010,6 +1179,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
             }
 
             self.state.discard_checkpoint();
+            let (struct_logs, struct_logs_truncated) = self.drain_struct_logs();
             Ok(Executed {
                 exception: result.exception,
                 gas: t.gas,
@@ -1027,6 +1197,10 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
                 } else {
                     result.return_data.to_vec()
                 },
+                trace: self.drain_traces(),
+                struct_logs: struct_logs,
+                struct_logs_truncated: struct_logs_truncated,
+                avm_trace: None,
                 state_diff: None,
                 transaction_fee: fees_value,
                 touched: HashSet::new(),
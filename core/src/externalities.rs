@@ -27,12 +27,14 @@ use std::sync::mpsc::Sender;
 use aion_types::{H256, U256, H128, Address};
 use vms::{ActionParams, ActionValue, EnvInfo, CallType, ExecutionResult, ExecStatus, ReturnData, ParamsType};
 use vms::traits::Ext;
+use vms::AvmInternalTransaction;
 use acore_bytes::Bytes;
 use state::{Backend as StateBackend, State, Substate, CleanupMode};
 use machine::EthereumMachine as Machine;
 use executive::*;
 use kvdb::KeyValueDB;
 use db::{self, Readable};
+use trace::{AvmOperation, AvmTracer, ExecutiveTracer};
 
 /// Transaction properties that externalities need to know about.
 pub struct OriginInfo {
@@ -86,6 +88,9 @@ where B: StateBackend
     depth: usize,
     substates: &'a mut [Substate],
     tx: Sender<i32>,
+    tracer: Option<AvmTracer>,
+    /// Transaction whose state is being committed; advanced on every state root request.
+    trace_index: usize,
 }
 
 impl<'a, B: 'a> AVMExternalities<'a, B>
@@ -99,6 +104,7 @@ where B: StateBackend
         depth: usize,
         substates: &'a mut [Substate],
         tx: Sender<i32>,
+        tracer: Option<AvmTracer>,
     ) -> Self
     {
         AVMExternalities {
@@ -108,6 +114,14 @@ where B: StateBackend
             depth: depth,
             substates: substates,
             tx: tx,
+            tracer: tracer,
+            trace_index: 0,
+        }
+    }
+
+    fn trace_operation(&self, operation: AvmOperation) {
+        if let Some(ref tracer) = self.tracer {
+            tracer.trace_operation(self.trace_index, operation);
         }
     }
 }
@@ -123,6 +137,7 @@ where B: StateBackend
     origin_info: Vec<OriginInfo>,
     substate: &'a mut Substate,
     db: Arc<KeyValueDB>,
+    tracer: Option<&'a mut ExecutiveTracer>,
 }
 
 impl<'a, B: 'a> Externalities<'a, B>
@@ -137,6 +152,7 @@ where B: StateBackend
         origin_info: Vec<OriginInfo>,
         substate: &'a mut Substate,
         kvdb: Arc<KeyValueDB>,
+        tracer: Option<&'a mut ExecutiveTracer>,
     ) -> Self
     {
         Externalities {
@@ -147,6 +163,7 @@ where B: StateBackend
             origin_info: origin_info,
             substate: substate,
             db: kvdb,
+            tracer: tracer,
         }
     }
 }
@@ -316,9 +333,18 @@ where B: StateBackend
         };
 
         let mut result = {
-            let mut ex =
-                Executive::from_parent(self.state, self.env_info, self.machine, self.depth);
-            ex.create(params, self.substate)
+            let mut ex = Executive::from_parent(
+                self.state,
+                self.env_info,
+                self.machine,
+                self.depth,
+                self.tracer.as_ref().map(|tracer| tracer.subtracer()),
+            );
+            let result = ex.create(params, self.substate);
+            if let Some(ref mut tracer) = self.tracer {
+                tracer.extend(ex.drain_traces());
+            }
+            result
         };
 
         // If succeed, add address into substate, set the return_data (normally should be the deployed code) to address
@@ -429,8 +455,18 @@ where B: StateBackend
             nonce: 0,
         };
 
-        let mut ex = Executive::from_parent(self.state, self.env_info, self.machine, self.depth);
-        ex.call(params, self.substate)
+        let mut ex = Executive::from_parent(
+            self.state,
+            self.env_info,
+            self.machine,
+            self.depth,
+            self.tracer.as_ref().map(|tracer| tracer.subtracer()),
+        );
+        let result = ex.call(params, self.substate);
+        if let Some(ref mut tracer) = self.tracer {
+            tracer.extend(ex.drain_traces());
+        }
+        result
     }
 
     fn extcode(&self, address: &Address) -> Arc<Bytes> {
@@ -487,6 +523,36 @@ where B: StateBackend
 
     fn depth(&self) -> usize { self.depth }
 
+    fn is_step_tracing(&self) -> bool {
+        self.tracer
+            .as_ref()
+            .map_or(false, |tracer| tracer.vm_tracer().is_some())
+    }
+
+    fn trace_step(
+        &mut self,
+        pc: usize,
+        instruction: u8,
+        gas: U256,
+        gas_cost: U256,
+        stack: &[H128],
+        memory: &[u8],
+    )
+    {
+        if let Some(vm_tracer) = self.tracer.as_ref().and_then(|tracer| tracer.vm_tracer()) {
+            vm_tracer.trace_step(
+                &self.origin_info[0].address,
+                self.depth + 1,
+                pc,
+                instruction,
+                gas,
+                gas_cost,
+                stack,
+                memory,
+            );
+        }
+    }
+
     fn inc_sstore_clears(&mut self) {
         self.substate.sstore_clears_count = self.substate.sstore_clears_count + U256::one();
     }
@@ -717,6 +783,7 @@ where B: StateBackend
 
     fn save_code_at(&mut self, address: &Address, code: Bytes) {
         debug!(target: "vm", "AVM save code at: {:?}", address);
+        self.trace_operation(AvmOperation::PutCode(*address, code.clone()));
         self.state
             .lock()
             .unwrap()
@@ -739,6 +806,7 @@ where B: StateBackend
     fn set_special_empty_flag(&mut self) { unimplemented!() }
 
     fn create_account(&mut self, a: &Address) {
+        self.trace_operation(AvmOperation::CreateAccount(*a));
         self.state
             .lock()
             .unwrap()
@@ -746,6 +814,7 @@ where B: StateBackend
     }
 
     fn sstore(&mut self, a: &Address, key: Vec<u8>, value: Vec<u8>) {
+        self.trace_operation(AvmOperation::PutStorage(*a, key.clone(), value.clone()));
         self.state
             .lock()
             .unwrap()
@@ -761,6 +830,7 @@ where B: StateBackend
     }
 
     fn remove_storage(&mut self, a: &Address, key: Vec<u8>) {
+        self.trace_operation(AvmOperation::RemoveStorage(*a, key.clone()));
         self.state
             .lock()
             .unwrap()
@@ -771,6 +841,7 @@ where B: StateBackend
     fn kill_account(&mut self, a: &Address) { self.state.lock().unwrap().kill_account(a) }
 
     fn inc_balance(&mut self, a: &Address, value: &U256) {
+        self.trace_operation(AvmOperation::IncreaseBalance(*a, *value));
         self.state
             .lock()
             .unwrap()
@@ -779,6 +850,7 @@ where B: StateBackend
     }
 
     fn dec_balance(&mut self, a: &Address, value: &U256) {
+        self.trace_operation(AvmOperation::DecreaseBalance(*a, *value));
         self.state
             .lock()
             .unwrap()
@@ -807,7 +879,13 @@ where B: StateBackend
         self.substates[index as usize].touched.insert(*a);
     }
 
-    fn send_signal(&mut self, signal: i32) { self.tx.send(signal).expect("ext send failed"); }
+    fn send_signal(&mut self, signal: i32) {
+        // the state root of a transaction is requested once all of its state is committed
+        if signal >= 0 {
+            self.trace_index += 1;
+        }
+        self.tx.send(signal).expect("ext send failed");
+    }
 
     fn commit(&mut self) {
         self.state
@@ -821,11 +899,23 @@ where B: StateBackend
 
     fn avm_log(&mut self, address: &Address, topics: Vec<H256>, data: Vec<u8>, index: i32) {
         use log_entry::LogEntry;
-        self.substates[index as usize].logs.push(LogEntry {
+        let log = LogEntry {
             address: address.clone(),
             topics,
             data,
-        });
+        };
+        if let Some(ref tracer) = self.tracer {
+            tracer.trace_operation(index as usize, AvmOperation::Log(log.clone()));
+        }
+        self.substates[index as usize].logs.push(log);
+    }
+
+    fn is_tracing(&self) -> bool { self.tracer.is_some() }
+
+    fn trace_avm_internal_transactions(&mut self, idx: i32, txs: &[AvmInternalTransaction]) {
+        if let Some(ref tracer) = self.tracer {
+            tracer.trace_internal_transactions(idx as usize, txs);
+        }
     }
 
     fn get_transformed_code(&self, address: &Address) -> Option<Arc<Vec<u8>>> {
@@ -109,8 +109,11 @@ pub mod transaction;
 pub mod engine;
 //pub mod error;
 pub mod header;
+pub mod light;
 pub mod views;
+pub mod snapshot;
 pub mod sync;
+pub mod trace;
 
 // boot
 pub mod service;
@@ -135,6 +138,7 @@ mod types;
 mod tests;
 
 pub use types::{
+    account::account_diff,
     filter,
     state::log_entry,
     state::receipt,
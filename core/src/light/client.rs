@@ -0,0 +1,206 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Light client: answers state and receipt queries from peers on demand.
+
+use std::sync::Arc;
+
+use futures::{future, Future};
+
+use aion_types::{Address, H128, H256, U256};
+use client::{transaction_receipt, BlockId, Executed};
+use kvdb::KeyValueDB;
+use receipt::LocalizedReceipt;
+use spec::Spec;
+use state::BasicAccount;
+use transaction::{SignedTransaction, AVM_TRANSACTION_TYPE};
+
+use super::error::Error;
+use super::header_chain::HeaderChain;
+use super::on_demand::OnDemand;
+use super::request::{Request, Response};
+
+/// Boxed future of a light client query.
+pub type BoxFuture<T> = Box<Future<Item = T, Error = Error> + Send>;
+
+/// Light client.
+pub struct LightClient {
+    chain: Arc<HeaderChain>,
+    on_demand: Arc<OnDemand>,
+}
+
+impl LightClient {
+    /// Create a light client keeping its header chain in `db`.
+    pub fn new(db: Arc<KeyValueDB>, spec: &Spec) -> Result<Self, Error> {
+        let unity_update = spec.engine.machine().params().unity_update;
+        let chain = Arc::new(HeaderChain::new(
+            db.clone(),
+            &spec.genesis_header(),
+            unity_update,
+        )?);
+        let on_demand = Arc::new(OnDemand::new(chain.clone(), spec.engine.clone(), db));
+        Ok(LightClient {
+            chain: chain,
+            on_demand: on_demand,
+        })
+    }
+
+    /// Header chain.
+    pub fn chain(&self) -> Arc<HeaderChain> { self.chain.clone() }
+
+    /// Request queue.
+    pub fn on_demand(&self) -> Arc<OnDemand> { self.on_demand.clone() }
+
+    fn block_hash(&self, id: BlockId) -> Result<H256, Error> {
+        self.chain
+            .block_header(id)
+            .map(|header| header.hash())
+            .ok_or(Error::UnknownBlock)
+    }
+
+    /// Account at the given block, `None` if it does not exist.
+    pub fn account(&self, address: Address, id: BlockId) -> BoxFuture<Option<BasicAccount>> {
+        let block_hash = match self.block_hash(id) {
+            Ok(hash) => hash,
+            Err(err) => return Box::new(future::err(err)),
+        };
+        Box::new(
+            self.on_demand
+                .request(Request::Account {
+                    block_hash: block_hash,
+                    address: address,
+                })
+                .and_then(|res| {
+                    match res {
+                        Response::Account(account) => Ok(account),
+                        _ => Err(Error::UnexpectedResponse),
+                    }
+                }),
+        )
+    }
+
+    /// Balance of an account at the given block.
+    pub fn balance(&self, address: Address, id: BlockId) -> BoxFuture<U256> {
+        Box::new(
+            self.account(address, id)
+                .map(|account| account.map_or_else(U256::zero, |account| account.balance)),
+        )
+    }
+
+    /// Storage value of an account at the given block.
+    pub fn storage_at(&self, address: Address, key: H128, id: BlockId) -> BoxFuture<H128> {
+        let block_hash = match self.block_hash(id) {
+            Ok(hash) => hash,
+            Err(err) => return Box::new(future::err(err)),
+        };
+        Box::new(
+            self.on_demand
+                .request(Request::Storage {
+                    block_hash: block_hash,
+                    address: address,
+                    key: key,
+                })
+                .and_then(|res| {
+                    match res {
+                        Response::Storage(value) => {
+                            let mut ret = H128::zero();
+                            let len = ::std::cmp::min(value.len(), 16);
+                            ret[16 - len..].copy_from_slice(&value[value.len() - len..]);
+                            Ok(ret)
+                        }
+                        _ => Err(Error::UnexpectedResponse),
+                    }
+                }),
+        )
+    }
+
+    /// Execute a virtual call on top of the given block.
+    pub fn call(&self, transaction: SignedTransaction, id: BlockId) -> BoxFuture<Executed> {
+        if transaction.tx_type() == AVM_TRANSACTION_TYPE {
+            return Box::new(future::err(Error::NotSupported("AVM call")));
+        }
+        let block_hash = match self.block_hash(id) {
+            Ok(hash) => hash,
+            Err(err) => return Box::new(future::err(err)),
+        };
+        Box::new(
+            self.on_demand
+                .request(Request::Execution {
+                    block_hash: block_hash,
+                    transaction: transaction,
+                })
+                .and_then(|res| {
+                    match res {
+                        Response::Execution(res) => res.map_err(Error::Execution),
+                        _ => Err(Error::UnexpectedResponse),
+                    }
+                }),
+        )
+    }
+
+    /// Receipt of a transaction on the canonical chain.
+    pub fn transaction_receipt(&self, hash: H256) -> BoxFuture<Option<LocalizedReceipt>> {
+        let chain = self.chain.clone();
+        let on_demand = self.on_demand.clone();
+        Box::new(
+            self.on_demand
+                .request(Request::TransactionIndex {
+                    hash: hash,
+                })
+                .and_then(move |res| -> BoxFuture<Option<LocalizedReceipt>> {
+                    let index = match res {
+                        Response::TransactionIndex(index) => index,
+                        _ => return Box::new(future::err(Error::UnexpectedResponse)),
+                    };
+                    if chain.block_hash(index.block_number) != Some(index.block_hash) {
+                        return Box::new(future::ok(None));
+                    }
+                    let body = on_demand.request(Request::Body {
+                        block_hash: index.block_hash,
+                    });
+                    let receipts = on_demand.request(Request::Receipts {
+                        block_hash: index.block_hash,
+                    });
+                    Box::new(body.join(receipts).and_then(move |res| {
+                        let (body, mut receipts) = match res {
+                            (Response::Body(body), Response::Receipts(receipts)) => {
+                                (body, receipts)
+                            }
+                            _ => return Err(Error::UnexpectedResponse),
+                        };
+                        let tx = body
+                            .view()
+                            .localized_transaction_at(
+                                &index.block_hash,
+                                index.block_number,
+                                index.index,
+                            )
+                            .ok_or(Error::BadProof)?;
+                        if tx.hash() != &hash || receipts.len() <= index.index {
+                            return Err(Error::BadProof);
+                        }
+                        receipts.truncate(index.index + 1);
+                        Ok(Some(transaction_receipt(tx, receipts)))
+                    }))
+                }),
+        )
+    }
+}
@@ -0,0 +1,104 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Light client errors.
+
+use std::fmt;
+
+use aion_types::H256;
+use kvdb;
+use rlp::DecoderError;
+use trie::TrieError;
+use types::error::{Error as CoreError, ExecutionError};
+
+/// Light client errors.
+#[derive(Debug)]
+pub enum Error {
+    /// The parent of an imported header is not in the header chain.
+    UnknownParent(H256),
+    /// The requested block is not in the header chain.
+    UnknownBlock,
+    /// Header failed validation.
+    InvalidHeader(CoreError),
+    /// A response did not match the header it was requested against.
+    BadProof,
+    /// A response was decoded into a different kind than requested.
+    UnexpectedResponse,
+    /// No peer answered the request in time.
+    Timeout,
+    /// The request was dropped before being answered.
+    Canceled,
+    /// The request cannot be served to light clients.
+    NotSupported(&'static str),
+    /// Proved execution of a call failed.
+    Execution(ExecutionError),
+    /// Trie error.
+    Trie(TrieError),
+    /// Decoder error.
+    Decoder(DecoderError),
+    /// Database error.
+    Database(kvdb::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnknownParent(ref hash) => write!(f, "Unknown parent header {}", hash),
+            Error::UnknownBlock => write!(f, "Block is not in the header chain"),
+            Error::InvalidHeader(ref err) => write!(f, "Invalid header: {}", err),
+            Error::BadProof => write!(f, "Response does not prove the request"),
+            Error::UnexpectedResponse => write!(f, "Unexpected response kind"),
+            Error::Timeout => write!(f, "Request timed out"),
+            Error::Canceled => write!(f, "Request canceled"),
+            Error::NotSupported(ref what) => write!(f, "{} not supported in light mode", what),
+            Error::Execution(ref err) => err.fmt(f),
+            Error::Trie(ref err) => err.fmt(f),
+            Error::Decoder(ref err) => err.fmt(f),
+            Error::Database(ref err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<CoreError> for Error {
+    fn from(err: CoreError) -> Self { Error::InvalidHeader(err) }
+}
+
+impl From<ExecutionError> for Error {
+    fn from(err: ExecutionError) -> Self { Error::Execution(err) }
+}
+
+impl From<TrieError> for Error {
+    fn from(err: TrieError) -> Self { Error::Trie(err) }
+}
+
+impl From<DecoderError> for Error {
+    fn from(err: DecoderError) -> Self { Error::Decoder(err) }
+}
+
+impl From<kvdb::Error> for Error {
+    fn from(err: kvdb::Error) -> Self { Error::Database(err) }
+}
+
+impl<E> From<Box<E>> for Error
+where Error: From<E>
+{
+    fn from(err: Box<E>) -> Self { Error::from(*err) }
+}
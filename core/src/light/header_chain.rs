@@ -0,0 +1,262 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Header-only chain kept by light clients.
+//!
+//! Every header is stored together with its total difficulty, keyed by hash. The canonical
+//! chain is indexed by number and follows the header with the highest total difficulty.
+
+use std::sync::Arc;
+
+use aion_types::{H256, U256};
+use byteorder::{BigEndian, ByteOrder};
+use db::COL_LIGHT_CHAIN;
+use encoded;
+use engine::unity_engine::UnityEngine;
+use header::{BlockNumber, Header};
+use kvdb::{DBTransaction, KeyValueDB};
+use parking_lot::{Mutex, RwLock};
+use rlp::{Rlp, RlpStream, UntrustedRlp};
+use types::ids::BlockId;
+use vms::LastHashes;
+
+use super::error::Error;
+
+const BEST_KEY: &'static [u8] = b"best";
+
+/// Best header of the chain.
+#[derive(Debug, Clone)]
+struct BestHeader {
+    hash: H256,
+    number: BlockNumber,
+    total_difficulty: U256,
+}
+
+/// Header chain of a light client.
+pub struct HeaderChain {
+    db: Arc<KeyValueDB>,
+    genesis_hash: H256,
+    unity_update: Option<BlockNumber>,
+    best: RwLock<BestHeader>,
+    import_lock: Mutex<()>,
+}
+
+fn number_key(number: BlockNumber) -> [u8; 8] {
+    let mut key = [0u8; 8];
+    BigEndian::write_u64(&mut key, number);
+    key
+}
+
+fn encode_entry(header: &[u8], total_difficulty: &U256) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(2);
+    stream.append_raw(header, 1);
+    stream.append(total_difficulty);
+    stream.out()
+}
+
+impl HeaderChain {
+    /// Open the header chain stored in `db`, initializing it with the genesis header if empty.
+    pub fn new(
+        db: Arc<KeyValueDB>,
+        genesis: &Header,
+        unity_update: Option<BlockNumber>,
+    ) -> Result<Self, Error>
+    {
+        let genesis_hash = genesis.hash();
+        let best = match db.get(COL_LIGHT_CHAIN, BEST_KEY)? {
+            Some(hash) => {
+                let hash = H256::from_slice(&hash);
+                let entry = db
+                    .get(COL_LIGHT_CHAIN, &hash)?
+                    .ok_or(Error::UnknownBlock)?;
+                let rlp = UntrustedRlp::new(&entry);
+                let header: Header = rlp.val_at(0)?;
+                BestHeader {
+                    hash: hash,
+                    number: header.number(),
+                    total_difficulty: rlp.val_at(1)?,
+                }
+            }
+            None => {
+                let mut batch = DBTransaction::new();
+                batch.put(
+                    COL_LIGHT_CHAIN,
+                    &genesis_hash,
+                    &encode_entry(&genesis.encoded().into_inner(), genesis.difficulty()),
+                );
+                batch.put(COL_LIGHT_CHAIN, &number_key(genesis.number()), &genesis_hash);
+                batch.put(COL_LIGHT_CHAIN, BEST_KEY, &genesis_hash);
+                db.write(batch)?;
+                BestHeader {
+                    hash: genesis_hash,
+                    number: genesis.number(),
+                    total_difficulty: *genesis.difficulty(),
+                }
+            }
+        };
+
+        Ok(HeaderChain {
+            db: db,
+            genesis_hash: genesis_hash,
+            unity_update: unity_update,
+            best: RwLock::new(best),
+            import_lock: Mutex::new(()),
+        })
+    }
+
+    /// Genesis block hash.
+    pub fn genesis_hash(&self) -> H256 { self.genesis_hash }
+
+    /// Hash of the best header.
+    pub fn best_block_hash(&self) -> H256 { self.best.read().hash }
+
+    /// Number of the best header.
+    pub fn best_block_number(&self) -> BlockNumber { self.best.read().number }
+
+    /// Total difficulty of the best header.
+    pub fn best_total_difficulty(&self) -> U256 { self.best.read().total_difficulty }
+
+    /// Hash of the canonical header with the given number.
+    pub fn block_hash(&self, number: BlockNumber) -> Option<H256> {
+        self.db
+            .get(COL_LIGHT_CHAIN, &number_key(number))
+            .expect("Low level database error. Some issue with disk?")
+            .map(|hash| H256::from_slice(&hash))
+    }
+
+    fn entry(&self, hash: &H256) -> Option<(encoded::Header, U256)> {
+        self.db
+            .get(COL_LIGHT_CHAIN, hash)
+            .expect("Low level database error. Some issue with disk?")
+            .map(|entry| {
+                let rlp = Rlp::new(&entry);
+                (
+                    encoded::Header::new(rlp.at(0).as_raw().to_vec()),
+                    rlp.val_at(1),
+                )
+            })
+    }
+
+    /// Header with the given hash, canonical or not.
+    pub fn block_header_data(&self, hash: &H256) -> Option<encoded::Header> {
+        self.entry(hash).map(|(header, _)| header)
+    }
+
+    /// Total difficulty of the header with the given hash.
+    pub fn total_difficulty(&self, hash: &H256) -> Option<U256> {
+        self.entry(hash).map(|(_, td)| td)
+    }
+
+    /// Header of the given block. Pending maps to the best header.
+    pub fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+        let hash = match id {
+            BlockId::Hash(hash) => Some(hash),
+            BlockId::Number(number) => self.block_hash(number),
+            BlockId::Earliest => Some(self.genesis_hash),
+            BlockId::Latest | BlockId::Pending => Some(self.best_block_hash()),
+        };
+        hash.and_then(|hash| self.block_header_data(&hash))
+    }
+
+    /// Hashes of the 256 ancestors of a block, starting with `parent_hash` itself.
+    pub fn last_hashes(&self, parent_hash: &H256) -> Arc<LastHashes> {
+        let mut last_hashes = LastHashes::new();
+        last_hashes.resize(256, H256::default());
+        last_hashes[0] = *parent_hash;
+        let mut hash = *parent_hash;
+        for i in 0..255 {
+            match self.block_header_data(&hash) {
+                Some(header) => {
+                    hash = header.parent_hash();
+                    last_hashes[i + 1] = hash;
+                }
+                None => break,
+            }
+        }
+        Arc::new(last_hashes)
+    }
+
+    /// Verify and insert a header. Returns whether the header was new.
+    pub fn insert(&self, header: &Header) -> Result<bool, Error> {
+        let _lock = self.import_lock.lock();
+
+        let hash = header.hash();
+        if self.entry(&hash).is_some() {
+            return Ok(false);
+        }
+
+        let parent_hash = *header.parent_hash();
+        let (parent, parent_td) = self
+            .entry(&parent_hash)
+            .ok_or(Error::UnknownParent(parent_hash))?;
+        let parent = parent.decode();
+        let grand_parent = self
+            .block_header_data(parent.parent_hash())
+            .map(|header| header.decode());
+        UnityEngine::validate_light_header(
+            header,
+            &parent,
+            grand_parent.as_ref(),
+            self.unity_update,
+        )?;
+
+        let total_difficulty = parent_td + *header.difficulty();
+        let mut batch = DBTransaction::new();
+        batch.put(
+            COL_LIGHT_CHAIN,
+            &hash,
+            &encode_entry(&header.encoded().into_inner(), &total_difficulty),
+        );
+
+        let mut best = self.best.write();
+        if total_difficulty > best.total_difficulty {
+            // rewrite the canonical index back to the common ancestor
+            let number = header.number();
+            batch.put(COL_LIGHT_CHAIN, &number_key(number), &hash);
+            let mut ancestor = parent;
+            while self.block_hash(ancestor.number()) != Some(ancestor.hash()) {
+                batch.put(
+                    COL_LIGHT_CHAIN,
+                    &number_key(ancestor.number()),
+                    &ancestor.hash(),
+                );
+                ancestor = match self.block_header_data(ancestor.parent_hash()) {
+                    Some(header) => header.decode(),
+                    None => break,
+                };
+            }
+            for stale in (number + 1)..(best.number + 1) {
+                batch.delete(COL_LIGHT_CHAIN, &number_key(stale));
+            }
+            batch.put(COL_LIGHT_CHAIN, BEST_KEY, &hash);
+            self.db.write(batch)?;
+            *best = BestHeader {
+                hash: hash,
+                number: number,
+                total_difficulty: total_difficulty,
+            };
+        } else {
+            self.db.write(batch)?;
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,40 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Light client.
+//!
+//! A light client only imports and verifies block headers. Account, storage, call and
+//! receipt queries are sent to full peers, which answer them with merkle proofs that
+//! are checked against the state, receipts or transactions root of a known header.
+//! Calls are proved by re-executing them over the state items read by the server, so
+//! only FastVM calls are supported.
+
+mod client;
+mod error;
+mod header_chain;
+mod on_demand;
+mod request;
+
+pub use self::client::{BoxFuture, LightClient};
+pub use self::error::Error;
+pub use self::header_chain::HeaderChain;
+pub use self::on_demand::{OnDemand, ResponseFuture};
+pub use self::request::{Request, Response, TransactionIndex};
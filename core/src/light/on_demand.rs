@@ -0,0 +1,238 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Dispatch of light requests to peers.
+//!
+//! Requests are queued until the sync layer hands them to a peer. A response is verified
+//! before its future resolves; unanswered, unavailable or badly proved requests go back
+//! to the queue and fail once they ran out of attempts.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use futures::sync::oneshot;
+use futures::Future;
+use engine::Engine;
+use header::BlockNumber;
+use kvdb::KeyValueDB;
+use parking_lot::Mutex;
+
+use super::error::Error;
+use super::header_chain::HeaderChain;
+use super::request::{Request, Response};
+
+/// Number of peers a request is sent to before giving up.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Future of a light request.
+pub type ResponseFuture = Box<Future<Item = Response, Error = Error> + Send>;
+
+struct Pending {
+    request: Request,
+    attempts: usize,
+    sender: oneshot::Sender<Result<Response, Error>>,
+}
+
+struct InFlight {
+    node_hash: u64,
+    sent: SystemTime,
+    pending: Pending,
+}
+
+/// Queue of requests waiting to be answered by peers.
+pub struct OnDemand {
+    chain: Arc<HeaderChain>,
+    engine: Arc<Engine>,
+    kvdb: Arc<KeyValueDB>,
+    pending: Mutex<VecDeque<Pending>>,
+    in_flight: Mutex<HashMap<u64, InFlight>>,
+    next_id: Mutex<u64>,
+}
+
+impl OnDemand {
+    /// Create a new request queue verifying responses against `chain`.
+    pub fn new(chain: Arc<HeaderChain>, engine: Arc<Engine>, kvdb: Arc<KeyValueDB>) -> Self {
+        OnDemand {
+            chain: chain,
+            engine: engine,
+            kvdb: kvdb,
+            pending: Mutex::new(VecDeque::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            next_id: Mutex::new(0),
+        }
+    }
+
+    /// Queue a request. The future resolves with the verified response.
+    pub fn request(&self, request: Request) -> ResponseFuture {
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().push_back(Pending {
+            request: request,
+            attempts: 0,
+            sender: sender,
+        });
+        Box::new(receiver.then(|res| {
+            match res {
+                Ok(res) => res,
+                Err(_) => Err(Error::Canceled),
+            }
+        }))
+    }
+
+    /// Number of requests waiting for a peer or a response.
+    pub fn pending_count(&self) -> usize { self.pending.lock().len() + self.in_flight.lock().len() }
+
+    /// Hand queued requests to peers. `send` gets the request id, the request and the lowest
+    /// best block number a peer needs to serve it, and returns the hash of the node the
+    /// request was sent to, if any.
+    pub fn dispatch<F>(&self, mut send: F)
+    where F: FnMut(u64, &Request, BlockNumber) -> Option<u64> {
+        let mut pending = self.pending.lock();
+        let mut unsent = VecDeque::new();
+        while let Some(req) = pending.pop_front() {
+            if req.sender.is_canceled() {
+                continue;
+            }
+            let required = match req.request.block_hash() {
+                Some(hash) => {
+                    match self.chain.block_header_data(&hash) {
+                        Some(header) => header.number(),
+                        None => {
+                            let _ = req.sender.send(Err(Error::UnknownBlock));
+                            continue;
+                        }
+                    }
+                }
+                None => self.chain.best_block_number(),
+            };
+            let id = {
+                let mut next_id = self.next_id.lock();
+                *next_id += 1;
+                *next_id
+            };
+            match send(id, &req.request, required) {
+                Some(node_hash) => {
+                    self.in_flight.lock().insert(
+                        id,
+                        InFlight {
+                            node_hash: node_hash,
+                            sent: SystemTime::now(),
+                            pending: req,
+                        },
+                    );
+                }
+                None => unsent.push_back(req),
+            }
+        }
+        *pending = unsent;
+    }
+
+    /// Requeue requests not answered within `timeout`.
+    pub fn expire(&self, timeout: Duration) {
+        let now = SystemTime::now();
+        let expired: Vec<u64> = self
+            .in_flight
+            .lock()
+            .iter()
+            .filter(|&(_, req)| {
+                now.duration_since(req.sent)
+                    .map(|elapsed| elapsed > timeout)
+                    .unwrap_or(false)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired {
+            let req = self.in_flight.lock().remove(&id);
+            if let Some(req) = req {
+                debug!(target: "light", "request {} to node {} timed out", id, req.node_hash);
+                self.retry(req.pending, Error::Timeout);
+            }
+        }
+    }
+
+    /// Handle the answer of `node_hash` to request `id`. `None` means the peer could not
+    /// serve the request.
+    pub fn on_response(&self, node_hash: u64, id: u64, payload: Option<&[u8]>) {
+        let req = {
+            let mut in_flight = self.in_flight.lock();
+            match in_flight.get(&id) {
+                Some(req) if req.node_hash == node_hash => {}
+                _ => {
+                    trace!(target: "light", "unexpected response {} from node {}", id, node_hash);
+                    return;
+                }
+            }
+            in_flight
+                .remove(&id)
+                .expect("presence checked above; qed")
+                .pending
+        };
+
+        let payload = match payload {
+            Some(payload) => payload,
+            None => {
+                self.retry(req, Error::Timeout);
+                return;
+            }
+        };
+
+        match req.request.check_response(
+            &self.chain,
+            self.engine.machine(),
+            self.kvdb.clone(),
+            payload,
+        ) {
+            Ok(response) => {
+                let _ = req.sender.send(Ok(response));
+            }
+            Err(err) => {
+                debug!(target: "light", "bad response {} from node {}: {}", id, node_hash, err);
+                self.retry(req, err);
+            }
+        }
+    }
+
+    /// Requeue the requests in flight to a disconnected node.
+    pub fn on_disconnect(&self, node_hash: u64) {
+        let ids: Vec<u64> = self
+            .in_flight
+            .lock()
+            .iter()
+            .filter(|&(_, req)| req.node_hash == node_hash)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in ids {
+            let req = self.in_flight.lock().remove(&id);
+            if let Some(req) = req {
+                self.pending.lock().push_back(req.pending);
+            }
+        }
+    }
+
+    fn retry(&self, mut req: Pending, err: Error) {
+        req.attempts += 1;
+        if req.attempts >= MAX_ATTEMPTS {
+            let _ = req.sender.send(Err(err));
+        } else {
+            self.pending.lock().push_back(req);
+        }
+    }
+}
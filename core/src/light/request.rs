@@ -0,0 +1,531 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Requests a light client sends to full nodes, and the verification of their responses.
+//!
+//! Responses carry the merkle proofs of the requested data. Each response is checked
+//! against a header of the local header chain before it is handed out.
+
+use std::sync::Arc;
+
+use acore_bytes::Bytes;
+use aion_types::{Address, H128, H256, U256};
+use blake2b::blake2b;
+use client::{BlockId, ProvingBlockChainClient, TransactionId};
+use encoded;
+use executive::Executed;
+use header::BlockNumber;
+use kvdb::{DBValue, KeyValueDB};
+use machine::EthereumMachine;
+use receipt::Receipt;
+use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
+use state::{self, BasicAccount, ProvedExecution};
+use state::backend::ProofCheck;
+use transaction::{Action, SignedTransaction, Transaction};
+use trie::{Trie, TrieDB};
+use triehash::ordered_trie_root;
+use types::executed::ExecutionError;
+use vms::EnvInfo;
+
+use super::error::Error;
+use super::header_chain::HeaderChain;
+
+const KIND_ACCOUNT: u8 = 0;
+const KIND_STORAGE: u8 = 1;
+const KIND_EXECUTION: u8 = 2;
+const KIND_TRANSACTION_INDEX: u8 = 3;
+const KIND_RECEIPTS: u8 = 4;
+const KIND_BODY: u8 = 5;
+
+/// A request for data a light client cannot compute itself.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    /// Account of `address` in the state of the given block.
+    Account {
+        /// Block hash.
+        block_hash: H256,
+        /// Account address.
+        address: Address,
+    },
+    /// Storage value of `key` of `address` in the state of the given block.
+    Storage {
+        /// Block hash.
+        block_hash: H256,
+        /// Account address.
+        address: Address,
+        /// Storage key.
+        key: H128,
+    },
+    /// Proof of a virtual call on top of the state of the given block.
+    Execution {
+        /// Block hash.
+        block_hash: H256,
+        /// Transaction to execute. It does not need a valid signature.
+        transaction: SignedTransaction,
+    },
+    /// Location of a transaction on the chain.
+    TransactionIndex {
+        /// Transaction hash.
+        hash: H256,
+    },
+    /// Receipts of the given block.
+    Receipts {
+        /// Block hash.
+        block_hash: H256,
+    },
+    /// Body of the given block.
+    Body {
+        /// Block hash.
+        block_hash: H256,
+    },
+}
+
+/// Location of a transaction on the chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionIndex {
+    /// Block number.
+    pub block_number: BlockNumber,
+    /// Block hash.
+    pub block_hash: H256,
+    /// Index of the transaction within the block.
+    pub index: usize,
+}
+
+/// A verified response.
+#[derive(Debug, Clone)]
+pub enum Response {
+    /// Account, `None` if it does not exist.
+    Account(Option<BasicAccount>),
+    /// Storage value with leading zeros stripped, empty if unset.
+    Storage(Bytes),
+    /// Result of the proved execution.
+    Execution(Result<Executed, ExecutionError>),
+    /// Location of a transaction. Not provable, check it against the block body.
+    TransactionIndex(TransactionIndex),
+    /// Block receipts.
+    Receipts(Vec<Receipt>),
+    /// Block body.
+    Body(encoded::Body),
+}
+
+impl Request {
+    /// Hash of the block whose header proves the response, if any.
+    pub fn block_hash(&self) -> Option<H256> {
+        match *self {
+            Request::Account {
+                block_hash, ..
+            }
+            | Request::Storage {
+                block_hash, ..
+            }
+            | Request::Execution {
+                block_hash, ..
+            }
+            | Request::Receipts {
+                block_hash,
+            }
+            | Request::Body {
+                block_hash,
+            } => Some(block_hash),
+            Request::TransactionIndex {
+                ..
+            } => None,
+        }
+    }
+
+    /// Serve the request from a full client. Returns `None` if the data is unavailable.
+    pub fn serve(&self, provider: &ProvingBlockChainClient) -> Option<Bytes> {
+        match *self {
+            Request::Account {
+                block_hash,
+                address,
+            } => {
+                let (proof, _) =
+                    provider.prove_account(blake2b(&address), BlockId::Hash(block_hash))?;
+                let mut stream = RlpStream::new_list(proof.len());
+                for node in proof {
+                    stream.append(&node);
+                }
+                Some(stream.out())
+            }
+            Request::Storage {
+                block_hash,
+                address,
+                key,
+            } => {
+                let id = BlockId::Hash(block_hash);
+                let account_key = blake2b(&address);
+                let (account_proof, _) = provider.prove_account(account_key, id)?;
+                let (storage_proof, _) = provider.prove_storage(account_key, blake2b(&key), id)?;
+                let mut stream = RlpStream::new_list(2);
+                stream.append_list::<Bytes, Bytes>(&account_proof);
+                stream.append_list::<Bytes, Bytes>(&storage_proof);
+                Some(stream.out())
+            }
+            Request::Execution {
+                block_hash,
+                ref transaction,
+            } => {
+                let (_, proof) =
+                    provider.prove_transaction(transaction.clone(), BlockId::Hash(block_hash))?;
+                let mut stream = RlpStream::new_list(proof.len());
+                for item in proof {
+                    stream.append(&item.to_vec());
+                }
+                Some(stream.out())
+            }
+            Request::TransactionIndex {
+                hash,
+            } => {
+                let tx = provider.transaction(TransactionId::Hash(hash))?;
+                let mut stream = RlpStream::new_list(3);
+                stream.append(&tx.block_number);
+                stream.append(&tx.block_hash);
+                stream.append(&tx.transaction_index);
+                Some(stream.out())
+            }
+            Request::Receipts {
+                block_hash,
+            } => provider.block_receipts(&block_hash),
+            Request::Body {
+                block_hash,
+            } => {
+                provider
+                    .block_body(BlockId::Hash(block_hash))
+                    .map(|body| body.into_inner())
+            }
+        }
+    }
+
+    /// Verify a response against the header chain.
+    pub fn check_response(
+        &self,
+        chain: &HeaderChain,
+        machine: &EthereumMachine,
+        kvdb: Arc<KeyValueDB>,
+        payload: &[u8],
+    ) -> Result<Response, Error>
+    {
+        let payload = UntrustedRlp::new(payload);
+        let header = match self.block_hash() {
+            Some(hash) => {
+                Some(
+                    chain
+                        .block_header_data(&hash)
+                        .ok_or(Error::UnknownBlock)?,
+                )
+            }
+            None => None,
+        };
+        let header = header.as_ref();
+
+        match *self {
+            Request::Account {
+                address, ..
+            } => {
+                let header = header.expect("account requests name a block; qed");
+                let proof = proof_items(&payload)?;
+                let account = check_account(&proof, &header.state_root(), &address)?;
+                Ok(Response::Account(account))
+            }
+            Request::Storage {
+                address,
+                key,
+                ..
+            } => {
+                let header = header.expect("storage requests name a block; qed");
+                let account_proof = proof_items(&payload.at(0)?)?;
+                let storage_proof = proof_items(&payload.at(1)?)?;
+                let storage_root = match check_account(
+                    &account_proof,
+                    &header.state_root(),
+                    &address,
+                )? {
+                    Some(account) => account.storage_root,
+                    None => return Ok(Response::Storage(Bytes::new())),
+                };
+                let db = ProofCheck::new(&storage_proof);
+                let trie = TrieDB::new(&db, &storage_root)?;
+                let value: Option<Bytes> = trie.get_with(&blake2b(&key), ::rlp::decode)?;
+                Ok(Response::Storage(value.unwrap_or_else(Bytes::new)))
+            }
+            Request::Execution {
+                ref transaction,
+                ..
+            } => {
+                let header = header.expect("execution requests name a block; qed");
+                let proof = proof_items(&payload)?;
+                let env_info = EnvInfo {
+                    number: header.number(),
+                    author: header.author(),
+                    timestamp: header.timestamp(),
+                    difficulty: header.difficulty(),
+                    last_hashes: chain.last_hashes(&header.parent_hash()),
+                    gas_used: U256::default(),
+                    gas_limit: U256::max_value(),
+                };
+                match state::check_proof(
+                    &proof,
+                    header.state_root(),
+                    transaction,
+                    machine,
+                    &env_info,
+                    kvdb,
+                ) {
+                    ProvedExecution::BadProof => Err(Error::BadProof),
+                    ProvedExecution::Failed(err) => Ok(Response::Execution(Err(err))),
+                    ProvedExecution::Complete(executed) => Ok(Response::Execution(Ok(executed))),
+                }
+            }
+            Request::TransactionIndex {
+                ..
+            } => {
+                Ok(Response::TransactionIndex(TransactionIndex {
+                    block_number: payload.val_at(0)?,
+                    block_hash: payload.val_at(1)?,
+                    index: payload.val_at(2)?,
+                }))
+            }
+            Request::Receipts {
+                ..
+            } => {
+                let header = header.expect("receipt requests name a block; qed");
+                let receipts: Vec<Receipt> = payload.as_list()?;
+                let root = ordered_trie_root(
+                    receipts
+                        .iter()
+                        .map(|r| r.simple_receipt().rlp_bytes()),
+                );
+                if root != header.receipts_root() {
+                    return Err(Error::BadProof);
+                }
+                Ok(Response::Receipts(receipts))
+            }
+            Request::Body {
+                ..
+            } => {
+                let header = header.expect("body requests name a block; qed");
+                let root = ordered_trie_root(payload.at(0)?.iter().map(|tx| tx.as_raw()));
+                if root != header.transactions_root() {
+                    return Err(Error::BadProof);
+                }
+                Ok(Response::Body(encoded::Body::new(
+                    payload.as_raw().to_vec(),
+                )))
+            }
+        }
+    }
+}
+
+fn proof_items(rlp: &UntrustedRlp) -> Result<Vec<DBValue>, DecoderError> {
+    rlp.iter()
+        .map(|item| item.data().map(DBValue::from_slice))
+        .collect()
+}
+
+fn check_account(
+    proof: &[DBValue],
+    state_root: &H256,
+    address: &Address,
+) -> Result<Option<BasicAccount>, Error>
+{
+    let db = ProofCheck::new(proof);
+    let trie = TrieDB::new(&db, state_root)?;
+    Ok(trie.get_with(&blake2b(address), ::rlp::decode)?)
+}
+
+impl Encodable for Request {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        match *self {
+            Request::Account {
+                ref block_hash,
+                ref address,
+            } => {
+                s.begin_list(3);
+                s.append(&KIND_ACCOUNT);
+                s.append(block_hash);
+                s.append(address);
+            }
+            Request::Storage {
+                ref block_hash,
+                ref address,
+                ref key,
+            } => {
+                s.begin_list(4);
+                s.append(&KIND_STORAGE);
+                s.append(block_hash);
+                s.append(address);
+                s.append(key);
+            }
+            Request::Execution {
+                ref block_hash,
+                ref transaction,
+            } => {
+                s.begin_list(11);
+                s.append(&KIND_EXECUTION);
+                s.append(block_hash);
+                s.append(transaction.sender());
+                s.append(&transaction.nonce);
+                s.append(&transaction.gas_price);
+                s.append(&transaction.gas);
+                s.append(&transaction.action);
+                s.append(&transaction.value);
+                s.append(&transaction.data);
+                s.append(&transaction.transaction_type);
+                match transaction.beacon {
+                    Some(ref beacon) => s.append(beacon),
+                    None => s.append_empty_data(),
+                };
+            }
+            Request::TransactionIndex {
+                ref hash,
+            } => {
+                s.begin_list(2);
+                s.append(&KIND_TRANSACTION_INDEX);
+                s.append(hash);
+            }
+            Request::Receipts {
+                ref block_hash,
+            } => {
+                s.begin_list(2);
+                s.append(&KIND_RECEIPTS);
+                s.append(block_hash);
+            }
+            Request::Body {
+                ref block_hash,
+            } => {
+                s.begin_list(2);
+                s.append(&KIND_BODY);
+                s.append(block_hash);
+            }
+        }
+    }
+}
+
+impl Decodable for Request {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        let kind: u8 = rlp.val_at(0)?;
+        match kind {
+            KIND_ACCOUNT => {
+                Ok(Request::Account {
+                    block_hash: rlp.val_at(1)?,
+                    address: rlp.val_at(2)?,
+                })
+            }
+            KIND_STORAGE => {
+                Ok(Request::Storage {
+                    block_hash: rlp.val_at(1)?,
+                    address: rlp.val_at(2)?,
+                    key: rlp.val_at(3)?,
+                })
+            }
+            KIND_EXECUTION => {
+                let sender: Address = rlp.val_at(2)?;
+                let beacon = rlp.at(10)?;
+                let beacon = if beacon.is_empty() {
+                    None
+                } else {
+                    Some(beacon.as_val()?)
+                };
+                let transaction = Transaction::new(
+                    rlp.val_at(3)?,
+                    rlp.val_at(4)?,
+                    rlp.val_at(5)?,
+                    rlp.val_at::<Action>(6)?,
+                    rlp.val_at(7)?,
+                    rlp.val_at(8)?,
+                    rlp.val_at(9)?,
+                    beacon,
+                );
+                Ok(Request::Execution {
+                    block_hash: rlp.val_at(1)?,
+                    transaction: transaction.fake_sign(sender),
+                })
+            }
+            KIND_TRANSACTION_INDEX => {
+                Ok(Request::TransactionIndex {
+                    hash: rlp.val_at(1)?,
+                })
+            }
+            KIND_RECEIPTS => {
+                Ok(Request::Receipts {
+                    block_hash: rlp.val_at(1)?,
+                })
+            }
+            KIND_BODY => {
+                Ok(Request::Body {
+                    block_hash: rlp.val_at(1)?,
+                })
+            }
+            _ => Err(DecoderError::Custom("Unknown light request kind")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rlp;
+
+    #[test]
+    fn request_rlp_roundtrip() {
+        let requests = vec![
+            Request::Account {
+                block_hash: H256::from(1),
+                address: Address::from(2),
+            },
+            Request::Storage {
+                block_hash: H256::from(1),
+                address: Address::from(2),
+                key: H128::from(3),
+            },
+            Request::Execution {
+                block_hash: H256::from(1),
+                transaction: Transaction::new(
+                    U256::from(1),
+                    U256::from(10_000_000_000u64),
+                    U256::from(21_000),
+                    Action::Call(Address::from(2)),
+                    U256::from(5),
+                    vec![1, 2, 3],
+                    U256::from(1),
+                    None,
+                )
+                .fake_sign(Address::from(4)),
+            },
+            Request::TransactionIndex {
+                hash: H256::from(5),
+            },
+            Request::Receipts {
+                block_hash: H256::from(6),
+            },
+            Request::Body {
+                block_hash: H256::from(7),
+            },
+        ];
+
+        for request in requests {
+            let encoded = rlp::encode(&request);
+            let decoded: Request = rlp::decode(&encoded);
+            assert_eq!(decoded, request);
+        }
+    }
+}
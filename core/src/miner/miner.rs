@@ -37,7 +37,7 @@ use engine::Engine;
 use header::{BlockNumber, Header, SealType};
 use types::error::*;
 use io::IoChannel;
-use miner::{MinerService, MinerStatus};
+use miner::{LocalTransactionStatus, MinerService, MinerStatus};
 use parking_lot::{Mutex, RwLock};
 use receipt::Receipt;
 use spec::Spec;
@@ -51,9 +51,11 @@ use transaction::{
 };
 use transaction::banning_queue::{BanningTransactionQueue, Threshold};
 use transaction::local_transactions::TxIoMessage;
+use transaction::queue_policy::QueuePolicy;
 use transaction::transaction_pool::TransactionPool;
 use transaction::transaction_queue::{
-    AccountDetails, PrioritizationStrategy, RemovalReason, TransactionOrigin, TransactionQueue,
+    AccountDetails, PrioritizationStrategy, QueueQuotas, RemovalReason, TransactionOrigin,
+    TransactionQueue,
 };
 use using_queue::{GetAction, UsingQueue};
 use rcrypto::ed25519;
@@ -107,8 +109,10 @@ pub struct MinerOptions {
     pub tx_gas_limit: U256,
     /// Maximum memory usage of transactions in the queue (current / future).
     pub tx_queue_memory_limit: Option<usize>,
-    /// Strategy to use for prioritizing transactions in the queue.
-    pub tx_queue_strategy: PrioritizationStrategy,
+    /// Policy to use for prioritizing and admitting transactions in the queue.
+    pub tx_queue_strategy: QueuePolicy,
+    /// Per sender and per recipient limits of transactions in the queue.
+    pub tx_queue_quotas: QueueQuotas,
     /// Whether we should fallback to providing all the queue's transactions or just pending.
     pub pending_set: PendingSet,
     /// How many historical work packages can we store before running out?
@@ -137,7 +141,8 @@ impl Default for MinerOptions {
             force_sealing: false,
             tx_gas_limit: !U256::zero(),
             tx_queue_memory_limit: Some(2 * 1024 * 1024),
-            tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+            tx_queue_strategy: QueuePolicy::Strategy(PrioritizationStrategy::GasPriceOnly),
+            tx_queue_quotas: QueueQuotas::default(),
             pending_set: PendingSet::AlwaysQueue,
             reseal_min_period: Duration::from_secs(4),
             prepare_block_interval: Duration::from_secs(4),
@@ -494,11 +499,12 @@ impl Miner {
             .tx_queue_memory_limit
             .unwrap_or_else(usize::max_value);
 
-        let transaction_queue = TransactionQueue::with_limits(
-            options.tx_queue_strategy,
+        let mut transaction_queue = TransactionQueue::with_limits(
+            options.tx_queue_strategy.build(),
             mem_limit,
             Mutex::new(message_channel.clone()),
         );
+        transaction_queue.set_quotas(options.tx_queue_quotas);
         let transaction_queue = match options.tx_queue_banning {
             Banning::Disabled => {
                 BanningTransactionQueue::new(
@@ -1058,6 +1064,31 @@ impl Miner {
         )
     }
 
+    /// Get local transactions waiting in the queue, to be journaled across restarts
+    pub fn local_pending_transactions(&self) -> Vec<PendingTransaction> {
+        self.transaction_pool.local_pending_transactions()
+    }
+
+    /// Re-inject journaled local transactions into the queue
+    pub fn restore_local_transactions(
+        &self,
+        client: &MiningBlockChainClient,
+        transactions: Vec<PendingTransaction>,
+    )
+    {
+        for pending in transactions {
+            let hash = pending.hash().clone();
+            if let Err(e) = self.add_transaction_to_queue(
+                client,
+                pending.transaction,
+                TransactionOrigin::Local,
+                pending.condition,
+            ) {
+                debug!(target: "own_tx", "Journaled transaction {:?} not restored: {:?}", hash, e);
+            }
+        }
+    }
+
     #[cfg(test)]
     /// Replace tx message channel. Useful for testing.
     pub fn set_tx_message_channel(&self, tx_message: IoChannel<TxIoMessage>) {
@@ -1152,27 +1183,29 @@ impl MinerService for Miner {
     ) -> Vec<Result<(), Error>>
     {
         trace!(target: "client", "Importing external transactions");
-        let mut is_imported: bool = false;
+        let mut imported_hashes = Vec::new();
 
         let results = transactions
             .into_iter()
             .map(|unverified_transaction| {
                 self.verify_transaction(client, unverified_transaction)
                     .and_then(|transaction| {
+                        let hash = transaction.hash().clone();
                         let import_result = self.add_transaction_to_queue(
                             client,
                             transaction,
                             TransactionOrigin::External,
                             None,
                         );
-                        if !is_imported && import_result.is_ok() {
-                            is_imported = true;
+                        if import_result.is_ok() {
+                            imported_hashes.push(hash);
                         }
                         import_result
                     })
             })
             .collect();
 
+        client.notify_transactions_imported(imported_hashes);
         results
     }
 
@@ -1201,6 +1234,7 @@ impl MinerService for Miner {
             Ok(_) => {
                 debug!(target: "rpc_tx", "{:?} tx start broadcast [{:?}]", thread::current().id(), time::Instant::now());
                 client.broadcast_transaction(::rlp::encode(&pending.transaction).into_vec());
+                client.notify_transactions_imported(vec![pending.hash().clone()]);
             }
             Err(ref e) => {
                 let _ = self.tx_message.lock().send(TxIoMessage::Dropped {
@@ -1221,9 +1255,9 @@ impl MinerService for Miner {
             .pending_transactions(BlockNumber::max_value(), u64::max_value())
     }
 
-    //    fn local_transactions(&self) -> HashMap<H256, LocalTransactionStatus> {
-    //        self.transaction_pool.local_transactions()
-    //    }
+    fn local_transactions(&self) -> HashMap<H256, LocalTransactionStatus> {
+        self.transaction_pool.local_transactions()
+    }
 
     // Return all future transactions, and transfer them to Pending
     fn future_transactions(&self) -> Vec<PendingTransaction> {
@@ -1347,6 +1381,14 @@ impl MinerService for Miner {
     //            .remove_transaction(hash, RemovalReason::Canceled);
     //    }
 
+    fn local_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+        self.transaction_pool.local_transaction(hash)
+    }
+
+    fn cancel_transaction(&self, hash: &H256) -> bool {
+        self.transaction_pool.cancel_transaction(hash)
+    }
+
     //    fn pending_receipt(&self, best_block: BlockNumber, hash: &H256) -> Option<RichReceipt> {
     //        self.from_pending_block(
     //            best_block,
@@ -1774,7 +1816,7 @@ mod tests {
     use block::IsBlock;
     use io::IoChannel;
     use keychain;
-    use miner::{Miner, MinerService};
+    use miner::{LocalTransactionStatus, Miner, MinerService};
     use rustc_hex::FromHex;
     use spec::Spec;
     use std::sync::Arc;
@@ -1786,7 +1828,8 @@ mod tests {
     use transaction::{PendingTransaction, SignedTransaction, Error as TransactionError};
     use transaction::Action;
     use transaction::Transaction;
-    use transaction::transaction_queue::PrioritizationStrategy;
+    use transaction::queue_policy::QueuePolicy;
+    use transaction::transaction_queue::{PrioritizationStrategy, QueueQuotas};
 
     #[test]
     fn should_prepare_block_to_seal() {
@@ -1828,7 +1871,10 @@ mod tests {
                 prepare_block_interval: Duration::from_secs(5),
                 tx_gas_limit: !U256::zero(),
                 tx_queue_memory_limit: None,
-                tx_queue_strategy: PrioritizationStrategy::GasFactorAndGasPrice,
+                tx_queue_strategy: QueuePolicy::Strategy(
+                    PrioritizationStrategy::GasFactorAndGasPrice,
+                ),
+                tx_queue_quotas: QueueQuotas::default(),
                 pending_set: PendingSet::AlwaysSealing,
                 work_queue_size: 50,
                 enable_resubmission: true,
@@ -1855,7 +1901,10 @@ mod tests {
                 prepare_block_interval: Duration::from_secs(5),
                 tx_gas_limit: !U256::zero(),
                 tx_queue_memory_limit: None,
-                tx_queue_strategy: PrioritizationStrategy::GasFactorAndGasPrice,
+                tx_queue_strategy: QueuePolicy::Strategy(
+                    PrioritizationStrategy::GasFactorAndGasPrice,
+                ),
+                tx_queue_quotas: QueueQuotas::default(),
                 pending_set: PendingSet::AlwaysSealing,
                 work_queue_size: 5,
                 enable_resubmission: true,
@@ -1954,6 +2003,85 @@ mod tests {
         assert_eq!(miner.pending_receipts(best_block).len(), 0);
     }
 
+    #[test]
+    fn should_cancel_local_transaction() {
+        // given
+        let client = TestBlockChainClient::default();
+        let miner = miner();
+        let transaction = transaction();
+        let hash = transaction.hash().clone();
+        let external = transaction();
+        let external_hash = external.hash().clone();
+        let res = miner.import_own_transaction(&client, PendingTransaction::new(transaction, None));
+        assert!(res.is_ok());
+        miner.import_external_transactions(&client, vec![external.into()]);
+        miner.update_transaction_pool(&client, true);
+        assert!(miner.local_transaction(&hash).is_some());
+        assert!(miner.local_transaction(&external_hash).is_none());
+
+        // when
+        assert!(!miner.cancel_transaction(&external_hash));
+        assert!(miner.cancel_transaction(&hash));
+        miner.update_transaction_pool(&client, true);
+
+        // then
+        assert!(miner.local_transaction(&hash).is_none());
+        assert!(!miner.cancel_transaction(&hash));
+        assert_eq!(
+            miner.local_transactions().get(&hash),
+            Some(&LocalTransactionStatus::Canceled)
+        );
+    }
+
+    #[test]
+    fn should_replace_local_transaction_with_higher_gas_price_at_same_nonce() {
+        // given
+        let client = TestBlockChainClient::default();
+        let miner = miner();
+        let keypair = keychain::ethkey::generate_keypair();
+        let transaction = |gas_price: U256| {
+            Transaction {
+                action: Action::Create,
+                value: U256::zero(),
+                data: "3331600055".from_hex().unwrap(),
+                gas: U256::from(300_000),
+                gas_price,
+                nonce: U256::zero(),
+                transaction_type: ::transaction::DEFAULT_TRANSACTION_TYPE,
+                nonce_bytes: Vec::new(),
+                gas_price_bytes: Vec::new(),
+                gas_bytes: Vec::new(),
+                value_bytes: Vec::new(),
+                beacon: None,
+            }
+            .sign(keypair.secret())
+        };
+        let original = transaction(default_gas_price());
+        let hash = original.hash().clone();
+        let res = miner.import_own_transaction(&client, PendingTransaction::new(original, None));
+        assert!(res.is_ok());
+        miner.update_transaction_pool(&client, true);
+
+        // when
+        let replacement = transaction(default_gas_price() + 1);
+        let replacement_hash = replacement.hash().clone();
+        let res =
+            miner.import_own_transaction(&client, PendingTransaction::new(replacement, None));
+        miner.update_transaction_pool(&client, true);
+
+        // then
+        assert!(res.is_ok());
+        assert!(miner.local_transaction(&hash).is_none());
+        assert_eq!(
+            miner.local_transaction(&replacement_hash).map(|tx| tx.nonce),
+            Some(U256::zero())
+        );
+        assert_eq!(
+            miner.local_transactions().get(&hash),
+            Some(&LocalTransactionStatus::Replaced)
+        );
+    }
+
     //
     #[test]
     fn should_import_external_transaction() {
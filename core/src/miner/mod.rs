@@ -26,7 +26,7 @@ pub mod external;
 pub use self::miner::{Miner, MinerOptions, Banning, PendingSet};
 pub use transaction::local_transactions::Status as LocalTransactionStatus;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
 use aion_types::{H256, U256, Address};
 use acore_bytes::Bytes;
@@ -192,6 +192,13 @@ pub trait MinerService: Send + Sync {
     /// NOTE: The transaction is not removed from pending block if mining.
     //    fn remove_pending_transaction(&self, hash: H256);
 
+    /// Query local transactions still waiting in the queue for hash.
+    fn local_transaction(&self, hash: &H256) -> Option<PendingTransaction>;
+
+    /// Removes a local transaction from the queue, returns false if it is not queued.
+    /// NOTE: The transaction is not removed from pending block if mining.
+    fn cancel_transaction(&self, hash: &H256) -> bool;
+
     /// Get a list of all pending transactions in the queue.
     fn pending_transactions(&self) -> Vec<PendingTransaction>;
 
@@ -206,7 +213,7 @@ pub trait MinerService: Send + Sync {
     fn future_transactions(&self) -> Vec<PendingTransaction>;
 
     /// Get a list of local transactions with statuses.
-    //    fn local_transactions(&self) -> HashMap<H256, LocalTransactionStatus>;
+    fn local_transactions(&self) -> HashMap<H256, LocalTransactionStatus>;
 
     /// Get a list of all pending receipts.
     fn pending_receipts(&self, best_block: BlockNumber) -> BTreeMap<H256, Receipt>;
@@ -164,15 +164,8 @@ pub fn diff_pod(pre: Option<&PodAccount>, post: Option<&PodAccount>) -> Option<A
             Some(AccountDiff {
                 balance: Diff::Born(x.balance),
                 nonce: Diff::Born(x.nonce),
-                code: Diff::Born(
-                    x.code
-                        .as_ref()
-                        .expect(
-                            "account is newly created; newly created accounts must be given code; \
-                             all caches should remain in place; qed",
-                        )
-                        .clone(),
-                ),
+                // accounts without code, e.g. created by a plain transfer, have no code cached.
+                code: Diff::Born(x.code.clone().unwrap_or_else(Vec::new)),
                 storage: x
                     .storage
                     .iter()
@@ -184,16 +177,7 @@ pub fn diff_pod(pre: Option<&PodAccount>, post: Option<&PodAccount>) -> Option<A
             Some(AccountDiff {
                 balance: Diff::Died(x.balance),
                 nonce: Diff::Died(x.nonce),
-                code: Diff::Died(
-                    x.code
-                        .as_ref()
-                        .expect(
-                            "account is deleted; only way to delete account is running SUICIDE; \
-                             account must have had own code cached to make operation; all caches \
-                             should remain in place; qed",
-                        )
-                        .clone(),
-                ),
+                code: Diff::Died(x.code.clone().unwrap_or_else(Vec::new)),
                 storage: x
                     .storage
                     .iter()
@@ -114,6 +114,7 @@ pub fn run_transaction_pool(executor: TaskExecutor, client: Arc<Client>) -> ones
         .for_each(move |_| {
             let client: Arc<Client> = client.clone();
             client.miner().update_transaction_pool(&*client, false);
+            client.journal_local_transactions();
             Ok(())
         })
         .map_err(|e| panic!("interval err: {:?}", e))
@@ -171,6 +172,7 @@ impl ClientService {
             .map_err(|_e| Error::Database(DbError::Other(format!("db is not correct"))))?;
 
         let client = Client::new(config, &spec, dbs.clone(), miner, io_service.channel())?;
+        client.restore_local_transactions();
 
         let client_io = Arc::new(ClientIoHandler {
             client: client.clone(),
@@ -0,0 +1,165 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Account state encoding and decoding for snapshots.
+//!
+//! Each account is written as a "fat" rlp list carrying everything needed to rebuild it
+//! without access to the original database:
+//!
+//! `[account_key, account_rlp, code, [[storage_key, storage_value], ...], [object_graph]]`
+//!
+//! `account_rlp` is the raw value of the state trie. For AVM accounts its storage root
+//! slot holds the delta root, and the real storage root and object graph live in the
+//! `avm_graph` column; the last element then holds the object graph, otherwise it is empty.
+//! Transformed AVM code is not carried, as nothing in the state commits to it; the AVM
+//! transforms the verified code again the first time the contract is called.
+
+use aion_types::H256;
+use acore_bytes::Bytes;
+use blake2b::{BLAKE2B_EMPTY, BLAKE2B_NULL_RLP, blake2b};
+use kvdb::{DBTransaction, DBValue, HashStore, KeyValueDB};
+use rlp::{RlpStream, UntrustedRlp};
+use state::BasicAccount;
+use trie::{Trie, TrieDB, TrieDBMut, TrieMut};
+
+use super::Error;
+
+/// Encode a single account together with its code, storage and AVM object graph.
+/// `acct_db` must be the account database of `account_key`.
+pub fn to_fat_rlp(
+    account_key: &H256,
+    account_rlp: &[u8],
+    acct_db: &HashStore,
+    graph_db: &KeyValueDB,
+) -> Result<Bytes, Error>
+{
+    let basic: BasicAccount = UntrustedRlp::new(account_rlp).as_val()?;
+
+    let (storage_root, object_graph) =
+        match graph_db.get(::db::COL_AVM_GRAPH, &basic.storage_root)? {
+            Some(roots) => {
+                let roots: Vec<H256> = UntrustedRlp::new(&roots).as_list()?;
+                if roots.len() != 2 {
+                    return Err(Error::MissingObjectGraph(basic.storage_root));
+                }
+                let graph = graph_db
+                    .get(::db::COL_AVM_GRAPH, &roots[1])?
+                    .ok_or(Error::MissingObjectGraph(basic.storage_root))?;
+                (roots[0], Some(graph.into_vec()))
+            }
+            None => (basic.storage_root, None),
+        };
+
+    let code = if basic.code_hash == BLAKE2B_EMPTY {
+        Vec::new()
+    } else {
+        acct_db
+            .get(&basic.code_hash)
+            .ok_or(Error::MissingCode(basic.code_hash))?
+            .into_vec()
+    };
+
+    let mut storage = Vec::new();
+    let trie = TrieDB::new(acct_db, &storage_root)?;
+    for item in trie.iter()? {
+        let (key, value) = item?;
+        storage.push((key, value));
+    }
+
+    let mut stream = RlpStream::new_list(5);
+    stream.append(account_key);
+    stream.append(&account_rlp);
+    stream.append(&code);
+    stream.begin_list(storage.len());
+    for (key, value) in storage {
+        stream.begin_list(2);
+        stream.append(&key);
+        stream.append(&&*value);
+    }
+    match object_graph {
+        Some(graph) => {
+            stream.begin_list(1);
+            stream.append(&graph);
+        }
+        None => {
+            stream.begin_list(0);
+        }
+    }
+
+    Ok(stream.out())
+}
+
+/// Decode a fat account, writing its code and storage into `acct_db` and its AVM object
+/// graph into `graph_batch`. `acct_db` must be the account database of the decoded account
+/// key. Returns the account key and the raw account rlp to be inserted into the state trie.
+pub fn from_fat_rlp(
+    acct_db: &mut HashStore,
+    graph_batch: &mut DBTransaction,
+    rlp: UntrustedRlp,
+) -> Result<(H256, Bytes), Error>
+{
+    let account_key: H256 = rlp.val_at(0)?;
+    let account_rlp: Bytes = rlp.val_at(1)?;
+    let basic: BasicAccount = UntrustedRlp::new(&account_rlp).as_val()?;
+
+    let code: Bytes = rlp.val_at(2)?;
+    if !code.is_empty() {
+        let code_hash = blake2b(&code);
+        if code_hash != basic.code_hash {
+            return Err(Error::MissingCode(basic.code_hash));
+        }
+        acct_db.emplace(code_hash, DBValue::from_slice(&code));
+    }
+
+    let mut storage_root = BLAKE2B_NULL_RLP;
+    {
+        let mut storage_trie = TrieDBMut::new(acct_db, &mut storage_root);
+        for pair in rlp.at(3)?.iter() {
+            let key: Bytes = pair.val_at(0)?;
+            let value: Bytes = pair.val_at(1)?;
+            storage_trie.insert(&key, &value)?;
+        }
+    }
+
+    let object_graph = rlp.at(4)?;
+    if object_graph.item_count()? == 1 {
+        let graph: Bytes = object_graph.val_at(0)?;
+        let graph_hash = blake2b(&graph);
+
+        let mut concatenated_root = Vec::new();
+        concatenated_root.extend_from_slice(&storage_root[..]);
+        concatenated_root.extend_from_slice(&graph_hash[..]);
+        let delta_root = blake2b(&concatenated_root);
+        if delta_root != basic.storage_root {
+            return Err(Error::StorageRootMismatch(basic.storage_root, delta_root));
+        }
+
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&storage_root);
+        stream.append(&graph_hash);
+        graph_batch.put(::db::COL_AVM_GRAPH, &delta_root[..], &stream.out());
+        graph_batch.put(::db::COL_AVM_GRAPH, &graph_hash[..], &graph);
+    } else if storage_root != basic.storage_root {
+        return Err(Error::StorageRootMismatch(basic.storage_root, storage_root));
+    }
+
+    Ok((account_key, account_rlp))
+}
@@ -0,0 +1,178 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Snapshot-related errors.
+
+use std::fmt;
+
+use aion_types::H256;
+use trie::TrieError;
+use rlp::DecoderError;
+use kvdb;
+use util_error::UtilError;
+use types::error::Error as BlockImportError;
+
+/// Snapshot-related errors.
+#[derive(Debug)]
+pub enum Error {
+    /// Invalid starting block for snapshot.
+    InvalidStartingBlock(H256),
+    /// Block not found.
+    BlockNotFound(H256),
+    /// Incomplete chain.
+    IncompleteChain,
+    /// Old starting block in a pruned database.
+    OldBlockPrunedDB,
+    /// Missing code.
+    MissingCode(H256),
+    /// Missing AVM object graph for the given delta root.
+    MissingObjectGraph(H256),
+    /// Restoration aborted.
+    RestorationAborted,
+    /// Target database is not empty.
+    NonEmptyDatabase,
+    /// Chunk hash does not match the manifest.
+    ChunkHashMismatch(H256, H256),
+    /// Chunk is not listed in the manifest.
+    UnknownChunk(H256),
+    /// Restored state root mismatch.
+    StateRootMismatch(H256, H256),
+    /// Restored account storage root mismatch.
+    StorageRootMismatch(H256, H256),
+    /// Restored block chain does not end at the manifest block.
+    BlockHashMismatch(H256, H256),
+    /// Blocks in a chunk are not consecutive.
+    WrongBlockOrder(H256),
+    /// Block header or seal failed verification.
+    InvalidHeader(H256, BlockImportError),
+    /// Snapshot format version not supported.
+    VersionNotSupported(u64),
+    /// Trie error.
+    Trie(TrieError),
+    /// Decoder error.
+    Decoder(DecoderError),
+    /// Database error.
+    Database(kvdb::Error),
+    /// Util error.
+    Util(UtilError),
+    /// Io error.
+    Io(::std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidStartingBlock(ref hash) => {
+                write!(f, "Invalid starting block hash: {}", hash)
+            }
+            Error::BlockNotFound(ref hash) => write!(f, "Block not found in chain: {}", hash),
+            Error::IncompleteChain => write!(f, "Incomplete blockchain."),
+            Error::OldBlockPrunedDB => {
+                write!(
+                    f,
+                    "Attempted to create a snapshot at an old block while using a pruned \
+                     database. Please re-run with the --pruning archive flag."
+                )
+            }
+            Error::MissingCode(ref hash) => write!(f, "Missing code: {}", hash),
+            Error::MissingObjectGraph(ref root) => {
+                write!(f, "Missing AVM object graph for delta root {}", root)
+            }
+            Error::RestorationAborted => write!(f, "Snapshot restoration aborted."),
+            Error::NonEmptyDatabase => {
+                write!(
+                    f,
+                    "Snapshot can only be restored into a database that contains the genesis \
+                     block only."
+                )
+            }
+            Error::ChunkHashMismatch(ref expected, ref found) => {
+                write!(
+                    f,
+                    "Chunk hash mismatch: expected {}, found {}",
+                    expected, found
+                )
+            }
+            Error::UnknownChunk(ref hash) => write!(f, "Chunk {} is not in the manifest", hash),
+            Error::StateRootMismatch(ref expected, ref found) => {
+                write!(
+                    f,
+                    "State root mismatch: expected {}, found {}",
+                    expected, found
+                )
+            }
+            Error::StorageRootMismatch(ref expected, ref found) => {
+                write!(
+                    f,
+                    "Storage root mismatch: expected {}, found {}",
+                    expected, found
+                )
+            }
+            Error::BlockHashMismatch(ref expected, ref found) => {
+                write!(
+                    f,
+                    "Block hash mismatch: expected {}, found {}",
+                    expected, found
+                )
+            }
+            Error::WrongBlockOrder(ref hash) => {
+                write!(f, "Block {} does not follow its predecessor in the chunk", hash)
+            }
+            Error::InvalidHeader(ref hash, ref err) => {
+                write!(f, "Invalid header of block {}: {}", hash, err)
+            }
+            Error::VersionNotSupported(ref ver) => {
+                write!(f, "Snapshot version {} is not supported.", ver)
+            }
+            Error::Trie(ref err) => err.fmt(f),
+            Error::Decoder(ref err) => err.fmt(f),
+            Error::Database(ref err) => err.fmt(f),
+            Error::Util(ref err) => err.fmt(f),
+            Error::Io(ref err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<TrieError> for Error {
+    fn from(err: TrieError) -> Self { Error::Trie(err) }
+}
+
+impl From<DecoderError> for Error {
+    fn from(err: DecoderError) -> Self { Error::Decoder(err) }
+}
+
+impl From<kvdb::Error> for Error {
+    fn from(err: kvdb::Error) -> Self { Error::Database(err) }
+}
+
+impl From<UtilError> for Error {
+    fn from(err: UtilError) -> Self { Error::Util(err) }
+}
+
+impl From<::std::io::Error> for Error {
+    fn from(err: ::std::io::Error) -> Self { Error::Io(err) }
+}
+
+impl<E> From<Box<E>> for Error
+where Error: From<E>
+{
+    fn from(err: Box<E>) -> Self { Error::from(*err) }
+}
@@ -0,0 +1,140 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Snapshot i/o.
+//!
+//! A snapshot is stored as a directory holding one file per chunk, named by the
+//! hex-encoded blake2b hash of the chunk, plus a `MANIFEST` file with the rlp-encoded
+//! `ManifestData`.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use aion_types::H256;
+use acore_bytes::Bytes;
+
+use super::{Error, ManifestData};
+
+const MANIFEST_FILE: &'static str = "MANIFEST";
+
+/// Something which can write snapshots.
+/// Writing the same chunk multiple times will lead to implementation-defined
+/// behavior, and is not advised.
+pub trait SnapshotWriter {
+    /// Write a compressed state chunk.
+    fn write_state_chunk(&mut self, hash: H256, chunk: &[u8]) -> Result<(), Error>;
+
+    /// Write a compressed block chunk.
+    fn write_block_chunk(&mut self, hash: H256, chunk: &[u8]) -> Result<(), Error>;
+
+    /// Complete writing. The manifest's chunk lists must be consistent
+    /// with the chunks written.
+    fn finish(self, manifest: ManifestData) -> Result<(), Error>
+    where Self: Sized;
+}
+
+/// Something which can read compressed snapshots.
+pub trait SnapshotReader {
+    /// Get the manifest data for this snapshot.
+    fn manifest(&self) -> &ManifestData;
+
+    /// Get raw chunk data by hash. implementation defined behavior
+    /// if a chunk not in the manifest is requested.
+    fn chunk(&self, hash: H256) -> Result<Bytes, Error>;
+}
+
+/// A snapshot writer which writes chunks into a directory, one file per chunk.
+pub struct LooseWriter {
+    dir: PathBuf,
+}
+
+impl LooseWriter {
+    /// Create a new LooseWriter which will write into the given directory,
+    /// creating it if it doesn't exist.
+    pub fn new(path: PathBuf) -> Result<Self, Error> {
+        fs::create_dir_all(&path)?;
+
+        Ok(LooseWriter {
+            dir: path,
+        })
+    }
+
+    // writing logic is the same for both kinds of chunks.
+    fn write_chunk(&mut self, hash: H256, chunk: &[u8]) -> Result<(), Error> {
+        let file_path = self.dir.join(format!("{:x}", hash));
+        let mut file = File::create(file_path)?;
+        file.write_all(chunk)?;
+        Ok(())
+    }
+}
+
+impl SnapshotWriter for LooseWriter {
+    fn write_state_chunk(&mut self, hash: H256, chunk: &[u8]) -> Result<(), Error> {
+        self.write_chunk(hash, chunk)
+    }
+
+    fn write_block_chunk(&mut self, hash: H256, chunk: &[u8]) -> Result<(), Error> {
+        self.write_chunk(hash, chunk)
+    }
+
+    fn finish(self, manifest: ManifestData) -> Result<(), Error> {
+        let rlp = manifest.into_rlp();
+        let mut file = File::create(self.dir.join(MANIFEST_FILE))?;
+        file.write_all(&rlp[..])?;
+        Ok(())
+    }
+}
+
+/// A reader for snapshots written by `LooseWriter`.
+pub struct LooseReader {
+    dir: PathBuf,
+    manifest: ManifestData,
+}
+
+impl LooseReader {
+    /// Create a new `LooseReader` which will read the manifest and chunk data from
+    /// the given directory.
+    pub fn new(dir: PathBuf) -> Result<Self, Error> {
+        let mut manifest_buf = Vec::new();
+        File::open(dir.join(MANIFEST_FILE))?.read_to_end(&mut manifest_buf)?;
+        let manifest = ManifestData::from_rlp(&manifest_buf[..])?;
+
+        Ok(LooseReader {
+            dir: dir,
+            manifest: manifest,
+        })
+    }
+
+    /// Whether the given directory holds a snapshot.
+    pub fn exists(dir: &Path) -> bool { dir.join(MANIFEST_FILE).is_file() }
+}
+
+impl SnapshotReader for LooseReader {
+    fn manifest(&self) -> &ManifestData { &self.manifest }
+
+    fn chunk(&self, hash: H256) -> Result<Bytes, Error> {
+        let path = self.dir.join(format!("{:x}", hash));
+        let mut buf = Vec::new();
+        File::open(&path)?.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+}
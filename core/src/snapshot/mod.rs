@@ -0,0 +1,640 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Snapshot creation and restoration.
+//!
+//! A snapshot captures the state at a given block together with a window of recent
+//! blocks, split into chunks addressed by their blake2b hash. State chunks hold the
+//! accounts of the state trie in key order, each with its code, storage and AVM object
+//! graph (see `account`). Block chunks hold consecutive blocks with their receipts,
+//! prefixed with the number, hash and total difficulty of the parent of the first block.
+//!
+//! Restoration only targets a database which contains nothing but the genesis block.
+//! Chunks may be fed in any order; the state root and the block chain are checked
+//! against the manifest when the restoration is finalized.
+
+mod account;
+mod error;
+mod io;
+mod service;
+mod warp;
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use aion_types::{H256, U256};
+use acore_bytes::Bytes;
+use blake2b::{BLAKE2B_NULL_RLP, blake2b};
+use bloom_journal::Bloom;
+use blockchain::{BlockChain, BlockProvider};
+use db::{AccountDB, AccountDBMut, StateDB};
+use engine::Engine;
+use header::{BlockNumber, Header};
+use journaldb::JournalDB;
+use kvdb::{DBTransaction, HashStore, KeyValueDB};
+use receipt::Receipt;
+use rlp::{RlpStream, UntrustedRlp};
+use trie::{Trie, TrieDB, TrieDBMut, TrieMut};
+
+pub use self::error::Error;
+pub use self::io::{LooseReader, LooseWriter, SnapshotReader, SnapshotWriter};
+pub use self::service::{RestorationStatus, SnapshotService};
+pub use self::warp::{
+    pivot_chunk, state_node, NodeKind, NodeRequest, Pivot, StateHealer, WarpRestoration,
+    PIVOT_DISTANCE, WARP_BLOCKS,
+};
+
+/// Snapshot format version.
+pub const SNAPSHOT_VERSION: u64 = 2;
+
+/// Number of recent blocks included in a snapshot.
+pub const SNAPSHOT_BLOCKS: u64 = 30000;
+
+// Chunks are closed once they grow beyond this size.
+const PREFERRED_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Manifest data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestData {
+    /// Snapshot format version.
+    pub version: u64,
+    /// List of state chunk hashes.
+    pub state_hashes: Vec<H256>,
+    /// List of block chunk hashes.
+    pub block_hashes: Vec<H256>,
+    /// The final, expected state root.
+    pub state_root: H256,
+    /// Block number this snapshot was taken at.
+    pub block_number: u64,
+    /// Block hash this snapshot was taken at.
+    pub block_hash: H256,
+}
+
+impl ManifestData {
+    /// Encode the manifest data to rlp.
+    pub fn into_rlp(self) -> Bytes {
+        let mut stream = RlpStream::new_list(6);
+        stream.append(&self.version);
+        stream.append_list(&self.state_hashes);
+        stream.append_list(&self.block_hashes);
+        stream.append(&self.state_root);
+        stream.append(&self.block_number);
+        stream.append(&self.block_hash);
+        stream.out()
+    }
+
+    /// Try to restore manifest data from raw bytes, interpreted as rlp.
+    pub fn from_rlp(raw: &[u8]) -> Result<Self, Error> {
+        let decoder = UntrustedRlp::new(raw);
+        let version: u64 = decoder.val_at(0)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(Error::VersionNotSupported(version));
+        }
+
+        Ok(ManifestData {
+            version: version,
+            state_hashes: decoder.list_at(1)?,
+            block_hashes: decoder.list_at(2)?,
+            state_root: decoder.val_at(3)?,
+            block_number: decoder.val_at(4)?,
+            block_hash: decoder.val_at(5)?,
+        })
+    }
+}
+
+/// Take a snapshot of the state at `block_hash` and of the blocks leading up to it.
+/// `state_db` is the state database and `graph_db` the database holding the
+/// `avm_graph` column.
+pub fn take_snapshot<W: SnapshotWriter>(
+    chain: &BlockChain,
+    block_hash: H256,
+    state_db: &HashStore,
+    graph_db: &KeyValueDB,
+    mut writer: W,
+) -> Result<ManifestData, Error>
+{
+    let header = chain
+        .block_header(&block_hash)
+        .ok_or(Error::InvalidStartingBlock(block_hash))?;
+    let state_root = *header.state_root();
+
+    info!(target: "snapshot", "Taking snapshot starting at block #{} ({})", header.number(), block_hash);
+
+    let state_hashes = chunk_state(state_db, &state_root, graph_db, &mut writer)?;
+    let block_hashes = chunk_secondary(chain, block_hash, &mut writer)?;
+
+    info!(
+        target: "snapshot",
+        "Produced {} state chunks and {} block chunks.",
+        state_hashes.len(),
+        block_hashes.len()
+    );
+
+    let manifest = ManifestData {
+        version: SNAPSHOT_VERSION,
+        state_hashes: state_hashes,
+        block_hashes: block_hashes,
+        state_root: state_root,
+        block_number: header.number(),
+        block_hash: block_hash,
+    };
+    writer.finish(manifest.clone())?;
+
+    Ok(manifest)
+}
+
+/// Collects rlp items into size-bounded chunks, writing each chunk when full.
+struct Chunker<'a> {
+    writer: &'a mut SnapshotWriter,
+    items: Vec<Bytes>,
+    size: usize,
+    hashes: Vec<H256>,
+    state: bool,
+}
+
+impl<'a> Chunker<'a> {
+    fn new(writer: &'a mut SnapshotWriter, state: bool) -> Self {
+        Chunker {
+            writer: writer,
+            items: Vec::new(),
+            size: 0,
+            hashes: Vec::new(),
+            state: state,
+        }
+    }
+
+    fn is_full(&self, next: usize) -> bool {
+        !self.items.is_empty() && self.size + next > PREFERRED_CHUNK_SIZE
+    }
+
+    fn push(&mut self, item: Bytes) {
+        self.size += item.len();
+        self.items.push(item);
+    }
+
+    // write out the pending items, prefixed with the given raw rlp items.
+    fn write(&mut self, prefix: &[Bytes]) -> Result<(), Error> {
+        if self.items.is_empty() {
+            return Ok(());
+        }
+
+        let mut stream = RlpStream::new_list(prefix.len() + self.items.len());
+        for item in prefix.iter().chain(self.items.iter()) {
+            stream.append_raw(item, 1);
+        }
+        let chunk = stream.out();
+        let hash = blake2b(&chunk);
+        if self.state {
+            self.writer.write_state_chunk(hash, &chunk)?;
+        } else {
+            self.writer.write_block_chunk(hash, &chunk)?;
+        }
+        trace!(target: "snapshot", "wrote chunk {} of {} bytes", hash, chunk.len());
+
+        self.hashes.push(hash);
+        self.items.clear();
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Walk the account trie under `root`, writing state chunks.
+/// Returns the hashes of the chunks written.
+pub fn chunk_state(
+    db: &HashStore,
+    root: &H256,
+    graph_db: &KeyValueDB,
+    writer: &mut SnapshotWriter,
+) -> Result<Vec<H256>, Error>
+{
+    let account_trie = TrieDB::new(db, root)?;
+    let mut chunker = Chunker::new(writer, true);
+    let mut accounts = 0usize;
+
+    for item in account_trie.iter()? {
+        let (key, account_rlp) = item?;
+        let account_key = H256::from_slice(&key);
+        let acct_db = AccountDB::from_hash(db, account_key);
+        let fat_rlp = account::to_fat_rlp(&account_key, &account_rlp, &acct_db, graph_db)?;
+
+        if chunker.is_full(fat_rlp.len()) {
+            chunker.write(&[])?;
+        }
+        chunker.push(fat_rlp);
+
+        accounts += 1;
+        if accounts % 10000 == 0 {
+            info!(target: "snapshot", "{} accounts processed", accounts);
+        }
+    }
+    chunker.write(&[])?;
+
+    Ok(chunker.hashes)
+}
+
+/// Write block chunks with up to `SNAPSHOT_BLOCKS` blocks ending at `block_hash`.
+/// Returns the hashes of the chunks written.
+pub fn chunk_secondary(
+    chain: &BlockChain,
+    block_hash: H256,
+    writer: &mut SnapshotWriter,
+) -> Result<Vec<H256>, Error>
+{
+    let mut chunker = Chunker::new(writer, false);
+    let mut prefix: Vec<Bytes> = Vec::new();
+
+    for hash in block_hashes(chain, block_hash, SNAPSHOT_BLOCKS)? {
+        let (item, header) = block_item(chain, hash)?;
+
+        if chunker.is_full(item.len()) {
+            chunker.write(&prefix)?;
+            prefix.clear();
+        }
+
+        // every chunk starts with the details of the parent of its first block.
+        if prefix.is_empty() {
+            prefix = block_chunk_prefix(chain, &header)?;
+        }
+        chunker.push(item);
+    }
+    chunker.write(&prefix)?;
+
+    Ok(chunker.hashes)
+}
+
+/// Hashes of up to `count` blocks ending at `block_hash`, in ascending order and
+/// excluding the genesis.
+fn block_hashes(chain: &BlockChain, block_hash: H256, count: u64) -> Result<Vec<H256>, Error> {
+    let mut hashes = Vec::new();
+    let mut current = block_hash;
+    while (hashes.len() as u64) < count {
+        let header = chain
+            .block_header(&current)
+            .ok_or(Error::BlockNotFound(current))?;
+        if header.number() == 0 {
+            break;
+        }
+        hashes.push(current);
+        current = *header.parent_hash();
+    }
+    hashes.reverse();
+    Ok(hashes)
+}
+
+/// Encode a block with its receipts as an item of a block chunk.
+fn block_item(chain: &BlockChain, hash: H256) -> Result<(Bytes, Header), Error> {
+    let block = chain.block(&hash).ok_or(Error::BlockNotFound(hash))?;
+    let receipts = chain
+        .block_receipts(&hash)
+        .ok_or(Error::BlockNotFound(hash))?;
+
+    let mut stream = RlpStream::new_list(2);
+    stream.append_raw(block.rlp().as_raw(), 1);
+    stream.append_list(&receipts.receipts);
+    Ok((stream.out(), block.decode_header()))
+}
+
+/// Number, hash and total difficulty of the parent of `header`, which start a block chunk.
+fn block_chunk_prefix(chain: &BlockChain, header: &Header) -> Result<Vec<Bytes>, Error> {
+    let parent_hash = *header.parent_hash();
+    let parent_td = chain
+        .block_details(&parent_hash)
+        .ok_or(Error::IncompleteChain)?
+        .total_difficulty;
+    Ok(vec![
+        ::rlp::encode(&(header.number() - 1)).into_vec(),
+        ::rlp::encode(&parent_hash).into_vec(),
+        ::rlp::encode(&parent_td).into_vec(),
+    ])
+}
+
+/// Rebuilds the state trie and account databases from state chunks.
+pub struct StateRebuilder {
+    db: Box<JournalDB>,
+    state_root: H256,
+    bloom: Bloom,
+}
+
+impl StateRebuilder {
+    /// Create a new state rebuilder writing into the given journal database.
+    pub fn new(db: Box<JournalDB>) -> Self {
+        let bloom = StateDB::load_bloom(&**db.backing());
+        StateRebuilder {
+            db: db,
+            state_root: BLAKE2B_NULL_RLP,
+            bloom: bloom,
+        }
+    }
+
+    /// Feed an uncompressed state chunk into the rebuilder.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), Error> {
+        let rlp = UntrustedRlp::new(chunk);
+        let mut batch = DBTransaction::new();
+        let mut accounts = Vec::with_capacity(rlp.item_count()?);
+
+        for fat_rlp in rlp.iter() {
+            let account_key: H256 = fat_rlp.val_at(0)?;
+            let mut acct_db = AccountDBMut::from_hash(self.db.as_hashstore_mut(), account_key);
+            accounts.push(account::from_fat_rlp(&mut acct_db, &mut batch, fat_rlp)?);
+        }
+
+        {
+            let mut account_trie = if self.state_root == BLAKE2B_NULL_RLP {
+                TrieDBMut::new(self.db.as_hashstore_mut(), &mut self.state_root)
+            } else {
+                TrieDBMut::from_existing(self.db.as_hashstore_mut(), &mut self.state_root)?
+            };
+            for &(ref account_key, ref account_rlp) in &accounts {
+                account_trie.insert(&account_key[..], account_rlp)?;
+            }
+        }
+
+        for &(ref account_key, _) in &accounts {
+            self.bloom.set(&**account_key);
+        }
+
+        self.db.inject(&mut batch)?;
+        self.db.backing().write(batch)?;
+        Ok(())
+    }
+
+    /// Get the state root of the rebuilder.
+    pub fn state_root(&self) -> H256 { self.state_root }
+
+    /// Finalize the restoration. Writes the account bloom and journals the
+    /// restored state under the given era and block hash.
+    pub fn finalize(mut self, era: u64, id: H256) -> Result<(), Error> {
+        let mut batch = DBTransaction::new();
+        StateDB::commit_bloom(&mut batch, self.bloom.drain_journal())?;
+        self.db.journal_under(&mut batch, era, &id)?;
+        self.db.backing().write(batch)?;
+        Ok(())
+    }
+}
+
+/// Inserts blocks from block chunks into the chain.
+pub struct BlockRebuilder {
+    chain: Arc<BlockChain>,
+    db: Arc<KeyValueDB>,
+    engine: Arc<Engine>,
+    best_hash: H256,
+    // blocks fed before their parent, verified once the whole chain is in.
+    unverified: Vec<H256>,
+}
+
+impl BlockRebuilder {
+    /// Create a new block rebuilder. `best_hash` is the block the snapshot was taken at,
+    /// which becomes the best block once inserted. Headers are verified with `engine`.
+    pub fn new(
+        chain: Arc<BlockChain>,
+        db: Arc<KeyValueDB>,
+        engine: Arc<Engine>,
+        best_hash: H256,
+    ) -> Self
+    {
+        BlockRebuilder {
+            chain: chain,
+            db: db,
+            engine: engine,
+            best_hash: best_hash,
+            unverified: Vec::new(),
+        }
+    }
+
+    /// Feed an uncompressed block chunk into the rebuilder.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), Error> {
+        let rlp = UntrustedRlp::new(chunk);
+        let item_count = rlp.item_count()?;
+        if item_count < 3 {
+            return Err(Error::IncompleteChain);
+        }
+
+        let parent_number: BlockNumber = rlp.val_at(0)?;
+        let mut parent_hash: H256 = rlp.val_at(1)?;
+        let mut parent_td: Option<U256> = Some(rlp.val_at(2)?);
+        let mut parent = self.header(&parent_hash);
+        let mut grand_parent = parent
+            .as_ref()
+            .and_then(|parent| self.header(parent.parent_hash()));
+
+        let mut batch = DBTransaction::new();
+        for (i, pair) in rlp.iter().skip(3).enumerate() {
+            let block_rlp = pair.at(0)?;
+            let header: Header = block_rlp.val_at(0)?;
+            let hash = header.hash();
+            if *header.parent_hash() != parent_hash
+                || header.number() != parent_number + i as u64 + 1
+            {
+                return Err(Error::WrongBlockOrder(hash));
+            }
+            match parent {
+                Some(ref parent) if grand_parent.is_some() || parent.number() == 0 => {
+                    verify_header(&*self.engine, &header, Some(parent), grand_parent.as_ref())?
+                }
+                _ => self.unverified.push(hash),
+            }
+            let receipts: Vec<Receipt> = pair.list_at(1)?;
+
+            self.chain.insert_unordered_block(
+                &mut batch,
+                block_rlp.as_raw(),
+                receipts,
+                parent_td.take(),
+                hash == self.best_hash,
+                false,
+            );
+            parent_hash = hash;
+            grand_parent = parent.take();
+            parent = Some(header);
+        }
+
+        self.db.write_buffered(batch);
+        self.chain.commit();
+        Ok(())
+    }
+
+    /// Verify the blocks fed before their parent and check that the chain ends at the
+    /// snapshot block.
+    pub fn finalize(self) -> Result<(), Error> {
+        let best_hash = self.chain.best_block_hash();
+        if best_hash != self.best_hash {
+            return Err(Error::BlockHashMismatch(self.best_hash, best_hash));
+        }
+        for hash in &self.unverified {
+            let header = self.header(hash).ok_or(Error::BlockNotFound(*hash))?;
+            let parent = self.header(header.parent_hash());
+            let grand_parent = parent
+                .as_ref()
+                .and_then(|parent| self.header(parent.parent_hash()));
+            // the oldest blocks of a warp sync have no restored ancestors to check against
+            verify_header(
+                &*self.engine,
+                &header,
+                parent.as_ref(),
+                grand_parent.as_ref(),
+            )?;
+        }
+        self.db.flush()?;
+        Ok(())
+    }
+
+    fn header(&self, hash: &H256) -> Option<Header> {
+        self.chain
+            .block_header_data(hash)
+            .map(|header| header.decode())
+    }
+}
+
+// Verify a header with the engine as far as its known ancestors allow. PoS seals are checked
+// against the grand parent, without it only what the header proves on its own is verified.
+fn verify_header(
+    engine: &Engine,
+    header: &Header,
+    parent: Option<&Header>,
+    grand_parent: Option<&Header>,
+) -> Result<(), Error>
+{
+    let result = match parent {
+        Some(parent) if grand_parent.is_some() || parent.number() == 0 => {
+            engine.verify_header_seal(header, parent, grand_parent)
+        }
+        _ => {
+            engine
+                .verify_block_basic(header)
+                .and_then(|_| engine.verify_block_unordered(header))
+        }
+    };
+    result.map_err(|err| Error::InvalidHeader(header.hash(), err))
+}
+
+/// An ongoing restoration of a snapshot.
+pub struct Restoration {
+    manifest: ManifestData,
+    state_chunks_left: HashSet<H256>,
+    block_chunks_left: HashSet<H256>,
+    state: StateRebuilder,
+    blocks: BlockRebuilder,
+}
+
+impl Restoration {
+    /// Start restoring the given manifest into the state database, key-value database
+    /// and chain, verifying the restored headers with `engine`.
+    pub fn new(
+        manifest: ManifestData,
+        state_db: Box<JournalDB>,
+        db: Arc<KeyValueDB>,
+        chain: Arc<BlockChain>,
+        engine: Arc<Engine>,
+    ) -> Self
+    {
+        let state_chunks_left = manifest.state_hashes.iter().cloned().collect();
+        let block_chunks_left = manifest.block_hashes.iter().cloned().collect();
+        let best_hash = manifest.block_hash;
+
+        Restoration {
+            manifest: manifest,
+            state_chunks_left: state_chunks_left,
+            block_chunks_left: block_chunks_left,
+            state: StateRebuilder::new(state_db),
+            blocks: BlockRebuilder::new(chain, db, engine, best_hash),
+        }
+    }
+
+    /// The manifest being restored.
+    pub fn manifest(&self) -> &ManifestData { &self.manifest }
+
+    /// Feed a state chunk. Chunks already fed are ignored.
+    pub fn feed_state(&mut self, hash: H256, chunk: &[u8]) -> Result<(), Error> {
+        if self.state_chunks_left.contains(&hash) {
+            check_chunk_hash(&hash, chunk)?;
+            self.state.feed(chunk)?;
+            self.state_chunks_left.remove(&hash);
+        }
+        Ok(())
+    }
+
+    /// Feed a block chunk. Chunks already fed are ignored.
+    pub fn feed_blocks(&mut self, hash: H256, chunk: &[u8]) -> Result<(), Error> {
+        if self.block_chunks_left.contains(&hash) {
+            check_chunk_hash(&hash, chunk)?;
+            self.blocks.feed(chunk)?;
+            self.block_chunks_left.remove(&hash);
+        }
+        Ok(())
+    }
+
+    /// Feed a chunk of either kind, as identified by the manifest.
+    pub fn feed(&mut self, hash: H256, chunk: &[u8]) -> Result<(), Error> {
+        if self.manifest.state_hashes.contains(&hash) {
+            self.feed_state(hash, chunk)
+        } else if self.manifest.block_hashes.contains(&hash) {
+            self.feed_blocks(hash, chunk)
+        } else {
+            Err(Error::UnknownChunk(hash))
+        }
+    }
+
+    /// Hashes of the chunks still to be fed.
+    pub fn pending_chunks(&self) -> Vec<H256> {
+        self.state_chunks_left
+            .iter()
+            .chain(self.block_chunks_left.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Whether all chunks have been fed.
+    pub fn is_done(&self) -> bool {
+        self.state_chunks_left.is_empty() && self.block_chunks_left.is_empty()
+    }
+
+    /// Check the restored state and chain against the manifest and finish writing.
+    pub fn finalize(self) -> Result<(), Error> {
+        if !self.is_done() {
+            return Err(Error::RestorationAborted);
+        }
+
+        let state_root = self.state.state_root();
+        if state_root != self.manifest.state_root {
+            return Err(Error::StateRootMismatch(self.manifest.state_root, state_root));
+        }
+        // the manifest block is seal verified, bind the restored state to it
+        let header = self
+            .blocks
+            .header(&self.manifest.block_hash)
+            .ok_or(Error::BlockNotFound(self.manifest.block_hash))?;
+        if *header.state_root() != state_root {
+            return Err(Error::StateRootMismatch(*header.state_root(), state_root));
+        }
+
+        self.state
+            .finalize(self.manifest.block_number, self.manifest.block_hash)?;
+        self.blocks.finalize()
+    }
+}
+
+fn check_chunk_hash(expected: &H256, chunk: &[u8]) -> Result<(), Error> {
+    let hash = blake2b(chunk);
+    if &hash != expected {
+        return Err(Error::ChunkHashMismatch(*expected, hash));
+    }
+    Ok(())
+}
@@ -0,0 +1,324 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Snapshot service: serves the local snapshot to peers and restores snapshots
+//! downloaded by sync.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use aion_types::H256;
+use acore_bytes::Bytes;
+use client::Client;
+use parking_lot::{Mutex, RwLock};
+
+use super::{
+    Error, LooseReader, ManifestData, NodeRequest, Pivot, Restoration, SnapshotReader,
+    WarpRestoration,
+};
+
+/// Status of a snapshot restoration.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RestorationStatus {
+    /// No restoration.
+    Inactive,
+    /// Ongoing restoration.
+    Ongoing {
+        /// Total number of chunks in the snapshot.
+        total_chunks: usize,
+        /// Number of chunks restored so far.
+        restored_chunks: usize,
+    },
+    /// Restoration failed.
+    Failed,
+}
+
+/// Number of consecutive warp responses missing every requested item after which a newer
+/// pivot is needed.
+const REPIVOT_AFTER: usize = 8;
+
+/// Serves the local snapshot and drives restorations fed by sync.
+pub struct SnapshotService {
+    client: Arc<Client>,
+    reader: RwLock<Option<LooseReader>>,
+    restoration: Mutex<Option<Restoration>>,
+    status: RwLock<RestorationStatus>,
+    warp: Mutex<Option<WarpRestoration>>,
+    // consecutive warp responses missing every requested item.
+    warp_misses: Mutex<usize>,
+}
+
+impl SnapshotService {
+    /// Create a new snapshot service serving the snapshot in `snapshot_dir`, if any.
+    pub fn new(client: Arc<Client>, snapshot_dir: PathBuf) -> Self {
+        let reader = if LooseReader::exists(&snapshot_dir) {
+            match LooseReader::new(snapshot_dir) {
+                Ok(reader) => Some(reader),
+                Err(e) => {
+                    warn!(target: "snapshot", "Failed to open local snapshot: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        SnapshotService {
+            client: client,
+            reader: RwLock::new(reader),
+            restoration: Mutex::new(None),
+            status: RwLock::new(RestorationStatus::Inactive),
+            warp: Mutex::new(None),
+            warp_misses: Mutex::new(0),
+        }
+    }
+
+    /// Manifest of the local snapshot, if any.
+    pub fn manifest(&self) -> Option<ManifestData> {
+        self.reader
+            .read()
+            .as_ref()
+            .map(|reader| reader.manifest().clone())
+    }
+
+    /// Raw data of a chunk of the local snapshot.
+    pub fn chunk(&self, hash: H256) -> Option<Bytes> {
+        let reader = self.reader.read();
+        let reader = reader.as_ref()?;
+        {
+            let manifest = reader.manifest();
+            if !manifest.state_hashes.contains(&hash) && !manifest.block_hashes.contains(&hash) {
+                return None;
+            }
+        }
+        reader.chunk(hash).ok()
+    }
+
+    /// Status of the current restoration.
+    pub fn status(&self) -> RestorationStatus { *self.status.read() }
+
+    /// Whether a restoration is in progress.
+    pub fn is_restoring(&self) -> bool {
+        match self.status() {
+            RestorationStatus::Ongoing {
+                ..
+            } => true,
+            _ => false,
+        }
+    }
+
+    /// Begin restoring the given manifest. Does nothing if a restoration is in progress.
+    pub fn begin_restore(&self, manifest: ManifestData) {
+        let mut restoration = self.restoration.lock();
+        if restoration.is_some() || self.is_warping() {
+            return;
+        }
+
+        let total_chunks = manifest.state_hashes.len() + manifest.block_hashes.len();
+        info!(
+            target: "snapshot",
+            "Restoring snapshot at block #{} ({} chunks)",
+            manifest.block_number, total_chunks
+        );
+        match self.client.begin_restoration(manifest) {
+            Ok(new_restoration) => {
+                *restoration = Some(new_restoration);
+                *self.status.write() = RestorationStatus::Ongoing {
+                    total_chunks: total_chunks,
+                    restored_chunks: 0,
+                };
+            }
+            Err(e) => {
+                warn!(target: "snapshot", "Failed to begin restoration: {}", e);
+                *self.status.write() = RestorationStatus::Failed;
+            }
+        }
+    }
+
+    /// Abort the current restoration, if any.
+    pub fn abort_restore(&self) {
+        *self.restoration.lock() = None;
+        *self.status.write() = RestorationStatus::Inactive;
+    }
+
+    /// Hashes of the chunks the current restoration still needs.
+    pub fn pending_chunks(&self) -> Vec<H256> {
+        self.restoration
+            .lock()
+            .as_ref()
+            .map_or_else(Vec::new, |restoration| restoration.pending_chunks())
+    }
+
+    /// Feed a downloaded chunk into the current restoration. Once every chunk has been
+    /// fed the restoration is finalized and the client switches to the restored chain.
+    pub fn restore_chunk(&self, hash: H256, chunk: Bytes) {
+        let mut restoration = self.restoration.lock();
+        let result = match *restoration {
+            Some(ref mut restoration) => restoration.feed(hash, &chunk),
+            None => return,
+        };
+        if let Err(e) = result {
+            warn!(target: "snapshot", "Failed to restore chunk {}: {}", hash, e);
+            *restoration = None;
+            *self.status.write() = RestorationStatus::Failed;
+            return;
+        }
+
+        let done = match *restoration {
+            Some(ref restoration) => {
+                let manifest = restoration.manifest();
+                let total_chunks = manifest.state_hashes.len() + manifest.block_hashes.len();
+                let pending_chunks = restoration.pending_chunks().len();
+                *self.status.write() = RestorationStatus::Ongoing {
+                    total_chunks: total_chunks,
+                    restored_chunks: total_chunks - pending_chunks,
+                };
+                pending_chunks == 0
+            }
+            None => false,
+        };
+        if !done {
+            return;
+        }
+
+        let finished = restoration.take().expect("restoration checked to be done above; qed");
+        match self.client.finish_restoration(finished) {
+            Ok(()) => *self.status.write() = RestorationStatus::Inactive,
+            Err(e) => {
+                warn!(target: "snapshot", "Failed to finalize restoration: {}", e);
+                *self.status.write() = RestorationStatus::Failed;
+            }
+        }
+    }
+
+    /// Pivot offered to warp syncing peers, if the chain is long enough.
+    pub fn warp_pivot(&self) -> Option<Bytes> { self.client.warp_pivot() }
+
+    /// Items of the current state requested by a warp syncing peer, in request order.
+    /// Items not found are answered empty.
+    pub fn state_nodes(&self, requests: &[NodeRequest]) -> Vec<Bytes> {
+        requests
+            .iter()
+            .map(|request| self.client.state_node(request).unwrap_or_default())
+            .collect()
+    }
+
+    /// Whether a warp sync is in progress.
+    pub fn is_warping(&self) -> bool { self.warp.lock().is_some() }
+
+    /// Whether the current warp sync needs a newer pivot because peers no longer serve
+    /// the state of the current one.
+    pub fn needs_pivot(&self) -> bool {
+        self.is_warping() && *self.warp_misses.lock() >= REPIVOT_AFTER
+    }
+
+    /// Verify a pivot received from a peer with the engine of the client.
+    pub fn verify_pivot(&self, pivot: &Pivot) -> Result<(), Error> {
+        pivot.verify(self.client.engine())
+    }
+
+    /// Begin a warp sync at the given pivot. While a warp sync is in progress, the pivot is
+    /// only taken if a newer one is needed.
+    pub fn begin_warp(&self, pivot: Pivot) {
+        if self.is_restoring() {
+            return;
+        }
+        let needs_pivot = self.needs_pivot();
+        let mut warp = self.warp.lock();
+        if let Some(ref mut warp) = *warp {
+            if !needs_pivot || pivot.number <= warp.pivot().number {
+                return;
+            }
+            info!(target: "snapshot", "Moving warp sync pivot to block #{}", pivot.number);
+            if let Err(e) = warp.repivot(pivot) {
+                warn!(target: "snapshot", "Failed to move warp sync pivot: {}", e);
+            }
+            *self.warp_misses.lock() = 0;
+            return;
+        }
+
+        info!(target: "snapshot", "Warp syncing state at block #{}", pivot.number);
+        match self.client.begin_warp(pivot) {
+            Ok(new_warp) => {
+                *warp = Some(new_warp);
+                *self.warp_misses.lock() = 0;
+            }
+            Err(e) => warn!(target: "snapshot", "Failed to begin warp sync: {}", e),
+        }
+    }
+
+    /// Take up to `max` state items to request from peers.
+    pub fn warp_requests(&self, max: usize) -> Vec<NodeRequest> {
+        self.warp
+            .lock()
+            .as_mut()
+            .map_or_else(Vec::new, |warp| warp.next_requests(max))
+    }
+
+    /// Put back warp requests which were not answered.
+    pub fn requeue_warp(&self, requests: &[NodeRequest]) {
+        if let Some(ref mut warp) = *self.warp.lock() {
+            warp.requeue(requests);
+        }
+    }
+
+    /// Feed the data answered for `requests` into the current warp sync. Once the whole
+    /// state has been downloaded the warp sync is finalized and the client switches to the
+    /// chain ending at the pivot.
+    pub fn feed_warp(&self, requests: &[NodeRequest], data: &[Bytes]) {
+        let mut warp = self.warp.lock();
+        let result = match *warp {
+            Some(ref mut warp) => warp.feed(requests, data),
+            None => return,
+        };
+        match result {
+            Ok(missing) if !requests.is_empty() && missing == requests.len() => {
+                *self.warp_misses.lock() += 1;
+            }
+            Ok(_) => *self.warp_misses.lock() = 0,
+            Err(e) => {
+                warn!(target: "snapshot", "Failed to feed warp sync: {}", e);
+                *warp = None;
+                return;
+            }
+        }
+
+        let done = match *warp {
+            Some(ref warp) => {
+                trace!(
+                    target: "snapshot",
+                    "warp sync: {} state items downloaded",
+                    warp.downloaded()
+                );
+                warp.is_done()
+            }
+            None => false,
+        };
+        if !done {
+            return;
+        }
+
+        let finished = warp.take().expect("warp sync checked to be done above; qed");
+        if let Err(e) = self.client.finish_warp(finished) {
+            warn!(target: "snapshot", "Failed to finalize warp sync: {}", e);
+        }
+    }
+}
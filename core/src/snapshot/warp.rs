@@ -0,0 +1,657 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Warp sync: download the state at a recent pivot block node by node.
+//!
+//! Rather than replaying every historical block, a warp syncing node asks a peer for a
+//! pivot: a window of recent blocks ending at a block whose state the peer still holds,
+//! in the block chunk format of snapshots. It then requests the nodes of the pivot state
+//! trie by hash, together with the code, storage tries and AVM object graphs of every
+//! account reached. Nodes already present in the local database are expanded locally
+//! instead of being downloaded, so switching to a newer pivot when peers have pruned the
+//! old one only fetches what changed. Once the state is complete the pivot blocks are
+//! inserted and normal block import continues from the pivot.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aion_types::H256;
+use acore_bytes::Bytes;
+use blake2b::{BLAKE2B_EMPTY, BLAKE2B_NULL_RLP, blake2b};
+use bloom_journal::Bloom;
+use blockchain::BlockChain;
+use db::{AccountDB, AccountDBMut, StateDB};
+use engine::Engine;
+use header::{BlockNumber, Header};
+use journaldb::JournalDB;
+use kvdb::{DBTransaction, DBValue, HashStore, KeyValueDB};
+use rlp::{Decodable, DecoderError, Encodable, RlpStream, UntrustedRlp};
+use state::BasicAccount;
+
+use super::{
+    block_chunk_prefix, block_hashes, block_item, verify_header, BlockRebuilder, Error,
+};
+
+/// Number of blocks ending at the pivot sent along with it.
+pub const WARP_BLOCKS: u64 = 256;
+
+/// Distance from the best block at which peers pick the pivot, so that its state is
+/// not pruned while it is being downloaded.
+pub const PIVOT_DISTANCE: u64 = 16;
+
+/// Kind of item of the state requested from a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// Node of the account trie.
+    State,
+    /// Node of an account storage trie, or the roots entry of an AVM delta root.
+    Storage,
+    /// Account code.
+    Code,
+    /// AVM object graph.
+    AvmGraph,
+}
+
+impl NodeKind {
+    fn value(&self) -> u8 {
+        match *self {
+            NodeKind::State => 0,
+            NodeKind::Storage => 1,
+            NodeKind::Code => 2,
+            // 3 was transformed AVM code, which is no longer exchanged.
+            NodeKind::AvmGraph => 4,
+        }
+    }
+
+    fn from(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(NodeKind::State),
+            1 => Some(NodeKind::Storage),
+            2 => Some(NodeKind::Code),
+            4 => Some(NodeKind::AvmGraph),
+            _ => None,
+        }
+    }
+}
+
+/// Request for a single item of the state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeRequest {
+    /// Kind of the item.
+    pub kind: NodeKind,
+    /// Account the item belongs to; zero for account trie nodes.
+    pub account_key: H256,
+    /// Hash of the item.
+    pub hash: H256,
+}
+
+impl Encodable for NodeRequest {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3);
+        s.append(&self.kind.value());
+        s.append(&self.account_key);
+        s.append(&self.hash);
+    }
+}
+
+impl Decodable for NodeRequest {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 3 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        let kind: u8 = rlp.val_at(0)?;
+        Ok(NodeRequest {
+            kind: NodeKind::from(kind).ok_or(DecoderError::Custom("Unknown node kind"))?,
+            account_key: rlp.val_at(1)?,
+            hash: rlp.val_at(2)?,
+        })
+    }
+}
+
+/// Build the pivot for warp sync: up to `WARP_BLOCKS` blocks ending at `block_hash` with
+/// their receipts, encoded as a single block chunk.
+pub fn pivot_chunk(chain: &BlockChain, block_hash: H256) -> Result<Bytes, Error> {
+    let mut items = Vec::new();
+    for hash in block_hashes(chain, block_hash, WARP_BLOCKS)? {
+        let (item, header) = block_item(chain, hash)?;
+        if items.is_empty() {
+            items = block_chunk_prefix(chain, &header)?;
+        }
+        items.push(item);
+    }
+    if items.is_empty() {
+        return Err(Error::InvalidStartingBlock(block_hash));
+    }
+
+    let mut stream = RlpStream::new_list(items.len());
+    for item in &items {
+        stream.append_raw(item, 1);
+    }
+    Ok(stream.out())
+}
+
+/// Pivot of a warp sync, as received from a peer.
+#[derive(Debug, Clone)]
+pub struct Pivot {
+    /// Number of the pivot block.
+    pub number: BlockNumber,
+    /// Hash of the pivot block.
+    pub hash: H256,
+    /// State root of the pivot block.
+    pub state_root: H256,
+    blocks: Bytes,
+    // hashes of the blocks of the chunk, the pivot block last.
+    hashes: Vec<H256>,
+}
+
+impl Pivot {
+    /// Decode a pivot from a block chunk. The last block of the chunk is the pivot block.
+    pub fn from_rlp(raw: &[u8]) -> Result<Self, Error> {
+        let headers = Self::headers(raw)?;
+        let header = headers.last().expect("a chunk has at least one block; qed");
+        Ok(Pivot {
+            number: header.number(),
+            hash: header.hash(),
+            state_root: *header.state_root(),
+            blocks: raw.to_vec(),
+            hashes: headers.iter().map(|header| header.hash()).collect(),
+        })
+    }
+
+    /// Verify the pivot block and its ancestors in the chunk with `engine`.
+    pub fn verify(&self, engine: &Engine) -> Result<(), Error> {
+        let headers = Self::headers(&self.blocks)?;
+        for (i, header) in headers.iter().enumerate() {
+            let parent = if i > 0 { headers.get(i - 1) } else { None };
+            let grand_parent = if i > 1 { headers.get(i - 2) } else { None };
+            verify_header(engine, header, parent, grand_parent)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `hash` is the pivot block or one of its ancestors in the chunk.
+    pub fn contains(&self, hash: &H256) -> bool { self.hashes.contains(hash) }
+
+    // headers of a block chunk, checking that each block follows its predecessor.
+    fn headers(raw: &[u8]) -> Result<Vec<Header>, Error> {
+        let rlp = UntrustedRlp::new(raw);
+        let item_count = rlp.item_count()?;
+        if item_count < 4 {
+            return Err(Error::IncompleteChain);
+        }
+
+        let parent_number: BlockNumber = rlp.val_at(0)?;
+        let mut parent_hash: H256 = rlp.val_at(1)?;
+        let mut headers = Vec::with_capacity(item_count - 3);
+        for (i, pair) in rlp.iter().skip(3).enumerate() {
+            let header: Header = pair.at(0)?.val_at(0)?;
+            if *header.parent_hash() != parent_hash
+                || header.number() != parent_number + i as u64 + 1
+            {
+                return Err(Error::WrongBlockOrder(header.hash()));
+            }
+            parent_hash = header.hash();
+            headers.push(header);
+        }
+        Ok(headers)
+    }
+}
+
+/// Read an item of the state from the database, to serve it to a warp syncing peer.
+/// `db` is the state database and `graph_db` the database holding AVM object graphs.
+pub fn state_node(db: &HashStore, graph_db: &KeyValueDB, request: &NodeRequest) -> Option<Bytes> {
+    let value = match request.kind {
+        NodeKind::State => db.get(&request.hash),
+        NodeKind::Storage => {
+            AccountDB::from_hash(db, request.account_key)
+                .get(&request.hash)
+                .or_else(|| graph_db.get(::db::COL_AVM_GRAPH, &request.hash).ok()?)
+        }
+        NodeKind::Code => AccountDB::from_hash(db, request.account_key).get(&request.hash),
+        NodeKind::AvmGraph => graph_db.get(::db::COL_AVM_GRAPH, &request.hash).ok()?,
+    };
+    value.map(|value| value.into_vec())
+}
+
+/// Downloads the state trie under a given root together with the code, storage and AVM
+/// object graphs of its accounts. Items already in the database are not requested again.
+pub struct StateHealer {
+    db: Box<JournalDB>,
+    root: H256,
+    bloom: Bloom,
+    // items to request, with the nibble path of account trie nodes. Used as a stack so
+    // that the trie is walked depth first and the queue stays small.
+    pending: Vec<(NodeRequest, Vec<u8>)>,
+    requested: HashMap<NodeRequest, Vec<u8>>,
+    downloaded: usize,
+}
+
+impl StateHealer {
+    /// Create a new healer writing into the given journal database.
+    pub fn new(db: Box<JournalDB>, root: H256) -> Result<Self, Error> {
+        let bloom = StateDB::load_bloom(&**db.backing());
+        let mut healer = StateHealer {
+            db: db,
+            root: BLAKE2B_NULL_RLP,
+            bloom: bloom,
+            pending: Vec::new(),
+            requested: HashMap::new(),
+            downloaded: 0,
+        };
+        healer.set_root(root)?;
+        Ok(healer)
+    }
+
+    /// State root being downloaded.
+    pub fn root(&self) -> H256 { self.root }
+
+    /// Number of items downloaded so far.
+    pub fn downloaded(&self) -> usize { self.downloaded }
+
+    /// Switch to another state root. Whatever was downloaded for the previous root is kept
+    /// and reused where the two states share nodes.
+    pub fn set_root(&mut self, root: H256) -> Result<(), Error> {
+        self.root = root;
+        self.pending.clear();
+        self.requested.clear();
+        let request = NodeRequest {
+            kind: NodeKind::State,
+            account_key: H256::zero(),
+            hash: root,
+        };
+        self.enqueue(vec![(request, Vec::new())])
+    }
+
+    /// Take up to `max` items to request from peers.
+    pub fn next_requests(&mut self, max: usize) -> Vec<NodeRequest> {
+        let mut requests = Vec::new();
+        while requests.len() < max {
+            match self.pending.pop() {
+                Some((request, path)) => {
+                    self.requested.insert(request, path);
+                    requests.push(request);
+                }
+                None => break,
+            }
+        }
+        requests
+    }
+
+    /// Put back requests which were not answered.
+    pub fn requeue(&mut self, requests: &[NodeRequest]) {
+        for request in requests {
+            if let Some(path) = self.requested.remove(request) {
+                self.pending.push((*request, path));
+            }
+        }
+    }
+
+    /// Feed the data answered for `requests`, one entry per request. Items which are
+    /// missing or do not match their hash are requested again. Returns the number of
+    /// such items.
+    pub fn feed(&mut self, requests: &[NodeRequest], data: &[Bytes]) -> Result<usize, Error> {
+        let mut missing = 0;
+        let mut batch = DBTransaction::new();
+        let mut children = Vec::new();
+
+        for (i, request) in requests.iter().enumerate() {
+            let path = match self.requested.remove(request) {
+                Some(path) => path,
+                None => continue,
+            };
+            let value = data.get(i).map_or(&[][..], |value| &value[..]);
+
+            if value.is_empty() || !verify(request, value) {
+                missing += 1;
+                self.pending.push((*request, path));
+                continue;
+            }
+
+            self.write(request, value, &mut batch);
+            children.extend(self.expand(request, &path, value)?);
+            self.downloaded += 1;
+        }
+
+        self.enqueue(children)?;
+        self.db.inject(&mut batch)?;
+        self.db.backing().write(batch)?;
+        Ok(missing)
+    }
+
+    /// Whether the whole state has been downloaded.
+    pub fn is_done(&self) -> bool { self.pending.is_empty() && self.requested.is_empty() }
+
+    /// Finalize the download. Writes the account bloom and journals the state under the
+    /// given era and block hash.
+    pub fn finalize(mut self, era: u64, id: H256) -> Result<(), Error> {
+        if !self.is_done() {
+            return Err(Error::RestorationAborted);
+        }
+        let mut batch = DBTransaction::new();
+        StateDB::commit_bloom(&mut batch, self.bloom.drain_journal())?;
+        self.db.journal_under(&mut batch, era, &id)?;
+        self.db.backing().write(batch)?;
+        Ok(())
+    }
+
+    // queue items for download, expanding those already present locally.
+    fn enqueue(&mut self, items: Vec<(NodeRequest, Vec<u8>)>) -> Result<(), Error> {
+        let mut stack = items;
+        while let Some((request, path)) = stack.pop() {
+            match self.local(&request) {
+                Some(value) => stack.extend(self.expand(&request, &path, &value)?),
+                None => self.pending.push((request, path)),
+            }
+        }
+        Ok(())
+    }
+
+    fn local(&self, request: &NodeRequest) -> Option<DBValue> {
+        let db = self.db.as_hashstore();
+        match request.kind {
+            NodeKind::State => db.get(&request.hash),
+            NodeKind::Storage => {
+                AccountDB::from_hash(db, request.account_key)
+                    .get(&request.hash)
+                    .or_else(|| self.graph(&request.hash))
+            }
+            NodeKind::Code => AccountDB::from_hash(db, request.account_key).get(&request.hash),
+            NodeKind::AvmGraph => self.graph(&request.hash),
+        }
+    }
+
+    fn graph(&self, hash: &H256) -> Option<DBValue> {
+        self.db
+            .backing()
+            .get(::db::COL_AVM_GRAPH, hash)
+            .ok()
+            .and_then(|value| value)
+    }
+
+    fn write(&mut self, request: &NodeRequest, value: &[u8], batch: &mut DBTransaction) {
+        match request.kind {
+            NodeKind::State => {
+                self.db
+                    .as_hashstore_mut()
+                    .emplace(request.hash, DBValue::from_slice(value))
+            }
+            NodeKind::Storage if blake2b(value) != request.hash => {
+                batch.put(::db::COL_AVM_GRAPH, &request.hash[..], value)
+            }
+            NodeKind::Storage | NodeKind::Code => {
+                AccountDBMut::from_hash(self.db.as_hashstore_mut(), request.account_key)
+                    .emplace(request.hash, DBValue::from_slice(value))
+            }
+            NodeKind::AvmGraph => batch.put(::db::COL_AVM_GRAPH, &request.hash[..], value),
+        }
+    }
+
+    // items referenced by a verified item.
+    fn expand(
+        &mut self,
+        request: &NodeRequest,
+        path: &[u8],
+        value: &[u8],
+    ) -> Result<Vec<(NodeRequest, Vec<u8>)>, Error>
+    {
+        let mut items = Vec::new();
+        match request.kind {
+            NodeKind::State => {
+                let mut children = Vec::new();
+                let mut leaves = Vec::new();
+                walk_node(&UntrustedRlp::new(value), path, &mut children, &mut leaves)?;
+                for (hash, child_path) in children {
+                    let child = NodeRequest {
+                        kind: NodeKind::State,
+                        account_key: H256::zero(),
+                        hash: hash,
+                    };
+                    items.push((child, child_path));
+                }
+                for (leaf_path, account_rlp) in leaves {
+                    let account_key = account_key(&leaf_path)?;
+                    let account: BasicAccount = UntrustedRlp::new(&account_rlp).as_val()?;
+                    self.bloom.set(&*account_key);
+                    if account.code_hash != BLAKE2B_EMPTY {
+                        items.push((
+                            NodeRequest {
+                                kind: NodeKind::Code,
+                                account_key: account_key,
+                                hash: account.code_hash,
+                            },
+                            Vec::new(),
+                        ));
+                    }
+                    if account.storage_root != BLAKE2B_NULL_RLP {
+                        items.push((
+                            NodeRequest {
+                                kind: NodeKind::Storage,
+                                account_key: account_key,
+                                hash: account.storage_root,
+                            },
+                            Vec::new(),
+                        ));
+                    }
+                }
+            }
+            NodeKind::Storage if blake2b(value) != request.hash => {
+                // roots entry of an AVM delta root: `[storage_root, graph_hash]`.
+                let roots: Vec<H256> = UntrustedRlp::new(value).as_list()?;
+                if roots.len() != 2 {
+                    return Err(Error::MissingObjectGraph(request.hash));
+                }
+                let account_key = request.account_key;
+                let mut push = |kind, hash| {
+                    let item = NodeRequest {
+                        kind: kind,
+                        account_key: account_key,
+                        hash: hash,
+                    };
+                    items.push((item, Vec::new()));
+                };
+                if roots[0] != BLAKE2B_NULL_RLP {
+                    push(NodeKind::Storage, roots[0]);
+                }
+                // transformed AVM code is not requested: nothing commits to it, and the
+                // AVM transforms the verified code again on the first call.
+                push(NodeKind::AvmGraph, roots[1]);
+            }
+            NodeKind::Storage => {
+                let mut children = Vec::new();
+                walk_node(&UntrustedRlp::new(value), path, &mut children, &mut Vec::new())?;
+                for (hash, _) in children {
+                    let child = NodeRequest {
+                        kind: NodeKind::Storage,
+                        account_key: request.account_key,
+                        hash: hash,
+                    };
+                    items.push((child, Vec::new()));
+                }
+            }
+            NodeKind::Code | NodeKind::AvmGraph => {}
+        }
+        Ok(items)
+    }
+}
+
+/// An ongoing warp sync of the state at a pivot block.
+pub struct WarpRestoration {
+    pivot: Pivot,
+    healer: StateHealer,
+    db: Arc<KeyValueDB>,
+    chain: Arc<BlockChain>,
+    engine: Arc<Engine>,
+}
+
+impl WarpRestoration {
+    /// Start downloading the state of `pivot` into the state database, key-value database
+    /// and chain, verifying the pivot blocks with `engine`.
+    pub fn new(
+        pivot: Pivot,
+        state_db: Box<JournalDB>,
+        db: Arc<KeyValueDB>,
+        chain: Arc<BlockChain>,
+        engine: Arc<Engine>,
+    ) -> Result<Self, Error>
+    {
+        let healer = StateHealer::new(state_db, pivot.state_root)?;
+        Ok(WarpRestoration {
+            pivot: pivot,
+            healer: healer,
+            db: db,
+            chain: chain,
+            engine: engine,
+        })
+    }
+
+    /// The pivot being synced.
+    pub fn pivot(&self) -> &Pivot { &self.pivot }
+
+    /// Switch to a newer pivot, keeping the state downloaded so far.
+    pub fn repivot(&mut self, pivot: Pivot) -> Result<(), Error> {
+        self.healer.set_root(pivot.state_root)?;
+        self.pivot = pivot;
+        Ok(())
+    }
+
+    /// Take up to `max` state items to request from peers.
+    pub fn next_requests(&mut self, max: usize) -> Vec<NodeRequest> {
+        self.healer.next_requests(max)
+    }
+
+    /// Put back requests which were not answered.
+    pub fn requeue(&mut self, requests: &[NodeRequest]) { self.healer.requeue(requests) }
+
+    /// Feed the data answered for `requests`. Returns the number of items still missing.
+    pub fn feed(&mut self, requests: &[NodeRequest], data: &[Bytes]) -> Result<usize, Error> {
+        self.healer.feed(requests, data)
+    }
+
+    /// Number of state items downloaded so far.
+    pub fn downloaded(&self) -> usize { self.healer.downloaded() }
+
+    /// Whether the whole state has been downloaded.
+    pub fn is_done(&self) -> bool { self.healer.is_done() }
+
+    /// Write the state and insert the pivot blocks, making the pivot the best block.
+    pub fn finalize(self) -> Result<(), Error> {
+        self.healer.finalize(self.pivot.number, self.pivot.hash)?;
+        let mut blocks = BlockRebuilder::new(self.chain, self.db, self.engine, self.pivot.hash);
+        blocks.feed(&self.pivot.blocks)?;
+        blocks.finalize()
+    }
+}
+
+// check an item against the hash it was requested by.
+fn verify(request: &NodeRequest, value: &[u8]) -> bool {
+    match request.kind {
+        NodeKind::Storage if blake2b(value) != request.hash => {
+            // roots entry of an AVM delta root.
+            match UntrustedRlp::new(value).as_list::<H256>() {
+                Ok(ref roots) if roots.len() == 2 => {
+                    let mut concatenated_root = Vec::new();
+                    concatenated_root.extend_from_slice(&roots[0][..]);
+                    concatenated_root.extend_from_slice(&roots[1][..]);
+                    blake2b(&concatenated_root) == request.hash
+                }
+                _ => false,
+            }
+        }
+        _ => blake2b(value) == request.hash,
+    }
+}
+
+// account key of a state trie leaf from its full nibble path.
+fn account_key(path: &[u8]) -> Result<H256, DecoderError> {
+    if path.len() != 64 {
+        return Err(DecoderError::Custom("Account trie leaf at wrong depth"));
+    }
+    let bytes: Vec<u8> = path
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect();
+    Ok(H256::from_slice(&bytes))
+}
+
+// collect the hashes of the children referenced by a trie node and the leaves it holds,
+// with their nibble paths. Nodes shorter than 32 bytes are inlined in their parent.
+fn walk_node(
+    node: &UntrustedRlp,
+    path: &[u8],
+    children: &mut Vec<(H256, Vec<u8>)>,
+    leaves: &mut Vec<(Vec<u8>, Bytes)>,
+) -> Result<(), DecoderError>
+{
+    match node.item_count()? {
+        2 => {
+            let encoded = node.at(0)?.data()?;
+            if encoded.is_empty() {
+                return Err(DecoderError::RlpIsTooShort);
+            }
+            // hex-prefix encoding: 0x20 flags a leaf, 0x10 an odd number of nibbles.
+            let mut full_path = path.to_vec();
+            if encoded[0] & 0x10 != 0 {
+                full_path.push(encoded[0] & 0x0f);
+            }
+            for byte in &encoded[1..] {
+                full_path.push(byte >> 4);
+                full_path.push(byte & 0x0f);
+            }
+            if encoded[0] & 0x20 != 0 {
+                leaves.push((full_path, node.val_at(1)?));
+            } else {
+                walk_child(&node.at(1)?, &full_path, children, leaves)?;
+            }
+        }
+        17 => {
+            for i in 0..16 {
+                let mut child_path = path.to_vec();
+                child_path.push(i as u8);
+                walk_child(&node.at(i)?, &child_path, children, leaves)?;
+            }
+            let value: Bytes = node.val_at(16)?;
+            if !value.is_empty() {
+                leaves.push((path.to_vec(), value));
+            }
+        }
+        _ => return Err(DecoderError::RlpIncorrectListLen),
+    }
+    Ok(())
+}
+
+fn walk_child(
+    child: &UntrustedRlp,
+    path: &[u8],
+    children: &mut Vec<(H256, Vec<u8>)>,
+    leaves: &mut Vec<(Vec<u8>, Bytes)>,
+) -> Result<(), DecoderError>
+{
+    if child.is_list() {
+        return walk_node(child, path, children, leaves);
+    }
+    let data = child.data()?;
+    match data.len() {
+        0 => {}
+        32 => children.push((H256::from_slice(data), path.to_vec())),
+        _ => return Err(DecoderError::RlpInvalidLength),
+    }
+    Ok(())
+}
@@ -772,7 +772,7 @@ impl VMAccount for AionVMAccount {
         &self,
         db: &HashStore,
         storage_key: H256,
-    ) -> Result<(Vec<Bytes>, H256), Box<TrieError>>
+    ) -> Result<(Vec<Bytes>, Bytes), Box<TrieError>>
     {
         use trie::{Trie, TrieDB};
         use trie::recorder::Recorder;
@@ -780,15 +780,16 @@ impl VMAccount for AionVMAccount {
         let mut recorder = Recorder::new();
 
         let trie = TrieDB::new(db, &self.storage_root)?;
-        let item: U256 = {
+        // values are stored as raw bytes, with leading zeros of fastvm words stripped
+        let item: Bytes = {
             let query = (&mut recorder, ::rlp::decode);
             trie.get_with(&storage_key, query)?
-                .unwrap_or_else(U256::zero)
+                .unwrap_or_else(Vec::new)
         };
 
         Ok((
             recorder.drain().into_iter().map(|r| r.data).collect(),
-            item.into(),
+            item,
         ))
     }
 }
@@ -142,5 +142,5 @@ pub trait VMAccount: Sync + Send {
         &self,
         db: &HashStore,
         storage_key: H256,
-    ) -> Result<(Vec<Bytes>, H256), Box<TrieError>>;
+    ) -> Result<(Vec<Bytes>, Bytes), Box<TrieError>>;
 }
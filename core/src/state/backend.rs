@@ -81,18 +81,17 @@ pub trait Backend: Send {
 // usage.
 #[derive(Clone, PartialEq)]
 pub struct ProofCheck(MemoryDB);
-//
-//impl ProofCheck {
-//    /// Create a new `ProofCheck` backend from the given state items.
-//    #[cfg(test)]
-//    pub fn new(proof: &[DBValue]) -> Self {
-//        let mut db = MemoryDB::new();
-//        for item in proof {
-//            db.insert(item);
-//        }
-//        ProofCheck(db)
-//    }
-//}
+
+impl ProofCheck {
+    /// Create a new `ProofCheck` backend from the given state items.
+    pub fn new(proof: &[DBValue]) -> Self {
+        let mut db = MemoryDB::new();
+        for item in proof {
+            db.insert(item);
+        }
+        ProofCheck(db)
+    }
+}
 
 impl HashStore for ProofCheck {
     fn keys(&self) -> HashMap<H256, i32> { self.0.keys() }
@@ -140,6 +139,21 @@ pub struct Proving<H: AsHashStore> {
     proof: Mutex<HashSet<DBValue>>,
 }
 
+impl<H: AsHashStore> Proving<H> {
+    /// Create a new `Proving` over a base database.
+    /// This will store all values ever fetched from that base.
+    pub fn new(base: H) -> Self {
+        Proving {
+            base: base,
+            changed: MemoryDB::new(),
+            proof: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Consume the backend, extracting the gathered proof.
+    pub fn extract_proof(self) -> Vec<DBValue> { self.proof.into_inner().into_iter().collect() }
+}
+
 impl<H: AsHashStore + Send + Sync> HashStore for Proving<H> {
     fn keys(&self) -> HashMap<H256, i32> {
         let mut keys = self.base.as_hashstore().keys();
@@ -45,10 +45,11 @@ use db::StateDB;
 use transaction::SignedTransaction;
 use types::state::state_diff::StateDiff;
 use vms::EnvInfo;
+use trace::FlatTrace;
 
 use aion_types::{Address, H256, U256};
 use acore_bytes::Bytes;
-use kvdb::{KeyValueDB};
+use kvdb::{AsHashStore, DBValue, KeyValueDB};
 
 use trie;
 use trie::recorder::Recorder;
@@ -80,11 +81,103 @@ use self::account_state::{AccountEntry, AccountState};
 pub struct ApplyOutcome {
     /// The receipt for the applied transaction.
     pub receipt: Receipt,
+    /// The trace for the applied transaction, empty if tracing was not enabled.
+    pub trace: Vec<FlatTrace>,
 }
 
 /// Result type for the execution ("application") of a transaction.
 pub type ApplyResult = Result<ApplyOutcome, Error>;
 
+/// Return type of proof validity check.
+#[derive(Debug, Clone)]
+pub enum ProvedExecution {
+    /// Proof wasn't enough to complete execution.
+    BadProof,
+    /// The transaction failed, but not due to a bad proof.
+    Failed(ExecutionError),
+    /// The transaction successfully completed with the given proof.
+    Complete(Executed),
+}
+
+/// Check the given proof of a virtual call.
+/// Returns `ProvedExecution::BadProof` when the state items are not enough to run the call.
+pub fn check_proof(
+    proof: &[DBValue],
+    root: H256,
+    transaction: &SignedTransaction,
+    machine: &Machine,
+    env_info: &EnvInfo,
+    kvdb: Arc<KeyValueDB>,
+) -> ProvedExecution
+{
+    let backend = self::backend::ProofCheck::new(proof);
+    // proof items are keyed by their plain hashes.
+    let mut factories = Factories::default();
+    factories.accountdb = ::db::Factory::Plain;
+
+    let res = State::from_existing(
+        backend,
+        root,
+        machine.account_start_nonce(env_info.number),
+        factories,
+        kvdb,
+    );
+    let mut state = match res {
+        Ok(state) => state,
+        Err(_) => return ProvedExecution::BadProof,
+    };
+
+    let result = {
+        let mut executive = Executive::new(&mut state, env_info, machine);
+        executive.transact_virtual(transaction, false)
+    };
+    match result {
+        Ok(executed) => ProvedExecution::Complete(executed),
+        Err(ExecutionError::Internal(_)) => ProvedExecution::BadProof,
+        Err(e) => ProvedExecution::Failed(e),
+    }
+}
+
+/// Prove a virtual call on the given state.
+/// Returns `None` when the call could not be proved, otherwise its output and the
+/// state items it read.
+pub fn prove_transaction_virtual<H: AsHashStore + Send + Sync>(
+    db: H,
+    root: H256,
+    transaction: &SignedTransaction,
+    machine: &Machine,
+    env_info: &EnvInfo,
+    factories: Factories,
+    kvdb: Arc<KeyValueDB>,
+) -> Option<(Bytes, Vec<DBValue>)>
+{
+    let backend = self::backend::Proving::new(db);
+    let res = State::from_existing(
+        backend,
+        root,
+        machine.account_start_nonce(env_info.number),
+        factories,
+        kvdb,
+    );
+    let mut state = match res {
+        Ok(state) => state,
+        Err(_) => return None,
+    };
+
+    let result = {
+        let mut executive = Executive::new(&mut state, env_info, machine);
+        executive.transact_virtual(transaction, false)
+    };
+    match result {
+        Err(ExecutionError::Internal(_)) => None,
+        Err(e) => {
+            trace!(target: "state", "Proved call failed: {}", e);
+            Some((Vec::new(), state.drop().1.extract_proof()))
+        }
+        Ok(res) => Some((res.output, state.drop().1.extract_proof())),
+    }
+}
+
 /// Representation of the entire state of all accounts in the system.
 ///
 /// `State` can work together with `StateDB` to share account cache.
@@ -662,7 +755,7 @@ impl<B: Backend> State<B> {
         Ok(())
     }
 
-    /// Execute a given transaction, producing a receipt.
+    /// Execute a given transaction, producing a receipt and an optional trace.
     /// This will change the state accordingly.
     pub fn apply(
         &mut self,
@@ -670,9 +763,10 @@ impl<B: Backend> State<B> {
         machine: &Machine,
         t: &SignedTransaction,
         check_gas_limit: bool,
+        tracing: bool,
     ) -> ApplyResult
     {
-        let e = self.execute(env_info, machine, t, true, false, check_gas_limit)?;
+        let e = self.execute(env_info, machine, t, true, false, check_gas_limit, tracing)?;
 
         self.commit()?;
         let state_root = self.root().clone();
@@ -689,6 +783,7 @@ impl<B: Backend> State<B> {
 
         Ok(ApplyOutcome {
             receipt,
+            trace: e.trace,
         })
     }
 
@@ -698,9 +793,11 @@ impl<B: Backend> State<B> {
         machine: &Machine,
         txs: &[SignedTransaction],
         check_gas: bool,
+        tracing: bool,
     ) -> Vec<ApplyResult>
     {
-        let exec_results = self.execute_bulk(env_info, machine, txs, false, false, check_gas);
+        let exec_results =
+            self.execute_bulk(env_info, machine, txs, false, false, check_gas, tracing);
         if !exec_results.is_empty() && !exec_results[0].is_ok() {
             return vec![Err(From::from(exec_results[0].clone().unwrap_err()))];
         }
@@ -721,6 +818,7 @@ impl<B: Backend> State<B> {
                     );
                     Ok(ApplyOutcome {
                         receipt,
+                        trace: e.trace,
                     })
                 }
                 Err(x) => Err(From::from(x)),
@@ -741,9 +839,13 @@ impl<B: Backend> State<B> {
         check_nonce: bool,
         virt: bool,
         check_gas: bool,
+        tracing: bool,
     ) -> Vec<Result<Executed, ExecutionError>>
     {
-        let mut e = Executive::new(self, env_info, machine);
+        let mut e = match tracing {
+            true => Executive::new_with_tracing(self, env_info, machine),
+            false => Executive::new(self, env_info, machine),
+        };
 
         match virt {
             true => e.transact_virtual_bulk(txs, check_nonce),
@@ -763,9 +865,13 @@ impl<B: Backend> State<B> {
         check_nonce: bool,
         virt: bool,
         check_gas_limit: bool,
+        tracing: bool,
     ) -> Result<Executed, ExecutionError>
     {
-        let mut e = Executive::new(self, env_info, machine);
+        let mut e = match tracing {
+            true => Executive::new_with_tracing(self, env_info, machine),
+            false => Executive::new(self, env_info, machine),
+        };
 
         match virt {
             true => e.transact_virtual(t, check_nonce),
@@ -905,34 +1011,52 @@ impl<B: Backend> State<B> {
         self.cache.borrow().iter().map(|(add, _)| *add).collect()
     }
 
-    fn query_pod(&mut self, query: &PodState, touched_addresses: &[Address]) -> trie::Result<()> {
+    // Return the accounts at `touched_addresses` as they are in this state, each with
+    // the current values of the storage keys that `query` holds for it.
+    fn pod_for_query(
+        &self,
+        query: &PodState,
+        touched_addresses: &[Address],
+    ) -> trie::Result<PodState>
+    {
         let pod = query.get();
+        let mut accounts = BTreeMap::new();
 
         for address in touched_addresses {
             if !self.ensure_cached(address, RequireCache::Code, true, |a| a.is_some())? {
                 continue;
             }
 
+            let mut storage = BTreeMap::new();
             if let Some(pod_account) = pod.get(address) {
-                // needs to be split into two parts for the refcell code here
-                // to work.
                 for key in pod_account.storage.keys() {
-                    self.storage_at(address, &key[..].to_vec())?;
+                    if let Some(value) = self.storage_at(address, key)? {
+                        storage.insert(key.clone(), value);
+                    }
                 }
             }
+
+            accounts.insert(
+                *address,
+                PodAccount {
+                    balance: self.balance(address)?,
+                    nonce: self.nonce(address)?,
+                    code: self.code(address)?.map(|code| (*code).clone()),
+                    storage,
+                },
+            );
         }
 
-        Ok(())
+        Ok(PodState::from(accounts))
     }
 
     /// Returns a `StateDiff` describing the difference from `orig` to `self`.
-    /// Consumes self.
+    /// Covers balance, nonce, code and storage changes of every account touched in `self`.
     pub fn diff_from<X: Backend>(&self, orig: State<X>) -> trie::Result<StateDiff> {
         let addresses_post = self.touched_addresses();
         let pod_state_post = self.to_pod();
-        let mut state_pre = orig;
-        state_pre.query_pod(&pod_state_post, &addresses_post)?;
-        Ok(pod_state::diff_pod(&state_pre.to_pod(), &pod_state_post))
+        let pod_state_pre = orig.pod_for_query(&pod_state_post, &addresses_post)?;
+        Ok(pod_state::diff_pod(&pod_state_pre, &pod_state_post))
     }
 
     /// Check caches for required data
@@ -1143,7 +1267,7 @@ impl<B: Backend> State<B> {
         &self,
         account_key: H256,
         storage_key: H256,
-    ) -> trie::Result<(Vec<Bytes>, H256)>
+    ) -> trie::Result<(Vec<Bytes>, Bytes)>
     {
         // TODO: probably could look into cache somehow but it's keyed by
         // address, not blake2b(address).
@@ -1151,7 +1275,7 @@ impl<B: Backend> State<B> {
         //TODO: update account type
         let acc = match trie.get_with(&account_key, AionVMAccount::from_rlp)? {
             Some(acc) => acc,
-            None => return Ok((Vec::new(), H256::new())),
+            None => return Ok((Vec::new(), Vec::new())),
         };
 
         let account_db = self
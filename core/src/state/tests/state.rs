@@ -23,6 +23,7 @@
 use super::super::{State, CleanupMode, AccType, AionVMAccount};
 use std::sync::Arc;
 use aion_types::{Address, U256};
+use blake2b::blake2b;
 use helpers::{get_temp_state,get_temp_state_with_nonce};
 use kvdb::MockDbRepository;
 
@@ -213,3 +214,28 @@ fn ensure_cached() {
         "9d6d4b335038e1ffe0f060c29e52d6eed2aec4a085dfa37afba9d1e10cc7be85".into()
     );
 }
+
+#[test]
+fn prove_account_and_storage() {
+    let a = Address::from(1);
+    let key = vec![0x01; 16];
+    let mut state = get_temp_state_with_nonce();
+    state
+        .add_balance(&a, &U256::from(69u64), CleanupMode::NoEmpty)
+        .unwrap();
+    // leading zero bytes of a stored value are kept
+    state.set_storage(&a, key.clone(), vec![0x00, 0x45]).unwrap();
+    state.commit().unwrap();
+
+    let (proof, account) = state.prove_account(blake2b(&a)).unwrap();
+    assert!(!proof.is_empty());
+    assert_eq!(account.balance, U256::from(69u64));
+    assert_eq!(account.storage_root, state.storage_root(&a).unwrap().unwrap());
+
+    let (proof, value) = state.prove_storage(blake2b(&a), blake2b(&key)).unwrap();
+    assert!(!proof.is_empty());
+    assert_eq!(value, vec![0x00, 0x45]);
+
+    let (_, value) = state.prove_storage(blake2b(&a), blake2b(&[0x02; 16])).unwrap();
+    assert!(value.is_empty());
+}
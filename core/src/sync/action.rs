@@ -31,6 +31,16 @@ pub enum Action {
     BODIESRES = 5,
     BROADCASTTX = 6,
     BROADCASTBLOCK = 7,
+    SNAPSHOTMANIFESTREQ = 8,
+    SNAPSHOTMANIFESTRES = 9,
+    SNAPSHOTDATAREQ = 10,
+    SNAPSHOTDATARES = 11,
+    LIGHTREQ = 12,
+    LIGHTRES = 13,
+    WARPPIVOTREQ = 14,
+    WARPPIVOTRES = 15,
+    WARPNODESREQ = 16,
+    WARPNODESRES = 17,
     UNKNOWN = 0xFF,
 }
 
@@ -45,6 +55,16 @@ impl Action {
             Action::BODIESRES => 5 as u8,
             Action::BROADCASTTX => 6 as u8,
             Action::BROADCASTBLOCK => 7 as u8,
+            Action::SNAPSHOTMANIFESTREQ => 8 as u8,
+            Action::SNAPSHOTMANIFESTRES => 9 as u8,
+            Action::SNAPSHOTDATAREQ => 10 as u8,
+            Action::SNAPSHOTDATARES => 11 as u8,
+            Action::LIGHTREQ => 12 as u8,
+            Action::LIGHTRES => 13 as u8,
+            Action::WARPPIVOTREQ => 14 as u8,
+            Action::WARPPIVOTRES => 15 as u8,
+            Action::WARPNODESREQ => 16 as u8,
+            Action::WARPNODESRES => 17 as u8,
             Action::UNKNOWN => 0xFF as u8,
         }
     }
@@ -59,6 +79,16 @@ impl Action {
             5 => Action::BODIESRES,
             6 => Action::BROADCASTTX,
             7 => Action::BROADCASTBLOCK,
+            8 => Action::SNAPSHOTMANIFESTREQ,
+            9 => Action::SNAPSHOTMANIFESTRES,
+            10 => Action::SNAPSHOTDATAREQ,
+            11 => Action::SNAPSHOTDATARES,
+            12 => Action::LIGHTREQ,
+            13 => Action::LIGHTRES,
+            14 => Action::WARPPIVOTREQ,
+            15 => Action::WARPPIVOTRES,
+            16 => Action::WARPNODESREQ,
+            17 => Action::WARPNODESRES,
             _ => Action::UNKNOWN,
         }
     }
@@ -27,7 +27,7 @@ use client::{BlockId, BlockChainClient};
 use aion_types::H256;
 use bytes::BufMut;
 use rlp::{RlpStream, UntrustedRlp};
-use p2p::{ChannelBuffer, Mgr};
+use p2p::{ChannelBuffer, Mgr, Report};
 use sync::action::Action;
 use sync::storage::SyncStorage;
 use sync::wrappers::{HeadersWrapper, BlocksWrapper};
@@ -85,7 +85,14 @@ pub fn send(p2p: Mgr, hash: u64, hashes: Vec<u8>) -> bool {
     p2p.send(hash, cb)
 }
 
-pub fn receive_req(p2p: Mgr, hash: u64, client: Arc<BlockChainClient>, cb_in: ChannelBuffer) {
+pub fn receive_req(
+    p2p: Mgr,
+    hash: u64,
+    client: Arc<BlockChainClient>,
+    cb_in: ChannelBuffer,
+    storage: Arc<SyncStorage>,
+)
+{
     trace!(target: "sync", "bodies/receive_req");
 
     // check channelbuffer len
@@ -102,6 +109,11 @@ pub fn receive_req(p2p: Mgr, hash: u64, client: Arc<BlockChainClient>, cb_in: Ch
 
     let mut res_body = Vec::new();
     let hash_count = cb_in.head.len / HASH_LEN as u32;
+    if !storage.allow_request(hash, Action::BODIESREQ, hash_count as u64) {
+        debug!(target: "sync", "bodies/receive_req quota exceeded by node {}", hash);
+        p2p.report(hash, Report::ExceededQuota);
+        return;
+    }
     let mut rest = cb_in.body.as_slice();
     let mut data = Vec::new();
     let mut body_count = 0;
@@ -197,6 +209,7 @@ pub fn receive_res(p2p: Mgr, node_hash: u64, cb_in: ChannelBuffer, storage: Arc<
             bodies.len(),
             node_hash
         );
+        p2p.report(node_hash, Report::InvalidBody);
     }
 
     // end if no block to download
@@ -213,4 +226,5 @@ pub fn receive_res(p2p: Mgr, node_hash: u64, cb_in: ChannelBuffer, storage: Arc<
     // TODO: maybe we should consider reset the header request cooldown here
 
     p2p.update_node(&node_hash);
+    p2p.report(node_hash, Report::UsefulResponse);
 }
@@ -28,18 +28,18 @@ use parking_lot::{Mutex, RwLock};
 
 use engine::unity_engine::UnityEngine;
 use header::Header;
+use light::{Error as LightError, HeaderChain};
 use acore_bytes::to_hex;
 use aion_types::U256;
 use client::{BlockChainClient, BlockId};
 use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
 use bytes::BufMut;
 use rlp::{RlpStream, UntrustedRlp};
-use p2p::{ChannelBuffer, Mgr, Node};
+use p2p::{pick_preferred, ChannelBuffer, Mgr, Module, Node, Report};
 use sync::action::Action;
 use sync::wrappers::{HeadersWrapper};
 use sync::node_info::{NodeInfo, Mode};
 use sync::storage::SyncStorage;
-use rand::{thread_rng, Rng};
 
 use super::{channel_buffer_template,channel_buffer_template_with_version};
 
@@ -60,12 +60,16 @@ pub fn sync_headers(
     storage: Arc<SyncStorage>,
 )
 {
-    let active_nodes = p2p.get_active_nodes();
+    let active_nodes: Vec<Node> = p2p
+        .get_active_nodes()
+        .into_iter()
+        .filter(|node| node.supports(Module::SYNC.value(), Action::HEADERSREQ.value()))
+        .collect();
     // Filter nodes. Only sync from nodes with higher total difficulty and with a cooldown restriction.
     let candidates: Vec<Node> =
         filter_nodes_to_sync_headers(active_nodes, nodes_info.clone(), local_total_diff);
-    // Pick a random node among all candidates
-    if let Some(candidate) = pick_random_node(&candidates) {
+    // Pick a random node among the better scored candidates
+    if let Some(candidate) = pick_preferred(&candidates) {
         let candidate_hash = candidate.get_hash();
         let mut node_info;
         let nodes_info_read = nodes_info.read();
@@ -169,7 +173,14 @@ fn send(p2p: Mgr, hash: u64, from: u64, size: u32) -> bool {
     p2p.send(hash, cb)
 }
 
-pub fn receive_req(p2p: Mgr, hash: u64, client: Arc<BlockChainClient>, cb_in: ChannelBuffer) {
+pub fn receive_req(
+    p2p: Mgr,
+    hash: u64,
+    client: Arc<BlockChainClient>,
+    cb_in: ChannelBuffer,
+    storage: Arc<SyncStorage>,
+)
+{
     trace!(target: "sync", "headers/receive_req");
 
     // check channelbuffer len
@@ -188,6 +199,11 @@ pub fn receive_req(p2p: Mgr, hash: u64, client: Arc<BlockChainClient>, cb_in: Ch
     let mut res_body = Vec::new();
 
     if size <= LARGE_REQUEST_SIZE {
+        if !storage.allow_request(hash, Action::HEADERSREQ, size as u64) {
+            debug!(target: "sync", "headers/receive_req quota exceeded by node {}", hash);
+            p2p.report(hash, Report::ExceededQuota);
+            return;
+        }
         for i in from..(from + size as u64) {
             match client.block_header(BlockId::Number(i)) {
                 Some(hdr) => {
@@ -269,11 +285,13 @@ pub fn receive_res(p2p: Mgr, hash: u64, cb_in: ChannelBuffer, storage: Arc<SyncS
                 Err(e) => {
                     // ignore this batch if any invalidated header
                     error!(target: "sync", "Invalid header: {:?}, header: {}", e, to_hex(header_rlp.as_raw()));
+                    p2p.report(hash, Report::InvalidHeader);
                     break;
                 }
             }
         } else {
             error!(target: "sync", "Invalid header: {}", to_hex(header_rlp.as_raw()));
+            p2p.report(hash, Report::InvalidHeader);
             break;
         }
     }
@@ -284,6 +302,7 @@ pub fn receive_res(p2p: Mgr, hash: u64, cb_in: ChannelBuffer, storage: Arc<SyncS
         header_wrapper.headers = headers;
         header_wrapper.timestamp = SystemTime::now();
         p2p.update_node(&hash);
+        p2p.report(hash, Report::UsefulResponse);
         let mut downloaded_headers = downloaded_headers.lock();
         downloaded_headers.push_back(header_wrapper);
     } else {
@@ -291,6 +310,80 @@ pub fn receive_res(p2p: Mgr, hash: u64, cb_in: ChannelBuffer, storage: Arc<SyncS
     }
 }
 
+/// Import headers into the header chain of a light client.
+pub fn receive_res_light(
+    p2p: Mgr,
+    hash: u64,
+    cb_in: ChannelBuffer,
+    nodes_info: Arc<RwLock<HashMap<u64, RwLock<NodeInfo>>>>,
+    chain: Arc<HeaderChain>,
+)
+{
+    trace!(target: "sync", "headers/receive_res_light");
+
+    let rlp = UntrustedRlp::new(cb_in.body.as_slice());
+    let mut unknown_number = 0u64;
+    let mut first_imported_number = 0u64;
+    let mut last_imported_number = 0u64;
+
+    for header_rlp in rlp.iter() {
+        let header: Header = match header_rlp.as_val() {
+            Ok(header) => header,
+            Err(_) => {
+                error!(target: "sync", "Invalid header: {}", to_hex(header_rlp.as_raw()));
+                p2p.report(hash, Report::InvalidHeader);
+                break;
+            }
+        };
+        match chain.insert(&header) {
+            Ok(imported) => {
+                if imported {
+                    if first_imported_number == 0 {
+                        first_imported_number = header.number();
+                    }
+                    last_imported_number = header.number();
+                }
+            }
+            Err(LightError::UnknownParent(_)) => {
+                unknown_number = header.number();
+                break;
+            }
+            Err(e) => {
+                error!(target: "sync", "Invalid header: {}, header: {}", e, to_hex(header_rlp.as_raw()));
+                p2p.report(hash, Report::InvalidHeader);
+                break;
+            }
+        }
+    }
+
+    p2p.update_node(&hash);
+    if first_imported_number != 0 {
+        p2p.report(hash, Report::UsefulResponse);
+    }
+
+    let local_best_block = chain.best_block_number();
+    let nodes_info = nodes_info.read();
+    if let Some(node_info) = nodes_info.get(&hash) {
+        let mut info = node_info.write();
+        if first_imported_number != 0 {
+            debug!(target: "sync", "Node: {}, imported headers from {} to {}", hash, first_imported_number, last_imported_number);
+            match info.mode {
+                Mode::Backward | Mode::Forward => {
+                    info.switch_mode(Mode::Forward, &local_best_block, &hash);
+                    info.sync_base_number = last_imported_number + 1;
+                }
+                _ => {}
+            }
+        } else if unknown_number != 0 && unknown_number <= local_best_block {
+            // fork deeper than the overlapping headers, search backward for the fork point
+            info.switch_mode(Mode::Backward, &local_best_block, &hash);
+            info.sync_base_number = unknown_number;
+        } else if info.mode == Mode::Backward || info.mode == Mode::Forward {
+            info.switch_mode(Mode::Normal, &local_best_block, &hash);
+        }
+    }
+}
+
 /// Filter candidates to sync from based on total difficulty and syncing cool down
 fn filter_nodes_to_sync_headers(
     nodes: Vec<Node>,
@@ -316,15 +409,3 @@ fn filter_nodes_to_sync_headers(
         })
         .collect()
 }
-
-/// Pick a random node
-fn pick_random_node(nodes: &Vec<Node>) -> Option<Node> {
-    let count = nodes.len();
-    if count > 0 {
-        let mut rng = thread_rng();
-        let random_index: usize = rng.gen_range(0, count);
-        Some(nodes[random_index].clone())
-    } else {
-        None
-    }
-}
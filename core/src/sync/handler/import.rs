@@ -30,6 +30,7 @@ use views::BlockView;
 use sync::storage::SyncStorage;
 use sync::node_info::{NodeInfo, Mode};
 use aion_types::H256;
+use p2p::{Mgr, Report};
 
 pub fn import_staged_blocks(hash: &H256, client: Arc<BlockChainClient>, storage: Arc<SyncStorage>) {
     let mut blocks_to_import = Vec::new();
@@ -72,6 +73,7 @@ pub fn import_staged_blocks(hash: &H256, client: Arc<BlockChainClient>, storage:
 }
 
 pub fn import_blocks(
+    p2p: Mgr,
     client: Arc<BlockChainClient>,
     storage: Arc<SyncStorage>,
     nodes_info: Arc<RwLock<HashMap<u64, RwLock<NodeInfo>>>>,
@@ -135,6 +137,11 @@ pub fn import_blocks(
                     unknown_parent_hash = block_view.header_view().parent_hash();
                     break;
                 }
+                Err(BlockImportError::Block(e)) => {
+                    warn!(target: "sync", "Node: {}, sent invalid block #{}: {:?}", blocks_wrapper.node_hash, block_number, e);
+                    p2p.report(blocks_wrapper.node_hash, Report::InvalidBody);
+                    break;
+                }
                 Err(_e) => {
                     // TODO add repeat threshold
                     break;
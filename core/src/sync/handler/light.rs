@@ -0,0 +1,140 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::BufMut;
+use client::ProvingBlockChainClient;
+use light::{OnDemand, Request};
+use p2p::{ChannelBuffer, Mgr, Module};
+use parking_lot::RwLock;
+use rand::{thread_rng, Rng};
+use rlp::{RlpStream, UntrustedRlp};
+use sync::action::Action;
+use sync::node_info::NodeInfo;
+
+use super::{channel_buffer_template, channel_buffer_template_with_version};
+
+const LIGHT_REQUEST_TIMEOUT: u64 = 10;
+
+/// Send queued light requests, each to a random peer whose best block covers it.
+pub fn dispatch_requests(
+    p2p: Mgr,
+    on_demand: Arc<OnDemand>,
+    nodes_info: Arc<RwLock<HashMap<u64, RwLock<NodeInfo>>>>,
+)
+{
+    on_demand.expire(Duration::from_secs(LIGHT_REQUEST_TIMEOUT));
+
+    let active_nodes: Vec<u64> = p2p
+        .get_active_nodes()
+        .into_iter()
+        .filter(|node| node.supports(Module::SYNC.value(), Action::LIGHTREQ.value()))
+        .map(|node| node.get_hash())
+        .collect();
+    on_demand.dispatch(|id, request, required| {
+        let candidates: Vec<u64> = {
+            let nodes_info = nodes_info.read();
+            active_nodes
+                .iter()
+                .filter(|node_hash| {
+                    nodes_info.get(node_hash).map_or(false, |info| {
+                        info.read().best_block_number >= required
+                    })
+                })
+                .cloned()
+                .collect()
+        };
+        if candidates.is_empty() {
+            return None;
+        }
+        let node_hash = candidates[thread_rng().gen_range(0, candidates.len())];
+        if send_req(p2p.clone(), node_hash, id, request) {
+            Some(node_hash)
+        } else {
+            None
+        }
+    });
+}
+
+fn send_req(p2p: Mgr, node_hash: u64, id: u64, request: &Request) -> bool {
+    trace!(target: "sync", "light/send_req: {}", id);
+    let mut cb = channel_buffer_template(Action::LIGHTREQ.value());
+    let mut stream = RlpStream::new_list(2);
+    stream.append(&id);
+    stream.append(request);
+    cb.body.put_slice(&stream.out());
+    cb.head.len = cb.body.len() as u32;
+    p2p.send(node_hash, cb)
+}
+
+pub fn receive_req(
+    p2p: Mgr,
+    node_hash: u64,
+    provider: Arc<ProvingBlockChainClient>,
+    cb_in: ChannelBuffer,
+)
+{
+    trace!(target: "sync", "light/receive_req");
+
+    let rlp = UntrustedRlp::new(cb_in.body.as_slice());
+    let (id, request): (u64, Request) = match (rlp.val_at(0), rlp.val_at(1)) {
+        (Ok(id), Ok(request)) => (id, request),
+        _ => {
+            debug!(target: "sync", "light req channelbuffer is invalid");
+            return;
+        }
+    };
+
+    // an answer without payload tells the peer to ask someone else
+    let payload = request.serve(&*provider);
+    let mut stream = RlpStream::new_list(if payload.is_some() { 2 } else { 1 });
+    stream.append(&id);
+    if let Some(ref payload) = payload {
+        stream.append_raw(payload, 1);
+    }
+
+    let mut res = channel_buffer_template_with_version(cb_in.head.ver, Action::LIGHTRES.value());
+    res.body.put_slice(&stream.out());
+    res.head.len = res.body.len() as u32;
+
+    p2p.update_node(&node_hash);
+    p2p.send(node_hash, res);
+}
+
+pub fn receive_res(p2p: Mgr, node_hash: u64, cb_in: ChannelBuffer, on_demand: Arc<OnDemand>) {
+    trace!(target: "sync", "light/receive_res");
+
+    let rlp = UntrustedRlp::new(cb_in.body.as_slice());
+    let id: u64 = match rlp.val_at(0) {
+        Ok(id) => id,
+        Err(_) => {
+            debug!(target: "sync", "light res channelbuffer is invalid");
+            return;
+        }
+    };
+    let payload = rlp.at(1).ok().map(|payload| payload.as_raw());
+
+    p2p.update_node(&node_hash);
+    on_demand.on_response(node_hash, id, payload);
+}
@@ -24,6 +24,9 @@ pub mod headers;
 pub mod bodies;
 pub mod broadcast;
 pub mod import;
+pub mod snapshot;
+pub mod light;
+pub mod warp;
 
 use p2p::{Module, PROTOCAL_VERSION, ChannelBuffer};
 
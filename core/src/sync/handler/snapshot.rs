@@ -0,0 +1,266 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use aion_types::H256;
+use blake2b::blake2b;
+use bytes::BufMut;
+use p2p::{ChannelBuffer, Mgr, Module};
+use snapshot::{ManifestData, RestorationStatus, SnapshotService};
+use sync::action::Action;
+use sync::storage::SyncStorage;
+
+use super::{channel_buffer_template, channel_buffer_template_with_version};
+
+const HASH_LEN: usize = 32;
+const CHUNK_REQUEST_TIMEOUT: u64 = 30;
+/// Nodes which have to offer the same snapshot before it is restored, unless it is at the
+/// sync checkpoint
+const MIN_MANIFEST_OFFERS: usize = 3;
+
+/// Whether block sync should wait for snapshot sync: either a snapshot is being
+/// restored or a state warp synced, or we are still looking for a peer offering one.
+pub fn is_syncing(snapshot: &SnapshotService, storage: &SyncStorage) -> bool {
+    snapshot.is_restoring() || snapshot.is_warping() || storage.is_waiting_for_snapshot()
+}
+
+pub fn sync_snapshot(
+    p2p: Mgr,
+    snapshot: Arc<SnapshotService>,
+    storage: Arc<SyncStorage>,
+    snapshot_sync: bool,
+)
+{
+    match snapshot.status() {
+        RestorationStatus::Inactive => {
+            // with only warp sync enabled, wait for a pivot rather than a manifest
+            if snapshot_sync && storage.is_waiting_for_snapshot() {
+                request_manifests(p2p, storage);
+            }
+        }
+        RestorationStatus::Ongoing {
+            ..
+        } => request_chunks(p2p, snapshot, storage),
+        RestorationStatus::Failed => {}
+    }
+}
+
+// Ask every peer which has not offered a snapshot yet for its manifest, as a snapshot is
+// only restored once several peers offer the same one.
+fn request_manifests(p2p: Mgr, storage: Arc<SyncStorage>) {
+    let offered: Vec<u64> = storage.snapshot_manifests().lock().keys().cloned().collect();
+    for node in p2p.get_active_nodes() {
+        let node_hash = node.get_hash();
+        if !offered.contains(&node_hash)
+            && node.supports(Module::SYNC.value(), Action::SNAPSHOTMANIFESTREQ.value())
+        {
+            send_manifest_req(p2p.clone(), node_hash);
+        }
+    }
+}
+
+// Ask every idle peer for one chunk which is neither restored nor requested yet.
+fn request_chunks(p2p: Mgr, snapshot: Arc<SnapshotService>, storage: Arc<SyncStorage>) {
+    let timeout = Duration::from_secs(CHUNK_REQUEST_TIMEOUT);
+    let mut requests = storage.snapshot_chunk_requests().lock();
+    requests.retain(|_, &mut (_, time)| {
+        time.elapsed().map_or(true, |elapsed| elapsed < timeout)
+    });
+
+    let mut pending: Vec<H256> = snapshot
+        .pending_chunks()
+        .into_iter()
+        .filter(|hash| !requests.values().any(|&(requested, _)| requested == *hash))
+        .collect();
+
+    for node in p2p.get_active_nodes() {
+        let node_hash = node.get_hash();
+        if requests.contains_key(&node_hash)
+            || !node.supports(Module::SYNC.value(), Action::SNAPSHOTDATAREQ.value())
+        {
+            continue;
+        }
+        let chunk_hash = match pending.pop() {
+            Some(chunk_hash) => chunk_hash,
+            None => break,
+        };
+        if send_data_req(p2p.clone(), node_hash, &chunk_hash) {
+            requests.insert(node_hash, (chunk_hash, SystemTime::now()));
+        }
+    }
+}
+
+pub fn send_manifest_req(p2p: Mgr, node_hash: u64) -> bool {
+    trace!(target: "sync", "snapshot/send_manifest_req");
+    let cb = channel_buffer_template(Action::SNAPSHOTMANIFESTREQ.value());
+    p2p.send(node_hash, cb)
+}
+
+pub fn send_data_req(p2p: Mgr, node_hash: u64, chunk_hash: &H256) -> bool {
+    trace!(target: "sync", "snapshot/send_data_req: {}", chunk_hash);
+    let mut cb = channel_buffer_template(Action::SNAPSHOTDATAREQ.value());
+    cb.body.put_slice(chunk_hash);
+    cb.head.len = cb.body.len() as u32;
+    p2p.send(node_hash, cb)
+}
+
+pub fn receive_manifest_req(
+    p2p: Mgr,
+    node_hash: u64,
+    snapshot: Arc<SnapshotService>,
+    cb_in: ChannelBuffer,
+)
+{
+    trace!(target: "sync", "snapshot/receive_manifest_req");
+
+    let mut res = channel_buffer_template_with_version(
+        cb_in.head.ver,
+        Action::SNAPSHOTMANIFESTRES.value(),
+    );
+    if let Some(manifest) = snapshot.manifest() {
+        res.body.put_slice(&manifest.into_rlp());
+    }
+    res.head.len = res.body.len() as u32;
+
+    p2p.update_node(&node_hash);
+    p2p.send(node_hash, res);
+}
+
+pub fn receive_manifest_res(
+    node_hash: u64,
+    cb_in: ChannelBuffer,
+    snapshot: Arc<SnapshotService>,
+    storage: Arc<SyncStorage>,
+)
+{
+    trace!(target: "sync", "snapshot/receive_manifest_res");
+
+    // peer has no snapshot to offer
+    if cb_in.body.is_empty() {
+        return;
+    }
+    if !storage.is_waiting_for_snapshot() || snapshot.status() != RestorationStatus::Inactive {
+        return;
+    }
+
+    match ManifestData::from_rlp(&cb_in.body) {
+        Ok(manifest) => {
+            if !anchor_manifest(node_hash, &manifest, &storage) {
+                debug!(
+                    target: "sync",
+                    "Node {} offers snapshot at block #{}, waiting for more nodes to offer it",
+                    node_hash, manifest.block_number
+                );
+                return;
+            }
+            info!(
+                target: "sync",
+                "Node {} offers snapshot at block #{}, starting snapshot sync",
+                node_hash, manifest.block_number
+            );
+            storage.stop_waiting_for_snapshot();
+            snapshot.begin_restore(manifest);
+        }
+        Err(e) => {
+            debug!(target: "sync", "Invalid snapshot manifest from node {}: {}", node_hash, e);
+        }
+    }
+}
+
+// Record the manifest offered by a node. Returns whether it may be restored: either it is at
+// the sync checkpoint or enough nodes offer the same one. The restored headers are verified
+// against each other down to their seals, so a manifest agreed on only needs its block hash
+// to be the one of the chain the network follows.
+fn anchor_manifest(node_hash: u64, manifest: &ManifestData, storage: &SyncStorage) -> bool {
+    let mut manifests = storage.snapshot_manifests().lock();
+    if storage.sync_checkpoint() != Some(manifest.block_hash) {
+        manifests.insert(node_hash, manifest.clone());
+        let offers = manifests
+            .values()
+            .filter(|offered| *offered == manifest)
+            .count();
+        if offers < MIN_MANIFEST_OFFERS {
+            return false;
+        }
+    }
+    manifests.clear();
+    true
+}
+
+pub fn receive_data_req(
+    p2p: Mgr,
+    node_hash: u64,
+    snapshot: Arc<SnapshotService>,
+    cb_in: ChannelBuffer,
+)
+{
+    trace!(target: "sync", "snapshot/receive_data_req");
+
+    // check channelbuffer len
+    if cb_in.head.len as usize != HASH_LEN {
+        debug!(target: "sync", "snapshot data req channelbuffer is invalid");
+        return;
+    }
+
+    let mut res =
+        channel_buffer_template_with_version(cb_in.head.ver, Action::SNAPSHOTDATARES.value());
+    if let Some(chunk) = snapshot.chunk(H256::from(&cb_in.body[..])) {
+        res.body.put_slice(&chunk);
+    }
+    res.head.len = res.body.len() as u32;
+
+    p2p.update_node(&node_hash);
+    p2p.send(node_hash, res);
+}
+
+pub fn receive_data_res(
+    node_hash: u64,
+    cb_in: ChannelBuffer,
+    snapshot: Arc<SnapshotService>,
+    storage: Arc<SyncStorage>,
+)
+{
+    trace!(target: "sync", "snapshot/receive_data_res");
+
+    let requested = match storage.snapshot_chunk_requests().lock().remove(&node_hash) {
+        Some((chunk_hash, _)) => chunk_hash,
+        None => return,
+    };
+
+    // peer does not have the chunk
+    if cb_in.body.is_empty() {
+        return;
+    }
+
+    let hash = blake2b(&cb_in.body);
+    if hash != requested {
+        debug!(
+            target: "sync",
+            "Node {} sent chunk {} instead of {}",
+            node_hash, hash, requested
+        );
+        return;
+    }
+
+    snapshot.restore_chunk(hash, cb_in.body);
+}
@@ -0,0 +1,266 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use acore_bytes::Bytes;
+use bytes::BufMut;
+use p2p::{ChannelBuffer, Mgr, Module};
+use rlp::{self, UntrustedRlp};
+use snapshot::{NodeRequest, Pivot, SnapshotService};
+use sync::action::Action;
+use sync::storage::SyncStorage;
+
+use super::{channel_buffer_template, channel_buffer_template_with_version};
+
+const NODES_REQUEST_TIMEOUT: u64 = 10;
+const PIVOT_REQUEST_INTERVAL: u64 = 5;
+const MAX_NODES_PER_REQUEST: usize = 384;
+/// Nodes which have to offer a pivot on the same chain before it is warp synced, unless it
+/// descends from the sync checkpoint
+const MIN_PIVOT_OFFERS: usize = 3;
+
+pub fn sync_warp(p2p: Mgr, snapshot: Arc<SnapshotService>, storage: Arc<SyncStorage>) {
+    if snapshot.is_warping() {
+        if snapshot.needs_pivot() {
+            request_pivot(p2p.clone(), &storage);
+        }
+        request_nodes(p2p, snapshot, storage);
+    } else if storage.is_waiting_for_snapshot() && !snapshot.is_restoring() {
+        request_pivot(p2p, &storage);
+    }
+}
+
+fn request_pivot(p2p: Mgr, storage: &SyncStorage) {
+    if !storage.should_request_pivot(Duration::from_secs(PIVOT_REQUEST_INTERVAL)) {
+        return;
+    }
+    // a pivot is only taken once several peers agree on its chain, ask all of them
+    for node in p2p.get_active_nodes() {
+        if node.supports(Module::SYNC.value(), Action::WARPPIVOTREQ.value()) {
+            send_pivot_req(p2p.clone(), node.get_hash());
+        }
+    }
+}
+
+// Ask every idle peer for a batch of state items, putting back the timed out ones.
+fn request_nodes(p2p: Mgr, snapshot: Arc<SnapshotService>, storage: Arc<SyncStorage>) {
+    let timeout = Duration::from_secs(NODES_REQUEST_TIMEOUT);
+    let mut requests = storage.warp_node_requests().lock();
+    let expired: Vec<u64> = requests
+        .iter()
+        .filter(|&(_, &(_, time))| time.elapsed().map_or(false, |elapsed| elapsed >= timeout))
+        .map(|(node_hash, _)| *node_hash)
+        .collect();
+    for node_hash in expired {
+        if let Some((expired_requests, _)) = requests.remove(&node_hash) {
+            snapshot.requeue_warp(&expired_requests);
+        }
+    }
+
+    for node in p2p.get_active_nodes() {
+        let node_hash = node.get_hash();
+        if requests.contains_key(&node_hash)
+            || !node.supports(Module::SYNC.value(), Action::WARPNODESREQ.value())
+        {
+            continue;
+        }
+        let node_requests = snapshot.warp_requests(MAX_NODES_PER_REQUEST);
+        if node_requests.is_empty() {
+            break;
+        }
+        if send_nodes_req(p2p.clone(), node_hash, &node_requests) {
+            requests.insert(node_hash, (node_requests, SystemTime::now()));
+        } else {
+            snapshot.requeue_warp(&node_requests);
+        }
+    }
+}
+
+pub fn send_pivot_req(p2p: Mgr, node_hash: u64) -> bool {
+    trace!(target: "sync", "warp/send_pivot_req");
+    let cb = channel_buffer_template(Action::WARPPIVOTREQ.value());
+    p2p.send(node_hash, cb)
+}
+
+pub fn send_nodes_req(p2p: Mgr, node_hash: u64, requests: &[NodeRequest]) -> bool {
+    trace!(target: "sync", "warp/send_nodes_req: {} items", requests.len());
+    let mut cb = channel_buffer_template(Action::WARPNODESREQ.value());
+    cb.body.put_slice(&rlp::encode_list::<NodeRequest, _>(requests));
+    cb.head.len = cb.body.len() as u32;
+    p2p.send(node_hash, cb)
+}
+
+pub fn receive_pivot_req(
+    p2p: Mgr,
+    node_hash: u64,
+    snapshot: Arc<SnapshotService>,
+    cb_in: ChannelBuffer,
+)
+{
+    trace!(target: "sync", "warp/receive_pivot_req");
+
+    let mut res =
+        channel_buffer_template_with_version(cb_in.head.ver, Action::WARPPIVOTRES.value());
+    if let Some(pivot) = snapshot.warp_pivot() {
+        res.body.put_slice(&pivot);
+    }
+    res.head.len = res.body.len() as u32;
+
+    p2p.update_node(&node_hash);
+    p2p.send(node_hash, res);
+}
+
+pub fn receive_pivot_res(
+    node_hash: u64,
+    cb_in: ChannelBuffer,
+    snapshot: Arc<SnapshotService>,
+    storage: Arc<SyncStorage>,
+)
+{
+    trace!(target: "sync", "warp/receive_pivot_res");
+
+    // peer has no pivot to offer
+    if cb_in.body.is_empty() {
+        return;
+    }
+    if !snapshot.needs_pivot() && !storage.is_waiting_for_snapshot() {
+        return;
+    }
+
+    let pivot = Pivot::from_rlp(&cb_in.body)
+        .and_then(|pivot| snapshot.verify_pivot(&pivot).map(|_| pivot));
+    match pivot {
+        Ok(pivot) => {
+            let offered = pivot.number;
+            let pivot = match anchor_pivot(node_hash, pivot, &storage) {
+                Some(pivot) => pivot,
+                None => {
+                    debug!(
+                        target: "sync",
+                        "Node {} offers warp pivot at block #{}, waiting for more nodes to agree",
+                        node_hash, offered
+                    );
+                    return;
+                }
+            };
+            info!(
+                target: "sync",
+                "Node {} offers warp pivot at block #{}, agreed on at block #{}",
+                node_hash, offered, pivot.number
+            );
+            storage.stop_waiting_for_snapshot();
+            snapshot.begin_warp(pivot);
+        }
+        Err(e) => {
+            debug!(target: "sync", "Invalid warp pivot from node {}: {}", node_hash, e);
+        }
+    }
+}
+
+// Record the pivot offered by a node. Returns the pivot to warp sync at once it is anchored:
+// either its chunk holds the sync checkpoint, or it is the newest pivot whose block is in the
+// chunks offered by most nodes, enough of them.
+fn anchor_pivot(node_hash: u64, pivot: Pivot, storage: &SyncStorage) -> Option<Pivot> {
+    let mut pivots = storage.warp_pivots().lock();
+    if storage
+        .sync_checkpoint()
+        .map_or(false, |checkpoint| pivot.contains(&checkpoint))
+    {
+        pivots.clear();
+        return Some(pivot);
+    }
+
+    pivots.insert(node_hash, pivot);
+    let anchored = pivots
+        .values()
+        .filter(|candidate| {
+            let agreeing = pivots
+                .values()
+                .filter(|offered| offered.contains(&candidate.hash))
+                .count();
+            agreeing >= MIN_PIVOT_OFFERS && agreeing * 2 > pivots.len()
+        })
+        .max_by_key(|candidate| candidate.number)
+        .cloned();
+    if anchored.is_some() {
+        pivots.clear();
+    }
+    anchored
+}
+
+pub fn receive_nodes_req(
+    p2p: Mgr,
+    node_hash: u64,
+    snapshot: Arc<SnapshotService>,
+    cb_in: ChannelBuffer,
+)
+{
+    trace!(target: "sync", "warp/receive_nodes_req");
+
+    let requests: Vec<NodeRequest> = match UntrustedRlp::new(&cb_in.body).as_list() {
+        Ok(ref requests) if requests.len() > MAX_NODES_PER_REQUEST => {
+            debug!(target: "sync", "warp nodes req from node {} is too large", node_hash);
+            return;
+        }
+        Ok(requests) => requests,
+        Err(e) => {
+            debug!(target: "sync", "Invalid warp nodes req from node {}: {}", node_hash, e);
+            return;
+        }
+    };
+
+    let mut res =
+        channel_buffer_template_with_version(cb_in.head.ver, Action::WARPNODESRES.value());
+    res.body.put_slice(&rlp::encode_list::<Bytes, _>(&snapshot.state_nodes(&requests)));
+    res.head.len = res.body.len() as u32;
+
+    p2p.update_node(&node_hash);
+    p2p.send(node_hash, res);
+}
+
+pub fn receive_nodes_res(
+    node_hash: u64,
+    cb_in: ChannelBuffer,
+    snapshot: Arc<SnapshotService>,
+    storage: Arc<SyncStorage>,
+)
+{
+    trace!(target: "sync", "warp/receive_nodes_res");
+
+    let requested = match storage.warp_node_requests().lock().remove(&node_hash) {
+        Some((requests, _)) => requests,
+        None => return,
+    };
+
+    match UntrustedRlp::new(&cb_in.body).as_list::<Bytes>() {
+        Ok(ref data) if data.len() == requested.len() => snapshot.feed_warp(&requested, data),
+        Ok(_) => {
+            debug!(target: "sync", "Node {} answered a different number of items", node_hash);
+            snapshot.requeue_warp(&requested);
+        }
+        Err(e) => {
+            debug!(target: "sync", "Invalid warp nodes res from node {}: {}", node_hash, e);
+            snapshot.requeue_warp(&requested);
+        }
+    }
+}
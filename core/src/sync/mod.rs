@@ -23,6 +23,7 @@ mod handler;
 mod action;
 mod wrappers;
 mod node_info;
+mod quota;
 mod storage;
 mod sync_provider;
 
@@ -31,7 +32,8 @@ use std::time::Duration;
 use std::time::Instant;
 use itertools::Itertools;
 use std::collections::{HashMap};
-use client::{BlockId, BlockChainClient, ChainNotify};
+use std::net::Ipv4Addr;
+use client::{BlockId, BlockChainClient, ChainNotify, ProvingBlockChainClient};
 use transaction::UnverifiedTransaction;
 use aion_types::{H256,U256};
 use futures::Future;
@@ -43,18 +45,27 @@ use futures::sync::oneshot;
 use futures::sync::oneshot::Sender;
 use parking_lot::{Mutex, RwLock};
 
-use p2p::{ ChannelBuffer, Config, Mgr, Callable, PROTOCAL_VERSION, Module};
+use p2p::{ ChannelBuffer, Config, Mgr, Callable, Capability, PROTOCAL_VERSION, Module, Traffic};
 use sync::action::Action;
 use sync::handler::status;
 use sync::handler::bodies;
 use sync::handler::headers;
 use sync::handler::broadcast;
 use sync::handler::import;
+use sync::handler::snapshot;
+use sync::handler::light;
+use sync::handler::warp;
 use sync::node_info::{NodeInfo, Mode};
+use sync::quota::QuotaLimits;
 use sync::storage::SyncStorage;
 use sync::sync_provider::SyncStatus;
+use snapshot::SnapshotService;
+use light::LightClient;
 
-pub use sync::sync_provider::SyncProvider;
+pub use sync::storage::StorageStatics;
+pub use sync::sync_provider::{
+    LocalNodeInfo, ManageNetwork, PeerInfo, RouteTraffic, SyncProvider, TrafficInfo,
+};
 
 const INTERVAL_TRANSACTIONS_BROADCAST: u64 = 50;
 const INTERVAL_STATUS: u64 = 1000;
@@ -62,6 +73,10 @@ const INTERVAL_HEADERS: u64 = 100;
 const INTERVAL_BODIES: u64 = 100;
 const INTERVAL_IMPORT: u64 = 50;
 const INTERVAL_STATISICS: u64 = 10;
+const INTERVAL_SNAPSHOT: u64 = 1000;
+const INTERVAL_LIGHT: u64 = 100;
+const INTERVAL_WARP: u64 = 200;
+const SNAPSHOT_WAIT: u64 = 60;
 const MAX_TX_CACHE: usize = 20480;
 const MAX_BLOCK_CACHE: usize = 32;
 
@@ -69,6 +84,9 @@ pub struct Sync {
     /// Blockchain kernel interface
     client: Arc<BlockChainClient>,
 
+    /// Proofs served to light peers
+    provider: Arc<ProvingBlockChainClient>,
+
     /// Oneshots to shutdown threads
     shutdown_hooks: Arc<Mutex<Vec<Sender<()>>>>,
 
@@ -98,12 +116,42 @@ pub struct Sync {
 
     /// cache block hash which has been committed and broadcasted
     _cached_block_hashes: Arc<Mutex<LruCache<H256, u8>>>,
+
+    /// Local snapshot and snapshot restoration
+    snapshot: Arc<SnapshotService>,
+
+    /// Bootstrap from a peer's snapshot when starting with an empty chain
+    snapshot_sync: bool,
+
+    /// Download the state at a peer's pivot block when starting with an empty chain
+    warp_sync: bool,
+
+    /// Light client, only headers are synced when set
+    light: Option<Arc<LightClient>>,
 }
 
 impl Sync {
-    pub fn new(config: Config, client: Arc<BlockChainClient>) -> Sync {
-        let local_best_td: U256 = client.chain_info().total_difficulty;
-        let local_best_block_number: u64 = client.chain_info().best_block_number;
+    pub fn new(
+        config: Config,
+        client: Arc<BlockChainClient>,
+        provider: Arc<ProvingBlockChainClient>,
+        snapshot: Arc<SnapshotService>,
+        snapshot_sync: bool,
+        warp_sync: bool,
+        sync_checkpoint: Option<H256>,
+        light: Option<Arc<LightClient>>,
+    ) -> Sync
+    {
+        let (local_best_td, local_best_block_number) = match light {
+            Some(ref light) => {
+                let chain = light.chain();
+                (chain.best_total_difficulty(), chain.best_block_number())
+            }
+            None => {
+                let chain_info = client.chain_info();
+                (chain_info.total_difficulty, chain_info.best_block_number)
+            }
+        };
 
         let mut token_rules: Vec<[u32; 2]> = vec![];
         let sync_rule_base =
@@ -120,12 +168,57 @@ impl Sync {
             sync_rule_base + Action::BODIESREQ.value() as u32,
             sync_rule_base + Action::BODIESRES.value() as u32,
         ]);
+        token_rules.push([
+            sync_rule_base + Action::SNAPSHOTMANIFESTREQ.value() as u32,
+            sync_rule_base + Action::SNAPSHOTMANIFESTRES.value() as u32,
+        ]);
+        token_rules.push([
+            sync_rule_base + Action::SNAPSHOTDATAREQ.value() as u32,
+            sync_rule_base + Action::SNAPSHOTDATARES.value() as u32,
+        ]);
+        token_rules.push([
+            sync_rule_base + Action::LIGHTREQ.value() as u32,
+            sync_rule_base + Action::LIGHTRES.value() as u32,
+        ]);
+        token_rules.push([
+            sync_rule_base + Action::WARPPIVOTREQ.value() as u32,
+            sync_rule_base + Action::WARPPIVOTRES.value() as u32,
+        ]);
+        token_rules.push([
+            sync_rule_base + Action::WARPNODESREQ.value() as u32,
+            sync_rule_base + Action::WARPNODESRES.value() as u32,
+        ]);
+
+        // actions handled by this node, light clients do not serve chain data
+        let actions = match light {
+            Some(_) => {
+                vec![
+                    Action::STATUSREQ.value(),
+                    Action::STATUSRES.value(),
+                    Action::HEADERSRES.value(),
+                    Action::LIGHTRES.value(),
+                    Action::BROADCASTTX.value(),
+                ]
+            }
+            None => (Action::STATUSREQ.value()..Action::WARPNODESRES.value() + 1).collect(),
+        };
+        let capabilities = vec![Capability::new(
+            Module::SYNC.value(),
+            PROTOCAL_VERSION,
+            actions,
+        )];
+        let quota_limits = QuotaLimits {
+            window: Duration::from_secs(config.request_quota_window),
+            max_headers: config.max_headers_per_window,
+            max_bodies: config.max_bodies_per_window,
+        };
 
         Sync {
             client,
-            p2p: Mgr::new(config, token_rules),
+            provider,
+            p2p: Mgr::new(config, token_rules, capabilities),
             shutdown_hooks: Arc::new(Mutex::new(Vec::new())),
-            storage: Arc::new(SyncStorage::new()),
+            storage: Arc::new(SyncStorage::new(quota_limits, sync_checkpoint)),
             node_info: Arc::new(RwLock::new(HashMap::new())),
             network_best_td: Arc::new(RwLock::new(local_best_td)),
             network_best_block_number: Arc::new(RwLock::new(local_best_block_number)),
@@ -133,6 +226,10 @@ impl Sync {
             _local_best_block_number: Arc::new(RwLock::new(local_best_block_number)),
             _cached_tx_hashes: Arc::new(Mutex::new(LruCache::new(MAX_TX_CACHE))),
             _cached_block_hashes: Arc::new(Mutex::new(LruCache::new(MAX_BLOCK_CACHE))),
+            snapshot,
+            snapshot_sync,
+            warp_sync,
+            light,
         }
     }
 
@@ -152,6 +249,7 @@ impl Sync {
         let node_info = self.node_info.clone();
         let p2p_statics = p2p.clone();
         let client_statics = self.client.clone();
+        let light_statics = self.light.clone();
         let storage_statics = self.storage.clone();
         let (tx, rx) = oneshot::channel::<()>();
         executor.spawn(
@@ -161,9 +259,16 @@ impl Sync {
             ).for_each(move |_| {
                 let (total_len, active_nodes) = p2p_statics.get_statics_info();
                 {
-                    let local_best_number = client_statics.chain_info().best_block_number;
-                    let local_best_hash = client_statics.chain_info().best_block_hash;
-                    let local_total_difficulty = client_statics.chain_info().total_difficulty;
+                    let (local_best_number, local_best_hash, local_total_difficulty) = match light_statics {
+                        Some(ref light) => {
+                            let chain = light.chain();
+                            (chain.best_block_number(), chain.best_block_hash(), chain.best_total_difficulty())
+                        }
+                        None => {
+                            let chain_info = client_statics.chain_info();
+                            (chain_info.best_block_number, chain_info.best_block_hash, chain_info.total_difficulty)
+                        }
+                    };
                     let active_len = active_nodes.len();
                     info!(target: "sync", "total/active {}/{}, local_best_num {}, hash {}, diff {}", total_len, active_len, local_best_number, local_best_hash, local_total_difficulty);
                     let (downloaded_blocks_size, downloaded_blocks_capacity) = storage_statics.downloaded_blocks_hashes_statics();
@@ -171,9 +276,9 @@ impl Sync {
                     debug!(target: "sync", "download record cache size/capacity {}/{}", downloaded_blocks_size, downloaded_blocks_capacity);
                     debug!(target: "sync", "staged cache size/capacity {}/{}", staged_blocks_size, staged_blocks_capacity);
                     debug!(target: "sync", "lightning syncing height: {}", storage_statics.lightning_base());
-                    info!(target: "sync", "{:-^127}", "");
-                    info!(target: "sync", "                              td         bn          bh                    addr                 rev      conn  seed       mode");
-                    info!(target: "sync", "{:-^127}", "");
+                    info!(target: "sync", "{:-^136}", "");
+                    info!(target: "sync", "                              td         bn          bh                    addr                 rev      conn  seed       mode      rtt");
+                    info!(target: "sync", "{:-^136}", "");
 
                     if active_len > 0 {
                         let mut nodes_info = HashMap::new();
@@ -193,9 +298,9 @@ impl Sync {
                                 })
                             .iter()
                             {
-                                if let Some((addr, revision, connection, seed)) = active_nodes.get(*hash) {
+                                if let Some((addr, revision, connection, seed, latency)) = active_nodes.get(*hash) {
                                     info!(target: "sync",
-                                          "{:>32}{:>11}{:>12}{:>24}{:>20}{:>10}{:>6}{:>11}",
+                                          "{:>32}{:>11}{:>12}{:>24}{:>20}{:>10}{:>6}{:>11}{:>9}",
                                           format!("{}", info.total_difficulty),
                                           format!("{}", info.best_block_number),
                                           format!("{}", info.best_block_hash),
@@ -203,13 +308,14 @@ impl Sync {
                                           revision,
                                           connection,
                                           seed,
-                                          format!("{}", info.mode)
+                                          format!("{}", info.mode),
+                                          latency
                                     );
                                 }
                             }
                     }
 
-                    info!(target: "sync", "{:-^127}", "");
+                    info!(target: "sync", "{:-^136}", "");
                 }
                 Ok(())
             })
@@ -242,13 +348,25 @@ impl Sync {
         let node_info_header = self.node_info.clone();
         let client_header = self.client.clone();
         let storage_header = self.storage.clone();
+        let snapshot_header = self.snapshot.clone();
+        let light_header = self.light.clone();
         let (tx, rx) = oneshot::channel::<()>();
         executor.spawn(
             Interval::new(Instant::now(), Duration::from_millis(INTERVAL_HEADERS))
                 .for_each(move |_| {
-                    let chain_info = client_header.chain_info();
-                    let local_total_diff: U256 = chain_info.total_difficulty;
-                    let local_best_block_number: u64 = chain_info.best_block_number;
+                    let (local_total_diff, local_best_block_number) = match light_header {
+                        Some(ref light) => {
+                            let chain = light.chain();
+                            (chain.best_total_difficulty(), chain.best_block_number())
+                        }
+                        None => {
+                            if snapshot::is_syncing(&snapshot_header, &storage_header) {
+                                return Ok(());
+                            }
+                            let chain_info = client_header.chain_info();
+                            (chain_info.total_difficulty, chain_info.best_block_number)
+                        }
+                    };
                     headers::sync_headers(
                         p2p_header.clone(),
                         node_info_header.clone(),
@@ -265,44 +383,124 @@ impl Sync {
         );
         shutdown_hooks.push(tx);
 
-        // sync bodies thread
-        let p2p_body = p2p.clone();
-        let storage_body = self.storage.clone();
-        let (tx, rx) = oneshot::channel::<()>();
-        executor.spawn(
-            Interval::new(Instant::now(), Duration::from_millis(INTERVAL_BODIES))
-                .for_each(move |_| {
-                    bodies::sync_bodies(p2p_body.clone(), storage_body.clone());
-                    Ok(())
-                })
-                .map_err(|err| error!(target: "sync", "executor body: {:?}", err))
-                .select(rx.map_err(|_| {}))
-                .map(|_| ())
-                .map_err(|_| ()),
-        );
-        shutdown_hooks.push(tx);
+        if let Some(ref light) = self.light {
+            // light requests thread, replaces the snapshot, bodies and import threads
+            let p2p_light = p2p.clone();
+            let on_demand_light = light.on_demand();
+            let node_info_light = self.node_info.clone();
+            let (tx, rx) = oneshot::channel::<()>();
+            executor.spawn(
+                Interval::new(Instant::now(), Duration::from_millis(INTERVAL_LIGHT))
+                    .for_each(move |_| {
+                        light::dispatch_requests(
+                            p2p_light.clone(),
+                            on_demand_light.clone(),
+                            node_info_light.clone(),
+                        );
+                        Ok(())
+                    })
+                    .map_err(|err| error!(target: "sync", "executor light: {:?}", err))
+                    .select(rx.map_err(|_| {}))
+                    .map(|_| ())
+                    .map_err(|_| ()),
+            );
+            shutdown_hooks.push(tx);
+        } else {
+            // snapshot thread
+            if (self.snapshot_sync || self.warp_sync)
+                && self.client.chain_info().best_block_number == 0
+            {
+                self.storage
+                    .wait_for_snapshot(Duration::from_secs(SNAPSHOT_WAIT));
+            }
+            let p2p_snapshot = p2p.clone();
+            let snapshot_snapshot = self.snapshot.clone();
+            let storage_snapshot = self.storage.clone();
+            let snapshot_sync = self.snapshot_sync;
+            let (tx, rx) = oneshot::channel::<()>();
+            executor.spawn(
+                Interval::new(Instant::now(), Duration::from_millis(INTERVAL_SNAPSHOT))
+                    .for_each(move |_| {
+                        snapshot::sync_snapshot(
+                            p2p_snapshot.clone(),
+                            snapshot_snapshot.clone(),
+                            storage_snapshot.clone(),
+                            snapshot_sync,
+                        );
+                        Ok(())
+                    })
+                    .map_err(|err| error!(target: "sync", "executor snapshot: {:?}", err))
+                    .select(rx.map_err(|_| {}))
+                    .map(|_| ())
+                    .map_err(|_| ()),
+            );
+            shutdown_hooks.push(tx);
+
+            // warp sync thread
+            if self.warp_sync {
+                let p2p_warp = p2p.clone();
+                let snapshot_warp = self.snapshot.clone();
+                let storage_warp = self.storage.clone();
+                let (tx, rx) = oneshot::channel::<()>();
+                executor.spawn(
+                    Interval::new(Instant::now(), Duration::from_millis(INTERVAL_WARP))
+                        .for_each(move |_| {
+                            warp::sync_warp(
+                                p2p_warp.clone(),
+                                snapshot_warp.clone(),
+                                storage_warp.clone(),
+                            );
+                            Ok(())
+                        })
+                        .map_err(|err| error!(target: "sync", "executor warp: {:?}", err))
+                        .select(rx.map_err(|_| {}))
+                        .map(|_| ())
+                        .map_err(|_| ()),
+                );
+                shutdown_hooks.push(tx);
+            }
 
-        // import thread
-        let client_import = self.client.clone();
-        let storage_import = self.storage.clone();
-        let node_info_import = self.node_info.clone();
-        let (tx, rx) = oneshot::channel::<()>();
-        executor.spawn(
-            Interval::new(Instant::now(), Duration::from_millis(INTERVAL_IMPORT))
-                .for_each(move |_| {
-                    import::import_blocks(
-                        client_import.clone(),
-                        storage_import.clone(),
-                        node_info_import.clone(),
-                    );
-                    Ok(())
-                })
-                .map_err(|err| error!(target: "sync", "executor import: {:?}", err))
-                .select(rx.map_err(|_| {}))
-                .map(|_| ())
-                .map_err(|_| ()),
-        );
-        shutdown_hooks.push(tx);
+            // sync bodies thread
+            let p2p_body = p2p.clone();
+            let storage_body = self.storage.clone();
+            let (tx, rx) = oneshot::channel::<()>();
+            executor.spawn(
+                Interval::new(Instant::now(), Duration::from_millis(INTERVAL_BODIES))
+                    .for_each(move |_| {
+                        bodies::sync_bodies(p2p_body.clone(), storage_body.clone());
+                        Ok(())
+                    })
+                    .map_err(|err| error!(target: "sync", "executor body: {:?}", err))
+                    .select(rx.map_err(|_| {}))
+                    .map(|_| ())
+                    .map_err(|_| ()),
+            );
+            shutdown_hooks.push(tx);
+
+            // import thread
+            let p2p_import = p2p.clone();
+            let client_import = self.client.clone();
+            let storage_import = self.storage.clone();
+            let node_info_import = self.node_info.clone();
+            let (tx, rx) = oneshot::channel::<()>();
+            executor.spawn(
+                Interval::new(Instant::now(), Duration::from_millis(INTERVAL_IMPORT))
+                    .for_each(move |_| {
+                        import::import_blocks(
+                            p2p_import.clone(),
+                            client_import.clone(),
+                            storage_import.clone(),
+                            node_info_import.clone(),
+                        );
+                        Ok(())
+                    })
+                    .map_err(|err| error!(target: "sync", "executor import: {:?}", err))
+                    .select(rx.map_err(|_| {}))
+                    .map(|_| ())
+                    .map_err(|_| ()),
+            );
+            shutdown_hooks.push(tx);
+        }
 
         let executor_broadcast = executor.clone();
         let p2p_broadcast = p2p.clone();
@@ -349,6 +547,9 @@ impl Sync {
     }
 
     pub fn get_local_node_info(&self) -> &String { self.p2p.get_local_node_info() }
+
+    /// Get the sizes of the sync queues and caches
+    pub fn storage_statics(&self) -> StorageStatics { self.storage.statics() }
 }
 
 impl SyncProvider for Sync {
@@ -358,11 +559,91 @@ impl SyncProvider for Sync {
         SyncStatus {
             protocol_version: PROTOCAL_VERSION as u8,
             network_id: self.p2p.get_net_id(),
-            start_block_number: self.client.chain_info().best_block_number,
+            start_block_number: match self.light {
+                Some(ref light) => light.chain().best_block_number(),
+                None => self.client.chain_info().best_block_number,
+            },
             highest_block_number: Some(*self.network_best_block_number.read()),
             num_peers: self.p2p.get_active_nodes_len() as usize,
         }
     }
+
+    /// Get active peers
+    fn peers(&self) -> Vec<PeerInfo> {
+        let node_info = self.node_info.read();
+        self.p2p
+            .get_active_nodes()
+            .iter()
+            .map(|node| {
+                let info = node_info
+                    .get(&node.get_hash())
+                    .map(|info_lock| info_lock.read().clone())
+                    .unwrap_or_else(NodeInfo::new);
+                PeerInfo {
+                    id: node.get_id_string(),
+                    addr: node.addr.to_string(),
+                    revision: String::from_utf8_lossy(&node.revision).trim().to_owned(),
+                    direction: format!("{}", node.connection),
+                    best_block_number: info.best_block_number,
+                    best_block_hash: info.best_block_hash,
+                    total_difficulty: info.total_difficulty,
+                    latency: node.latency_millis(),
+                    score: node.score,
+                    traffic: traffic_info(&node.traffic),
+                }
+            })
+            .collect()
+    }
+
+    /// Get local node
+    fn node_info(&self) -> LocalNodeInfo {
+        let node = self.p2p.get_local_node_info().clone();
+        let (id, binding) = self.p2p.get_local_id_and_binding();
+        LocalNodeInfo {
+            id,
+            node,
+            binding,
+            network_id: self.p2p.get_net_id(),
+        }
+    }
+
+    /// Get traffic with all peers by protocol message since start
+    fn traffic(&self) -> Vec<RouteTraffic> {
+        self.p2p
+            .get_traffic()
+            .iter()
+            .map(|&(module, action, ref traffic)| {
+                RouteTraffic {
+                    module: match Module::from(module) {
+                        Module::P2P => "p2p".into(),
+                        Module::SYNC => "sync".into(),
+                        Module::UNKNOWN => module.to_string(),
+                    },
+                    action,
+                    traffic: traffic_info(traffic),
+                }
+            })
+            .collect()
+    }
+}
+
+fn traffic_info(traffic: &Traffic) -> TrafficInfo {
+    TrafficInfo {
+        messages_sent: traffic.sent.messages,
+        bytes_sent: traffic.sent.bytes,
+        messages_received: traffic.received.messages,
+        bytes_received: traffic.received.bytes,
+    }
+}
+
+impl ManageNetwork for Sync {
+    fn add_peer(&self, node: &str) -> Result<(), String> { self.p2p.add_node(node) }
+
+    fn remove_peer(&self, id_or_addr: &str) -> bool { self.p2p.disconnect_node(id_or_addr) }
+
+    fn ban_ip(&self, ip: Ipv4Addr) { self.p2p.ban_ip(&ip.to_string()) }
+
+    fn unban_ip(&self, ip: Ipv4Addr) -> bool { self.p2p.unban_ip(&ip.to_string()) }
 }
 
 impl ChainNotify for Sync {
@@ -462,14 +743,62 @@ impl Callable for Sync {
             }
             Action::HEADERSREQ => {
                 let client = self.client.clone();
-                headers::receive_req(p2p, hash, client, cb)
+                headers::receive_req(p2p, hash, client, cb, self.storage.clone())
+            }
+            Action::HEADERSRES => {
+                match self.light {
+                    Some(ref light) => {
+                        headers::receive_res_light(
+                            p2p,
+                            hash,
+                            cb,
+                            self.node_info.clone(),
+                            light.chain(),
+                        )
+                    }
+                    None => headers::receive_res(p2p, hash, cb, self.storage.clone()),
+                }
             }
-            Action::HEADERSRES => headers::receive_res(p2p, hash, cb, self.storage.clone()),
             Action::BODIESREQ => {
                 let client = self.client.clone();
-                bodies::receive_req(p2p, hash, client, cb)
+                bodies::receive_req(p2p, hash, client, cb, self.storage.clone())
             }
             Action::BODIESRES => bodies::receive_res(p2p, hash, cb, self.storage.clone()),
+            Action::SNAPSHOTMANIFESTREQ => {
+                snapshot::receive_manifest_req(p2p, hash, self.snapshot.clone(), cb)
+            }
+            Action::SNAPSHOTMANIFESTRES => {
+                snapshot::receive_manifest_res(
+                    hash,
+                    cb,
+                    self.snapshot.clone(),
+                    self.storage.clone(),
+                )
+            }
+            Action::SNAPSHOTDATAREQ => {
+                snapshot::receive_data_req(p2p, hash, self.snapshot.clone(), cb)
+            }
+            Action::SNAPSHOTDATARES => {
+                snapshot::receive_data_res(hash, cb, self.snapshot.clone(), self.storage.clone())
+            }
+            Action::WARPPIVOTREQ => {
+                warp::receive_pivot_req(p2p, hash, self.snapshot.clone(), cb)
+            }
+            Action::WARPPIVOTRES => {
+                warp::receive_pivot_res(hash, cb, self.snapshot.clone(), self.storage.clone())
+            }
+            Action::WARPNODESREQ => {
+                warp::receive_nodes_req(p2p, hash, self.snapshot.clone(), cb)
+            }
+            Action::WARPNODESRES => {
+                warp::receive_nodes_res(hash, cb, self.snapshot.clone(), self.storage.clone())
+            }
+            Action::LIGHTREQ => light::receive_req(p2p, hash, self.provider.clone(), cb),
+            Action::LIGHTRES => {
+                if let Some(ref light) = self.light {
+                    light::receive_res(p2p, hash, cb, light.on_demand())
+                }
+            }
             Action::BROADCASTTX => {
                 let client = self.client.clone();
                 broadcast::handle_broadcast_tx(
@@ -482,6 +811,8 @@ impl Callable for Sync {
                     self.network_best_block_number.clone(),
                 )
             }
+            // light clients learn about new blocks through header sync
+            Action::BROADCASTBLOCK if self.light.is_some() => (),
             Action::BROADCASTBLOCK => {
                 let client = self.client.clone();
                 broadcast::handle_broadcast_block(
@@ -512,6 +843,19 @@ impl Callable for Sync {
 
         let mut headers = self.storage.downloaded_headers().lock();
         headers.retain(|x| x.node_hash != hash);
+        drop(headers);
+
+        self.storage.snapshot_chunk_requests().lock().remove(&hash);
+        self.storage.snapshot_manifests().lock().remove(&hash);
+        self.storage.warp_pivots().lock().remove(&hash);
+        self.storage.remove_request_quota(hash);
+        if let Some((requests, _)) = self.storage.warp_node_requests().lock().remove(&hash) {
+            self.snapshot.requeue_warp(&requests);
+        }
+
+        if let Some(ref light) = self.light {
+            light.on_demand().on_disconnect(hash);
+        }
 
         trace!(target: "sync", "finish cleaning disconnected node: {}", &hash);
     }
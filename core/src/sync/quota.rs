@@ -0,0 +1,79 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+use std::time::{Duration, SystemTime};
+
+use sync::action::Action;
+
+/// Items served to each peer within a window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuotaLimits {
+    /// length of a quota window
+    pub window: Duration,
+    /// headers served to a peer within a window
+    pub max_headers: u64,
+    /// bodies served to a peer within a window
+    pub max_bodies: u64,
+}
+
+/// Items served to a peer in the current window, so that a single peer cannot make us
+/// serve unlimited header ranges or bodies.
+pub struct RequestQuota {
+    /// limits of the window
+    limits: QuotaLimits,
+    /// window start
+    since: SystemTime,
+    /// headers served in the window
+    headers: u64,
+    /// bodies served in the window
+    bodies: u64,
+}
+
+impl RequestQuota {
+    pub fn new(limits: QuotaLimits) -> Self {
+        RequestQuota {
+            limits,
+            since: SystemTime::now(),
+            headers: 0,
+            bodies: 0,
+        }
+    }
+
+    /// Account `items` requested through `action`, returns false if they exceed the quota
+    /// left in the window. Actions other than headers and bodies requests are not limited.
+    pub fn allow(&mut self, action: Action, items: u64) -> bool {
+        let window = self.limits.window;
+        if self.since.elapsed().map_or(true, |elapsed| elapsed >= window) {
+            self.since = SystemTime::now();
+            self.headers = 0;
+            self.bodies = 0;
+        }
+        let (served, max) = match action {
+            Action::HEADERSREQ => (&mut self.headers, self.limits.max_headers),
+            Action::BODIESREQ => (&mut self.bodies, self.limits.max_bodies),
+            _ => return true,
+        };
+        if *served + items > max {
+            return false;
+        }
+        *served += items;
+        true
+    }
+}
@@ -19,11 +19,15 @@
  *
  ******************************************************************************/
 use std::collections::{VecDeque, HashMap};
+use std::time::{Duration, SystemTime};
 
 use lru_cache::LruCache;
 use parking_lot::{Mutex, RwLock};
 
 use aion_types::H256;
+use snapshot::{ManifestData, NodeRequest, Pivot};
+use sync::action::Action;
+use sync::quota::{QuotaLimits, RequestQuota};
 use sync::wrappers::{HeadersWrapper, BlocksWrapper};
 
 // const MAX_DOWNLOADED_HEADERS_COUNT: usize = 4096;
@@ -31,6 +35,21 @@ const MAX_CACHED_BLOCK_HASHES: usize = 32;
 const MAX_CACHED_TRANSACTION_HASHES: usize = 20480;
 const MAX_RECEIVED_TRANSACTIONS_COUNT: usize = 20480;
 
+/// Sizes of the sync queues and caches
+#[derive(Clone, Copy, Default)]
+pub struct StorageStatics {
+    /// Downloaded headers wrappers waiting for bodies
+    pub downloaded_headers: usize,
+    /// Downloaded blocks wrappers waiting for import
+    pub downloaded_blocks: usize,
+    /// Downloaded blocks hashes cached and the cache capacity
+    pub downloaded_blocks_hashes: (usize, usize),
+    /// Blocks staged for later import and the capacity
+    pub staged_blocks: (usize, usize),
+    /// Received txs waiting for import
+    pub received_transactions: usize,
+}
+
 pub struct SyncStorage {
     /// Downloaded headers wrappers
     downloaded_headers: Mutex<VecDeque<HeadersWrapper>>,
@@ -58,10 +77,37 @@ pub struct SyncStorage {
 
     // Lightning sync block height
     lightning_base: RwLock<u64>,
+
+    /// Snapshot chunk requested from each node and when
+    snapshot_chunk_requests: Mutex<HashMap<u64, (H256, SystemTime)>>,
+
+    /// Snapshot manifest offered by each node, until one is anchored
+    snapshot_manifests: Mutex<HashMap<u64, ManifestData>>,
+
+    /// Block trusted to anchor snapshots and warp sync pivots
+    sync_checkpoint: Option<H256>,
+
+    /// Block sync waits for a snapshot offer until this time
+    snapshot_wait_until: RwLock<Option<SystemTime>>,
+
+    /// Warp state items requested from each node and when
+    warp_node_requests: Mutex<HashMap<u64, (Vec<NodeRequest>, SystemTime)>>,
+
+    /// Warp sync pivot offered by each node, until one is anchored
+    warp_pivots: Mutex<HashMap<u64, Pivot>>,
+
+    /// When a warp sync pivot was last requested
+    warp_pivot_requested_at: Mutex<Option<SystemTime>>,
+
+    /// Headers and bodies served to each node in the current quota window
+    request_quotas: Mutex<HashMap<u64, RequestQuota>>,
+
+    /// Limits of the quota windows
+    quota_limits: QuotaLimits,
 }
 
 impl SyncStorage {
-    pub fn new() -> Self {
+    pub fn new(quota_limits: QuotaLimits, sync_checkpoint: Option<H256>) -> Self {
         SyncStorage {
             downloaded_headers: Mutex::new(VecDeque::new()),
             downloaded_blocks: Mutex::new(VecDeque::new()),
@@ -72,6 +118,15 @@ impl SyncStorage {
             recorded_transaction_hashes: Mutex::new(LruCache::new(MAX_CACHED_TRANSACTION_HASHES)),
             received_transactions: Mutex::new(VecDeque::new()),
             lightning_base: RwLock::new(0u64),
+            snapshot_chunk_requests: Mutex::new(HashMap::new()),
+            snapshot_manifests: Mutex::new(HashMap::new()),
+            sync_checkpoint,
+            snapshot_wait_until: RwLock::new(None),
+            warp_node_requests: Mutex::new(HashMap::new()),
+            warp_pivots: Mutex::new(HashMap::new()),
+            warp_pivot_requested_at: Mutex::new(None),
+            request_quotas: Mutex::new(HashMap::new()),
+            quota_limits,
         }
     }
 
@@ -160,6 +215,16 @@ impl SyncStorage {
         (staged_blocks.len(), staged_blocks.capacity())
     }
 
+    pub fn statics(&self) -> StorageStatics {
+        StorageStatics {
+            downloaded_headers: self.downloaded_headers.lock().len(),
+            downloaded_blocks: self.downloaded_blocks.lock().len(),
+            downloaded_blocks_hashes: self.downloaded_blocks_hashes_statics(),
+            staged_blocks: self.staged_blocks_statics(),
+            received_transactions: self.received_transactions.lock().len(),
+        }
+    }
+
     pub fn recorded_transaction_hashes(&self) -> &Mutex<LruCache<H256, u8>> {
         &self.recorded_transaction_hashes
     }
@@ -176,4 +241,59 @@ impl SyncStorage {
     pub fn lightning_base(&self) -> u64 { *self.lightning_base.read() }
 
     pub fn set_lightning_base(&self, base: u64) { *self.lightning_base.write() = base; }
+
+    pub fn snapshot_chunk_requests(&self) -> &Mutex<HashMap<u64, (H256, SystemTime)>> {
+        &self.snapshot_chunk_requests
+    }
+
+    pub fn snapshot_manifests(&self) -> &Mutex<HashMap<u64, ManifestData>> {
+        &self.snapshot_manifests
+    }
+
+    pub fn sync_checkpoint(&self) -> Option<H256> { self.sync_checkpoint }
+
+    pub fn wait_for_snapshot(&self, timeout: Duration) {
+        *self.snapshot_wait_until.write() = Some(SystemTime::now() + timeout);
+    }
+
+    pub fn stop_waiting_for_snapshot(&self) { *self.snapshot_wait_until.write() = None; }
+
+    pub fn is_waiting_for_snapshot(&self) -> bool {
+        self.snapshot_wait_until
+            .read()
+            .map_or(false, |until| SystemTime::now() < until)
+    }
+
+    pub fn warp_node_requests(&self) -> &Mutex<HashMap<u64, (Vec<NodeRequest>, SystemTime)>> {
+        &self.warp_node_requests
+    }
+
+    pub fn warp_pivots(&self) -> &Mutex<HashMap<u64, Pivot>> { &self.warp_pivots }
+
+    /// Whether a warp sync pivot may be requested again, `interval` after the last
+    /// request. Records the request when it is.
+    pub fn should_request_pivot(&self, interval: Duration) -> bool {
+        let mut requested_at = self.warp_pivot_requested_at.lock();
+        let due = requested_at.map_or(true, |at| {
+            at.elapsed().map_or(true, |elapsed| elapsed >= interval)
+        });
+        if due {
+            *requested_at = Some(SystemTime::now());
+        }
+        due
+    }
+
+    /// Whether `items` requested by a node through `action` may be served within its quota.
+    /// Records them when they are.
+    pub fn allow_request(&self, node_hash: u64, action: Action, items: u64) -> bool {
+        self.request_quotas
+            .lock()
+            .entry(node_hash)
+            .or_insert_with(|| RequestQuota::new(self.quota_limits))
+            .allow(action, items)
+    }
+
+    pub fn remove_request_quota(&self, node_hash: u64) {
+        self.request_quotas.lock().remove(&node_hash);
+    }
 }
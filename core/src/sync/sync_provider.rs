@@ -19,9 +19,36 @@
  *
  ******************************************************************************/
 
+use std::net::Ipv4Addr;
+use aion_types::{H256, U256};
+
 pub trait SyncProvider: Send + Sync {
     /// Get sync status
     fn status(&self) -> SyncStatus;
+
+    /// Get active peers
+    fn peers(&self) -> Vec<PeerInfo>;
+
+    /// Get local node
+    fn node_info(&self) -> LocalNodeInfo;
+
+    /// Get traffic with all peers by protocol message since start
+    fn traffic(&self) -> Vec<RouteTraffic>;
+}
+
+/// Manage peers at runtime
+pub trait ManageNetwork: Send + Sync {
+    /// Dial a node given as `p2p://<id>@<ip>:<port>`
+    fn add_peer(&self, node: &str) -> Result<(), String>;
+
+    /// Disconnect the peers with the given id or address, returns whether any peer was found
+    fn remove_peer(&self, id_or_addr: &str) -> bool;
+
+    /// Ban an ip and disconnect its peers
+    fn ban_ip(&self, ip: Ipv4Addr);
+
+    /// Lift the ban of an ip, returns whether the ip was banned
+    fn unban_ip(&self, ip: Ipv4Addr) -> bool;
 }
 
 #[derive(Clone, Copy)]
@@ -37,3 +64,60 @@ pub struct SyncStatus {
     /// Total number of connected peers
     pub num_peers: usize,
 }
+
+/// Active peer
+#[derive(Clone)]
+pub struct PeerInfo {
+    /// Node id
+    pub id: String,
+    /// Remote address
+    pub addr: String,
+    /// Client revision
+    pub revision: String,
+    /// "inbound" or "outbound"
+    pub direction: String,
+    /// Best block number announced by the peer
+    pub best_block_number: u64,
+    /// Best block hash announced by the peer
+    pub best_block_hash: H256,
+    /// Total difficulty announced by the peer
+    pub total_difficulty: U256,
+    /// Ping round trip in milliseconds, unknown until the peer answers a ping
+    pub latency: Option<u64>,
+    /// Reputation of the peer ip
+    pub score: i32,
+    /// Traffic with the peer
+    pub traffic: TrafficInfo,
+}
+
+/// Messages and bytes sent and received
+#[derive(Clone, Copy, Default)]
+pub struct TrafficInfo {
+    pub messages_sent: u64,
+    pub bytes_sent: u64,
+    pub messages_received: u64,
+    pub bytes_received: u64,
+}
+
+/// Traffic of a protocol message
+#[derive(Clone)]
+pub struct RouteTraffic {
+    /// "p2p" or "sync"
+    pub module: String,
+    /// Action code within the module
+    pub action: u8,
+    pub traffic: TrafficInfo,
+}
+
+/// Local node
+#[derive(Clone)]
+pub struct LocalNodeInfo {
+    /// Node id
+    pub id: String,
+    /// Node url as `p2p://<id>@<ip>:<port>`
+    pub node: String,
+    /// Listening address
+    pub binding: String,
+    /// The underlying p2p network version.
+    pub network_id: u32,
+}
@@ -80,6 +80,7 @@ fn enact_bytes(
     let mut b = OpenBlock::new(
         engine,
         factories,
+        false,
         db,
         parent,
         seal_type.unwrap_or_default(),
@@ -140,6 +141,7 @@ fn open_block() {
     let b = OpenBlock::new(
         &*spec.engine,
         Default::default(),
+        false,
         db,
         &genesis_header,
         Default::default(),
@@ -174,6 +176,7 @@ fn enact_block() {
     let b = OpenBlock::new(
         engine,
         Default::default(),
+        false,
         db,
         &genesis_header,
         Default::default(),
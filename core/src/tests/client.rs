@@ -416,6 +416,76 @@ fn does_not_propagate_delayed_transactions() {
     assert_eq!(2, client.miner().pending_transactions().len());
 }
 
+#[test]
+fn restores_journaled_local_transactions() {
+    let secret = Ed25519Secret::from_str("7ea8af7d0982509cd815096d35bc3a295f57b2a078e4e25731e3ea977b9544626702b86f33072a55f46003b1e3e242eb18556be54c5ab12044c3c20829e0abb5").unwrap();
+    let transaction = |nonce: u64| {
+        Transaction {
+            nonce: nonce.into(),
+            gas_price: 0.into(),
+            gas: 21000.into(),
+            action: Action::Call(Address::default()),
+            value: 0.into(),
+            data: Vec::new(),
+            nonce_bytes: Vec::new(),
+            gas_price_bytes: Vec::new(),
+            gas_bytes: Vec::new(),
+            value_bytes: Vec::new(),
+            transaction_type: 0x01.into(),
+            beacon: None,
+        }
+        .sign(&secret)
+    };
+    let tx0 = PendingTransaction::new(transaction(0), Some(Condition::Number(2)));
+    let tx1 = PendingTransaction::new(transaction(1), None);
+
+    let tempdir = TempDir::new("").unwrap();
+    let spec = get_test_spec();
+    let db_config = DatabaseConfig::default();
+    let mut db_configs = Vec::new();
+    for db_name in ::db::DB_NAMES.to_vec() {
+        db_configs.push(RepositoryConfig {
+            db_name: db_name.into(),
+            db_config: db_config.clone(),
+            db_path: tempdir.path().join(db_name).to_str().unwrap().to_string(),
+        });
+    }
+    let client_db = Arc::new(DbRepository::init(db_configs).unwrap());
+    let client = Client::new(
+        ClientConfig::default(),
+        &spec,
+        client_db.clone(),
+        Arc::new(Miner::with_spec(&spec)),
+        IoChannel::disconnected(),
+    )
+    .unwrap();
+    client
+        .miner()
+        .import_own_transaction(&*client, tx0)
+        .unwrap();
+    client
+        .miner()
+        .import_own_transaction(&*client, tx1)
+        .unwrap();
+    client.miner().update_transaction_pool(&*client, true);
+    client.journal_local_transactions();
+
+    // restart with an empty queue
+    let client = Client::new(
+        ClientConfig::default(),
+        &spec,
+        client_db,
+        Arc::new(Miner::with_spec(&spec)),
+        IoChannel::disconnected(),
+    )
+    .unwrap();
+    assert_eq!(0, client.miner().pending_transactions().len());
+    client.restore_local_transactions();
+    client.miner().update_transaction_pool(&*client, true);
+    assert_eq!(2, client.miner().pending_transactions().len());
+    assert_eq!(0, client.ready_transactions().len());
+}
+
 #[test]
 fn test_total_difficulty() {
     let tempdir = TempDir::new("").unwrap();
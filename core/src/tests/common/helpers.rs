@@ -209,6 +209,7 @@ where
         let mut b = OpenBlock::new(
             test_engine,
             Default::default(),
+            false,
             db,
             &last_header,
             Default::default(),
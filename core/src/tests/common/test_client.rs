@@ -43,7 +43,7 @@ use itertools::Itertools;
 use journaldb;
 use key::{generate_keypair, public_to_address_ed25519};
 use kvdb::{DatabaseConfig, DbRepository, RepositoryConfig};
-use kvdb::{KeyValueDB, MockDbRepository};
+use kvdb::{DBValue, KeyValueDB, MockDbRepository};
 use log_entry::LocalizedLogEntry;
 use miner::{Miner, MinerService};
 use parking_lot::RwLock;
@@ -52,6 +52,7 @@ use rlp::*;
 use rustc_hex::FromHex;
 use spec::Spec;
 use state::BasicAccount;
+use trace::{Filter as TraceFilter, LocalizedTrace};
 use std::collections::HashMap;
 use std::mem;
 use std::sync::Arc;
@@ -438,6 +439,7 @@ impl MiningBlockChainClient for TestBlockChainClient {
         let mut open_block = OpenBlock::new(
             engine,
             Default::default(),
+            false,
             db,
             &genesis_header,
             seal_type.unwrap_or_default(),
@@ -465,6 +467,8 @@ impl MiningBlockChainClient for TestBlockChainClient {
 
     fn broadcast_transaction(&self, _transactions: Bytes) {}
 
+    fn notify_transactions_imported(&self, _hashes: Vec<H256>) {}
+
     fn broadcast_proposal_block(&self, _block: SealedBlock) {}
 
     fn prepare_block_interval(&self) -> Duration { Duration::default() }
@@ -649,6 +653,12 @@ impl BlockChainClient for TestBlockChainClient {
         }
     }
 
+    fn block_traces(&self, _id: BlockId) -> Option<Vec<LocalizedTrace>> { None }
+
+    fn transaction_traces(&self, _id: TransactionId) -> Option<Vec<LocalizedTrace>> { None }
+
+    fn filter_traces(&self, _filter: TraceFilter) -> Option<Vec<LocalizedTrace>> { None }
+
     fn last_hashes(&self) -> LastHashes {
         unimplemented!();
     }
@@ -972,13 +982,13 @@ impl BlockChainClient for TestBlockChainClient {
 }
 
 impl ProvingBlockChainClient for TestBlockChainClient {
-    fn prove_storage(&self, _: H256, _: H256, _: BlockId) -> Option<(Vec<Bytes>, H256)> { None }
+    fn prove_storage(&self, _: H256, _: H256, _: BlockId) -> Option<(Vec<Bytes>, Bytes)> { None }
 
     fn prove_account(&self, _: H256, _: BlockId) -> Option<(Vec<Bytes>, BasicAccount)> { None }
 
-    // fn prove_transaction(&self, _: SignedTransaction, _: BlockId) -> Option<(Bytes, Vec<DBValue>)> {
-    //     None
-    // }
+    fn prove_transaction(&self, _: SignedTransaction, _: BlockId) -> Option<(Bytes, Vec<DBValue>)> {
+        None
+    }
 }
 
 impl ::client::EngineClient for TestBlockChainClient {
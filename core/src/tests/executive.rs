@@ -32,7 +32,10 @@ use vms::{
     ReturnData
 };
 use state::{Substate, CleanupMode};
-use transaction::{Action, Transaction, SignedTransaction, DEFAULT_TRANSACTION_TYPE};
+use transaction::{
+    Action, Transaction, SignedTransaction, AVM_TRANSACTION_TYPE, DEFAULT_TRANSACTION_TYPE,
+};
+use trace::{AvmOperation, VMTraceOptions};
 use types::error::ExecutionError;
 use executive::{Executive, contract_address};
 use avm_abi::{AVMEncoder, AbiToken, ToBytes};
@@ -1891,6 +1894,68 @@ fn hello_avm() {
     // );
 }
 
+#[test]
+/// Storage written by a traced local call shows up in its avm trace
+fn traced_avm_call_records_storage() {
+    let mut file = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // NOTE: tested with avm v1.3
+    file.push("src/tests/avmjars/demo-0.2.0.jar");
+    let file_str = file.to_str().expect("Failed to locate the demo.jar");
+    let mut code = read_file(file_str).expect("unable to open avm dapp");
+    let sender = Address::from_slice(b"cd1722f3947def4cf144679da39c4c32bdc35681");
+    let mut params = ActionParams::default();
+    params.address = contract_address(&sender, &U256::zero()).0;
+    params.sender = sender.clone();
+    params.origin = sender.clone();
+    params.gas = U256::from(5_000_000);
+    let mut avm_code: Vec<u8> = (code.len() as u32).to_vm_bytes();
+    avm_code.append(&mut code);
+    params.code = Some(Arc::new(avm_code));
+    params.value = ActionValue::Transfer(0.into());
+    params.call_type = CallType::None;
+    params.gas_price = 1.into();
+    let mut state = get_temp_state();
+    state
+        .add_balance(&sender, &U256::from(200_000_000), CleanupMode::NoEmpty)
+        .unwrap();
+    let info = EnvInfo::default();
+    let machine = make_aion_machine();
+    let execution_results = {
+        let mut ex = Executive::new(&mut state, &info, &machine);
+        ex.call_avm(vec![params], &mut [Substate::new()])
+    };
+    assert_eq!(execution_results[0].status_code, ExecStatus::Success);
+    let contract: Address = (*execution_results[0].return_data).into();
+
+    let transaction = Transaction::new(
+        U256::one(),
+        1.into(),
+        U256::from(2_000_000),
+        Action::Call(contract),
+        0.into(),
+        AbiToken::STRING(String::from("storageTest")).encode(),
+        AVM_TRANSACTION_TYPE,
+        None,
+    )
+    .fake_sign(sender);
+    let executed = {
+        let mut ex =
+            Executive::new_with_vm_tracing(&mut state, &info, &machine, VMTraceOptions::default());
+        ex.transact_virtual_bulk(&[transaction], false)
+            .remove(0)
+            .unwrap()
+    };
+    let operations = executed.avm_trace.expect("avm calls are traced").operations;
+    assert!(operations.iter().any(|operation| {
+        match *operation {
+            AvmOperation::PutStorage(ref address, _, ref value) => {
+                *address == contract && *value == vec![0u8, 2, 3, 4]
+            }
+            _ => false,
+        }
+    }));
+}
+
 use std::io::Error;
 use std::fs::File;
 use std::io::Read;
@@ -74,6 +74,7 @@ fn can_be_created() {
         vec![OriginInfo::get_test_origin()],
         &mut setup.sub_state,
         Arc::new(MockDbRepository::init(vec![String::new()])),
+        None,
     );
 
     assert_eq!(ext.env_info().number, 100);
@@ -102,6 +103,7 @@ fn can_return_block_hash() {
         vec![OriginInfo::get_test_origin()],
         &mut setup.sub_state,
         Arc::new(MockDbRepository::init(vec![String::new()])),
+        None,
     );
 
     let hash = ext.blockhash(
@@ -127,6 +129,7 @@ fn can_call_fail_empty() {
         vec![OriginInfo::get_test_origin()],
         &mut setup.sub_state,
         Arc::new(MockDbRepository::init(vec![String::new()])),
+        None,
     );
 
     // this should panic because we have no balance on any account
@@ -167,6 +170,7 @@ fn can_log() {
             vec![OriginInfo::get_test_origin()],
             &mut setup.sub_state,
             Arc::new(MockDbRepository::init(vec![String::new()])),
+            None,
         );
         ext.log(log_topics.clone(), &log_data);
     }
@@ -192,6 +196,7 @@ fn can_suicide() {
             vec![OriginInfo::get_test_origin()],
             &mut setup.sub_state,
             Arc::new(MockDbRepository::init(vec![String::new()])),
+            None,
         );
         ext.suicide(refund_account);
     }
@@ -0,0 +1,377 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use aion_types::{Address, H256, U256};
+use futures::executor::{self, Notify, NotifyHandle};
+use futures::Async;
+use header::{Header, SealType};
+use key::Ed25519Secret;
+use light::{Error, HeaderChain, LightClient, Request};
+use rcrypto::ed25519;
+use receipt::{LocalizedReceipt, Receipt};
+use rlp::{self, RlpStream};
+use spec::Spec;
+use transaction::{Action, SignedTransaction, Transaction};
+use triehash::ordered_trie_root;
+use types::error::{BlockError, Error as CoreError};
+use helpers::*;
+
+struct NoopNotify;
+
+impl Notify for NoopNotify {
+    fn notify(&self, _id: usize) {}
+}
+
+fn pos_child(parent: &Header, difficulty: u64) -> Header {
+    let mut header = Header::default();
+    header.set_seal_type(SealType::PoS);
+    header.set_parent_hash(parent.hash());
+    header.set_number(parent.number() + 1);
+    header.set_timestamp(parent.timestamp() + 10);
+    header.set_difficulty(U256::from(difficulty));
+    header
+}
+
+/// Seal a PoS header whose grand parent is not a PoS block.
+fn seal_pos(header: &mut Header) {
+    let (secret, public) = ed25519::keypair(&[7u8; 32]);
+    let seed = ed25519::signature(&[0u8; 64], &secret);
+    header.set_seal(vec![seed.to_vec(), vec![0u8; 64], public.to_vec()]);
+    let signature = ed25519::signature(&header.mine_hash().0, &secret);
+    header.set_seal(vec![seed.to_vec(), signature.to_vec(), public.to_vec()]);
+}
+
+fn signed_transaction() -> SignedTransaction {
+    let secret = Ed25519Secret::from_str("7ea8af7d0982509cd815096d35bc3a295f57b2a078e4e25731e3ea977b9544626702b86f33072a55f46003b1e3e242eb18556be54c5ab12044c3c20829e0abb5").unwrap();
+    Transaction::new(
+        U256::zero(),
+        U256::from(10_000_000_000u64),
+        U256::from(21_000),
+        Action::Call(Address::from(2)),
+        U256::from(5),
+        Vec::new(),
+        U256::from(1),
+        None,
+    )
+    .sign(&secret)
+}
+
+fn body_rlp(transactions: &[SignedTransaction]) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(1);
+    stream.begin_list(transactions.len());
+    for t in transactions {
+        stream.append_raw(&rlp::encode(t).into_vec(), 1);
+    }
+    stream.out()
+}
+
+fn unity_chain(spec: &Spec) -> HeaderChain {
+    HeaderChain::new(new_db(), &spec.genesis_header(), Some(0)).unwrap()
+}
+
+fn assert_block_error(res: Result<bool, Error>, expected: BlockError) {
+    match res {
+        Err(Error::InvalidHeader(CoreError::Block(ref err))) if *err == expected => {}
+        other => panic!("expected {:?}, got {:?}", expected, other),
+    }
+}
+
+fn assert_bad_proof<T: Debug>(res: Result<T, Error>) {
+    match res {
+        Err(Error::BadProof) => {}
+        other => panic!("expected a bad proof, got {:?}", other),
+    }
+}
+
+/// Run a receipt query, answering it as a peer claiming the transaction is at `index` of
+/// `header`, whose block holds `transactions` and `receipts`.
+fn query_receipt(
+    client: &LightClient,
+    hash: H256,
+    header: &Header,
+    index: usize,
+    transactions: &[SignedTransaction],
+    receipts: &[Receipt],
+) -> Result<Option<LocalizedReceipt>, Error>
+{
+    let on_demand = client.on_demand();
+    let notify = NotifyHandle::from(Arc::new(NoopNotify));
+    let mut query = executor::spawn(client.transaction_receipt(hash));
+    loop {
+        match query.poll_future_notify(&notify, 0) {
+            Ok(Async::Ready(receipt)) => return Ok(receipt),
+            Ok(Async::NotReady) => {}
+            Err(err) => return Err(err),
+        }
+
+        let mut sent = Vec::new();
+        on_demand.dispatch(|id, request, _| {
+            sent.push((id, request.clone()));
+            Some(1)
+        });
+        assert!(!sent.is_empty(), "receipt query is stuck");
+        for (id, request) in sent {
+            let payload = match request {
+                Request::TransactionIndex {
+                    ..
+                } => {
+                    let mut stream = RlpStream::new_list(3);
+                    stream.append(&header.number());
+                    stream.append(&header.hash());
+                    stream.append(&index);
+                    stream.out()
+                }
+                Request::Body {
+                    ..
+                } => body_rlp(transactions),
+                Request::Receipts {
+                    ..
+                } => rlp::encode_list::<Receipt, _>(receipts).into_vec(),
+                other => panic!("unexpected request {:?}", other),
+            };
+            on_demand.on_response(1, id, Some(&payload));
+        }
+    }
+}
+
+#[test]
+fn header_chain_follows_heaviest_header() {
+    let spec = Spec::new_unity(Some(0));
+    let chain = unity_chain(&spec);
+    let genesis = spec.genesis_header();
+
+    let mut light = pos_child(&genesis, 10);
+    seal_pos(&mut light);
+    assert!(chain.insert(&light).unwrap());
+    assert!(!chain.insert(&light).unwrap());
+    assert_eq!(chain.best_block_hash(), light.hash());
+    assert_eq!(chain.best_block_number(), 1);
+
+    let mut heavy = pos_child(&genesis, 20);
+    seal_pos(&mut heavy);
+    assert!(chain.insert(&heavy).unwrap());
+    assert_eq!(chain.best_block_hash(), heavy.hash());
+    assert_eq!(chain.block_hash(1), Some(heavy.hash()));
+    assert_eq!(
+        chain.best_total_difficulty(),
+        *genesis.difficulty() + U256::from(20)
+    );
+
+    // a lighter sibling is kept but does not become canonical
+    let mut lighter = pos_child(&genesis, 5);
+    seal_pos(&mut lighter);
+    assert!(chain.insert(&lighter).unwrap());
+    assert_eq!(chain.block_hash(1), Some(heavy.hash()));
+    assert_eq!(
+        chain.total_difficulty(&lighter.hash()),
+        Some(*genesis.difficulty() + U256::from(5))
+    );
+}
+
+#[test]
+fn header_chain_rejects_forged_pow_solution() {
+    let spec = Spec::new_unity(None);
+    let genesis = spec.genesis_header();
+    let chain = HeaderChain::new(new_db(), &genesis, None).unwrap();
+
+    let mut header = Header::default();
+    header.set_parent_hash(genesis.hash());
+    header.set_number(1);
+    header.set_timestamp(genesis.timestamp() + 10);
+    // the lowest difficulty accepts any hash, so only the solution is left to check
+    header.set_difficulty(U256::one());
+    header.set_seal(vec![vec![0u8; 32], vec![0u8; 1408]]);
+    assert_block_error(chain.insert(&header), BlockError::InvalidSolution);
+    assert_eq!(chain.best_block_hash(), genesis.hash());
+    assert!(chain.block_header_data(&header.hash()).is_none());
+}
+
+#[test]
+fn header_chain_rejects_forged_pos_seal() {
+    let spec = Spec::new_unity(Some(0));
+    let chain = unity_chain(&spec);
+    let genesis = spec.genesis_header();
+
+    let mut header = pos_child(&genesis, 10);
+    seal_pos(&mut header);
+
+    let mut tampered = header.clone();
+    tampered.set_timestamp(header.timestamp() + 1);
+    assert_block_error(chain.insert(&tampered), BlockError::InvalidPoSSignature);
+
+    let mut reseeded = header.clone();
+    let mut seal = header.seal().to_vec();
+    seal[0] = vec![1u8; 64];
+    reseeded.set_seal(seal);
+    assert_block_error(chain.insert(&reseeded), BlockError::InvalidPoSSeed);
+
+    assert_eq!(chain.best_block_hash(), genesis.hash());
+    assert!(chain.insert(&header).unwrap());
+}
+
+#[test]
+fn header_chain_rejects_pos_header_before_fork() {
+    let spec = Spec::new_unity(None);
+    let genesis = spec.genesis_header();
+    let chain = HeaderChain::new(new_db(), &genesis, None).unwrap();
+
+    let mut header = pos_child(&genesis, 10);
+    seal_pos(&mut header);
+    assert_block_error(chain.insert(&header), BlockError::InvalidPoSBlockNumber);
+    assert_eq!(chain.best_block_hash(), genesis.hash());
+}
+
+#[test]
+fn header_chain_rejects_unknown_parent_and_stale_timestamp() {
+    let spec = Spec::new_unity(Some(0));
+    let chain = unity_chain(&spec);
+    let genesis = spec.genesis_header();
+
+    let mut orphan = pos_child(&genesis, 10);
+    orphan.set_parent_hash(H256::from(1));
+    seal_pos(&mut orphan);
+    match chain.insert(&orphan) {
+        Err(Error::UnknownParent(hash)) => assert_eq!(hash, H256::from(1)),
+        other => panic!("expected an unknown parent, got {:?}", other),
+    }
+
+    let mut stale = pos_child(&genesis, 10);
+    stale.set_timestamp(genesis.timestamp());
+    seal_pos(&mut stale);
+    match chain.insert(&stale) {
+        Err(Error::InvalidHeader(CoreError::Block(BlockError::InvalidTimestamp(_)))) => {}
+        other => panic!("expected an invalid timestamp, got {:?}", other),
+    }
+    assert_eq!(chain.best_block_number(), 0);
+}
+
+#[test]
+fn forged_responses_are_bad_proofs() {
+    let spec = Spec::new_unity(Some(0));
+    let chain = unity_chain(&spec);
+    let machine = spec.engine.machine();
+    let block_hash = spec.genesis_header().hash();
+
+    let receipts = vec![Receipt::new(
+        H256::from(1),
+        U256::from(21_000),
+        U256::zero(),
+        Vec::new(),
+        Vec::new(),
+        String::new(),
+    )];
+    let request = Request::Receipts {
+        block_hash: block_hash,
+    };
+    let payload = rlp::encode_list::<Receipt, _>(&receipts).into_vec();
+    assert_bad_proof(request.check_response(&chain, machine, new_db(), &payload));
+
+    let request = Request::Body {
+        block_hash: block_hash,
+    };
+    let payload = body_rlp(&[signed_transaction()]);
+    assert_bad_proof(request.check_response(&chain, machine, new_db(), &payload));
+
+    // an empty proof does not hold the state root of the block
+    let request = Request::Execution {
+        block_hash: block_hash,
+        transaction: Transaction::new(
+            U256::zero(),
+            U256::zero(),
+            U256::from(21_000),
+            Action::Call(Address::from(2)),
+            U256::zero(),
+            Vec::new(),
+            U256::from(1),
+            None,
+        )
+        .fake_sign(Address::from(1)),
+    };
+    let payload = RlpStream::new_list(0).out();
+    assert_bad_proof(request.check_response(&chain, machine, new_db(), &payload));
+}
+
+#[test]
+fn transaction_receipt_checks_body_against_index() {
+    let spec = Spec::new_unity(Some(0));
+    let client = LightClient::new(new_db(), &spec).unwrap();
+    let genesis = spec.genesis_header();
+
+    let transactions = vec![signed_transaction()];
+    let receipts = vec![Receipt::new(
+        H256::from(1),
+        U256::from(21_000),
+        U256::zero(),
+        Vec::new(),
+        Vec::new(),
+        String::new(),
+    )];
+    let mut header = pos_child(&genesis, 10);
+    header.set_transactions_root(ordered_trie_root(
+        transactions.iter().map(|t| rlp::encode(t).into_vec()),
+    ));
+    header.set_receipts_root(ordered_trie_root(
+        receipts.iter().map(|r| rlp::encode(r.simple_receipt()).into_vec()),
+    ));
+    seal_pos(&mut header);
+    assert!(client.chain().insert(&header).unwrap());
+
+    let hash = *transactions[0].hash();
+    let receipt = query_receipt(&client, hash, &header, 0, &transactions, &receipts)
+        .unwrap()
+        .expect("transaction is on the canonical chain");
+    assert_eq!(receipt.transaction_hash, hash);
+    assert_eq!(receipt.block_hash, header.hash());
+
+    // the peer points another transaction at the same slot
+    assert_bad_proof(query_receipt(
+        &client,
+        H256::from(9),
+        &header,
+        0,
+        &transactions,
+        &receipts,
+    ));
+    // the peer points past the end of the body
+    assert_bad_proof(query_receipt(
+        &client,
+        hash,
+        &header,
+        1,
+        &transactions,
+        &receipts,
+    ));
+    // receipts not matching the header fail their proof on every attempt
+    let no_receipts: Vec<Receipt> = Vec::new();
+    assert_bad_proof(query_receipt(
+        &client,
+        hash,
+        &header,
+        0,
+        &transactions,
+        &no_receipts,
+    ));
+}
@@ -27,5 +27,7 @@ mod pod_account;
 mod pod_state;
 mod state;
 mod externalities;
+mod snapshot;
+mod light;
 
 pub mod common;
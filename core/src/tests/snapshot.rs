@@ -0,0 +1,165 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+use aion_types::H256;
+use client::{BlockChainClient, BlockId};
+use rlp::{RlpStream, UntrustedRlp};
+use snapshot::{
+    Error, LooseReader, LooseWriter, NodeRequest, Pivot, SnapshotReader, PIVOT_DISTANCE,
+};
+use spec::Spec;
+use tempdir::TempDir;
+use helpers::*;
+
+#[test]
+fn takes_and_restores_snapshot() {
+    let tempdir = TempDir::new("").unwrap();
+    let snapshot_path = tempdir.path().join("snapshot");
+
+    let source = generate_dummy_client(12);
+    let writer = LooseWriter::new(snapshot_path.clone()).unwrap();
+    let manifest = source.take_snapshot(writer, BlockId::Latest).unwrap();
+    assert_eq!(manifest.block_number, 12);
+    assert_eq!(manifest.block_hash, source.chain_info().best_block_hash);
+
+    let reader = LooseReader::new(snapshot_path).unwrap();
+    assert_eq!(reader.manifest(), &manifest);
+
+    let target = generate_dummy_client(0);
+    let mut restoration = target.begin_restoration(manifest.clone()).unwrap();
+    for hash in manifest
+        .state_hashes
+        .iter()
+        .chain(manifest.block_hashes.iter())
+    {
+        restoration.feed(*hash, &reader.chunk(*hash).unwrap()).unwrap();
+    }
+    assert!(restoration.is_done());
+    target.finish_restoration(restoration).unwrap();
+
+    let info = target.chain_info();
+    assert_eq!(info.best_block_number, 12);
+    assert_eq!(info.best_block_hash, manifest.block_hash);
+    assert_eq!(
+        target.block_header(BlockId::Latest).unwrap().state_root(),
+        manifest.state_root
+    );
+}
+
+#[test]
+fn restoration_requires_empty_chain() {
+    let tempdir = TempDir::new("").unwrap();
+    let source = generate_dummy_client(2);
+    let manifest = source
+        .take_snapshot(
+            LooseWriter::new(tempdir.path().to_path_buf()).unwrap(),
+            BlockId::Latest,
+        )
+        .unwrap();
+    assert!(source.begin_restoration(manifest).is_err());
+}
+
+#[test]
+fn warp_syncs_state_at_pivot() {
+    let source = generate_dummy_client(24);
+    let pivot = Pivot::from_rlp(&source.warp_pivot().unwrap()).unwrap();
+    assert_eq!(pivot.number, 24 - PIVOT_DISTANCE);
+    assert!(source.begin_warp(pivot.clone()).is_err());
+
+    let target = generate_dummy_client(0);
+    let mut warp = target.begin_warp(pivot.clone()).unwrap();
+    for _ in 0..1000 {
+        if warp.is_done() {
+            break;
+        }
+        let requests = warp.next_requests(16);
+        let data: Vec<_> = requests
+            .iter()
+            .map(|request| source.state_node(request).unwrap_or_default())
+            .collect();
+        assert_eq!(warp.feed(&requests, &data).unwrap(), 0);
+    }
+    assert!(warp.is_done());
+    target.finish_warp(warp).unwrap();
+
+    let info = target.chain_info();
+    assert_eq!(info.best_block_number, pivot.number);
+    assert_eq!(info.best_block_hash, pivot.hash);
+    assert_eq!(
+        target.block_header(BlockId::Latest).unwrap().state_root(),
+        pivot.state_root
+    );
+    assert!(target.state_at(BlockId::Latest).is_some());
+}
+
+#[test]
+fn pivot_holds_its_ancestors() {
+    let source = generate_dummy_client(24);
+    let pivot = Pivot::from_rlp(&source.warp_pivot().unwrap()).unwrap();
+    pivot.verify(source.engine()).unwrap();
+    assert!(pivot.contains(&pivot.hash));
+
+    let parent = source.block_header(BlockId::Number(pivot.number - 1)).unwrap();
+    assert!(pivot.contains(&parent.hash()));
+    let child = source.block_header(BlockId::Number(pivot.number + 1)).unwrap();
+    assert!(!pivot.contains(&child.hash()));
+}
+
+#[test]
+fn warp_requests_forged_state_again() {
+    let source = generate_dummy_client(24);
+    let pivot = Pivot::from_rlp(&source.warp_pivot().unwrap()).unwrap();
+    let target = generate_dummy_client(0);
+    let mut warp = target.begin_warp(pivot).unwrap();
+
+    let requests = warp.next_requests(16);
+    assert!(!requests.is_empty());
+    let forged = vec![b"forged".to_vec(); requests.len()];
+    assert_eq!(warp.feed(&requests, &forged).unwrap(), requests.len());
+    assert_eq!(warp.downloaded(), 0);
+    assert!(!warp.is_done());
+
+    let again = warp.next_requests(16);
+    assert_eq!(again.len(), requests.len());
+    assert!(requests.iter().all(|request| again.contains(request)));
+}
+
+#[test]
+fn pivot_with_invalid_seals_is_rejected() {
+    // the test chain is sealed for the null engine, its equihash solutions are all zero.
+    let source = generate_dummy_client(24);
+    let pivot = Pivot::from_rlp(&source.warp_pivot().unwrap()).unwrap();
+    let spec = Spec::new_unity(None);
+    match pivot.verify(&*spec.engine) {
+        Err(Error::InvalidHeader(..)) => {}
+        other => panic!("mis-sealed pivot was accepted: {:?}", other),
+    }
+}
+
+#[test]
+fn transformed_code_requests_are_refused() {
+    let mut stream = RlpStream::new_list(3);
+    stream.append(&3u8);
+    stream.append(&H256::from(1));
+    stream.append(&H256::from(2));
+    let request: Result<NodeRequest, _> = UntrustedRlp::new(&stream.out()).as_val();
+    assert!(request.is_err());
+}
@@ -27,6 +27,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 use vms::EnvInfo;
 use state::{State,CleanupMode};
+use types::account::account_diff::Diff;
 use super::common::helpers::{get_temp_state,get_temp_state_db};
 use kvdb::MockDbRepository;
 use transaction::{Transaction,Action};
@@ -76,7 +77,7 @@ fn should_apply_create_transaction() {
     state
         .add_balance(&t.sender(), &(100.into()), CleanupMode::NoEmpty)
         .unwrap();
-    let result = state.apply(&info, &machine, &t, true).unwrap();
+    let result = state.apply(&info, &machine, &t, true, false).unwrap();
 
     let expected_receipt = Receipt {
             simple_receipt: SimpleReceipt{log_bloom: "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000".into(),
@@ -344,3 +345,35 @@ fn should_not_panic_on_state_diff_with_storage() {
 
     new_state.diff_from(state).unwrap();
 }
+
+#[test]
+fn state_diff_reports_pre_and_post_values() {
+    let mut state = get_temp_state();
+
+    let a: Address = 0xa.into();
+    let b: Address = 0xb.into();
+    state.init_code(&a, b"abcdefg".to_vec()).unwrap();
+    state
+        .add_balance(&a, &256.into(), CleanupMode::NoEmpty)
+        .unwrap();
+    state.set_storage(&a, vec![0x0b], vec![0x0c]).unwrap();
+
+    let mut new_state = state.clone();
+    new_state.set_storage(&a, vec![0x0b], vec![0x0d]).unwrap();
+    new_state
+        .transfer_balance(&a, &b, &56.into(), CleanupMode::NoEmpty)
+        .unwrap();
+
+    let diff = new_state.diff_from(state).unwrap();
+    let a_diff = &diff.raw[&a];
+    assert_eq!(a_diff.balance, Diff::Changed(256.into(), 200.into()));
+    assert_eq!(a_diff.nonce, Diff::Same);
+    assert_eq!(a_diff.code, Diff::Same);
+    assert_eq!(
+        a_diff.storage[&vec![0x0b]],
+        Diff::Changed(vec![0x0c], vec![0x0d])
+    );
+    let b_diff = &diff.raw[&b];
+    assert_eq!(b_diff.balance, Diff::Born(56.into()));
+    assert_eq!(b_diff.code, Diff::Born(vec![]));
+}
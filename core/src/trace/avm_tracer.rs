@@ -0,0 +1,126 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Tracer of avm transactions.
+//!
+//! The avm executes a whole transaction inside the jvm; only the state it commits
+//! crosses the callback bridge, and internal transactions are reported with the
+//! transaction result.
+
+use std::sync::Arc;
+use parking_lot::Mutex;
+use aion_types::{Address, U256};
+use acore_bytes::Bytes;
+use log_entry::LogEntry;
+use vms::AvmInternalTransaction;
+
+/// A state change committed by an avm transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AvmOperation {
+    /// An account was created.
+    CreateAccount(Address),
+    /// Code was deployed to an account.
+    PutCode(Address, Bytes),
+    /// A storage key was set.
+    PutStorage(Address, Bytes, Bytes),
+    /// A storage key was removed.
+    RemoveStorage(Address, Bytes),
+    /// The balance of an account was increased.
+    IncreaseBalance(Address, U256),
+    /// The balance of an account was decreased.
+    DecreaseBalance(Address, U256),
+    /// A log was emitted.
+    Log(LogEntry),
+}
+
+/// Trace of a single avm transaction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AvmTrace {
+    /// Calls and creates made inside the avm, in execution order.
+    pub internal_transactions: Vec<AvmInternalTransaction>,
+    /// State changes, in the order they were committed.
+    pub operations: Vec<AvmOperation>,
+}
+
+/// Collects the traces of a bulk of avm transactions.
+///
+/// Clones share the same traces, so the tracer can be handed to the thread
+/// running the vm.
+#[derive(Debug, Default, Clone)]
+pub struct AvmTracer {
+    traces: Arc<Mutex<Vec<AvmTrace>>>,
+}
+
+impl AvmTracer {
+    /// Records a state change of the transaction at `index`.
+    pub fn trace_operation(&self, index: usize, operation: AvmOperation) {
+        self.with_trace(index, |trace| trace.operations.push(operation));
+    }
+
+    /// Records the internal transactions of the transaction at `index`.
+    pub fn trace_internal_transactions(&self, index: usize, txs: &[AvmInternalTransaction]) {
+        self.with_trace(index, |trace| {
+            trace.internal_transactions.extend_from_slice(txs)
+        });
+    }
+
+    /// Takes the traces recorded so far, indexed by transaction.
+    pub fn drain(&self) -> Vec<AvmTrace> {
+        ::std::mem::replace(&mut *self.traces.lock(), Vec::new())
+    }
+
+    fn with_trace<F: FnOnce(&mut AvmTrace)>(&self, index: usize, f: F) {
+        let mut traces = self.traces.lock();
+        if traces.len() <= index {
+            traces.resize(index + 1, AvmTrace::default());
+        }
+        f(&mut traces[index]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations_are_indexed_by_transaction() {
+        let tracer = AvmTracer::default();
+        let shared = tracer.clone();
+        let address = Address::from(1);
+        shared.trace_operation(0, AvmOperation::CreateAccount(address));
+        shared.trace_operation(2, AvmOperation::IncreaseBalance(address, 10.into()));
+        shared.trace_operation(0, AvmOperation::PutStorage(address, vec![1], vec![2]));
+
+        let traces = tracer.drain();
+        assert_eq!(traces.len(), 3);
+        assert_eq!(
+            traces[0].operations,
+            vec![
+                AvmOperation::CreateAccount(address),
+                AvmOperation::PutStorage(address, vec![1], vec![2]),
+            ]
+        );
+        assert!(traces[1].operations.is_empty());
+        assert_eq!(traces[2].operations.len(), 1);
+        assert!(tracer.drain().is_empty());
+    }
+}
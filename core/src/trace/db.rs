@@ -0,0 +1,71 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Persistent storage of block traces.
+
+use std::sync::Arc;
+use aion_types::H256;
+use kvdb::{DBTransaction, KeyValueDB};
+use db::{self, Key, Readable, Writable};
+use super::BlockTraces;
+
+impl Key<BlockTraces> for H256 {
+    type Target = H256;
+
+    fn key(&self) -> H256 { self.clone() }
+}
+
+/// Database of call traces, keyed by block hash.
+///
+/// Traces are only recorded for blocks imported while tracing is enabled.
+pub struct TraceDB {
+    /// Whether traces should be recorded on block import.
+    enabled: bool,
+    /// Underlying database.
+    db: Arc<KeyValueDB>,
+}
+
+impl TraceDB {
+    /// Creates a new trace database on top of `db`.
+    pub fn new(enabled: bool, db: Arc<KeyValueDB>) -> Self {
+        TraceDB {
+            enabled,
+            db,
+        }
+    }
+
+    /// Returns true if traces are recorded on block import.
+    pub fn tracing_enabled(&self) -> bool { self.enabled }
+
+    /// Writes the traces of a newly imported block into the batch.
+    pub fn import(&self, batch: &mut DBTransaction, block_hash: &H256, traces: &BlockTraces) {
+        if !self.enabled {
+            return;
+        }
+        batch.write(db::COL_TRACE, block_hash, traces);
+    }
+
+    /// Returns the traces of the block with given hash, if they were recorded.
+    pub fn block_traces(&self, block_hash: &H256) -> Option<BlockTraces> {
+        self.db.read(db::COL_TRACE, block_hash)
+    }
+}
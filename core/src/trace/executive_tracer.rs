@@ -0,0 +1,239 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Simple executive tracer.
+
+use aion_types::{Address, U256};
+use acore_bytes::Bytes;
+use vms::{ExecStatus, ExecutionResult};
+use super::types::{Action, Call, CallResult, Create, CreateResult, Error, FlatTrace, Res};
+use super::avm_tracer::AvmTracer;
+use super::vm_tracer::VMTracer;
+
+/// Returns the number of traces made directly by the frame owning `traces`.
+fn top_level_subtraces(traces: &[FlatTrace]) -> usize {
+    traces
+        .iter()
+        .filter(|t| t.trace_address.is_empty())
+        .count()
+}
+
+/// Prefixes the addresses of the subtraces with their index in the parent frame.
+///
+/// Input traces are expected to be ordered like
+/// []
+/// [0]
+/// [0, 0]
+/// []
+/// [0]
+///
+/// so they can be transformed to
+///
+/// [0]
+/// [0, 0]
+/// [0, 0, 0]
+/// [1]
+/// [1, 0]
+fn prefix_subtrace_addresses(mut traces: Vec<FlatTrace>) -> Vec<FlatTrace> {
+    let mut current_subtrace_index = 0;
+    let mut first = true;
+    for trace in &mut traces {
+        match (first, trace.trace_address.is_empty()) {
+            (true, _) => first = false,
+            (_, true) => current_subtrace_index += 1,
+            _ => {}
+        }
+        trace.trace_address.push_front(current_subtrace_index);
+    }
+    traces
+}
+
+/// Collects the call tree of an execution as a list of flat traces.
+///
+/// Every call frame is traced by its own tracer; once the frame is finished, its traces
+/// are attached to the tracer of the parent frame.
+#[derive(Debug, Default)]
+pub struct ExecutiveTracer {
+    traces: Vec<FlatTrace>,
+    vm_tracer: Option<VMTracer>,
+    avm_tracer: Option<AvmTracer>,
+}
+
+impl ExecutiveTracer {
+    /// Creates a tracer which also records the instructions executed by the vm,
+    /// and the state changes of avm transactions.
+    pub fn with_vm_tracer(vm_tracer: VMTracer) -> Self {
+        ExecutiveTracer {
+            traces: Vec::new(),
+            vm_tracer: Some(vm_tracer),
+            avm_tracer: Some(AvmTracer::default()),
+        }
+    }
+
+    /// Spawns a tracer for a nested call frame.
+    pub fn subtracer(&self) -> Self {
+        ExecutiveTracer {
+            traces: Vec::new(),
+            vm_tracer: self.vm_tracer.clone(),
+            avm_tracer: self.avm_tracer.clone(),
+        }
+    }
+
+    /// Instruction tracer shared by all frames, if enabled.
+    pub fn vm_tracer(&self) -> Option<&VMTracer> { self.vm_tracer.as_ref() }
+
+    /// Avm tracer shared by all frames, if enabled.
+    pub fn avm_tracer(&self) -> Option<&AvmTracer> { self.avm_tracer.as_ref() }
+
+    /// Attaches traces of finished nested frames.
+    pub fn extend(&mut self, traces: Vec<FlatTrace>) { self.traces.extend(traces); }
+
+    /// Records a successful call.
+    pub fn trace_call(&mut self, call: Call, gas_used: U256, output: Bytes, subs: Vec<FlatTrace>) {
+        let result = Res::Call(CallResult {
+            gas_used,
+            output,
+        });
+        self.record(Action::Call(call), result, subs);
+    }
+
+    /// Records a successful contract creation.
+    pub fn trace_create(
+        &mut self,
+        create: Create,
+        gas_used: U256,
+        code: Bytes,
+        address: Address,
+        subs: Vec<FlatTrace>,
+    )
+    {
+        let result = Res::Create(CreateResult {
+            gas_used,
+            code,
+            address,
+        });
+        self.record(Action::Create(create), result, subs);
+    }
+
+    /// Records a failed call.
+    pub fn trace_failed_call(&mut self, call: Call, error: Error, subs: Vec<FlatTrace>) {
+        self.record(Action::Call(call), Res::FailedCall(error), subs);
+    }
+
+    /// Records a failed contract creation.
+    pub fn trace_failed_create(&mut self, create: Create, error: Error, subs: Vec<FlatTrace>) {
+        self.record(Action::Create(create), Res::FailedCreate(error), subs);
+    }
+
+    /// Records a call frame according to the result returned by the vm.
+    pub fn trace_call_result(
+        &mut self,
+        call: Call,
+        result: &ExecutionResult,
+        subs: Vec<FlatTrace>,
+    )
+    {
+        match result.status_code {
+            ExecStatus::Success => {
+                let gas_used = call.gas.saturating_sub(result.gas_left);
+                self.trace_call(call, gas_used, result.return_data.to_vec(), subs)
+            }
+            _ => self.trace_failed_call(call, Error::from(result), subs),
+        }
+    }
+
+    /// Records a create frame according to the result returned by the vm.
+    pub fn trace_create_result(
+        &mut self,
+        create: Create,
+        address: Address,
+        code: Bytes,
+        result: &ExecutionResult,
+        subs: Vec<FlatTrace>,
+    )
+    {
+        match result.status_code {
+            ExecStatus::Success => {
+                let gas_used = create.gas.saturating_sub(result.gas_left);
+                self.trace_create(create, gas_used, code, address, subs)
+            }
+            _ => self.trace_failed_create(create, Error::from(result), subs),
+        }
+    }
+
+    /// Consumes self and returns all traces.
+    pub fn drain(self) -> Vec<FlatTrace> { self.traces }
+
+    fn record(&mut self, action: Action, result: Res, subs: Vec<FlatTrace>) {
+        let trace = FlatTrace {
+            action,
+            result,
+            subtraces: top_level_subtraces(&subs),
+            trace_address: Default::default(),
+        };
+        debug!(target: "trace", "traced frame: {:?}", trace);
+        self.traces.push(trace);
+        self.traces.extend(prefix_subtrace_addresses(subs));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use super::*;
+    use vms::CallType;
+
+    fn call(from: u64, to: u64) -> Call {
+        Call {
+            from: Address::from(from),
+            to: Address::from(to),
+            value: 0.into(),
+            gas: 100.into(),
+            input: vec![],
+            call_type: CallType::Call,
+        }
+    }
+
+    #[test]
+    fn nested_frames_are_addressed() {
+        // 1 -> 2 -> 3, then 2 -> 4 (reverted)
+        let mut root = ExecutiveTracer::default();
+        let mut frame2 = root.subtracer();
+        let mut frame3 = frame2.subtracer();
+        frame3.trace_call(call(2, 3), 10.into(), vec![], vec![]);
+        let mut frame4 = frame2.subtracer();
+        frame4.trace_failed_call(call(2, 4), Error::Reverted, vec![]);
+        let mut subs = frame3.drain();
+        subs.extend(frame4.drain());
+        frame2.trace_call(call(1, 2), 50.into(), vec![1], subs);
+        root.extend(frame2.drain());
+
+        let traces = root.drain();
+        assert_eq!(traces.len(), 3);
+        assert_eq!(traces[0].subtraces, 2);
+        assert!(traces[0].trace_address.is_empty());
+        assert_eq!(traces[1].trace_address, VecDeque::from(vec![0]));
+        assert_eq!(traces[2].trace_address, VecDeque::from(vec![1]));
+        assert_eq!(traces[2].result, Res::FailedCall(Error::Reverted));
+        assert_eq!(traces[0].result.gas_used(), Some(50.into()));
+    }
+}
@@ -0,0 +1,130 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Trace filter.
+
+use aion_types::Address;
+use types::ids::BlockId;
+use super::FlatTrace;
+
+/// Trace filter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    /// Blockchain will be searched from this block.
+    pub from_block: BlockId,
+
+    /// Till this block.
+    pub to_block: BlockId,
+
+    /// Sender addresses.
+    ///
+    /// If empty, match all.
+    /// If specified, the frame must be sent from one of these addresses.
+    pub from_address: Vec<Address>,
+
+    /// Destination addresses.
+    ///
+    /// If empty, match all.
+    /// If specified, the frame must be sent to, or create, one of these addresses.
+    pub to_address: Vec<Address>,
+
+    /// Number of matching traces to skip.
+    pub after: Option<usize>,
+
+    /// Maximal number of traces to return.
+    pub count: Option<usize>,
+}
+
+impl Filter {
+    /// Returns true if given trace matches the filter.
+    pub fn matches(&self, trace: &FlatTrace) -> bool {
+        let from_matches =
+            self.from_address.is_empty() || self.from_address.contains(trace.from_address());
+        let to_matches = self.to_address.is_empty() || trace
+            .to_address()
+            .map_or(false, |to| self.to_address.contains(to));
+        from_matches && to_matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use aion_types::Address;
+    use types::ids::BlockId;
+    use trace::{Action, Call, CallResult, Create, Error, FlatTrace, Res};
+    use vms::CallType;
+    use super::Filter;
+
+    fn filter(from_address: Vec<Address>, to_address: Vec<Address>) -> Filter {
+        Filter {
+            from_block: BlockId::Earliest,
+            to_block: BlockId::Latest,
+            from_address,
+            to_address,
+            after: None,
+            count: None,
+        }
+    }
+
+    #[test]
+    fn filter_matches_call_by_address() {
+        let trace = FlatTrace {
+            action: Action::Call(Call {
+                from: Address::from(1),
+                to: Address::from(2),
+                value: 10.into(),
+                gas: 21000.into(),
+                input: vec![],
+                call_type: CallType::Call,
+            }),
+            result: Res::Call(CallResult::default()),
+            subtraces: 0,
+            trace_address: VecDeque::new(),
+        };
+
+        assert!(filter(vec![], vec![]).matches(&trace));
+        assert!(filter(vec![Address::from(1)], vec![]).matches(&trace));
+        assert!(filter(vec![], vec![Address::from(2)]).matches(&trace));
+        assert!(filter(vec![Address::from(1)], vec![Address::from(2)]).matches(&trace));
+        assert!(!filter(vec![Address::from(2)], vec![]).matches(&trace));
+        assert!(!filter(vec![Address::from(1)], vec![Address::from(1)]).matches(&trace));
+    }
+
+    #[test]
+    fn failed_create_has_no_destination() {
+        let trace = FlatTrace {
+            action: Action::Create(Create {
+                from: Address::from(1),
+                value: 0.into(),
+                gas: 100000.into(),
+                init: vec![0x60, 0x50],
+            }),
+            result: Res::FailedCreate(Error::OutOfGas),
+            subtraces: 0,
+            trace_address: VecDeque::new(),
+        };
+
+        assert!(filter(vec![Address::from(1)], vec![]).matches(&trace));
+        assert!(!filter(vec![], vec![Address::from(2)]).matches(&trace));
+    }
+}
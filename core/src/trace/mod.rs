@@ -0,0 +1,41 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Tracing of transaction execution.
+
+mod avm_tracer;
+mod db;
+mod executive_tracer;
+mod filter;
+mod types;
+mod vm_tracer;
+
+pub use self::avm_tracer::{AvmOperation, AvmTrace, AvmTracer};
+pub use self::db::TraceDB;
+pub use self::executive_tracer::ExecutiveTracer;
+pub use self::filter::Filter;
+pub use self::types::{
+    Action, BlockTraces, Call, CallResult, Create, CreateResult, Error, FlatTrace,
+    LocalizedTrace, Res,
+};
+pub use self::vm_tracer::{StructLog, VMTraceOptions, VMTracer, MAX_STEPS};
+pub use vms::{AvmInternalTransaction, CallType};
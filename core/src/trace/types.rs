@@ -0,0 +1,468 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Call trace types.
+
+use std::collections::VecDeque;
+use std::fmt;
+use aion_types::{Address, H256, U256};
+use acore_bytes::Bytes;
+use header::BlockNumber;
+use rlp::{Encodable, Decodable, DecoderError, RlpStream, UntrustedRlp};
+use vms::{ActionParams, ActionValue, CallType, ExecStatus, ExecutionResult};
+
+/// Description of a _call_ action, either a `CALL` operation or a message transaction.
+#[derive(Debug, Clone, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct Call {
+    /// The sending account.
+    pub from: Address,
+    /// The destination account.
+    pub to: Address,
+    /// The value transferred to the destination account.
+    pub value: U256,
+    /// The gas available for executing the call.
+    pub gas: U256,
+    /// The input data provided to the call.
+    pub input: Bytes,
+    /// The type of the call.
+    pub call_type: CallType,
+}
+
+impl<'a> From<&'a ActionParams> for Call {
+    fn from(p: &'a ActionParams) -> Self {
+        Call {
+            from: p.sender.clone(),
+            to: p.address.clone(),
+            value: match p.value {
+                ActionValue::Transfer(value) | ActionValue::Apparent(value) => value,
+            },
+            gas: p.gas,
+            input: p.data.clone().unwrap_or_else(Vec::new),
+            call_type: p.call_type.clone(),
+        }
+    }
+}
+
+/// Description of a _create_ action, either a `CREATE` operation or a create transaction.
+#[derive(Debug, Clone, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct Create {
+    /// The address of the creator.
+    pub from: Address,
+    /// The value with which the new account is endowed.
+    pub value: U256,
+    /// The gas available for the creation init code.
+    pub gas: U256,
+    /// The init code.
+    pub init: Bytes,
+}
+
+impl<'a> From<&'a ActionParams> for Create {
+    fn from(p: &'a ActionParams) -> Self {
+        Create {
+            from: p.sender.clone(),
+            value: match p.value {
+                ActionValue::Transfer(value) | ActionValue::Apparent(value) => value,
+            },
+            gas: p.gas,
+            init: p.code.as_ref().map_or_else(Vec::new, |c| (**c).clone()),
+        }
+    }
+}
+
+/// Description of an action that we trace.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// It's a call action.
+    Call(Call),
+    /// It's a create action.
+    Create(Create),
+}
+
+impl Encodable for Action {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2);
+        match *self {
+            Action::Call(ref call) => {
+                s.append(&0u8);
+                s.append(call);
+            }
+            Action::Create(ref create) => {
+                s.append(&1u8);
+                s.append(create);
+            }
+        }
+    }
+}
+
+impl Decodable for Action {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        let action_type: u8 = rlp.val_at(0)?;
+        match action_type {
+            0 => rlp.val_at(1).map(Action::Call),
+            1 => rlp.val_at(1).map(Action::Create),
+            _ => Err(DecoderError::Custom("Invalid action type.")),
+        }
+    }
+}
+
+/// Call result.
+#[derive(Debug, Clone, PartialEq, Default, RlpEncodable, RlpDecodable)]
+pub struct CallResult {
+    /// Gas used by call.
+    pub gas_used: U256,
+    /// Call Output.
+    pub output: Bytes,
+}
+
+/// Create result.
+#[derive(Debug, Clone, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct CreateResult {
+    /// Gas used by create.
+    pub gas_used: U256,
+    /// Code of the newly created contract.
+    pub code: Bytes,
+    /// Address of the newly created contract.
+    pub address: Address,
+}
+
+/// Reason why a traced frame did not succeed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// Execution ran out of gas.
+    OutOfGas,
+    /// Execution was reverted by the contract; remaining gas is refunded.
+    Reverted,
+    /// Execution failed with the given exception.
+    Failure(String),
+    /// Execution was rejected by the vm.
+    Rejected(String),
+}
+
+impl<'a> From<&'a ExecutionResult> for Error {
+    fn from(result: &'a ExecutionResult) -> Self {
+        match result.status_code {
+            ExecStatus::OutOfGas => Error::OutOfGas,
+            ExecStatus::Revert => Error::Reverted,
+            ExecStatus::Rejected => Error::Rejected(result.exception.clone()),
+            ExecStatus::Success | ExecStatus::Failure => Error::Failure(result.exception.clone()),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::OutOfGas => write!(f, "Out of gas"),
+            Error::Reverted => write!(f, "Reverted"),
+            Error::Failure(ref message) => write!(f, "Failure: {}", message),
+            Error::Rejected(ref message) => write!(f, "Rejected: {}", message),
+        }
+    }
+}
+
+impl Encodable for Error {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        match *self {
+            Error::OutOfGas => {
+                s.begin_list(1).append(&0u8);
+            }
+            Error::Reverted => {
+                s.begin_list(1).append(&1u8);
+            }
+            Error::Failure(ref message) => {
+                s.begin_list(2).append(&2u8).append(message);
+            }
+            Error::Rejected(ref message) => {
+                s.begin_list(2).append(&3u8).append(message);
+            }
+        }
+    }
+}
+
+impl Decodable for Error {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        let error_type: u8 = rlp.val_at(0)?;
+        match error_type {
+            0 => Ok(Error::OutOfGas),
+            1 => Ok(Error::Reverted),
+            2 => rlp.val_at(1).map(Error::Failure),
+            3 => rlp.val_at(1).map(Error::Rejected),
+            _ => Err(DecoderError::Custom("Invalid error type.")),
+        }
+    }
+}
+
+/// The result of the performed action.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Res {
+    /// Successful call action result.
+    Call(CallResult),
+    /// Successful create action result.
+    Create(CreateResult),
+    /// Failed call.
+    FailedCall(Error),
+    /// Failed create.
+    FailedCreate(Error),
+    /// None
+    None,
+}
+
+impl Encodable for Res {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        match *self {
+            Res::Call(ref call) => {
+                s.begin_list(2).append(&0u8).append(call);
+            }
+            Res::Create(ref create) => {
+                s.begin_list(2).append(&1u8).append(create);
+            }
+            Res::FailedCall(ref error) => {
+                s.begin_list(2).append(&2u8).append(error);
+            }
+            Res::FailedCreate(ref error) => {
+                s.begin_list(2).append(&3u8).append(error);
+            }
+            Res::None => {
+                s.begin_list(1).append(&4u8);
+            }
+        }
+    }
+}
+
+impl Decodable for Res {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        let result_type: u8 = rlp.val_at(0)?;
+        match result_type {
+            0 => rlp.val_at(1).map(Res::Call),
+            1 => rlp.val_at(1).map(Res::Create),
+            2 => rlp.val_at(1).map(Res::FailedCall),
+            3 => rlp.val_at(1).map(Res::FailedCreate),
+            4 => Ok(Res::None),
+            _ => Err(DecoderError::Custom("Invalid result type.")),
+        }
+    }
+}
+
+impl Res {
+    /// Returns result's gas used, if the frame succeeded.
+    pub fn gas_used(&self) -> Option<U256> {
+        match *self {
+            Res::Call(ref call) => Some(call.gas_used),
+            Res::Create(ref create) => Some(create.gas_used),
+            Res::FailedCall(_) | Res::FailedCreate(_) | Res::None => None,
+        }
+    }
+
+    /// Returns true if the frame did not succeed.
+    pub fn is_failed(&self) -> bool {
+        match *self {
+            Res::FailedCall(_) | Res::FailedCreate(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Trace of a single call frame, flattened out of the call tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatTrace {
+    /// Type of action performed by a transaction.
+    pub action: Action,
+    /// Result of this action.
+    pub result: Res,
+    /// Number of subtraces.
+    pub subtraces: usize,
+    /// Exact location of trace.
+    ///
+    /// [index in root, index in first CALL, index in second CALL, ...]
+    pub trace_address: VecDeque<usize>,
+}
+
+impl FlatTrace {
+    /// Returns the address the traced frame was sent from.
+    pub fn from_address(&self) -> &Address {
+        match self.action {
+            Action::Call(ref call) => &call.from,
+            Action::Create(ref create) => &create.from,
+        }
+    }
+
+    /// Returns the address the traced frame was sent to, if it is known.
+    ///
+    /// For creations this is the address of the new contract, which is only known on success.
+    pub fn to_address(&self) -> Option<&Address> {
+        match (&self.action, &self.result) {
+            (&Action::Call(ref call), _) => Some(&call.to),
+            (&Action::Create(_), &Res::Create(ref create)) => Some(&create.address),
+            _ => None,
+        }
+    }
+}
+
+impl Encodable for FlatTrace {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(4);
+        s.append(&self.action);
+        s.append(&self.result);
+        s.append(&self.subtraces);
+        s.begin_list(self.trace_address.len());
+        for index in &self.trace_address {
+            s.append(index);
+        }
+    }
+}
+
+impl Decodable for FlatTrace {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        let v: Vec<usize> = rlp.list_at(3)?;
+        Ok(FlatTrace {
+            action: rlp.val_at(0)?,
+            result: rlp.val_at(1)?,
+            subtraces: rlp.val_at(2)?,
+            trace_address: v.into_iter().collect(),
+        })
+    }
+}
+
+/// Traces of all transactions within a block, in transaction order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BlockTraces(pub Vec<Vec<FlatTrace>>);
+
+impl Encodable for BlockTraces {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(self.0.len());
+        for traces in &self.0 {
+            s.append_list::<FlatTrace, FlatTrace>(traces);
+        }
+    }
+}
+
+impl Decodable for BlockTraces {
+    fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+        let traces = rlp
+            .iter()
+            .map(|tx| tx.as_list())
+            .collect::<Result<Vec<Vec<FlatTrace>>, DecoderError>>()?;
+        Ok(BlockTraces(traces))
+    }
+}
+
+impl BlockTraces {
+    /// Attaches block and transaction positions to every trace of the block.
+    ///
+    /// `transaction_hashes` must be given in the same order as the block's transactions.
+    pub fn localize(
+        self,
+        block_number: BlockNumber,
+        block_hash: H256,
+        transaction_hashes: &[H256],
+    ) -> Vec<LocalizedTrace>
+    {
+        self.0
+            .into_iter()
+            .zip(transaction_hashes.iter())
+            .enumerate()
+            .flat_map(|(transaction_number, (traces, transaction_hash))| {
+                let transaction_hash = transaction_hash.clone();
+                traces.into_iter().map(move |trace| {
+                    LocalizedTrace {
+                        action: trace.action,
+                        result: trace.result,
+                        subtraces: trace.subtraces,
+                        trace_address: trace.trace_address.into_iter().collect(),
+                        transaction_number,
+                        transaction_hash,
+                        block_number,
+                        block_hash,
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// Trace with information about its position in the chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalizedTrace {
+    /// Type of action performed by a transaction.
+    pub action: Action,
+    /// Result of this action.
+    pub result: Res,
+    /// Number of subtraces.
+    pub subtraces: usize,
+    /// Exact location of trace.
+    ///
+    /// [index in root, index in first CALL, index in second CALL, ...]
+    pub trace_address: Vec<usize>,
+    /// Transaction number within the block.
+    pub transaction_number: usize,
+    /// Signed transaction hash.
+    pub transaction_hash: H256,
+    /// Block number.
+    pub block_number: BlockNumber,
+    /// Block hash.
+    pub block_hash: H256,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use aion_types::Address;
+    use rlp;
+    use vms::CallType;
+    use super::{Action, BlockTraces, Call, Create, CreateResult, Error, FlatTrace, Res};
+
+    #[test]
+    fn block_traces_rlp_roundtrip() {
+        let call = FlatTrace {
+            action: Action::Call(Call {
+                from: Address::from(1),
+                to: Address::from(2),
+                value: 1.into(),
+                gas: 50000.into(),
+                input: vec![1, 2, 3],
+                call_type: CallType::Call,
+            }),
+            result: Res::FailedCall(Error::Rejected("invalid".into())),
+            subtraces: 1,
+            trace_address: VecDeque::new(),
+        };
+        let create = FlatTrace {
+            action: Action::Create(Create {
+                from: Address::from(2),
+                value: 0.into(),
+                gas: 30000.into(),
+                init: vec![0x60, 0x50],
+            }),
+            result: Res::Create(CreateResult {
+                gas_used: 20000.into(),
+                code: vec![0x60],
+                address: Address::from(3),
+            }),
+            subtraces: 0,
+            trace_address: vec![0].into_iter().collect(),
+        };
+        let traces = BlockTraces(vec![vec![call, create], vec![]]);
+
+        let encoded = rlp::encode(&traces);
+        assert_eq!(rlp::decode::<BlockTraces>(&encoded), traces);
+    }
+}
@@ -0,0 +1,227 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Instruction level tracer of the vm.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use parking_lot::Mutex;
+use aion_types::{Address, H128, U256};
+use acore_bytes::Bytes;
+
+/// SSTORE opcode; its first two stack items are the key and the value.
+const SSTORE: u8 = 0x55;
+/// Instructions recorded by a single trace at most.
+pub const MAX_STEPS: usize = 100_000;
+/// Stack, memory and storage bytes recorded by a single trace at most.
+const MAX_RECORDED_BYTES: usize = 64 * 1024 * 1024;
+
+/// What a vm trace records beside the executed instructions and their stack.
+#[derive(Eq, PartialEq, Default, Clone, Copy, Debug)]
+pub struct VMTraceOptions {
+    /// Record the memory of the call frame at each instruction.
+    pub memory: bool,
+    /// Record the storage written by the executing account at each instruction.
+    pub storage: bool,
+}
+
+/// A single instruction executed by the vm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLog {
+    /// Offset of the instruction in the code.
+    pub pc: usize,
+    /// Opcode of the instruction.
+    pub op: u8,
+    /// Gas available before the instruction.
+    pub gas: U256,
+    /// Static gas cost of the instruction.
+    pub gas_cost: U256,
+    /// Call depth, 1 for the transaction itself.
+    pub depth: usize,
+    /// Stack items read by the instruction, top first.
+    pub stack: Vec<H128>,
+    /// Memory of the call frame, empty unless recorded.
+    pub memory: Bytes,
+    /// Storage of the executing account written so far, empty unless recorded.
+    pub storage: BTreeMap<H128, H128>,
+}
+
+#[derive(Debug, Default)]
+struct Steps {
+    logs: Vec<StructLog>,
+    storage: HashMap<Address, BTreeMap<H128, H128>>,
+    recorded_bytes: usize,
+    truncated: bool,
+}
+
+/// Collects the instructions executed by the vm.
+///
+/// Clones share the same log, so the tracer can be handed to nested call frames
+/// and to the thread running the vm.
+/// Instructions past `MAX_STEPS`, or past a bounded amount of recorded data, are dropped
+/// and the trace is marked as truncated.
+#[derive(Debug, Default, Clone)]
+pub struct VMTracer {
+    options: VMTraceOptions,
+    steps: Arc<Mutex<Steps>>,
+}
+
+impl VMTracer {
+    /// Creates a tracer recording what `options` ask for.
+    pub fn new(options: VMTraceOptions) -> Self {
+        VMTracer {
+            options,
+            steps: Arc::new(Mutex::new(Steps::default())),
+        }
+    }
+
+    /// Records an instruction about to be executed on behalf of `address`.
+    pub fn trace_step(
+        &self,
+        address: &Address,
+        depth: usize,
+        pc: usize,
+        op: u8,
+        gas: U256,
+        gas_cost: U256,
+        stack: &[H128],
+        memory: &[u8],
+    )
+    {
+        let mut steps = self.steps.lock();
+        if steps.truncated {
+            return;
+        }
+        let storage = if self.options.storage {
+            let storage = steps
+                .storage
+                .entry(address.clone())
+                .or_insert_with(BTreeMap::new);
+            if op == SSTORE && stack.len() >= 2 {
+                storage.insert(stack[0], stack[1]);
+            }
+            storage.clone()
+        } else {
+            BTreeMap::new()
+        };
+        let memory = if self.options.memory {
+            memory.to_vec()
+        } else {
+            Bytes::new()
+        };
+
+        let recorded_bytes = steps.recorded_bytes
+            + (stack.len() + 2 * storage.len()) * H128::len()
+            + memory.len();
+        if steps.logs.len() >= MAX_STEPS || recorded_bytes > MAX_RECORDED_BYTES {
+            steps.truncated = true;
+            return;
+        }
+        steps.recorded_bytes = recorded_bytes;
+        steps.logs.push(StructLog {
+            pc,
+            op,
+            gas,
+            gas_cost,
+            depth,
+            stack: stack.to_vec(),
+            memory,
+            storage,
+        });
+    }
+
+    /// Takes the instructions recorded so far by all clones of this tracer, and whether
+    /// some were dropped for exceeding the limits of a trace.
+    pub fn drain(&self) -> (Vec<StructLog>, bool) {
+        let mut steps = ::std::mem::replace(&mut *self.steps.lock(), Steps::default());
+        (::std::mem::replace(&mut steps.logs, Vec::new()), steps.truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sstore_updates_storage_of_later_steps() {
+        let tracer = VMTracer::new(VMTraceOptions {
+            memory: false,
+            storage: true,
+        });
+        let shared = tracer.clone();
+        let address = Address::from(1);
+        let (key, value) = (H128::from(1), H128::from(2));
+        shared.trace_step(
+            &address,
+            1,
+            0,
+            SSTORE,
+            100.into(),
+            5000.into(),
+            &[key, value],
+            &[],
+        );
+        shared.trace_step(&address, 1, 1, 0x00, 95.into(), 0.into(), &[], &[]);
+        shared.trace_step(&Address::from(2), 2, 0, 0x00, 50.into(), 0.into(), &[], &[]);
+
+        let (logs, truncated) = tracer.drain();
+        assert_eq!(logs.len(), 3);
+        assert!(!truncated);
+        assert_eq!(logs[0].storage.get(&key), Some(&value));
+        assert_eq!(logs[1].storage.get(&key), Some(&value));
+        assert!(logs[2].storage.is_empty());
+        assert!(tracer.drain().0.is_empty());
+    }
+
+    #[test]
+    fn memory_and_storage_are_only_recorded_when_asked() {
+        let tracer = VMTracer::default();
+        let (key, value) = (H128::from(1), H128::from(2));
+        tracer.trace_step(
+            &Address::from(1),
+            1,
+            0,
+            SSTORE,
+            100.into(),
+            5000.into(),
+            &[key, value],
+            &[1, 2, 3],
+        );
+
+        let (logs, _) = tracer.drain();
+        assert_eq!(logs[0].stack, vec![key, value]);
+        assert!(logs[0].memory.is_empty());
+        assert!(logs[0].storage.is_empty());
+    }
+
+    #[test]
+    fn steps_past_the_limit_are_dropped() {
+        let tracer = VMTracer::default();
+        for pc in 0..MAX_STEPS + 1 {
+            tracer.trace_step(&Address::from(1), 1, pc, 0x5b, 100.into(), 1.into(), &[], &[]);
+        }
+
+        let (logs, truncated) = tracer.drain();
+        assert_eq!(logs.len(), MAX_STEPS);
+        assert!(truncated);
+    }
+}
@@ -113,6 +113,20 @@ pub enum Error {
     InvalidBeaconHash(H256),
     /// beacon hash is banned
     BeaconBanned,
+    /// Transaction's gas price left after the gas usage factor is below threshold.
+    InsufficientEffectiveGasPrice {
+        /// Minimal expected effective gas price
+        minimal: U256,
+        /// Transaction effective gas price
+        got: U256,
+    },
+    /// Transaction nonce is too far ahead of the sender's current nonce.
+    NonceTooFarInFuture {
+        /// Maximal expected distance from the current nonce
+        maximal: U256,
+        /// Transaction distance from the current nonce
+        got: U256,
+    },
 }
 
 impl From<key::Error> for Error {
@@ -200,6 +214,19 @@ impl fmt::Display for Error {
                 )
             }
             BeaconBanned => "Not yet forked, Beacon hash is banned.".into(),
+            InsufficientEffectiveGasPrice {
+                minimal,
+                got,
+            } => {
+                format!(
+                    "Insufficient effective gas price. Min={}, Given={}",
+                    minimal, got
+                )
+            }
+            NonceTooFarInFuture {
+                maximal,
+                got,
+            } => format!("Nonce too far in future. Max={}, Given={}", maximal, got),
         };
 
         f.write_fmt(format_args!("Transaction error ({})", msg))
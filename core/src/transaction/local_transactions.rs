@@ -24,8 +24,11 @@
 use std::collections::HashMap;
 use aion_types::{H256, U256};
 use transaction::error::Error;
+use transaction::transaction::{Condition, PendingTransaction, SignedTransaction,
+UnverifiedTransaction};
 use parking_lot::Mutex;
 use io::IoChannel;
+use rlp::{DecoderError, RlpStream, UntrustedRlp};
 
 /// Status of local transaction.
 /// Can indicate that the transaction is currently part of the queue (`Pending/Future`)
@@ -207,6 +210,48 @@ impl LocalTransactionsList {
     }
 }
 
+/// Encode local transactions waiting in the queue, with their conditions, into the journal
+/// kept across restarts.
+pub fn encode_journal(transactions: &[PendingTransaction]) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(transactions.len());
+    for pending in transactions {
+        stream.begin_list(3);
+        stream.append(&pending.transaction);
+        match pending.condition {
+            None => stream.append(&0u8).append(&0u64),
+            Some(Condition::Number(number)) => stream.append(&1u8).append(&number),
+            Some(Condition::Timestamp(timestamp)) => stream.append(&2u8).append(&timestamp),
+        };
+    }
+    stream.out()
+}
+
+/// Decode the journal of local transactions. Entries which fail to decode or whose signature
+/// can not be recovered are skipped.
+pub fn decode_journal(journal: &[u8]) -> Vec<PendingTransaction> {
+    UntrustedRlp::new(journal)
+        .iter()
+        .filter_map(|entry| {
+            decode_journal_entry(&entry)
+                .map_err(|e| warn!(target: "own_tx", "Skipped journaled transaction: {:?}", e))
+                .ok()
+        })
+        .collect()
+}
+
+fn decode_journal_entry(entry: &UntrustedRlp) -> Result<PendingTransaction, DecoderError> {
+    let transaction: UnverifiedTransaction = entry.val_at(0)?;
+    let transaction = SignedTransaction::new(transaction)
+        .map_err(|_| DecoderError::Custom("invalid transaction signature"))?;
+    let condition = match entry.val_at::<u8>(1)? {
+        0 => None,
+        1 => Some(Condition::Number(entry.val_at(2)?)),
+        2 => Some(Condition::Timestamp(entry.val_at(2)?)),
+        _ => return Err(DecoderError::Custom("unknown transaction condition")),
+    };
+    Ok(PendingTransaction::new(transaction, condition))
+}
+
 /// transaction status message useful in pb
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum TxIoMessage {
@@ -229,7 +274,8 @@ pub enum TxIoMessage {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use transaction::transaction::{Transaction, Action, SignedTransaction};
+    use transaction::transaction::{Transaction, Action, Condition, PendingTransaction,
+SignedTransaction};
     use key::generate_keypair;
     use io::IoService;
     use transaction::DEFAULT_TRANSACTION_TYPE;
@@ -277,6 +323,29 @@ mod tests {
         assert!(list.contains(&15.into()));
     }
 
+    #[test]
+    fn should_restore_journaled_transactions() {
+        // given
+        let transactions = vec![
+            PendingTransaction::new(new_tx(1.into()), None),
+            PendingTransaction::new(new_tx(2.into()), Some(Condition::Number(100))),
+            PendingTransaction::new(new_tx(3.into()), Some(Condition::Timestamp(1_600_000_000))),
+        ];
+
+        // when
+        let journal = encode_journal(&transactions);
+
+        // then
+        let restored = decode_journal(&journal);
+        assert_eq!(restored.len(), transactions.len());
+        for (restored, transaction) in restored.iter().zip(transactions.iter()) {
+            assert_eq!(restored.hash(), transaction.hash());
+            assert_eq!(restored.sender(), transaction.sender());
+            assert_eq!(restored.condition, transaction.condition);
+        }
+        assert!(decode_journal(&[]).is_empty());
+    }
+
     fn new_tx(nonce: U256) -> SignedTransaction {
         let keypair = generate_keypair();
         Transaction::new(
@@ -25,6 +25,7 @@ mod transaction;
 // miner
 pub mod banning_queue;
 pub mod local_transactions;
+pub mod queue_policy;
 pub mod transaction_pool;
 pub mod transaction_queue;
 
@@ -0,0 +1,199 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Ordering and eligibility policies of the transaction queue.
+//!
+//! The queue always orders transactions by penalties, origin and nonce height. A
+//! `TransactionPolicy` assigns senders to priority classes, orders transactions of equal nonce
+//! height and decides which transactions from remote senders are accepted in the first place.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+use aion_types::{Address, U256};
+use transaction::{self, SignedTransaction};
+use transaction::transaction_queue::PrioritizationStrategy;
+
+/// Gas price based attributes of a queued transaction used for ordering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionPrice {
+    /// Gas price of the transaction.
+    pub gas_price: U256,
+    /// Gas (limit) of the transaction.
+    pub gas: U256,
+}
+
+/// Ordering and eligibility rules of the transaction queue.
+pub trait TransactionPolicy: Send + Sync + fmt::Debug {
+    /// Priority class of the sender. Transactions of a higher class are processed before
+    /// transactions of a lower class with the same origin, regardless of their nonce height.
+    fn class(&self, _sender: &Address) -> u8 { 0 }
+
+    /// Compares two transactions of the same class and nonce height.
+    /// `Ordering::Less` means that `a` should be processed earlier.
+    fn compare(&self, a: &TransactionPrice, b: &TransactionPrice) -> Ordering;
+
+    /// Checks if a transaction from a remote sender may enter the queue.
+    /// Per sender limits are enforced by the queue quotas.
+    fn check(&self, _transaction: &SignedTransaction) -> Result<(), transaction::Error> { Ok(()) }
+}
+
+/// Gas factor of `GasFactorAndGasPrice` strategy: linear increment in required gas price
+/// for every 2^15 of gas.
+fn gas_factor(gas: U256, min_gas_price: U256) -> U256 { (gas >> 15) * min_gas_price }
+
+impl TransactionPolicy for PrioritizationStrategy {
+    fn compare(&self, a: &TransactionPrice, b: &TransactionPrice) -> Ordering {
+        match *self {
+            PrioritizationStrategy::GasAndGasPrice => {
+                if a.gas != b.gas {
+                    return a.gas.cmp(&b.gas);
+                }
+            }
+            PrioritizationStrategy::GasFactorAndGasPrice => {
+                // Hard code the minimal gas price here for now
+                // TODO: to pass minimal gas price parameter in or remove this strategy
+                let min_gas_price = U256::from(10_000_000_000u64);
+                // avoiding overflows
+                // (gp1 - g1) > (gp2 - g2) <=>
+                // (gp1 + g2) > (gp2 + g1)
+                let f_a = a.gas_price + gas_factor(b.gas, min_gas_price);
+                let f_b = b.gas_price + gas_factor(a.gas, min_gas_price);
+                if f_a != f_b {
+                    return f_b.cmp(&f_a);
+                }
+            }
+            PrioritizationStrategy::GasPriceOnly => {}
+        }
+
+        // Then compare gas_prices
+        b.gas_price.cmp(&a.gas_price)
+    }
+}
+
+/// Processes transactions of whitelisted senders before any other remote transaction
+/// and keeps them when the queue is full. Orders by gas price otherwise.
+#[derive(Debug)]
+pub struct PrioritySenders {
+    senders: HashSet<Address>,
+}
+
+impl PrioritySenders {
+    /// Creates a policy prioritizing given senders.
+    pub fn new<I: IntoIterator<Item = Address>>(senders: I) -> Self {
+        PrioritySenders {
+            senders: senders.into_iter().collect(),
+        }
+    }
+}
+
+impl TransactionPolicy for PrioritySenders {
+    fn class(&self, sender: &Address) -> u8 {
+        if self.senders.contains(sender) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn compare(&self, a: &TransactionPrice, b: &TransactionPrice) -> Ordering {
+        PrioritizationStrategy::GasPriceOnly.compare(a, b)
+    }
+}
+
+/// Rejects remote transactions whose effective gas price is below the minimum.
+/// EFFECTIVE_PRICE = GAS_PRICE - GAS/2^15 * MIN_PRICE
+///
+/// i.e. heavy transactions have to pay proportionally more per energy to be accepted.
+/// Accepted transactions are ordered by effective price.
+#[derive(Debug)]
+pub struct MinEffectivePrice {
+    min_price: U256,
+}
+
+impl MinEffectivePrice {
+    /// Creates a policy with given minimal effective price per energy.
+    pub fn new(min_price: U256) -> Self {
+        MinEffectivePrice {
+            min_price,
+        }
+    }
+
+    fn effective_price(&self, gas_price: U256, gas: U256) -> U256 {
+        gas_price.saturating_sub(gas_factor(gas, self.min_price))
+    }
+}
+
+impl TransactionPolicy for MinEffectivePrice {
+    fn compare(&self, a: &TransactionPrice, b: &TransactionPrice) -> Ordering {
+        // (gp1 - g1) > (gp2 - g2) <=> (gp1 + g2) > (gp2 + g1)
+        let f_a = a.gas_price + gas_factor(b.gas, self.min_price);
+        let f_b = b.gas_price + gas_factor(a.gas, self.min_price);
+        if f_a != f_b {
+            return f_b.cmp(&f_a);
+        }
+        b.gas_price.cmp(&a.gas_price)
+    }
+
+    fn check(&self, transaction: &SignedTransaction) -> Result<(), transaction::Error> {
+        let effective_price = self.effective_price(transaction.gas_price, transaction.gas);
+        if effective_price < self.min_price {
+            return Err(transaction::Error::InsufficientEffectiveGasPrice {
+                minimal: self.min_price,
+                got: effective_price,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Transaction queue policy selected in the configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueuePolicy {
+    /// Order transactions using given strategy and accept all of them.
+    Strategy(PrioritizationStrategy),
+    /// Prioritize transactions of given senders.
+    PrioritySenders(Vec<Address>),
+    /// Reject transactions below given effective price per energy.
+    MinEffectivePrice(U256),
+}
+
+impl QueuePolicy {
+    /// Creates the policy to be used by the queue.
+    pub fn build(&self) -> Arc<TransactionPolicy> {
+        match *self {
+            QueuePolicy::Strategy(strategy) => Arc::new(strategy),
+            QueuePolicy::PrioritySenders(ref senders) => {
+                Arc::new(PrioritySenders::new(senders.iter().cloned()))
+            }
+            QueuePolicy::MinEffectivePrice(min_price) => {
+                Arc::new(MinEffectivePrice::new(min_price))
+            }
+        }
+    }
+}
+
+impl From<PrioritizationStrategy> for QueuePolicy {
+    fn from(strategy: PrioritizationStrategy) -> Self { QueuePolicy::Strategy(strategy) }
+}
@@ -189,6 +189,58 @@ impl TransactionPool {
         self.transaction_queue.read().local_transactions().clone()
     }
 
+    /// Get a local transaction still waiting in the queue
+    pub fn local_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+        if self.transactions_to_remove.read().contains_key(hash) {
+            return None;
+        }
+        let transaction_queue = self.transaction_queue.read();
+        match transaction_queue.local_transactions().get(hash) {
+            Some(&LocalTransactionStatus::Pending) | Some(&LocalTransactionStatus::Future) => {
+                transaction_queue.find(hash)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get local transactions waiting to enter or still waiting in the queue
+    pub fn local_pending_transactions(&self) -> Vec<PendingTransaction> {
+        let mut transactions: Vec<PendingTransaction> = self
+            .waiting_transactions
+            .read()
+            .iter()
+            .filter(|transaction| *transaction.origin() == TransactionOrigin::Local)
+            .map(|transaction| {
+                PendingTransaction::new(
+                    transaction.transaction().clone(),
+                    transaction.condition().clone(),
+                )
+            })
+            .collect();
+        let hashes: Vec<H256> = self
+            .transaction_queue
+            .read()
+            .local_transactions()
+            .keys()
+            .cloned()
+            .collect();
+        transactions.extend(
+            hashes
+                .iter()
+                .filter_map(|hash| self.local_transaction(hash)),
+        );
+        transactions
+    }
+
+    /// Cancel a local transaction still waiting in the queue
+    pub fn cancel_transaction(&self, hash: &H256) -> bool {
+        if self.local_transaction(hash).is_none() {
+            return false;
+        }
+        self.remove_transaction(*hash, RemovalReason::Canceled);
+        true
+    }
+
     /// Get last nonce of an address in the queue
     pub fn last_nonce(&self, address: &Address) -> Option<U256> {
         self.transaction_queue.read().last_nonce(address)
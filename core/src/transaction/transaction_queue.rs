@@ -108,6 +108,7 @@ use std::cmp::Ordering;
 use std::cmp;
 use std::collections::{HashSet, HashMap, BTreeSet, BTreeMap};
 use std::ops::Deref;
+use std::sync::Arc;
 use std::thread;
 use std::time;
 
@@ -118,8 +119,9 @@ use heapsize::HeapSizeOf;
 use transaction::local_transactions::{
     LocalTransactionsList, Status as LocalTransactionStatus, TxIoMessage,
 };
+use transaction::queue_policy::{TransactionPolicy, TransactionPrice};
 use table::Table;
-use transaction::{self, SignedTransaction, PendingTransaction};
+use transaction::{self, Action, SignedTransaction, PendingTransaction};
 
 type BlockNumber = u64;
 
@@ -167,21 +169,19 @@ struct TransactionOrder {
     /// Gas Price of the transaction.
     /// Low gas price = Low priority (processed later)
     gas_price: U256,
-    /// Gas usage priority factor. Usage depends on strategy.
-    /// Represents the linear increment in required gas price for heavy transactions.
-    ///
-    /// High gas limit + Low gas price = Very Low priority
-    /// High gas limit + High gas price = High priority
-    gas_factor: U256,
-    /// Gas (limit) of the transaction. Usage depends on strategy.
-    /// Low gas limit = High priority (processed earlier)
+    /// Gas (limit) of the transaction. Usage depends on policy.
     gas: U256,
+    /// Priority class of the sender given by the policy.
+    /// High class = High priority (processed earlier)
+    class: u8,
     /// Heap usage of this transaction.
     memory_usage: usize,
-    /// Transaction ordering strategy
-    strategy: PrioritizationStrategy,
+    /// Transaction ordering policy
+    policy: Arc<TransactionPolicy>,
     /// Hash to identify associated transaction
     hash: H256,
+    /// Address called by the transaction
+    recipient: Option<Address>,
     /// Incremental id assigned when transaction is inserted to the queue.
     insertion_id: u64,
     /// Origin of the transaction
@@ -194,21 +194,18 @@ impl TransactionOrder {
     fn for_transaction(
         transaction: &VerifiedTransaction,
         base_nonce: U256,
-        strategy: PrioritizationStrategy,
+        policy: Arc<TransactionPolicy>,
     ) -> Self
     {
-        // Hard code the minimal gas price here for now
-        // TODO: to pass minimal gas price parameter in or remove this strategy
-        // let factor = (transaction.transaction().gas >> 15) * min_gas_price;
-        let factor = (transaction.transaction().gas >> 15) * U256::from(10_000_000_000u64);
         TransactionOrder {
             nonce_height: transaction.nonce().clone() - base_nonce,
             gas_price: transaction.transaction().gas_price,
-            gas_factor: factor,
             gas: transaction.transaction().gas,
+            class: policy.class(transaction.sender()),
             memory_usage: transaction.transaction().heap_size_of_children(),
-            strategy: strategy,
+            policy: policy,
             hash: transaction.hash().clone(),
+            recipient: transaction.recipient(),
             insertion_id: transaction.insertion_id().clone(),
             origin: transaction.origin().clone(),
             penalties: 0,
@@ -224,6 +221,13 @@ impl TransactionOrder {
         self.penalties = self.penalties.saturating_add(1);
         self
     }
+
+    fn price(&self) -> TransactionPrice {
+        TransactionPrice {
+            gas_price: self.gas_price,
+            gas: self.gas,
+        }
+    }
 }
 
 impl Eq for TransactionOrder {}
@@ -246,33 +250,20 @@ impl Ord for TransactionOrder {
             return self.origin.cmp(&b.origin);
         }
 
+        // Transactions of a higher class go first
+        if self.class != b.class {
+            return b.class.cmp(&self.class);
+        }
+
         // Check nonce_height
         if self.nonce_height != b.nonce_height {
             return self.nonce_height.cmp(&b.nonce_height);
         }
 
-        match self.strategy {
-            PrioritizationStrategy::GasAndGasPrice => {
-                if self.gas != b.gas {
-                    return self.gas.cmp(&b.gas);
-                }
-            }
-            PrioritizationStrategy::GasFactorAndGasPrice => {
-                // avoiding overflows
-                // (gp1 - g1) > (gp2 - g2) <=>
-                // (gp1 + g2) > (gp2 + g1)
-                let f_a = self.gas_price + b.gas_factor;
-                let f_b = b.gas_price + self.gas_factor;
-                if f_a != f_b {
-                    return f_b.cmp(&f_a);
-                }
-            }
-            PrioritizationStrategy::GasPriceOnly => {}
-        }
-
-        // Then compare gas_prices
-        if self.gas_price != b.gas_price {
-            return b.gas_price.cmp(&self.gas_price);
+        // Then let the policy compare prices
+        let by_price = self.policy.compare(&self.price(), &b.price());
+        if by_price != Ordering::Equal {
+            return by_price;
         }
 
         // Compare insertion_id. Insertion id indicates which transaction is
@@ -334,6 +325,13 @@ impl VerifiedTransaction {
 
     pub fn gas_price(&self) -> &U256 { &self.transaction.gas_price }
 
+    pub fn recipient(&self) -> Option<Address> {
+        match self.transaction.action {
+            Action::Call(ref address) => Some(*address),
+            Action::Create => None,
+        }
+    }
+
     pub fn insertion_id(&self) -> u64 { self.insertion_id }
 
     pub fn condition(&self) -> &Option<transaction::Condition> { &self.condition }
@@ -389,6 +387,41 @@ impl Deref for GasPriceQueue {
     fn deref(&self) -> &Self::Target { &self.backing }
 }
 
+#[derive(Debug, Default)]
+struct RecipientCounts {
+    backing: HashMap<Address, usize>,
+}
+
+impl RecipientCounts {
+    /// Count one more transaction calling given recipient.
+    fn insert(&mut self, recipient: &Option<Address>) {
+        if let Some(ref recipient) = *recipient {
+            *self.backing.entry(*recipient).or_insert(0) += 1;
+        }
+    }
+
+    /// Count one less transaction calling given recipient.
+    fn remove(&mut self, recipient: &Option<Address>) {
+        if let Some(ref recipient) = *recipient {
+            let is_empty = match self.backing.get_mut(recipient) {
+                Some(count) => {
+                    *count -= 1;
+                    *count == 0
+                }
+                None => false,
+            };
+            if is_empty {
+                self.backing.remove(recipient);
+            }
+        }
+    }
+
+    /// Number of transactions calling given recipient.
+    fn count(&self, recipient: &Address) -> usize {
+        self.backing.get(recipient).cloned().unwrap_or(0)
+    }
+}
+
 /// Holds transactions accessible by (address, nonce) and by priority
 ///
 /// `TransactionSet` keeps number of entries below limit, but it doesn't
@@ -398,8 +431,11 @@ struct TransactionSet {
     by_priority: BTreeSet<TransactionOrder>,
     by_address: Table<Address, U256, TransactionOrder>,
     by_gas_price: GasPriceQueue,
+    by_recipient: RecipientCounts,
     memory_limit: usize,
     memory_usage: usize,
+    sender_limit: usize,
+    recipient_limit: usize,
 }
 
 impl TransactionSet {
@@ -420,6 +456,7 @@ impl TransactionSet {
         debug!(target: "rpc_tx", "{:?} tx finished ordering [{:?}]", thread::current().id(), time::Instant::now());
         let order_hash = order.hash.clone();
         let order_gas_price = order.gas_price.clone();
+        let order_recipient = order.recipient.clone();
         let memory_usage = order.memory_usage.clone();
         let by_address_replaced = self.by_address.insert(sender, nonce, order);
         // If transaction was replaced remove it from priority queue
@@ -435,9 +472,11 @@ impl TransactionSet {
                 "hash is in `by_address`; all transactions' gas_prices in `by_address` must be in \
                  `by_gas_limit`; qed"
             );
+            self.by_recipient.remove(&old_order.recipient);
             self.memory_usage -= old_order.memory_usage;
         }
         self.by_gas_price.insert(order_gas_price, order_hash);
+        self.by_recipient.insert(&order_recipient);
         self.memory_usage += memory_usage;
         assert_eq!(self.by_priority.len(), self.by_address.len());
         assert_eq!(
@@ -488,32 +527,99 @@ impl TransactionSet {
             }
         }
 
-        Some(
-            to_drop
+        let mut removed = HashMap::new();
+        self.drop_all(to_drop, &mut removed, by_hash, local);
+        Some(removed)
+    }
+
+    /// Remove the lowest priority transactions of given sender or calling given recipient if
+    /// there are more of them than allowed by per sender and per recipient limits.
+    ///
+    /// The recipient quota of a transaction which has just been inserted can only be exceeded
+    /// by one, while a sender may exceed its quota by a whole chain moved to `future`.
+    /// Returns addresses and lowest nonces of transactions removed because of limit.
+    fn enforce_quotas(
+        &mut self,
+        sender: &Address,
+        recipient: &Option<Address>,
+        by_hash: &mut HashMap<H256, VerifiedTransaction>,
+        local: &mut LocalTransactionsList,
+    ) -> Option<HashMap<Address, U256>>
+    {
+        let evictable = |order: &TransactionOrder| {
+            !order.origin.is_local() && order.origin != TransactionOrigin::RetractedBlock
+        };
+        let mut removed = HashMap::new();
+
+        let sender_count = self.by_address.row(sender).map_or(0, |by_nonce| by_nonce.len());
+        if sender_count > self.sender_limit {
+            let mut nonces = self.by_address.row(sender).map_or(Vec::new(), |by_nonce| {
+                by_nonce
+                    .iter()
+                    .filter(|&(_, order)| evictable(order))
+                    .map(|(nonce, _)| *nonce)
+                    .collect::<Vec<_>>()
+            });
+            // highest nonces have the lowest priority
+            nonces.sort_by(|a, b| b.cmp(a));
+            let lowest = nonces
                 .into_iter()
-                .fold(HashMap::new(), |mut removed, (sender, nonce)| {
-                    let order = self.drop(&sender, &nonce).expect(
-                        "Transaction has just been found in `by_priority`; so it is in \
-                         `by_address` also.",
-                    );
-                    trace!(target: "txqueue", "Dropped out of limit transaction: {:?}", order.hash);
+                .take(sender_count - self.sender_limit)
+                .map(|nonce| (*sender, nonce))
+                .collect();
+            self.drop_all(lowest, &mut removed, by_hash, local);
+        }
 
-                    let order = by_hash.remove(&order.hash).expect(
-                        "hash is in `by_priority`; all hashes in `by_priority` must be in \
-                         `by_hash`; qed",
-                    );
+        if let Some(ref recipient) = *recipient {
+            if self.by_recipient.count(recipient) > self.recipient_limit {
+                let lowest = self
+                    .by_priority
+                    .iter()
+                    .rev()
+                    .find(|order| order.recipient.as_ref() == Some(recipient) && evictable(*order))
+                    .map(|order| {
+                        let transaction = by_hash.get(&order.hash).expect(
+                            "All transactions in `self.by_priority` are kept in sync with \
+                             `by_hash`.",
+                        );
+                        (transaction.sender().clone(), transaction.nonce().clone())
+                    });
+                self.drop_all(lowest.into_iter().collect(), &mut removed, by_hash, local);
+            }
+        }
 
-                    if order.origin.is_local() {
-                        local.mark_dropped(order.transaction.hash().clone());
-                    }
+        Some(removed)
+    }
 
-                    let min = removed
-                        .get(&sender)
-                        .map_or(nonce, |val| cmp::min(*val, nonce));
-                    removed.insert(sender, min);
-                    removed
-                }),
-        )
+    /// Drop given transactions from this set and remove associated `VerifiedTransaction`.
+    /// Keeps addresses and lowest nonces of dropped transactions in `removed`.
+    fn drop_all(
+        &mut self,
+        to_drop: Vec<(Address, U256)>,
+        removed: &mut HashMap<Address, U256>,
+        by_hash: &mut HashMap<H256, VerifiedTransaction>,
+        local: &mut LocalTransactionsList,
+    )
+    {
+        for (sender, nonce) in to_drop {
+            let order = self.drop(&sender, &nonce).expect(
+                "Transaction has just been found in `by_priority`; so it is in `by_address` also.",
+            );
+            trace!(target: "txqueue", "Dropped out of limit transaction: {:?}", order.hash);
+
+            let order = by_hash.remove(&order.hash).expect(
+                "hash is in `by_priority`; all hashes in `by_priority` must be in `by_hash`; qed",
+            );
+
+            if order.origin.is_local() {
+                local.mark_dropped(order.transaction.hash().clone());
+            }
+
+            let min = removed
+                .get(&sender)
+                .map_or(nonce, |val| cmp::min(*val, nonce));
+            removed.insert(sender, min);
+        }
     }
 
     /// Drop transaction from this set (remove from `by_priority` and `by_address`)
@@ -530,6 +636,7 @@ impl TransactionSet {
                 "hash is in `by_address`; all transactions' gas_prices in `by_address` must be in \
                  `by_priority`; qed"
             );
+            self.by_recipient.remove(&tx_order.recipient);
             self.memory_usage -= tx_order.memory_usage;
             assert_eq!(self.by_priority.len(), self.by_address.len());
             assert_eq!(
@@ -557,6 +664,7 @@ impl TransactionSet {
         self.by_priority.clear();
         self.by_address.clear();
         self.by_gas_price.backing.clear();
+        self.by_recipient.backing.clear();
         self.memory_usage = 0;
     }
 }
@@ -617,6 +725,32 @@ pub enum RemovalReason {
     InvalidBeaconHash(H256),
 }
 
+/// Limits on the number of transactions a single sender or recipient may keep in the queue.
+/// Lowest priority transactions are dropped first when a quota is exceeded.
+/// Local transactions are neither limited nor dropped.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct QueueQuotas {
+    /// Maximal number of pending transactions per sender.
+    pub pending_per_sender: usize,
+    /// Maximal number of future transactions per sender.
+    pub future_per_sender: usize,
+    /// Maximal difference between transaction nonce and sender's state nonce.
+    pub max_nonce_distance: U256,
+    /// Maximal number of pending and of future transactions calling the same address.
+    pub per_recipient: usize,
+}
+
+impl Default for QueueQuotas {
+    fn default() -> Self {
+        QueueQuotas {
+            pending_per_sender: usize::max_value(),
+            future_per_sender: usize::max_value(),
+            max_nonce_distance: U256::max_value(),
+            per_recipient: usize::max_value(),
+        }
+    }
+}
+
 /// Point in time when transaction was inserted.
 pub type QueuingInstant = BlockNumber;
 const DEFAULT_QUEUING_PERIOD: BlockNumber = 128;
@@ -629,12 +763,14 @@ pub trait TransactionDetailsProvider {
 
 /// `TransactionQueue` implementation
 pub struct TransactionQueue {
-    /// Prioritization strategy for this queue
-    strategy: PrioritizationStrategy,
+    /// Ordering and eligibility policy for this queue
+    policy: Arc<TransactionPolicy>,
     /// Maximal time transaction may occupy the queue.
     /// When we reach `max_time_in_queue / 2^3` we re-validate
     /// account balance.
     max_time_in_queue: QueuingInstant,
+    /// Maximal difference between nonce of a remote transaction and its sender's state nonce.
+    max_nonce_distance: U256,
     /// Priority queue for transactions that can go to block
     current: TransactionSet,
     /// Priority queue for transactions that has been received but are not yet valid to go to block
@@ -650,7 +786,7 @@ pub struct TransactionQueue {
 impl Default for TransactionQueue {
     fn default() -> Self {
         TransactionQueue::new(
-            PrioritizationStrategy::GasPriceOnly,
+            Arc::new(PrioritizationStrategy::GasPriceOnly),
             Mutex::new(IoChannel::disconnected()),
         )
     }
@@ -659,16 +795,16 @@ impl Default for TransactionQueue {
 impl TransactionQueue {
     /// Creates new instance of this Queue
     pub fn new(
-        strategy: PrioritizationStrategy,
+        policy: Arc<TransactionPolicy>,
         io_channel: Mutex<IoChannel<TxIoMessage>>,
     ) -> Self
     {
-        Self::with_limits(strategy, usize::max_value(), io_channel)
+        Self::with_limits(policy, usize::max_value(), io_channel)
     }
 
     /// Create new instance of this Queue with specified limits
     pub fn with_limits(
-        strategy: PrioritizationStrategy,
+        policy: Arc<TransactionPolicy>,
         memory_limit: usize,
         io_channel: Mutex<IoChannel<TxIoMessage>>,
     ) -> Self
@@ -677,21 +813,28 @@ impl TransactionQueue {
             by_priority: BTreeSet::new(),
             by_address: Table::new(),
             by_gas_price: Default::default(),
+            by_recipient: Default::default(),
             memory_limit,
             memory_usage: 0,
+            sender_limit: usize::max_value(),
+            recipient_limit: usize::max_value(),
         };
 
         let future = TransactionSet {
             by_priority: BTreeSet::new(),
             by_address: Table::new(),
             by_gas_price: Default::default(),
+            by_recipient: Default::default(),
             memory_limit,
             memory_usage: 0,
+            sender_limit: usize::max_value(),
+            recipient_limit: usize::max_value(),
         };
 
         TransactionQueue {
-            strategy,
+            policy,
             max_time_in_queue: DEFAULT_QUEUING_PERIOD,
+            max_nonce_distance: U256::max_value(),
             current,
             future,
             by_hash: HashMap::new(),
@@ -700,6 +843,16 @@ impl TransactionQueue {
         }
     }
 
+    /// Sets per sender and per recipient quotas of this queue.
+    /// Transactions above the quotas are not removed until next import.
+    pub fn set_quotas(&mut self, quotas: QueueQuotas) {
+        self.current.sender_limit = quotas.pending_per_sender;
+        self.future.sender_limit = quotas.future_per_sender;
+        self.current.recipient_limit = quotas.per_recipient;
+        self.future.recipient_limit = quotas.per_recipient;
+        self.max_nonce_distance = quotas.max_nonce_distance;
+    }
+
     /// Returns current status for this queue
     pub fn status(&self) -> TransactionQueueStatus {
         TransactionQueueStatus {
@@ -1245,6 +1398,7 @@ impl TransactionQueue {
                 self.future
                     .by_gas_price
                     .remove(&order.gas_price, &order.hash);
+                self.future.by_recipient.remove(&order.recipient);
                 self.future.memory_usage -= order.memory_usage;
                 // Put to current
                 let order = order.update_height(current_nonce, first_nonce);
@@ -1298,6 +1452,7 @@ impl TransactionQueue {
         let address = transaction.sender().clone();
         let nonce = transaction.nonce().clone();
         let hash = transaction.hash().clone();
+        let recipient = transaction.recipient();
 
         // The transaction might be old, let's check that.
         // This has to be the first test, otherwise calculating
@@ -1321,6 +1476,20 @@ impl TransactionQueue {
 
         if transaction.origin().is_local() {
             self.mark_transactions_local(&address);
+        } else {
+            // Don't let remote senders fill the future with far away nonces
+            if nonce - state_nonce > self.max_nonce_distance {
+                trace!(target: "txqueue", "Dropping transaction too far in future: {:?} (nonce: {} > {} + {})", hash, nonce, state_nonce, self.max_nonce_distance);
+                return Err(transaction::Error::NonceTooFarInFuture {
+                    maximal: self.max_nonce_distance,
+                    got: nonce - state_nonce,
+                });
+            }
+            // Let the policy decide if remote transaction is eligible
+            if let Err(err) = self.policy.check(transaction.transaction()) {
+                trace!(target: "txqueue", "Dropping transaction not allowed by policy: {:?} ({})", hash, err);
+                return Err(err);
+            }
         }
 
         // Future transaction
@@ -1330,11 +1499,19 @@ impl TransactionQueue {
             check_too_cheap(Self::replace_transaction(
                 transaction,
                 state_nonce,
-                self.strategy.clone(),
+                self.policy.clone(),
                 &mut self.future,
                 &mut self.by_hash,
                 &mut self.local_transactions,
             ))?;
+            // Enforce quotas in Future
+            let removed = self.future.enforce_quotas(
+                &address,
+                &recipient,
+                &mut self.by_hash,
+                &mut self.local_transactions,
+            );
+            check_if_removed(&address, &nonce, removed)?;
             // Enforce limit in Future
             let removed = self
                 .future
@@ -1355,7 +1532,7 @@ impl TransactionQueue {
         check_too_cheap(Self::replace_transaction(
             transaction,
             state_nonce,
-            self.strategy.clone(),
+            self.policy.clone(),
             &mut self.current,
             &mut self.by_hash,
             &mut self.local_transactions,
@@ -1366,6 +1543,16 @@ impl TransactionQueue {
             .get(&address)
             .map_or(nonce, |n| cmp::max(nonce, *n));
         self.last_nonces.insert(address, new_max);
+        // Enforce quotas
+        let removed = self.current.enforce_quotas(
+            &address,
+            &recipient,
+            &mut self.by_hash,
+            &mut self.local_transactions,
+        );
+        self.update_last_nonces(&removed);
+        self.move_gapped_to_future(&removed);
+        check_if_removed(&address, &nonce, removed)?;
         // Also enforce the limit
         let removed = self
             .current
@@ -1375,6 +1562,11 @@ impl TransactionQueue {
         // Trigger error if the transaction we are importing was removed.
         check_if_removed(&address, &nonce, removed)?;
         debug!(target: "rpc_tx", "{:?} tx finished checking limit [{:?}]", thread::current().id(), time::Instant::now());
+        if self.future.by_address.get(&address, &nonce).is_some() {
+            debug!(target: "txqueue", "Imported transaction to future: {:?}", hash);
+            debug!(target: "txqueue", "status: {:?}", self.status());
+            return Ok(transaction::ImportResult::Future);
+        }
         debug!(target: "txqueue", "Imported transaction to current: {:?}", hash);
         debug!(target: "txqueue", "status: {:?}", self.status());
         Ok(transaction::ImportResult::Current)
@@ -1393,6 +1585,31 @@ impl TransactionQueue {
         }
     }
 
+    /// Moves transactions queued after the ones removed from `current` back to `future`,
+    /// so that no sender is left with a nonce gap in `current`. The moved transactions are
+    /// subject to the quotas of `future`.
+    fn move_gapped_to_future(&mut self, removed_min_nonces: &Option<HashMap<Address, U256>>) {
+        if let Some(ref min_nonces) = *removed_min_nonces {
+            for (sender, nonce) in min_nonces.iter() {
+                let base_nonce = self.current.by_address.row(sender).and_then(|by_nonce| {
+                    by_nonce
+                        .iter()
+                        .find(|&(k, _)| k > nonce)
+                        .map(|(k, order)| *k - order.nonce_height)
+                });
+                if let Some(base_nonce) = base_nonce {
+                    self.cull_internal(*sender, base_nonce);
+                    self.future.enforce_quotas(
+                        sender,
+                        &None,
+                        &mut self.by_hash,
+                        &mut self.local_transactions,
+                    );
+                }
+            }
+        }
+    }
+
     /// Replaces transaction in given set (could be `future` or `current`).
     ///
     /// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price` is higher.
@@ -1403,14 +1620,14 @@ impl TransactionQueue {
     fn replace_transaction(
         tx: VerifiedTransaction,
         base_nonce: U256,
-        strategy: PrioritizationStrategy,
+        policy: Arc<TransactionPolicy>,
         set: &mut TransactionSet,
         by_hash: &mut HashMap<H256, VerifiedTransaction>,
         local: &mut LocalTransactionsList,
     ) -> bool
     {
         debug!(target: "rpc_tx", "{:?} tx begins insert/replace [{:?}]", thread::current().id(), time::Instant::now());
-        let order = TransactionOrder::for_transaction(&tx, base_nonce, strategy);
+        let order = TransactionOrder::for_transaction(&tx, base_nonce, policy);
         let hash = tx.hash().clone();
         let address = tx.sender().clone();
         let nonce = tx.nonce().clone();
@@ -1504,9 +1721,10 @@ fn check_if_removed(
 pub mod test {
     use aion_types::{U256, Address};
     use super::*;
-    use key::{generate_keypair};
+    use key::{generate_keypair, Ed25519KeyPair};
     use rustc_hex::FromHex;
     use transaction::{Transaction, DEFAULT_TRANSACTION_TYPE};
+    use transaction::queue_policy::{MinEffectivePrice, PrioritySenders};
     use io::IoService;
 
     pub struct DummyTransactionDetailsProvider {
@@ -1712,14 +1930,14 @@ pub mod test {
     }
 
     fn transaction_order(tx: &VerifiedTransaction, nonce: U256) -> TransactionOrder {
-        TransactionOrder::for_transaction(tx, nonce, PrioritizationStrategy::GasPriceOnly)
+        TransactionOrder::for_transaction(tx, nonce, Arc::new(PrioritizationStrategy::GasPriceOnly))
     }
 
     #[test]
     fn should_return_correct_nonces_when_dropped_because_of_limit() {
         // given
         let mut txq = TransactionQueue::with_limits(
-            PrioritizationStrategy::GasPriceOnly,
+            Arc::new(PrioritizationStrategy::GasPriceOnly),
             16,
             Mutex::new(IoService::<TxIoMessage>::start().unwrap().channel()),
         );
@@ -1754,8 +1972,11 @@ pub mod test {
             by_priority: BTreeSet::new(),
             by_address: Table::new(),
             by_gas_price: Default::default(),
+            by_recipient: Default::default(),
             memory_limit: 8,
             memory_usage: 0,
+            sender_limit: usize::max_value(),
+            recipient_limit: usize::max_value(),
         };
         let (tx1, tx2) = new_tx_pair_default(1.into(), 0.into(), TransactionOrigin::External);
         let mut by_hash = {
@@ -1791,8 +2012,11 @@ pub mod test {
             by_priority: BTreeSet::new(),
             by_address: Table::new(),
             by_gas_price: Default::default(),
+            by_recipient: Default::default(),
             memory_limit: 0,
             memory_usage: 0,
+            sender_limit: usize::max_value(),
+            recipient_limit: usize::max_value(),
         };
         // Create two transactions with same nonce
         // (same hash)
@@ -1836,18 +2060,27 @@ pub mod test {
             by_priority: BTreeSet::new(),
             by_address: Table::new(),
             by_gas_price: Default::default(),
+            by_recipient: Default::default(),
             memory_limit: 0,
             memory_usage: 0,
+            sender_limit: usize::max_value(),
+            recipient_limit: usize::max_value(),
         };
         let tx = new_tx_default(TransactionOrigin::External);
-        let order1 =
-            TransactionOrder::for_transaction(&tx, 0.into(), PrioritizationStrategy::GasPriceOnly);
+        let order1 = TransactionOrder::for_transaction(
+            &tx,
+            0.into(),
+            Arc::new(PrioritizationStrategy::GasPriceOnly),
+        );
         assert!(
             set.insert(tx.sender().clone(), tx.nonce().clone(), order1)
                 .is_none()
         );
-        let order2 =
-            TransactionOrder::for_transaction(&tx, 0.into(), PrioritizationStrategy::GasPriceOnly);
+        let order2 = TransactionOrder::for_transaction(
+            &tx,
+            0.into(),
+            Arc::new(PrioritizationStrategy::GasPriceOnly),
+        );
         assert!(
             set.insert(tx.sender().clone(), tx.nonce().clone(), order2)
                 .is_some()
@@ -1926,7 +2159,7 @@ pub mod test {
     fn should_order_by_gas() {
         // given
         let mut txq = TransactionQueue::new(
-            PrioritizationStrategy::GasAndGasPrice,
+            Arc::new(PrioritizationStrategy::GasAndGasPrice),
             Mutex::new(IoService::<TxIoMessage>::start().unwrap().channel()),
         );
         let tx1 = new_tx_with_gas(50000.into(), 40.into(), TransactionOrigin::External);
@@ -1962,7 +2195,7 @@ pub mod test {
     fn should_order_by_gas_factor() {
         // given
         let mut txq = TransactionQueue::new(
-            PrioritizationStrategy::GasFactorAndGasPrice,
+            Arc::new(PrioritizationStrategy::GasFactorAndGasPrice),
             Mutex::new(IoService::<TxIoMessage>::start().unwrap().channel()),
         );
 
@@ -1995,6 +2228,303 @@ pub mod test {
         assert_eq!(txq.top_transactions()[3].gas_price, 40.into());
     }
 
+    #[test]
+    fn should_order_priority_senders_first() {
+        // given
+        let tx1 = new_tx(default_nonce(), 1.into(), TransactionOrigin::External);
+        let tx2 = new_tx(default_nonce(), 10.into(), TransactionOrigin::External);
+        let tx3 = new_tx(default_nonce(), 5.into(), TransactionOrigin::External);
+        let mut txq = TransactionQueue::new(
+            Arc::new(PrioritySenders::new(vec![tx1.sender().clone()])),
+            Mutex::new(IoService::<TxIoMessage>::start().unwrap().channel()),
+        );
+        let fetch_account = |_: &Address| default_account_details();
+
+        // when
+        txq.add(tx1, &fetch_account).unwrap();
+        txq.add(tx2, &fetch_account).unwrap();
+        txq.add(tx3, &fetch_account).unwrap();
+
+        // then
+        let top = txq.top_transactions();
+        assert_eq!(top[0].gas_price, 1.into());
+        assert_eq!(top[1].gas_price, 10.into());
+        assert_eq!(top[2].gas_price, 5.into());
+    }
+
+    #[test]
+    fn should_reject_transactions_below_min_effective_price() {
+        // given
+        let mut txq = TransactionQueue::new(
+            Arc::new(MinEffectivePrice::new(10.into())),
+            Mutex::new(IoService::<TxIoMessage>::start().unwrap().channel()),
+        );
+        // effective price = gas_price - gas / 2^15 * 10
+        let tx1 = new_tx_with_gas(30_000.into(), 10.into(), TransactionOrigin::External);
+        let tx2 = new_tx_with_gas(150_000.into(), 45.into(), TransactionOrigin::External);
+        let tx3 = new_tx_with_gas(150_000.into(), 55.into(), TransactionOrigin::External);
+        let tx4 = new_tx_with_gas(150_000.into(), 45.into(), TransactionOrigin::Local);
+        let fetch_account = |_: &Address| default_account_details();
+
+        // when
+        let res1 = txq.add(tx1, &fetch_account);
+        let res2 = txq.add(tx2, &fetch_account);
+        let res3 = txq.add(tx3, &fetch_account);
+        let res4 = txq.add(tx4, &fetch_account);
+
+        // then
+        assert_eq!(res1.unwrap(), transaction::ImportResult::Current);
+        assert_eq!(
+            unwrap_tx_err(res2),
+            transaction::Error::InsufficientEffectiveGasPrice {
+                minimal: 10.into(),
+                got: 5.into(),
+            }
+        );
+        assert_eq!(res3.unwrap(), transaction::ImportResult::Current);
+        assert_eq!(res4.unwrap(), transaction::ImportResult::Current);
+        assert_eq!(txq.status().pending, 3);
+    }
+
+    #[test]
+    fn should_limit_pending_transactions_per_sender() {
+        // given
+        let mut txq = TransactionQueue::default();
+        txq.set_quotas(QueueQuotas {
+            pending_per_sender: 2,
+            ..Default::default()
+        });
+        let keypair = generate_keypair();
+        let tx = |nonce: U256, origin: TransactionOrigin| {
+            let signed = new_unsigned_tx(nonce, default_gas_val(), default_gas_price())
+                .sign(keypair.secret());
+            VerifiedTransaction::new(signed, origin, None, 0, 0)
+        };
+        let fetch_account = |_: &Address| default_account_details();
+        txq.add(tx(default_nonce(), TransactionOrigin::External), &fetch_account)
+            .unwrap();
+        txq.add(tx(default_nonce() + 1, TransactionOrigin::External), &fetch_account)
+            .unwrap();
+
+        // when
+        let res1 = txq.add(tx(default_nonce() + 2, TransactionOrigin::External), &fetch_account);
+        let res2 = txq.add(tx(default_nonce() + 2, TransactionOrigin::Local), &fetch_account);
+
+        // then
+        assert_eq!(unwrap_tx_err(res1), transaction::Error::LimitReached);
+        assert_eq!(res2.unwrap(), transaction::ImportResult::Current);
+        assert_eq!(txq.status().pending, 3);
+    }
+
+    #[test]
+    fn should_drop_highest_future_nonce_above_sender_quota() {
+        // given
+        let mut txq = TransactionQueue::default();
+        txq.set_quotas(QueueQuotas {
+            future_per_sender: 2,
+            ..Default::default()
+        });
+        let keypair = generate_keypair();
+        let tx = |nonce: U256| {
+            let signed = new_unsigned_tx(nonce, default_gas_val(), default_gas_price())
+                .sign(keypair.secret());
+            VerifiedTransaction::new(signed, TransactionOrigin::External, None, 0, 0)
+        };
+        let fetch_account = |_: &Address| default_account_details();
+        txq.add(tx(default_nonce() + 2), &fetch_account).unwrap();
+        txq.add(tx(default_nonce() + 3), &fetch_account).unwrap();
+
+        // when
+        let res1 = txq.add(tx(default_nonce() + 4), &fetch_account);
+        let res2 = txq.add(tx(default_nonce() + 1), &fetch_account);
+
+        // then
+        assert_eq!(unwrap_tx_err(res1), transaction::Error::LimitReached);
+        assert_eq!(res2.unwrap(), transaction::ImportResult::Future);
+        let nonces = txq
+            .future_transactions()
+            .into_iter()
+            .map(|tx| tx.nonce)
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            nonces,
+            vec![default_nonce() + 1, default_nonce() + 2]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn should_reject_transactions_too_far_in_future() {
+        // given
+        let mut txq = TransactionQueue::default();
+        txq.set_quotas(QueueQuotas {
+            max_nonce_distance: 2.into(),
+            ..Default::default()
+        });
+        let tx1 = new_tx(default_nonce() + 3, default_gas_price(), TransactionOrigin::External);
+        let tx2 = new_tx(default_nonce() + 2, default_gas_price(), TransactionOrigin::External);
+        let tx3 = new_tx(default_nonce() + 3, default_gas_price(), TransactionOrigin::Local);
+        let fetch_account = |_: &Address| default_account_details();
+
+        // when
+        let res1 = txq.add(tx1, &fetch_account);
+        let res2 = txq.add(tx2, &fetch_account);
+        let res3 = txq.add(tx3, &fetch_account);
+
+        // then
+        assert_eq!(
+            unwrap_tx_err(res1),
+            transaction::Error::NonceTooFarInFuture {
+                maximal: 2.into(),
+                got: 3.into(),
+            }
+        );
+        assert_eq!(res2.unwrap(), transaction::ImportResult::Future);
+        assert_eq!(res3.unwrap(), transaction::ImportResult::Future);
+    }
+
+    #[test]
+    fn should_drop_lowest_priority_transaction_calling_same_recipient() {
+        // given
+        let mut txq = TransactionQueue::default();
+        txq.set_quotas(QueueQuotas {
+            per_recipient: 2,
+            ..Default::default()
+        });
+        let recipient = Address::from(0xc0);
+        let tx = |gas_price: U256| {
+            let signed = Transaction::new(
+                default_nonce(),
+                gas_price,
+                default_gas_val(),
+                transaction::Action::Call(recipient),
+                U256::from(100),
+                "3331600055".from_hex().unwrap(),
+                DEFAULT_TRANSACTION_TYPE,
+                None,
+            )
+            .sign(generate_keypair().secret());
+            VerifiedTransaction::new(signed, TransactionOrigin::External, None, 0, 0)
+        };
+        let fetch_account = |_: &Address| default_account_details();
+        txq.add(tx(1.into()), &fetch_account).unwrap();
+        txq.add(tx(3.into()), &fetch_account).unwrap();
+
+        // when
+        let res1 = txq.add(tx(2.into()), &fetch_account);
+        let res2 = txq.add(tx(1.into()), &fetch_account);
+        let res3 = txq.add(new_tx_default(TransactionOrigin::External), &fetch_account);
+
+        // then
+        assert_eq!(res1.unwrap(), transaction::ImportResult::Current);
+        assert_eq!(unwrap_tx_err(res2), transaction::Error::LimitReached);
+        assert_eq!(res3.unwrap(), transaction::ImportResult::Current);
+        let top = txq.top_transactions();
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[0].gas_price, 3.into());
+        assert_eq!(top[1].gas_price, 2.into());
+        assert_eq!(top[2].gas_price, default_gas_price());
+    }
+
+    #[test]
+    fn should_move_sender_chain_to_future_when_evicting_for_recipient_quota() {
+        // given
+        let mut txq = TransactionQueue::default();
+        txq.set_quotas(QueueQuotas {
+            per_recipient: 1,
+            ..Default::default()
+        });
+        let contract = Address::from(0xc0);
+        let other = Address::from(0xc1);
+        let tx = |keypair: &Ed25519KeyPair, nonce: U256, recipient: Address, gas_price: U256| {
+            let signed = Transaction::new(
+                nonce,
+                gas_price,
+                default_gas_val(),
+                transaction::Action::Call(recipient),
+                U256::from(100),
+                "3331600055".from_hex().unwrap(),
+                DEFAULT_TRANSACTION_TYPE,
+                None,
+            )
+            .sign(keypair.secret());
+            VerifiedTransaction::new(signed, TransactionOrigin::External, None, 0, 0)
+        };
+        let (first, second) = (generate_keypair(), generate_keypair());
+        let fetch_account = |_: &Address| default_account_details();
+        let tx1 = tx(&first, default_nonce(), contract, 1.into());
+        let tx2 = tx(&first, default_nonce() + 1, other, 5.into());
+        let tx2_hash = tx2.hash().clone();
+        let tx2_sender = tx2.sender().clone();
+        txq.add(tx1, &fetch_account).unwrap();
+        txq.add(tx2, &fetch_account).unwrap();
+
+        // when
+        let res = txq.add(
+            tx(&second, default_nonce(), contract, 3.into()),
+            &fetch_account,
+        );
+
+        // then
+        assert_eq!(res.unwrap(), transaction::ImportResult::Current);
+        let top = txq.top_transactions();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].gas_price, 3.into());
+        assert_eq!(txq.status().future, 1);
+        assert_eq!(txq.future_transactions()[0].hash(), &tx2_hash);
+        assert_eq!(txq.last_nonce(top[0].sender()), Some(default_nonce()));
+        assert_eq!(txq.last_nonce(&tx2_sender), None);
+    }
+
+    #[test]
+    fn should_enforce_future_quota_on_chain_moved_out_of_current() {
+        // given
+        let mut txq = TransactionQueue::default();
+        txq.set_quotas(QueueQuotas {
+            future_per_sender: 1,
+            per_recipient: 1,
+            ..Default::default()
+        });
+        let contract = Address::from(0xc0);
+        let (other1, other2) = (Address::from(0xc1), Address::from(0xc2));
+        let tx = |keypair: &Ed25519KeyPair, nonce: U256, recipient: Address, gas_price: U256| {
+            let signed = Transaction::new(
+                nonce,
+                gas_price,
+                default_gas_val(),
+                transaction::Action::Call(recipient),
+                U256::from(100),
+                "3331600055".from_hex().unwrap(),
+                DEFAULT_TRANSACTION_TYPE,
+                None,
+            )
+            .sign(keypair.secret());
+            VerifiedTransaction::new(signed, TransactionOrigin::External, None, 0, 0)
+        };
+        let (first, second) = (generate_keypair(), generate_keypair());
+        let fetch_account = |_: &Address| default_account_details();
+        let tx2 = tx(&first, default_nonce() + 1, other1, 5.into());
+        let tx2_hash = tx2.hash().clone();
+        txq.add(tx(&first, default_nonce(), contract, 1.into()), &fetch_account)
+            .unwrap();
+        txq.add(tx2, &fetch_account).unwrap();
+        txq.add(tx(&first, default_nonce() + 2, other2, 5.into()), &fetch_account)
+            .unwrap();
+
+        // when
+        let res = txq.add(
+            tx(&second, default_nonce(), contract, 3.into()),
+            &fetch_account,
+        );
+
+        // then
+        assert_eq!(res.unwrap(), transaction::ImportResult::Current);
+        assert_eq!(txq.status().pending, 1);
+        assert_eq!(txq.status().future, 1);
+        assert_eq!(txq.future_transactions()[0].hash(), &tx2_hash);
+    }
+
     #[test]
     fn should_import_txs_from_same_sender() {
         // given
@@ -2395,7 +2925,7 @@ pub mod test {
     fn should_drop_old_transactions_when_hitting_the_limit() {
         // given
         let mut txq = TransactionQueue::with_limits(
-            PrioritizationStrategy::GasPriceOnly,
+            Arc::new(PrioritizationStrategy::GasPriceOnly),
             8,
             Mutex::new(IoService::<TxIoMessage>::start().unwrap().channel()),
         );
@@ -2420,7 +2950,7 @@ pub mod test {
     #[test]
     fn should_limit_transactions() {
         let mut txq = TransactionQueue::with_limits(
-            PrioritizationStrategy::GasPriceOnly,
+            Arc::new(PrioritizationStrategy::GasPriceOnly),
             8,
             Mutex::new(IoService::<TxIoMessage>::start().unwrap().channel()),
         );
@@ -2452,7 +2982,7 @@ pub mod test {
     #[test]
     fn should_keep_own_transactions_above_gas_limit() {
         let mut txq = TransactionQueue::with_limits(
-            PrioritizationStrategy::GasPriceOnly,
+            Arc::new(PrioritizationStrategy::GasPriceOnly),
             16,
             Mutex::new(IoService::<TxIoMessage>::start().unwrap().channel()),
         );
@@ -2761,7 +3291,7 @@ pub mod test {
     fn should_keep_right_order_in_future() {
         // given
         let mut txq = TransactionQueue::with_limits(
-            PrioritizationStrategy::GasPriceOnly,
+            Arc::new(PrioritizationStrategy::GasPriceOnly),
             usize::max_value(),
             Mutex::new(IoService::<TxIoMessage>::start().unwrap().channel()),
         );
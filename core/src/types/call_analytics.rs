@@ -22,13 +22,15 @@
 
 //! Call analytics related types
 
+use trace::VMTraceOptions;
+
 /// Options concerning what analytics we run on the call.
 #[derive(Eq, PartialEq, Default, Clone, Copy, Debug)]
 pub struct CallAnalytics {
     /// Make a transaction trace.
     pub transaction_tracing: bool,
-    /// Make a VM trace.
-    pub vm_tracing: bool,
+    /// Make a VM trace, recording what the options ask for.
+    pub vm_tracing: Option<VMTraceOptions>,
     /// Make a diff.
     pub state_diffing: bool,
 }
@@ -27,6 +27,7 @@ use acore_bytes::Bytes;
 use trie;
 use log_entry::LogEntry;
 use state_diff::StateDiff;
+use trace::{AvmTrace, FlatTrace, StructLog};
 
 use std::fmt;
 use std::collections::HashSet;
@@ -67,6 +68,16 @@ pub struct Executed {
     pub contracts_created: Vec<Address>,
     /// Transaction output.
     pub output: Bytes,
+    /// The trace of this transaction, empty unless tracing was enabled.
+    pub trace: Vec<FlatTrace>,
+    /// The instructions executed by the vm, empty unless vm tracing was enabled.
+    pub struct_logs: Vec<StructLog>,
+    /// Whether instructions were dropped from `struct_logs` for exceeding the limits of
+    /// a vm trace.
+    pub struct_logs_truncated: bool,
+    /// The state changes and internal transactions of an avm transaction, if vm tracing
+    /// was enabled.
+    pub avm_trace: Option<AvmTrace>,
     /// The state diff, if we traced it.
     pub state_diff: Option<StateDiff>,
     /// Transaction fee
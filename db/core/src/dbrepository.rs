@@ -25,7 +25,7 @@ use std::collections::{HashMap, BTreeMap};
 use parking_lot::RwLock;
 
 use super::{Result, DBValue};
-use traits::{KeyValueDAO, KeyValueDB};
+use traits::{IoStats, KeyValueDAO, KeyValueDB};
 use dbconfigs::RepositoryConfig;
 use dbtransaction::{DBTransaction, DBOp};
 use error::Error;
@@ -288,6 +288,13 @@ macro_rules! impl_keyvaluedb {
 
             fn flush(&self) -> Result<()> { $name::flush(self) }
 
+            fn io_stats(&self) -> Vec<(String, IoStats)> {
+                self.dbs
+                    .iter()
+                    .map(|(db_name, db)| (db_name.clone(), db.read().io_stats()))
+                    .collect()
+            }
+
             #[cfg(test)]
             fn close_all(&mut self) { $name::close_all(self); }
             #[cfg(test)]
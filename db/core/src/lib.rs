@@ -50,7 +50,7 @@ pub use dbrepository::{DbRepository, MockDbRepository};
 pub use dbtransaction::{DBOp, DBTransaction};
 pub use mockkvdb::Mockkvdb;
 pub use rockskvdb::Rockskvdb;
-pub use traits::{ HashStore, AsHashStore, KeyValueDB };
+pub use traits::{ HashStore, AsHashStore, IoStats, KeyValueDB };
 #[cfg(test)]
 #[allow(unused)]
 use traits::KeyValueDAO;
@@ -20,7 +20,8 @@
  ******************************************************************************/
 
 use std::cmp;
-use traits::KeyValueDAO;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use traits::{IoStats, KeyValueDAO};
 use parity_rocksdb::{
     DB, Options, BlockBasedOptions, Cache, ReadOptions, IteratorMode, Direction, WriteOptions,
     WriteBatch, DBIterator, Writable, DBCompactionStyle,
@@ -52,6 +53,10 @@ pub struct Rockskvdb {
     write_options: WriteOptions,
     read_options: ReadOptions,
     overlay: HashMap<Key, KeyState>,
+    reads: AtomicUsize,
+    bytes_read: AtomicUsize,
+    writes: usize,
+    bytes_written: usize,
 }
 impl Rockskvdb {
     /// Crate a new database file by default.
@@ -61,6 +66,10 @@ impl Rockskvdb {
             write_options: WriteOptions::new(),
             read_options: ReadOptions::new(),
             overlay: HashMap::new(),
+            reads: AtomicUsize::new(0),
+            bytes_read: AtomicUsize::new(0),
+            writes: 0,
+            bytes_written: 0,
         }
     }
 
@@ -92,6 +101,10 @@ impl Rockskvdb {
                             write_options: write_opts,
                             read_options: read_opts,
                             overlay: HashMap::new(),
+                            reads: AtomicUsize::new(0),
+                            bytes_read: AtomicUsize::new(0),
+                            writes: 0,
+                            bytes_written: 0,
                         })
                     }
                     Err(ref s)
@@ -107,6 +120,10 @@ impl Rockskvdb {
                             write_options: write_opts,
                             read_options: read_opts,
                             overlay: HashMap::new(),
+                            reads: AtomicUsize::new(0),
+                            bytes_read: AtomicUsize::new(0),
+                            writes: 0,
+                            bytes_written: 0,
                         })
                     }
                     Err(s) => return Err(s.into()),
@@ -196,7 +213,7 @@ impl Rockskvdb {
 
 impl KeyValueDAO for Rockskvdb {
     fn get(&self, k: &[u8]) -> Option<DBValue> {
-        match self.overlay.get(k) {
+        let value = match self.overlay.get(k) {
             Some(KeyState::Insert(ref value)) => Some(value.clone()),
             Some(KeyState::Delete) => None,
             None => {
@@ -205,10 +222,17 @@ impl KeyValueDAO for Rockskvdb {
                     .unwrap_or(None)
                     .map(|r| DBValue::from_slice(&r))
             }
+        };
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        if let Some(ref value) = value {
+            self.bytes_read.fetch_add(value.len(), Ordering::Relaxed);
         }
+        value
     }
 
     fn put(&mut self, k: &[u8], v: &DBValue) -> Option<DBValue> {
+        self.writes += 1;
+        self.bytes_written += k.len() + v.len();
         let mut ekey = Key::new();
         ekey.append_slice(k);
         self.overlay.insert(ekey, KeyState::Insert(v.clone()));
@@ -219,6 +243,8 @@ impl KeyValueDAO for Rockskvdb {
     }
 
     fn delete(&mut self, k: &[u8]) -> Option<DBValue> {
+        self.writes += 1;
+        self.bytes_written += k.len();
         let mut ekey = Key::new();
         ekey.append_slice(k);
         self.overlay.insert(ekey, KeyState::Delete);
@@ -278,6 +304,16 @@ impl KeyValueDAO for Rockskvdb {
             &self.read_options,
         ))
     }
+
+    fn io_stats(&self) -> IoStats {
+        IoStats {
+            reads: self.reads.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            writes: self.writes,
+            bytes_written: self.bytes_written,
+            pending_writes: self.overlay.len(),
+        }
+    }
 }
 impl Drop for Rockskvdb {
     fn drop(&mut self) { let _ = self.flush(); }
@@ -30,6 +30,29 @@ fn crud_test() {
     let _ = fs::remove_dir_all("./temp/testdb");
 }
 
+#[test]
+fn io_stats_test() {
+    {
+        let mut db = Rockskvdb::open(&DatabaseConfig::default(), "./temp/testdb_io_stats").unwrap();
+
+        let key: Vec<u8> = vec![1, 2];
+        let value: Vec<u8> = vec![1, 2, 3];
+
+        db.put(&key, &DBValue::from_vec(value.clone()));
+        assert_eq!(db.get(&key).unwrap(), value);
+        assert_eq!(db.get(&[3]), None);
+        db.delete(&key);
+
+        let stats = db.io_stats();
+        assert_eq!((stats.reads, stats.bytes_read), (2, 3));
+        assert_eq!((stats.writes, stats.bytes_written), (2, 7));
+        assert_eq!(stats.pending_writes, 1);
+        db.flush().unwrap();
+        assert_eq!(db.io_stats().pending_writes, 0);
+    }
+    let _ = fs::remove_dir_all("./temp/testdb_io_stats");
+}
+
 #[test]
 fn open_test() {
     {
@@ -21,6 +21,22 @@
 
 use super::{DBValue, Result};
 use dbtransaction::DBTransaction;
+
+/// db io statistics since open.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct IoStats {
+    /// Number of reads
+    pub reads: usize,
+    /// Bytes of the values read
+    pub bytes_read: usize,
+    /// Number of inserts and deletes
+    pub writes: usize,
+    /// Bytes of the keys and values written
+    pub bytes_written: usize,
+    /// Writes buffered in memory, not flushed to disk yet
+    pub pending_writes: usize,
+}
+
 /// basic kvdb operation.
 pub trait KeyValueDAO: Sync + Send {
     /// Get value by key
@@ -38,6 +54,8 @@ pub trait KeyValueDAO: Sync + Send {
         &self,
         prefix: &'static [u8],
     ) -> Box<Iterator<Item = (Box<[u8]>, Box<[u8]>)>>;
+    /// Return the io statistics
+    fn io_stats(&self) -> IoStats { IoStats::default() }
 }
 /// db repository operation.
 pub trait KeyValueDB: Sync + Send {
@@ -63,6 +81,8 @@ pub trait KeyValueDB: Sync + Send {
         db_name: &'static str,
         prefix: &'static [u8],
     ) -> Box<Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
+    /// Return the io statistics of each db
+    fn io_stats(&self) -> Vec<(String, IoStats)> { Vec::new() }
     /// Close all dbs
     #[cfg(test)]
     fn close_all(&mut self);
@@ -0,0 +1,164 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+use std::fmt;
+use byteorder::BigEndian;
+use byteorder::ByteOrder;
+use route::Module;
+
+/// Protocol a node speaks: one version of a module and the actions it handles at that version.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capability {
+    pub module: u8,
+    pub version: u16,
+    pub actions: Vec<u8>,
+}
+
+impl Capability {
+    pub fn new(module: u8, version: u16, actions: Vec<u8>) -> Capability {
+        Capability {
+            module,
+            version,
+            actions,
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match Module::from(self.module) {
+            Module::P2P => write!(f, "p2p/{}", self.version),
+            Module::SYNC => write!(f, "sync/{}", self.version),
+            Module::UNKNOWN => write!(f, "{}/{}", self.module, self.version),
+        }
+    }
+}
+
+/// Encode as a count followed by module, version, action count and actions of each capability.
+pub fn encode(capabilities: &[Capability]) -> Vec<u8> {
+    let mut bytes = vec![capabilities.len() as u8];
+    for capability in capabilities {
+        let mut version = [0u8; 2];
+        BigEndian::write_u16(&mut version, capability.version);
+        bytes.push(capability.module);
+        bytes.extend_from_slice(&version);
+        bytes.push(capability.actions.len() as u8);
+        bytes.extend_from_slice(&capability.actions);
+    }
+    bytes
+}
+
+/// Decode capabilities, ignoring bytes past the list so that later releases may extend it.
+pub fn decode(bytes: &[u8]) -> Option<Vec<Capability>> {
+    let (count, mut rest) = bytes.split_first()?;
+    let mut capabilities = Vec::with_capacity(*count as usize);
+    for _ in 0..*count {
+        if rest.len() < 4 {
+            return None;
+        }
+        let (head, tail) = rest.split_at(4);
+        let actions_len = head[3] as usize;
+        if tail.len() < actions_len {
+            return None;
+        }
+        let (actions, tail) = tail.split_at(actions_len);
+        capabilities.push(Capability::new(
+            head[0],
+            BigEndian::read_u16(&head[1..3]),
+            actions.to_vec(),
+        ));
+        rest = tail;
+    }
+    Some(capabilities)
+}
+
+/// For each module spoken by both sides, the highest common version with the actions
+/// the remote node handles at that version.
+pub fn negotiate(local: &[Capability], remote: &[Capability]) -> Vec<Capability> {
+    let mut common: Vec<Capability> = Vec::new();
+    for ours in local {
+        let theirs = match remote
+            .iter()
+            .find(|theirs| theirs.module == ours.module && theirs.version == ours.version)
+        {
+            Some(theirs) => theirs,
+            None => continue,
+        };
+        let capability = Capability::new(ours.module, ours.version, theirs.actions.clone());
+        let position = common.iter().position(|c| c.module == ours.module);
+        match position {
+            Some(i) if common[i].version < ours.version => common[i] = capability,
+            Some(_) => {}
+            None => common.push(capability),
+        }
+    }
+    common
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let capabilities = vec![
+            Capability::new(0, 0, vec![1, 2, 3, 4]),
+            Capability::new(1, 0, vec![]),
+            Capability::new(1, 258, vec![0, 1, 17]),
+        ];
+        let mut bytes = encode(&capabilities);
+        assert_eq!(decode(&bytes), Some(capabilities.clone()));
+
+        bytes.push(0xff);
+        assert_eq!(decode(&bytes), Some(capabilities));
+
+        assert_eq!(decode(&[]), None);
+        assert_eq!(decode(&[1, 0, 0]), None);
+        assert_eq!(decode(&[1, 0, 0, 0, 2, 1]), None);
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let local = vec![
+            Capability::new(0, 0, vec![1, 2, 3, 4]),
+            Capability::new(1, 0, vec![0, 1, 2, 3]),
+            Capability::new(1, 1, vec![0, 1, 2, 3, 8, 9]),
+        ];
+        let remote = vec![
+            Capability::new(1, 0, vec![0, 1, 2]),
+            Capability::new(1, 1, vec![0, 1, 8, 9, 10]),
+            Capability::new(1, 2, vec![0, 1]),
+            Capability::new(2, 0, vec![0]),
+        ];
+        assert_eq!(
+            negotiate(&local, &remote),
+            vec![Capability::new(1, 1, vec![0, 1, 8, 9, 10])]
+        );
+        assert!(negotiate(&local, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Capability::new(0, 0, vec![]).to_string(), "p2p/0");
+        assert_eq!(Capability::new(1, 2, vec![]).to_string(), "sync/2");
+        assert_eq!(Capability::new(7, 1, vec![]).to_string(), "7/1");
+    }
+}
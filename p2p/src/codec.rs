@@ -20,16 +20,31 @@
  ******************************************************************************/
 
 use std::io;
+use std::sync::Arc;
 use tokio_codec::Decoder;
 use tokio_codec::Encoder;
 use acore_bytes::to_hex;
 use bincode::config;
 use bytes::BytesMut;
+use parking_lot::Mutex;
 use node::HEADER_LENGTH;
-use msg::ChannelBuffer;
-use route::Version;
+use msg::{ChannelBuffer, Head};
+use route::{Action, Module, Version};
+use secure::SecureChannel;
 
-pub struct Codec;
+/// Frames channel buffers, in plain until the handshake of the connection completes
+/// and encrypted afterwards.
+pub struct Codec {
+    channel: Arc<Mutex<SecureChannel>>,
+}
+
+impl Codec {
+    pub fn new(channel: Arc<Mutex<SecureChannel>>) -> Codec {
+        Codec {
+            channel,
+        }
+    }
+}
 
 impl Encoder for Codec {
     type Item = ChannelBuffer;
@@ -39,8 +54,19 @@ impl Encoder for Codec {
         let mut encoder = config();
         let encoder = encoder.big_endian();
         if let Ok(encoded) = encoder.serialize(&item.head) {
+            let mut channel = self.channel.lock();
+            if let Some(egress) = channel.egress() {
+                let mut frame = encoded;
+                frame.extend_from_slice(item.body.as_slice());
+                egress.seal(&frame, dst);
+                return Ok(());
+            }
+
             dst.extend_from_slice(encoded.as_slice());
             dst.extend_from_slice(item.body.as_slice());
+            if is_handshake_res(&item.head) {
+                channel.handshake_written();
+            }
         }
 
         Ok(())
@@ -52,6 +78,13 @@ impl Decoder for Codec {
     type Error = io::Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<ChannelBuffer>> {
+        if let Some(ingress) = self.channel.lock().ingress() {
+            return match ingress.open(src)? {
+                Some(frame) => decode_frame(&frame).map(Some),
+                None => Ok(None),
+            };
+        }
+
         let len = src.len();
         if len >= HEADER_LENGTH {
             let mut decoder = config();
@@ -89,3 +122,49 @@ impl Decoder for Codec {
         }
     }
 }
+
+// decode a whole decrypted frame
+fn decode_frame(frame: &[u8]) -> io::Result<ChannelBuffer> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid frame");
+    if frame.len() < HEADER_LENGTH {
+        return Err(invalid());
+    }
+    let mut decoder = config();
+    let decoder = decoder.big_endian();
+    let (head_raw, body) = frame.split_at(HEADER_LENGTH);
+    let head: Head = decoder.deserialize(head_raw).map_err(|_| invalid())?;
+    if head.len as usize != body.len() {
+        return Err(invalid());
+    }
+    Ok(ChannelBuffer {
+        head,
+        body: body.to_vec(),
+    })
+}
+
+fn is_handshake_res(head: &Head) -> bool {
+    head.ctrl == Module::P2P.value() && head.action == Action::HANDSHAKERES.value()
+}
+
+/// Passes whole datagrams of the discovery socket through, packets are checked by
+/// the discovery itself.
+pub struct DatagramCodec;
+
+impl Encoder for DatagramCodec {
+    type Item = Vec<u8>;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut BytesMut) -> io::Result<()> {
+        dst.extend_from_slice(&item);
+        Ok(())
+    }
+}
+
+impl Decoder for DatagramCodec {
+    type Item = Vec<u8>;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Vec<u8>>> {
+        Ok(Some(src.take().to_vec()))
+    }
+}
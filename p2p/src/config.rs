@@ -19,14 +19,42 @@
  *
  ******************************************************************************/
 
+use std::path::PathBuf;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Config {
     pub boot_nodes: Vec<String>,
     pub max_peers: u32,
+    /// inbound nodes allowed out of max_peers
+    pub max_inbound: u32,
+    /// outbound nodes allowed out of max_peers
+    pub max_outbound: u32,
     pub net_id: u32,
     pub local_node: String,
     pub sync_from_boot_nodes_only: bool,
     pub ip_black_list: Vec<String>,
+    /// nodes always kept connected, not counted against the peer limits
+    pub reserved_nodes: Vec<String>,
+    /// only connect to and accept reserved nodes
+    pub reserved_only: bool,
+    /// file holding the node key, a key is generated for each run if not set
+    pub node_key_path: Option<PathBuf>,
+    /// file holding the banned ips, bans only last for the run if not set
+    pub bans_path: Option<PathBuf>,
+    /// file holding the known nodes, nodes are only dialed from boot nodes if not set
+    pub peers_path: Option<PathBuf>,
+    /// run udp node discovery on the binding port, exchange active nodes with peers otherwise
+    pub discovery: bool,
+    /// bytes per second sent to all nodes, not limited if not set
+    pub max_upload: Option<u64>,
+    /// bytes per second received from all nodes, not limited if not set
+    pub max_download: Option<u64>,
+    /// seconds over which the headers and bodies served to each node are limited
+    pub request_quota_window: u64,
+    /// headers served to a node within a quota window
+    pub max_headers_per_window: u64,
+    /// bodies served to a node within a quota window
+    pub max_bodies_per_window: u64,
 }
 
 impl Config {
@@ -34,10 +62,23 @@ impl Config {
         Config {
             boot_nodes: Vec::new(),
             max_peers: 64,
+            max_inbound: 64,
+            max_outbound: 64,
             net_id: 0,
             local_node: String::from("p2p://00000000-0000-0000-0000-000000000000@0.0.0.0:30303"),
             sync_from_boot_nodes_only: false,
             ip_black_list: Vec::new(),
+            reserved_nodes: Vec::new(),
+            reserved_only: false,
+            node_key_path: None,
+            bans_path: None,
+            peers_path: None,
+            discovery: true,
+            max_upload: None,
+            max_download: None,
+            request_quota_window: 10,
+            max_headers_per_window: 1000,
+            max_bodies_per_window: 1000,
         }
     }
 
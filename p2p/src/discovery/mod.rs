@@ -0,0 +1,497 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Kademlia-style node discovery over udp, running alongside the tcp connections.
+//!
+//! Nodes only enter the routing table after answering a ping, and only nodes in the table
+//! are answered `FINDNODE` requests, so a spoofed source address cannot be used to fill
+//! the table or to amplify traffic. Every node signs its own record, which lets records be
+//! relayed in `NEIGHBOURS` packets without trusting the relaying node. Nodes entering the
+//! table are handed to the tcp side to be dialed.
+
+mod packet;
+mod table;
+
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::random;
+
+use node::{convert_ip_string, TempNode, NODE_ID_LENGTH};
+use secure::{self, sha256, NodeKey};
+use self::packet::{NodeRecord, Packet, NEIGHBOURS_PER_PACKET};
+use self::table::{Entry, RoutingTable, Update, BUCKET_SIZE};
+
+/// Nodes asked in parallel during a lookup.
+const ALPHA: usize = 3;
+const PING_TIMEOUT: u64 = 2;
+const FINDNODE_TIMEOUT: u64 = 5;
+/// Seconds after which a lookup moves on to a new random target.
+const LOOKUP_DURATION: u64 = 30;
+/// Seconds after which the least recently seen node is pinged again.
+const REVALIDATE_AFTER: u64 = 60;
+const BOOTSTRAP_INTERVAL: u64 = 10;
+const MAX_PENDING_PINGS: usize = 64;
+const MAX_DISCOVERED: usize = 64;
+
+struct PendingPing {
+    endpoint: SocketAddr,
+    sent: Instant,
+    /// id a boot node must authenticate with
+    pinned: Option<[u8; NODE_ID_LENGTH]>,
+    /// node to drop from the table if it does not answer
+    evict: Option<[u8; 32]>,
+    /// node to add once the evicted one is gone
+    replacement: Option<Entry>,
+}
+
+struct Lookup {
+    target: [u8; 32],
+    asked: HashSet<[u8; 32]>,
+    started: Instant,
+}
+
+impl Lookup {
+    fn new(target: [u8; 32]) -> Lookup {
+        Lookup {
+            target,
+            asked: HashSet::new(),
+            started: Instant::now(),
+        }
+    }
+}
+
+pub struct Discovery {
+    key: Arc<NodeKey>,
+    record: NodeRecord,
+    endpoint: SocketAddr,
+    table: RoutingTable,
+    boot_nodes: Vec<TempNode>,
+    last_bootstrap: Option<Instant>,
+    /// pings waiting for a pong by packet hash
+    pings: HashMap<[u8; 32], PendingPing>,
+    /// nodes sent a `FINDNODE`, with the time and whether they answered
+    asked: HashMap<SocketAddr, (Instant, bool)>,
+    lookup: Lookup,
+    outbox: Vec<(Vec<u8>, SocketAddr)>,
+    discovered: Vec<TempNode>,
+}
+
+impl Discovery {
+    /// `ip` is the advertised ip, unspecified to let other nodes use the address they see.
+    pub fn new(
+        key: Arc<NodeKey>,
+        ip: Ipv4Addr,
+        port: u16,
+        boot_nodes: Vec<TempNode>,
+    ) -> Discovery
+    {
+        let record = NodeRecord::new(&key, ip, port, port, 1);
+        let local = record.id();
+        Discovery {
+            key,
+            endpoint: SocketAddr::new(ip.into(), port),
+            table: RoutingTable::new(local),
+            record,
+            boot_nodes,
+            last_bootstrap: None,
+            pings: HashMap::new(),
+            asked: HashMap::new(),
+            lookup: Lookup::new(local),
+            outbox: Vec::new(),
+            discovered: Vec::new(),
+        }
+    }
+
+    /// Number of nodes in the routing table.
+    pub fn len(&self) -> usize { self.table.len() }
+
+    /// Packets to send with their destination.
+    pub fn take_outbox(&mut self) -> Vec<(Vec<u8>, SocketAddr)> {
+        mem::replace(&mut self.outbox, Vec::new())
+    }
+
+    /// Nodes which entered the routing table since the last call, to be dialed.
+    pub fn take_discovered(&mut self) -> Vec<TempNode> {
+        mem::replace(&mut self.discovered, Vec::new())
+    }
+
+    /// Handle a datagram received from `from`.
+    pub fn handle(&mut self, bytes: &[u8], from: SocketAddr) {
+        let (packet, sender, hash) = match Packet::decode(bytes) {
+            Some(decoded) => decoded,
+            None => {
+                trace!(target: "p2p", "discovery: invalid packet from {}", from);
+                return;
+            }
+        };
+        if let SocketAddr::V6(_) = from {
+            return;
+        }
+        if &sender == self.key.public() {
+            return;
+        }
+
+        match packet {
+            Packet::Ping(record) => {
+                if record.public != sender {
+                    return;
+                }
+                let pong = Packet::Pong(hash, self.record.clone());
+                self.send(pong, from);
+                // bond in the other direction too, so that our requests are answered
+                if !self.table.contains(&record.id()) {
+                    self.ping(from, None, None, None);
+                }
+            }
+            Packet::Pong(ping_hash, record) => {
+                if record.public != sender {
+                    return;
+                }
+                let pinned = match self.pings.remove(&ping_hash) {
+                    Some(ref ping) if ping.endpoint == from => ping.pinned,
+                    _ => return,
+                };
+                if let Some(pinned) = pinned {
+                    if secure::node_id(&record.public)[..] != pinned[..] {
+                        warn!(target: "p2p", "discovery: boot node {} has another node key", from);
+                        return;
+                    }
+                }
+                self.bonded(Entry {
+                    record,
+                    endpoint: from,
+                    last_seen: Instant::now(),
+                });
+            }
+            Packet::FindNode(target) => {
+                if !self.table.contains(&sha256(&sender)) {
+                    return;
+                }
+                let closest = self.table.closest(&target, BUCKET_SIZE);
+                let mut chunks: Vec<Vec<(Ipv4Addr, NodeRecord)>> = closest
+                    .chunks(NEIGHBOURS_PER_PACKET)
+                    .map(|chunk| {
+                        chunk
+                            .iter()
+                            .map(|entry| (endpoint_ip(&entry.endpoint), entry.record.clone()))
+                            .collect()
+                    })
+                    .collect();
+                if chunks.is_empty() {
+                    chunks.push(Vec::new());
+                }
+                for nodes in chunks {
+                    self.send(Packet::Neighbours(nodes), from);
+                }
+            }
+            Packet::Neighbours(nodes) => {
+                match self.asked.get_mut(&from) {
+                    Some(&mut (_, ref mut answered)) => *answered = true,
+                    None => return,
+                }
+                for (ip, record) in nodes {
+                    if &record.public == self.key.public() {
+                        continue;
+                    }
+                    // a relayed record has to agree with where the node was seen
+                    if !record.ip.is_unspecified() && record.ip != ip {
+                        continue;
+                    }
+                    if self.table.contains(&record.id()) {
+                        continue;
+                    }
+                    self.ping(record.udp_endpoint(ip), None, None, None);
+                }
+            }
+        }
+    }
+
+    /// Expire requests, check stale nodes and continue lookups, to be called every second.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+
+        let ping_timeout = Duration::from_secs(PING_TIMEOUT);
+        let expired: Vec<[u8; 32]> = self
+            .pings
+            .iter()
+            .filter(|&(_, ping)| now.duration_since(ping.sent) >= ping_timeout)
+            .map(|(hash, _)| *hash)
+            .collect();
+        for hash in expired {
+            if let Some(ping) = self.pings.remove(&hash) {
+                if let Some(id) = ping.evict {
+                    self.table.remove(&id);
+                }
+                if let Some(replacement) = ping.replacement {
+                    self.bonded(replacement);
+                }
+            }
+        }
+
+        let findnode_timeout = Duration::from_secs(FINDNODE_TIMEOUT);
+        self.asked
+            .retain(|_, &mut (sent, _)| now.duration_since(sent) < findnode_timeout);
+
+        if self.table.len() == 0 {
+            self.bootstrap();
+            return;
+        }
+        self.revalidate();
+        self.lookup_step();
+    }
+
+    fn bootstrap(&mut self) {
+        let interval = Duration::from_secs(BOOTSTRAP_INTERVAL);
+        if self.last_bootstrap.map_or(false, |last| last.elapsed() < interval) {
+            return;
+        }
+        self.last_bootstrap = Some(Instant::now());
+        let boot_nodes = self.boot_nodes.clone();
+        for boot_node in boot_nodes {
+            if let Ok(endpoint) = boot_node.addr.to_string().parse::<SocketAddr>() {
+                let pinned = if boot_node.id_pinned {
+                    Some(boot_node.id)
+                } else {
+                    None
+                };
+                self.ping(endpoint, pinned, None, None);
+            }
+        }
+    }
+
+    // ping the least recently seen node once it has been silent for a while
+    fn revalidate(&mut self) {
+        let stale = Duration::from_secs(REVALIDATE_AFTER);
+        let oldest = self
+            .table
+            .entries()
+            .into_iter()
+            .min_by_key(|entry| entry.last_seen)
+            .filter(|entry| entry.last_seen.elapsed() >= stale)
+            .map(|entry| (entry.id(), entry.endpoint));
+        if let Some((id, endpoint)) = oldest {
+            self.ping(endpoint, None, Some(id), None);
+        }
+    }
+
+    // ask the closest nodes not asked yet for nodes closer to the lookup target
+    fn lookup_step(&mut self) {
+        if self.asked.values().any(|&(_, answered)| !answered) {
+            return;
+        }
+        if self.lookup.started.elapsed() >= Duration::from_secs(LOOKUP_DURATION) {
+            self.lookup = Lookup::new(random());
+        }
+
+        let candidates: Vec<Entry> = self
+            .table
+            .closest(&self.lookup.target, BUCKET_SIZE)
+            .into_iter()
+            .filter(|entry| !self.lookup.asked.contains(&entry.id()))
+            .take(ALPHA)
+            .collect();
+        if candidates.is_empty() {
+            // converged, look for a random target next time
+            self.lookup = Lookup::new(random());
+            return;
+        }
+
+        let target = self.lookup.target;
+        for entry in candidates {
+            self.lookup.asked.insert(entry.id());
+            self.asked.insert(entry.endpoint, (Instant::now(), false));
+            self.send(Packet::FindNode(target), entry.endpoint);
+        }
+    }
+
+    fn ping(
+        &mut self,
+        endpoint: SocketAddr,
+        pinned: Option<[u8; NODE_ID_LENGTH]>,
+        evict: Option<[u8; 32]>,
+        replacement: Option<Entry>,
+    )
+    {
+        if endpoint == self.endpoint
+            || self.pings.len() >= MAX_PENDING_PINGS
+            || self.pings.values().any(|ping| ping.endpoint == endpoint)
+        {
+            return;
+        }
+        let packet = Packet::Ping(self.record.clone()).encode(&self.key);
+        self.pings.insert(
+            sha256(&packet),
+            PendingPing {
+                endpoint,
+                sent: Instant::now(),
+                pinned,
+                evict,
+                replacement,
+            },
+        );
+        self.outbox.push((packet, endpoint));
+    }
+
+    fn send(&mut self, packet: Packet, to: SocketAddr) {
+        let encoded = packet.encode(&self.key);
+        self.outbox.push((encoded, to));
+    }
+
+    // a node answered a ping
+    fn bonded(&mut self, entry: Entry) {
+        match self.table.update(entry.clone()) {
+            Update::Added => {
+                debug!(target: "p2p", "discovery: added node {}", entry.endpoint);
+                self.discover(&entry);
+            }
+            Update::Full(oldest) => {
+                // keep the known node if it is still alive
+                self.ping(oldest.endpoint, None, Some(oldest.id()), Some(entry));
+            }
+            Update::Refreshed | Update::Rejected => {}
+        }
+    }
+
+    fn discover(&mut self, entry: &Entry) {
+        if entry.record.tcp_port == 0 || self.discovered.len() >= MAX_DISCOVERED {
+            return;
+        }
+        let ip = entry.record.ip_or(endpoint_ip(&entry.endpoint));
+        let mut temp = TempNode::default();
+        temp.id = secure::node_id(&entry.record.public);
+        temp.id_pinned = true;
+        temp.addr.ip = convert_ip_string(ip.to_string());
+        temp.addr.port = entry.record.tcp_port as u32;
+        self.discovered.push(temp);
+    }
+}
+
+fn endpoint_ip(endpoint: &SocketAddr) -> Ipv4Addr {
+    match *endpoint {
+        SocketAddr::V4(ref addr) => *addr.ip(),
+        SocketAddr::V6(_) => Ipv4Addr::new(0, 0, 0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::Arc;
+    use node::TempNode;
+    use secure::{self, NodeKey};
+    use super::Discovery;
+
+    fn boot_node(key: &NodeKey, port: u16) -> TempNode {
+        let id = String::from_utf8(secure::node_id(key.public()).to_vec()).unwrap();
+        TempNode::new_from_str(format!("p2p://{}@127.0.0.1:{}", id, port))
+    }
+
+    // deliver the packets of every node to the node listening at their destination
+    fn run(nodes: &mut Vec<(SocketAddr, Discovery)>, rounds: usize) {
+        for _ in 0..rounds {
+            let mut packets = Vec::new();
+            for &mut (endpoint, ref mut discovery) in nodes.iter_mut() {
+                discovery.tick();
+                for (packet, to) in discovery.take_outbox() {
+                    packets.push((endpoint, packet, to));
+                }
+            }
+            while !packets.is_empty() {
+                let mut replies = Vec::new();
+                for (from, packet, to) in packets {
+                    for &mut (endpoint, ref mut discovery) in nodes.iter_mut() {
+                        if endpoint == to {
+                            discovery.handle(&packet, from);
+                            for (reply, reply_to) in discovery.take_outbox() {
+                                replies.push((endpoint, reply, reply_to));
+                            }
+                        }
+                    }
+                }
+                packets = replies;
+            }
+        }
+    }
+
+    #[test]
+    fn test_nodes_find_each_other_through_boot_node() {
+        let localhost = Ipv4Addr::new(127, 0, 0, 1);
+        let boot_key = Arc::new(NodeKey::random());
+        let boot = boot_node(&boot_key, 30400);
+
+        let mut nodes = vec![(
+            SocketAddr::new(localhost.into(), 30400),
+            Discovery::new(boot_key.clone(), localhost, 30400, Vec::new()),
+        )];
+        for port in 30401..30404 {
+            let key = Arc::new(NodeKey::random());
+            nodes.push((
+                SocketAddr::new(localhost.into(), port),
+                Discovery::new(key, localhost, port, vec![boot.clone()]),
+            ));
+        }
+
+        run(&mut nodes, 5);
+
+        for &mut (_, ref mut discovery) in nodes.iter_mut() {
+            assert_eq!(discovery.len(), 3);
+            let discovered = discovery.take_discovered();
+            assert_eq!(discovered.len(), 3);
+            assert!(discovered.iter().all(|temp| temp.id_pinned));
+        }
+    }
+
+    #[test]
+    fn test_discovered_ids_follow_node_keys() {
+        let localhost = Ipv4Addr::new(127, 0, 0, 1);
+        let key = Arc::new(NodeKey::random());
+        // a boot node entry claiming an id of another key
+        let claimed = boot_node(&NodeKey::random(), 30500);
+
+        let mut nodes = vec![
+            (
+                SocketAddr::new(localhost.into(), 30500),
+                Discovery::new(key.clone(), localhost, 30500, Vec::new()),
+            ),
+            (
+                SocketAddr::new(localhost.into(), 30501),
+                Discovery::new(
+                    Arc::new(NodeKey::random()),
+                    localhost,
+                    30501,
+                    vec![claimed.clone()],
+                ),
+            ),
+        ];
+
+        run(&mut nodes, 3);
+
+        // the pong to the boot node ping is refused, the node is only known by its real id
+        let discovered = nodes[1].1.take_discovered();
+        assert_eq!(discovered.len(), 1);
+        assert_eq!(discovered[0].id[..], secure::node_id(key.public())[..]);
+        assert!(discovered[0].id[..] != claimed.id[..]);
+    }
+}
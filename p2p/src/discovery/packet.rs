@@ -0,0 +1,302 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Discovery packets, sent as `kind | expiration | body | public | signature` where the
+//! signature of the sender's node key covers everything before the public key.
+
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use byteorder::{BigEndian, ByteOrder};
+use crypto::ed25519;
+
+use secure::{sha256, NodeKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+
+pub const PING: u8 = 1;
+pub const PONG: u8 = 2;
+pub const FINDNODE: u8 = 3;
+pub const NEIGHBOURS: u8 = 4;
+
+/// Length of an encoded record: `public | ip | tcp port | udp port | seq | signature`.
+pub const RECORD_LENGTH: usize = PUBLIC_KEY_LENGTH + 4 + 2 + 2 + 8 + SIGNATURE_LENGTH;
+/// Records sent in one neighbours packet, keeping it within a single datagram.
+pub const NEIGHBOURS_PER_PACKET: usize = 8;
+
+const PACKET_LABEL: &[u8] = b"aion-p2p-discovery";
+const RECORD_LABEL: &[u8] = b"aion-p2p-record";
+const HEADER_LENGTH: usize = 1 + 8;
+const TRAILER_LENGTH: usize = PUBLIC_KEY_LENGTH + SIGNATURE_LENGTH;
+/// Seconds a packet stays valid.
+const EXPIRATION: u64 = 20;
+
+/// Endpoint of a node, signed by the node itself so that it can be relayed by others.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeRecord {
+    pub public: [u8; PUBLIC_KEY_LENGTH],
+    /// advertised ip, unspecified if the node does not know its public address
+    pub ip: Ipv4Addr,
+    pub tcp_port: u16,
+    pub udp_port: u16,
+    /// incremented whenever the endpoint changes
+    pub seq: u64,
+    signature: Vec<u8>,
+}
+
+impl NodeRecord {
+    pub fn new(key: &NodeKey, ip: Ipv4Addr, tcp_port: u16, udp_port: u16, seq: u64) -> NodeRecord {
+        let mut record = NodeRecord {
+            public: *key.public(),
+            ip,
+            tcp_port,
+            udp_port,
+            seq,
+            signature: Vec::new(),
+        };
+        record.signature = key.sign(&record.signed_data()).to_vec();
+        record
+    }
+
+    /// Kademlia id of the node.
+    pub fn id(&self) -> [u8; 32] { sha256(&self.public) }
+
+    /// Udp endpoint of the node, at `observed` ip if the record does not tell.
+    pub fn udp_endpoint(&self, observed: Ipv4Addr) -> SocketAddr {
+        SocketAddr::V4(SocketAddrV4::new(self.ip_or(observed), self.udp_port))
+    }
+
+    /// Advertised ip if any, `observed` otherwise.
+    pub fn ip_or(&self, observed: Ipv4Addr) -> Ipv4Addr {
+        if self.ip.is_unspecified() {
+            observed
+        } else {
+            self.ip
+        }
+    }
+
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.signed_data()[RECORD_LABEL.len()..]);
+        out.extend_from_slice(&self.signature);
+    }
+
+    /// Decode a record, checking its signature.
+    pub fn decode(bytes: &[u8]) -> Option<NodeRecord> {
+        if bytes.len() != RECORD_LENGTH {
+            return None;
+        }
+        let (data, signature) = bytes.split_at(RECORD_LENGTH - SIGNATURE_LENGTH);
+        let mut public = [0u8; PUBLIC_KEY_LENGTH];
+        public.copy_from_slice(&data[..PUBLIC_KEY_LENGTH]);
+        let rest = &data[PUBLIC_KEY_LENGTH..];
+        let record = NodeRecord {
+            public,
+            ip: Ipv4Addr::new(rest[0], rest[1], rest[2], rest[3]),
+            tcp_port: BigEndian::read_u16(&rest[4..6]),
+            udp_port: BigEndian::read_u16(&rest[6..8]),
+            seq: BigEndian::read_u64(&rest[8..16]),
+            signature: signature.to_vec(),
+        };
+        if ed25519::verify(&record.signed_data(), &record.public, signature) {
+            Some(record)
+        } else {
+            None
+        }
+    }
+
+    fn signed_data(&self) -> Vec<u8> {
+        let mut data = RECORD_LABEL.to_vec();
+        data.extend_from_slice(&self.public);
+        data.extend_from_slice(&self.ip.octets());
+        let mut buf = [0u8; 8];
+        BigEndian::write_u16(&mut buf[..2], self.tcp_port);
+        data.extend_from_slice(&buf[..2]);
+        BigEndian::write_u16(&mut buf[..2], self.udp_port);
+        data.extend_from_slice(&buf[..2]);
+        BigEndian::write_u64(&mut buf, self.seq);
+        data.extend_from_slice(&buf);
+        data
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Packet {
+    /// liveness check, carrying the sender's record
+    Ping(NodeRecord),
+    /// answer to the ping with the given packet hash, carrying the sender's record
+    Pong([u8; 32], NodeRecord),
+    /// ask for the nodes closest to the given id
+    FindNode([u8; 32]),
+    /// nodes closest to a requested id, with the ip each was seen at
+    Neighbours(Vec<(Ipv4Addr, NodeRecord)>),
+}
+
+impl Packet {
+    /// Encode and sign the packet.
+    pub fn encode(&self, key: &NodeKey) -> Vec<u8> {
+        let mut packet = Vec::new();
+        let kind = match *self {
+            Packet::Ping(_) => PING,
+            Packet::Pong(..) => PONG,
+            Packet::FindNode(_) => FINDNODE,
+            Packet::Neighbours(_) => NEIGHBOURS,
+        };
+        packet.push(kind);
+        let mut expiration = [0u8; 8];
+        BigEndian::write_u64(&mut expiration, now() + EXPIRATION);
+        packet.extend_from_slice(&expiration);
+
+        match *self {
+            Packet::Ping(ref record) => record.encode(&mut packet),
+            Packet::Pong(ref ping_hash, ref record) => {
+                packet.extend_from_slice(ping_hash);
+                record.encode(&mut packet);
+            }
+            Packet::FindNode(ref target) => packet.extend_from_slice(target),
+            Packet::Neighbours(ref nodes) => {
+                packet.push(nodes.len() as u8);
+                for &(ip, ref record) in nodes {
+                    packet.extend_from_slice(&ip.octets());
+                    record.encode(&mut packet);
+                }
+            }
+        }
+
+        let signature = key.sign(&signed_data(&packet));
+        packet.extend_from_slice(key.public());
+        packet.extend_from_slice(&signature);
+        packet
+    }
+
+    /// Decode a packet, checking its signature and expiration. Returns the packet, the
+    /// public key of its sender and the packet hash to be echoed by pongs.
+    pub fn decode(bytes: &[u8]) -> Option<(Packet, [u8; PUBLIC_KEY_LENGTH], [u8; 32])> {
+        if bytes.len() < HEADER_LENGTH + TRAILER_LENGTH {
+            return None;
+        }
+        let (signed, trailer) = bytes.split_at(bytes.len() - TRAILER_LENGTH);
+        let (public, signature) = trailer.split_at(PUBLIC_KEY_LENGTH);
+        if !ed25519::verify(&signed_data(signed), public, signature) {
+            return None;
+        }
+        if BigEndian::read_u64(&signed[1..HEADER_LENGTH]) < now() {
+            return None;
+        }
+
+        let body = &signed[HEADER_LENGTH..];
+        let packet = match signed[0] {
+            PING => Packet::Ping(NodeRecord::decode(body)?),
+            PONG => {
+                if body.len() != 32 + RECORD_LENGTH {
+                    return None;
+                }
+                let mut ping_hash = [0u8; 32];
+                ping_hash.copy_from_slice(&body[..32]);
+                Packet::Pong(ping_hash, NodeRecord::decode(&body[32..])?)
+            }
+            FINDNODE => {
+                if body.len() != 32 {
+                    return None;
+                }
+                let mut target = [0u8; 32];
+                target.copy_from_slice(body);
+                Packet::FindNode(target)
+            }
+            NEIGHBOURS => {
+                if body.is_empty() {
+                    return None;
+                }
+                let count = body[0] as usize;
+                let entries = &body[1..];
+                if count > NEIGHBOURS_PER_PACKET || entries.len() != count * (4 + RECORD_LENGTH) {
+                    return None;
+                }
+                let mut nodes = Vec::with_capacity(count);
+                for entry in entries.chunks(4 + RECORD_LENGTH) {
+                    let ip = Ipv4Addr::new(entry[0], entry[1], entry[2], entry[3]);
+                    nodes.push((ip, NodeRecord::decode(&entry[4..])?));
+                }
+                Packet::Neighbours(nodes)
+            }
+            _ => return None,
+        };
+
+        let mut sender = [0u8; PUBLIC_KEY_LENGTH];
+        sender.copy_from_slice(public);
+        Some((packet, sender, sha256(bytes)))
+    }
+}
+
+fn signed_data(packet: &[u8]) -> Vec<u8> {
+    let mut data = PACKET_LABEL.to_vec();
+    data.extend_from_slice(packet);
+    data
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use secure::NodeKey;
+    use super::*;
+
+    #[test]
+    fn test_packets_round_trip() {
+        let key = NodeKey::random();
+        let other = NodeKey::random();
+        let record = NodeRecord::new(&key, Ipv4Addr::new(10, 0, 0, 1), 30303, 30303, 1);
+        let other_record = NodeRecord::new(&other, Ipv4Addr::new(0, 0, 0, 0), 30304, 30305, 7);
+
+        let packets = vec![
+            Packet::Ping(record.clone()),
+            Packet::Pong([3u8; 32], record.clone()),
+            Packet::FindNode([5u8; 32]),
+            Packet::Neighbours(vec![(Ipv4Addr::new(10, 0, 0, 2), other_record.clone())]),
+        ];
+        for packet in packets {
+            let encoded = packet.encode(&key);
+            let (decoded, sender, hash) = Packet::decode(&encoded).unwrap();
+            assert_eq!(decoded, packet);
+            assert_eq!(&sender, key.public());
+            assert_eq!(hash, sha256(&encoded));
+        }
+    }
+
+    #[test]
+    fn test_tampering_is_rejected() {
+        let key = NodeKey::random();
+        let record = NodeRecord::new(&key, Ipv4Addr::new(10, 0, 0, 1), 30303, 30303, 1);
+        let mut encoded = Packet::Ping(record.clone()).encode(&key);
+        encoded[HEADER_LENGTH + PUBLIC_KEY_LENGTH] ^= 1;
+        assert!(Packet::decode(&encoded).is_none());
+
+        let mut bytes = Vec::new();
+        record.encode(&mut bytes);
+        assert_eq!(bytes.len(), RECORD_LENGTH);
+        assert_eq!(NodeRecord::decode(&bytes), Some(record));
+        bytes[PUBLIC_KEY_LENGTH + 4] ^= 1;
+        assert!(NodeRecord::decode(&bytes).is_none());
+    }
+}
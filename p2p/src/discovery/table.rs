@@ -0,0 +1,242 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Kademlia routing table: nodes are kept in buckets by the log distance of their id to
+//! the local id, each bucket holding the most recently seen nodes last.
+
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use super::packet::NodeRecord;
+
+pub const BUCKET_SIZE: usize = 16;
+const BUCKETS: usize = 256;
+/// Nodes of the same /24 subnet a bucket accepts, making it harder to eclipse a node
+/// from a handful of hosts.
+const MAX_PER_SUBNET: usize = 2;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub record: NodeRecord,
+    pub endpoint: SocketAddr,
+    pub last_seen: Instant,
+}
+
+impl Entry {
+    pub fn id(&self) -> [u8; 32] { self.record.id() }
+}
+
+/// Outcome of updating the table with a node answering a ping.
+#[derive(Debug, PartialEq)]
+pub enum Update {
+    Added,
+    Refreshed,
+    /// the bucket is full, the least recently seen entry should be checked before the node
+    /// may replace it
+    Full(Entry),
+    Rejected,
+}
+
+pub struct RoutingTable {
+    local: [u8; 32],
+    buckets: Vec<Vec<Entry>>,
+}
+
+impl RoutingTable {
+    pub fn new(local: [u8; 32]) -> RoutingTable {
+        RoutingTable {
+            local,
+            buckets: (0..BUCKETS).map(|_| Vec::new()).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize { self.buckets.iter().map(|bucket| bucket.len()).sum() }
+
+    pub fn contains(&self, id: &[u8; 32]) -> bool { self.get(id).is_some() }
+
+    pub fn get(&self, id: &[u8; 32]) -> Option<&Entry> {
+        let index = bucket_index(&self.local, id)?;
+        self.buckets[index].iter().find(|entry| &entry.id() == id)
+    }
+
+    pub fn entries(&self) -> Vec<&Entry> {
+        self.buckets.iter().flat_map(|bucket| bucket.iter()).collect()
+    }
+
+    /// Add or refresh a node which answered a ping.
+    pub fn update(&mut self, entry: Entry) -> Update {
+        let id = entry.id();
+        let index = match bucket_index(&self.local, &id) {
+            Some(index) => index,
+            None => return Update::Rejected,
+        };
+        let bucket = &mut self.buckets[index];
+
+        let position = bucket.iter().position(|known| known.id() == id);
+        if let Some(position) = position {
+            let mut known = bucket.remove(position);
+            known.endpoint = entry.endpoint;
+            known.last_seen = entry.last_seen;
+            if entry.record.seq >= known.record.seq {
+                known.record = entry.record;
+            }
+            bucket.push(known);
+            return Update::Refreshed;
+        }
+
+        let subnet = subnet(&entry.endpoint);
+        let same_subnet = bucket
+            .iter()
+            .filter(|known| subnet.is_some() && self::subnet(&known.endpoint) == subnet)
+            .count();
+        if same_subnet >= MAX_PER_SUBNET {
+            return Update::Rejected;
+        }
+
+        if bucket.len() >= BUCKET_SIZE {
+            return Update::Full(bucket[0].clone());
+        }
+        bucket.push(entry);
+        Update::Added
+    }
+
+    pub fn remove(&mut self, id: &[u8; 32]) -> Option<Entry> {
+        let index = bucket_index(&self.local, id)?;
+        let bucket = &mut self.buckets[index];
+        let position = bucket.iter().position(|entry| &entry.id() == id)?;
+        Some(bucket.remove(position))
+    }
+
+    /// The `count` nodes closest to `target`.
+    pub fn closest(&self, target: &[u8; 32], count: usize) -> Vec<Entry> {
+        let mut entries: Vec<&Entry> = self.entries();
+        entries.sort_by_key(|entry| distance(&entry.id(), target));
+        entries.into_iter().take(count).cloned().collect()
+    }
+}
+
+/// Xor distance of two ids.
+pub fn distance(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut distance = [0u8; 32];
+    for i in 0..32 {
+        distance[i] = a[i] ^ b[i];
+    }
+    distance
+}
+
+// bucket of `id`: its log distance to `local` minus one, none for the local id itself
+fn bucket_index(local: &[u8; 32], id: &[u8; 32]) -> Option<usize> {
+    let distance = distance(local, id);
+    let mut leading_zeros = 0;
+    for byte in distance.iter() {
+        if *byte == 0 {
+            leading_zeros += 8;
+        } else {
+            leading_zeros += byte.leading_zeros() as usize;
+            break;
+        }
+    }
+    if leading_zeros == BUCKETS {
+        None
+    } else {
+        Some(BUCKETS - 1 - leading_zeros)
+    }
+}
+
+fn subnet(endpoint: &SocketAddr) -> Option<[u8; 3]> {
+    match *endpoint {
+        SocketAddr::V4(ref addr) => {
+            let octets = addr.ip().octets();
+            // local networks are exempted so that test networks can run on one host
+            if addr.ip().is_loopback() || addr.ip().is_private() {
+                None
+            } else {
+                Some([octets[0], octets[1], octets[2]])
+            }
+        }
+        SocketAddr::V6(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::time::Instant;
+    use secure::NodeKey;
+    use super::super::packet::NodeRecord;
+    use super::*;
+
+    fn entry(ip: [u8; 4]) -> Entry {
+        let key = NodeKey::random();
+        let ip = Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]);
+        Entry {
+            record: NodeRecord::new(&key, ip, 30303, 30303, 1),
+            endpoint: SocketAddr::new(ip.into(), 30303),
+            last_seen: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_bucket_index() {
+        let local = [0u8; 32];
+        assert_eq!(bucket_index(&local, &local), None);
+        let mut id = [0u8; 32];
+        id[31] = 1;
+        assert_eq!(bucket_index(&local, &id), Some(0));
+        id[0] = 0x80;
+        assert_eq!(bucket_index(&local, &id), Some(255));
+    }
+
+    #[test]
+    fn test_update_and_closest() {
+        let mut table = RoutingTable::new(NodeKey::random().public().clone());
+        let first = entry([10, 0, 0, 1]);
+        let second = entry([10, 0, 0, 2]);
+        assert_eq!(table.update(first.clone()), Update::Added);
+        assert_eq!(table.update(second.clone()), Update::Added);
+        assert_eq!(table.update(first.clone()), Update::Refreshed);
+        assert_eq!(table.len(), 2);
+
+        let closest = table.closest(&second.id(), 1);
+        assert_eq!(closest.len(), 1);
+        assert_eq!(closest[0].id(), second.id());
+
+        assert!(table.remove(&first.id()).is_some());
+        assert!(!table.contains(&first.id()));
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_subnet_limit() {
+        let mut table = RoutingTable::new([0xffu8; 32]);
+        let mut added = 0;
+        for i in 0..32 {
+            if let Update::Added = table.update(entry([1, 2, 3, i])) {
+                added += 1;
+            }
+        }
+        // random ids spread over a few buckets, each taking at most two nodes of the subnet
+        assert!(added >= MAX_PER_SUBNET);
+        for bucket in table.buckets.iter() {
+            assert!(bucket.len() <= MAX_PER_SUBNET);
+        }
+    }
+}
@@ -121,11 +121,13 @@ pub fn receive_res(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
             temp.addr.ip.copy_from_slice(ip);
             temp.addr.port = port.read_u32::<BigEndian>().unwrap_or(30303);
             temp.id.copy_from_slice(id);
+            temp.id_pinned = true;
             trace!(target:"p2p", "get nodes {}: id: {} addr: {} ",_i,temp.get_id_string(),temp.addr.to_string());
 
             // TODO: complete if should add
             temp_list.push(temp);
         }
+        p2p.discovered(&temp_list);
         if let Ok(mut lock) = p2p.temp.try_lock() {
             for t in temp_list.iter() {
                 lock.push_back(t.to_owned());
@@ -24,6 +24,7 @@ use bytes::BufMut;
 use byteorder::BigEndian;
 use byteorder::ByteOrder;
 use byteorder::ReadBytesExt;
+use capability::{self, Capability};
 use version::short_version;
 use ChannelBuffer;
 use node::MAX_REVISION_LENGTH;
@@ -31,7 +32,11 @@ use node::IP_LENGTH;
 use node::NODE_ID_LENGTH;
 use node::REVISION_PREFIX;
 use node::convert_ip_string;
+use node::Node;
 use route::Action;
+use route::Module;
+use reputation::Report;
+use secure::{self, Ephemeral, SessionKeys, AUTH_LENGTH};
 use state::STATE;
 use super::super::Mgr;
 
@@ -73,17 +78,32 @@ pub fn send(p2p: Mgr, hash: u64) {
     req.body.push((VERSION.len() / 2) as u8);
     req.body.put_slice(VERSION.as_bytes());
 
+    // write node key and ephemeral key
+    let ephemeral = Ephemeral::new();
+    req.body.put_slice(&secure::auth_request(&p2p.key, &ephemeral));
+
+    // write capabilities
+    req.body.put_slice(&capability::encode(&p2p.capabilities));
+
     // get bodylen
     req.head.len = req.body.len() as u32;
 
+    // keep the ephemeral key until the response arrives
+    if let Some(node_lock) = p2p.nodes.read().get(&hash) {
+        node_lock.write().ephemeral = Some(ephemeral);
+    }
+
     // send
     p2p.send(hash, req);
 }
 
 /// 1. decode handshake msg
-/// 2. validate and prove incoming connection to active
-/// 3. acknowledge sender if it is proved
+/// 2. validate incoming connection and accept it
+/// 3. acknowledge sender if it is validated
 /// 4. update new hash
+///
+/// a handshake request is not bound to the responder and can be replayed, so the node
+/// only turns active once its first frame passes under the session keys, see `confirm`
 pub fn receive_req(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
     debug!(target: "p2p", "handshake/receive_req");
 
@@ -104,6 +124,7 @@ pub fn receive_req(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
     let local_net_id = p2p.config.net_id;
     if peer_net_id != local_net_id {
         warn!(target: "p2p", "Node: {:?}, invalid net id {}, should be {}.", node_id, peer_net_id, local_net_id);
+        p2p.report(hash, Report::WrongNetId);
         return;
     }
 
@@ -123,16 +144,61 @@ pub fn receive_req(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
     let version_len = version_len[0] as usize;
 
     // check version length
-    if version_len * 2 != version.len() {
+    if version.len() < version_len * 2 + AUTH_LENGTH {
         debug!(target: "p2p", "handshake req with wrong version length" );
         return;
     }
 
+    // authenticate the node id and agree on session keys
+    let (_, auth_capabilities) = version.split_at(version_len * 2);
+    let (auth, capabilities) = auth_capabilities.split_at(AUTH_LENGTH);
+    let (initiator, ephemeral, session_keys) = match authenticate_req(node_id, auth) {
+        Some(authenticated) => authenticated,
+        None => {
+            p2p.report(hash, Report::InvalidHandshake);
+            p2p.remove_node(hash);
+            return;
+        }
+    };
+
+    // agree on the protocols spoken over the connection
+    let common = match agree(&p2p, capabilities) {
+        Some(common) => common,
+        None => {
+            debug!(target: "p2p", "Node: {}, no common protocol", String::from_utf8_lossy(node_id));
+            p2p.remove_node(hash);
+            return;
+        }
+    };
+
+    // only reserved nodes may connect in reserved only mode
+    let (reserved, provisional) = match p2p.nodes.read().get(&hash) {
+        Some(node_lock) => {
+            let node = node_lock.read();
+            (p2p.is_reserved(node_id, &node.addr.get_ip()), node.if_reserved)
+        }
+        None => return,
+    };
+    if !reserved && p2p.config.reserved_only {
+        debug!(target: "p2p", "Node: {}, not reserved", String::from_utf8_lossy(node_id));
+        p2p.remove_node(hash);
+        return;
+    }
+
+    // a node let in by a reserved ip takes a slot once its id turns out not to be reserved
+    if !reserved && provisional && !p2p.has_inbound_slot() {
+        debug!(target: "p2p", "Node: {}, max peers reached", String::from_utf8_lossy(node_id));
+        p2p.remove_node(hash);
+        return;
+    }
+
     let nodes_read = p2p.nodes.read();
     if let Some(node_lock) = nodes_read.get(&hash) {
         let mut node = node_lock.write();
-        debug!(target: "p2p", "inbound node state: connected -> active");
+        debug!(target: "p2p", "inbound node state: connected -> accepted");
         node.id.copy_from_slice(node_id);
+        node.if_reserved = reserved;
+        node.capabilities = common;
         let addr_ip = node.addr.ip;
         trace!(target: "p2p", "ip:{:?} - {:?}", addr_ip, ip);
         if ip == &[0u8; 8] {
@@ -143,10 +209,7 @@ pub fn receive_req(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
         let port = port.read_u32::<BigEndian>().unwrap_or(30303);
         trace!(target: "p2p", "port:{} - {}", node.addr.port, port);
         node.real_addr.port = port;
-        node.state = STATE::ACTIVE;
-        if let Ok(mut id_set) = p2p.nodes_id.lock() {
-            id_set.insert(node.get_id_string());
-        }
+        node.state = STATE::ACCEPTED;
         if revision_len > MAX_REVISION_LENGTH {
             node.revision[0..MAX_REVISION_LENGTH].copy_from_slice(&revision[..MAX_REVISION_LENGTH]);
         } else {
@@ -162,9 +225,13 @@ pub fn receive_req(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
         revision.insert_str(0, REVISION_PREFIX);
         res_body.push(revision.len() as u8);
         res_body.put_slice(revision.as_bytes());
+        res_body.put_slice(&secure::auth_response(&p2p.key, &ephemeral, initiator));
+        res_body.put_slice(&capability::encode(&p2p.capabilities));
         cb_out.body.put_slice(res_body.as_slice());
         cb_out.head.len = cb_out.body.len() as u32;
 
+        // the response is the last frame sent in plain
+        node.channel.lock().start(session_keys, false);
         let mut tx = node.tx.clone();
         match tx.try_send(cb_out) {
             Ok(_) => trace!(target: "p2p", "succeed sending handshake res"),
@@ -177,6 +244,7 @@ pub fn receive_req(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
 
 /// 1. decode handshake res msg
 /// 2. update outbound node to active
+/// 3. send a first encrypted frame so that the responder confirms the connection
 pub fn receive_res(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
     debug!(target: "p2p", "handshake/receive_res");
 
@@ -187,18 +255,51 @@ pub fn receive_res(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
     }
 
     let (_, revision) = cb_in.body.split_at(1);
-    let (revision_len, revision_bytes) = revision.split_at(1);
+    let (revision_len, rest) = revision.split_at(1);
     let revision_len = revision_len[0] as usize;
 
     // check revision length
-    if revision_len != revision_bytes.len() {
+    if rest.len() < revision_len + AUTH_LENGTH {
         debug!(target: "p2p", "handshake req with wrong revision length" );
         return;
     }
+    let (revision_bytes, auth_capabilities) = rest.split_at(revision_len);
+    let (auth, capabilities) = auth_capabilities.split_at(AUTH_LENGTH);
 
-    let nodes_read = p2p.nodes.read();
-    if let Some(node_lock) = nodes_read.get(&hash) {
+    // agree on the protocols spoken over the connection
+    let common = match agree(&p2p, capabilities) {
+        Some(common) => common,
+        None => {
+            debug!(target: "p2p", "handshake res with no common protocol");
+            p2p.remove_node(hash);
+            return;
+        }
+    };
+
+    // authenticate the responder against the request sent and agree on session keys
+    let authenticated = match p2p.nodes.read().get(&hash) {
+        Some(node_lock) => authenticate_res(&mut node_lock.write(), auth),
+        None => return,
+    };
+    let (id, session_keys) = match authenticated {
+        Some(authenticated) => authenticated,
+        None => {
+            p2p.report(hash, Report::InvalidHandshake);
+            p2p.remove_node(hash);
+            return;
+        }
+    };
+
+    {
+        let nodes_read = p2p.nodes.read();
+        let node_lock = match nodes_read.get(&hash) {
+            Some(node_lock) => node_lock,
+            None => return,
+        };
         let mut node = node_lock.write();
+        node.channel.lock().start(session_keys, true);
+        node.id = id;
+
         if revision_len > MAX_REVISION_LENGTH {
             node.revision[0..MAX_REVISION_LENGTH]
                 .copy_from_slice(&revision_bytes[..MAX_REVISION_LENGTH]);
@@ -206,9 +307,125 @@ pub fn receive_res(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
             node.revision[0..revision_len].copy_from_slice(revision_bytes);
         }
 
+        debug!(target: "p2p", "Node: {}, protocols: {}", node.get_id_string(),
+            common.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" "));
+        node.capabilities = common;
+
         node.state = STATE::ACTIVE;
         if let Ok(mut id_set) = p2p.nodes_id.lock() {
             id_set.insert(node.get_id_string());
         }
+        p2p.seen(&node);
+    }
+    p2p.send(hash, channel_buffer_template(Action::ACTIVENODESREQ.value()));
+}
+
+/// turn an accepted inbound node active on its first frame under the session keys, which
+/// only the holder of the initiator ephemeral key can send. the node is dropped if its id
+/// has meanwhile been taken by another connection. returns whether the node is active
+pub fn confirm(p2p: &Mgr, hash: u64) -> bool {
+    let taken = {
+        let nodes_read = p2p.nodes.read();
+        let node_lock = match nodes_read.get(&hash) {
+            Some(node_lock) => node_lock,
+            None => return false,
+        };
+        let mut node = node_lock.write();
+        if node.state != STATE::ACCEPTED {
+            return node.is_active();
+        }
+        let taken = match p2p.nodes_id.lock() {
+            Ok(mut id_set) => !id_set.insert(node.get_id_string()),
+            Err(_) => return false,
+        };
+        if !taken {
+            debug!(target: "p2p", "inbound node state: accepted -> active");
+            node.state = STATE::ACTIVE;
+            p2p.seen(&node);
+        }
+        taken
+    };
+    if taken {
+        debug!(target: "p2p", "hash {}, node id already connected", hash);
+        p2p.remove_node(hash);
+    }
+    !taken
+}
+
+/// authenticate the node id of the initiator and agree on session keys,
+/// returning the initiator ephemeral key along with the ones of the responder
+fn authenticate_req<'a>(
+    node_id: &[u8],
+    auth: &'a [u8],
+) -> Option<(&'a [u8], Ephemeral, SessionKeys)>
+{
+    let (public, initiator) = match secure::check_request(auth) {
+        Some(keys) => keys,
+        None => {
+            warn!(target: "p2p", "handshake req with invalid signature");
+            return None;
+        }
+    };
+    if secure::node_id(public)[..] != node_id[..] {
+        warn!(target: "p2p", "Node: {}, id does not match its node key",
+            String::from_utf8_lossy(node_id));
+        return None;
+    }
+    let ephemeral = Ephemeral::new();
+    let session_keys = match ephemeral.agree(initiator, false) {
+        Some(session_keys) => session_keys,
+        None => {
+            warn!(target: "p2p", "handshake req with invalid ephemeral key");
+            return None;
+        }
+    };
+    Some((initiator, ephemeral, session_keys))
+}
+
+/// authenticate the responder against the request sent to the node and agree on
+/// session keys, returning the node id of the responder
+fn authenticate_res(
+    node: &mut Node,
+    auth: &[u8],
+) -> Option<([u8; NODE_ID_LENGTH], SessionKeys)>
+{
+    let ephemeral = match node.ephemeral.take() {
+        Some(ephemeral) => ephemeral,
+        None => {
+            debug!(target: "p2p", "unexpected handshake res");
+            return None;
+        }
+    };
+    let (public, responder) = match secure::check_response(auth, ephemeral.public()) {
+        Some(keys) => keys,
+        None => {
+            warn!(target: "p2p", "handshake res with invalid signature");
+            return None;
+        }
+    };
+    let id = secure::node_id(public);
+    if node.id_pinned && id[..] != node.id[..] {
+        warn!(target: "p2p", "Node: {}, id does not match its node key", node.get_id_string());
+        return None;
+    }
+    let session_keys = match ephemeral.agree(responder, true) {
+        Some(session_keys) => session_keys,
+        None => {
+            warn!(target: "p2p", "handshake res with invalid ephemeral key");
+            return None;
+        }
+    };
+    Some((id, session_keys))
+}
+
+/// decode the capabilities of the remote node and negotiate the common ones,
+/// the p2p protocol being mandatory
+fn agree(p2p: &Mgr, capabilities: &[u8]) -> Option<Vec<Capability>> {
+    let remote = capability::decode(capabilities)?;
+    let common = capability::negotiate(&p2p.capabilities, &remote);
+    if common.iter().any(|c| c.module == Module::P2P.value()) {
+        Some(common)
+    } else {
+        None
     }
 }
@@ -21,6 +21,7 @@
 
 pub mod active_nodes;
 pub mod handshake;
+pub mod ping;
 
 use super::{ChannelBuffer,PROTOCAL_VERSION,Module};
 
@@ -0,0 +1,124 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+use std::mem;
+use std::time::Instant;
+use bytes::BufMut;
+use byteorder::BigEndian;
+use byteorder::ByteOrder;
+use rand::random;
+use ChannelBuffer;
+use route::Action;
+use reputation::Report;
+use super::super::Mgr;
+use super::{channel_buffer_template, channel_buffer_template_with_version};
+
+/// pings a node may leave unanswered before it is disconnected
+pub const MAX_MISSED_PINGS: u32 = 3;
+
+/// ping active nodes, disconnecting nodes which stopped answering
+pub fn send(p2p: Mgr) {
+    let mut pings = Vec::new();
+    let mut dead = Vec::new();
+    {
+        let nodes_read = p2p.nodes.read();
+        for (hash, node_lock) in nodes_read.iter() {
+            let mut node = node_lock.write();
+            if !node.is_active() {
+                continue;
+            }
+            if node.ping.is_some() {
+                node.missed_pings += 1;
+                // nodes which never answered may not know ping, the node timeout covers them
+                if node.latency.is_some() && node.missed_pings >= MAX_MISSED_PINGS {
+                    dead.push(*hash);
+                    continue;
+                }
+            }
+            let nonce = random::<u64>();
+            node.ping = Some((nonce, Instant::now()));
+            pings.push((*hash, nonce));
+        }
+    }
+
+    for hash in dead {
+        debug!(target: "p2p", "ping/send: missed pings, remove hash {}", hash);
+        p2p.report(hash, Report::Timeout);
+        p2p.remove_node(hash);
+    }
+
+    for (hash, nonce) in pings {
+        trace!(target: "p2p", "ping/send: hash {}", hash);
+        let mut req = channel_buffer_template(Action::PING.value());
+        let mut nonce_bytes = [0; 8];
+        BigEndian::write_u64(&mut nonce_bytes, nonce);
+        req.body.put_slice(&nonce_bytes);
+        req.head.len = req.body.len() as u32;
+        p2p.send(hash, req);
+    }
+}
+
+/// answer a ping with its nonce
+pub fn receive_req(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
+    trace!(target: "p2p", "ping/receive_req");
+
+    if cb_in.body.len() != mem::size_of::<u64>() {
+        debug!(target: "p2p", "ping req with wrong length");
+        return;
+    }
+
+    let mut cb_out = channel_buffer_template_with_version(cb_in.head.ver, Action::PONG.value());
+    cb_out.body.put_slice(&cb_in.body);
+    cb_out.head.len = cb_out.body.len() as u32;
+    p2p.send(hash, cb_out);
+}
+
+/// measure the round trip of the pending ping
+pub fn receive_res(p2p: Mgr, hash: u64, cb_in: ChannelBuffer) {
+    trace!(target: "p2p", "ping/receive_res");
+
+    if cb_in.body.len() != mem::size_of::<u64>() {
+        debug!(target: "p2p", "ping res with wrong length");
+        return;
+    }
+    let nonce = BigEndian::read_u64(&cb_in.body);
+
+    let nodes_read = p2p.nodes.read();
+    if let Some(node_lock) = nodes_read.get(&hash) {
+        let mut node = node_lock.write();
+        let ping = node.ping;
+        match ping {
+            Some((pending, sent)) if pending == nonce => {
+                let rtt = sent.elapsed();
+                let latency = match node.latency {
+                    Some(latency) => (latency * 3 + rtt) / 4,
+                    None => rtt,
+                };
+                node.latency = Some(latency);
+                node.ping = None;
+                node.missed_pings = 0;
+                node.update();
+                trace!(target: "p2p", "ping/receive_res: hash/rtt {}/{:?}", hash, rtt);
+            }
+            _ => debug!(target: "p2p", "ping res with stale nonce"),
+        }
+    }
+}
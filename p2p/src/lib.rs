@@ -38,6 +38,8 @@ extern crate aion_version as version;
 extern crate bytes;
 extern crate byteorder;
 extern crate parking_lot;
+extern crate crypto;
+extern crate lru_cache;
 
 #[cfg(test)]
 mod test;
@@ -49,6 +51,12 @@ mod codec;
 mod state;
 mod handler;
 mod callable;
+mod secure;
+mod reputation;
+mod peer_store;
+mod discovery;
+mod capability;
+mod traffic;
 
 use std::io;
 use std::sync::{Arc,Weak};
@@ -59,38 +67,54 @@ use std::time::Duration;
 use std::time::Instant;
 use std::net::TcpStream as StdTcpStream;
 use std::net::Shutdown;
-use std::net::SocketAddr;
+use std::net::{Ipv4Addr, SocketAddr};
 use rand::random;
 use futures::prelude::*;
 use futures::sync::mpsc;
 use futures::{Future, Stream};
 use futures::lazy;
+use futures::future;
 use futures::sync::oneshot;
 use futures::sync::oneshot::Sender;
 use tokio::net::TcpListener;
 use tokio::net::TcpStream;
+use tokio::net::{UdpFramed, UdpSocket};
 use tokio::prelude::*;
 use tokio::runtime::TaskExecutor;
-use tokio::timer::Interval;
+use tokio::timer::{Delay, Interval};
 use tokio_reactor::Handle;
 use tokio_codec::{Decoder,Framed};
-use codec::Codec;
+use codec::{Codec, DatagramCodec};
 use route::Version;
 use route::Action;
 use state::STATE;
 use handler::handshake;
 use handler::active_nodes;
-use node::TempNode;
+use handler::ping;
+use node::{Connection, IpAddr, TempNode, HEADER_LENGTH};
 use parking_lot::RwLock as RwLockP;
+use parking_lot::Mutex as MutexP;
+use secure::{NodeKey, SecureChannel};
+use reputation::Reputation;
+use peer_store::PeerStore;
+use discovery::Discovery;
+use traffic::{Limiter, TrafficStats};
 
 pub use msg::ChannelBuffer;
 pub use node::Node;
 pub use config::Config;
 pub use callable::Callable;
+pub use reputation::{pick_preferred, Report};
+pub use capability::Capability;
+pub use traffic::{Counter, Traffic};
 
 const INTERVAL_OUTBOUND_CONNECT: u64 = 1;
 const INTERVAL_TIMEOUT: u64 = 5;
 const INTERVAL_ACTIVE_NODES: u64 = 3;
+const INTERVAL_PEER_STORE: u64 = 60;
+const INTERVAL_DISCOVERY: u64 = 1;
+const INTERVAL_PING: u64 = 5;
+const INTERVAL_RESERVED: u64 = 10;
 const TIMEOUT_MAX: u64 = 30;
 const TEMP_MAX: usize = 64;
 
@@ -113,13 +137,53 @@ pub struct Mgr {
     tokens_rule: Arc<HashMap<u32, u32>>,
     /// nodes ID
     nodes_id: Arc<Mutex<HashSet<String>>>,
+    /// local node key, authenticates the local node id in handshakes
+    key: Arc<NodeKey>,
+    /// scores and bans by ip
+    reputation: Arc<MutexP<Reputation>>,
+    /// known nodes kept across restarts
+    peer_store: Arc<MutexP<PeerStore>>,
+    /// udp node discovery, exchanging active nodes with peers instead if disabled
+    discovery: Option<Arc<MutexP<Discovery>>>,
+    /// nodes always kept connected
+    reserved: Arc<Vec<TempNode>>,
+    /// protocols of the local node, advertised in handshakes
+    capabilities: Arc<Vec<Capability>>,
+    /// traffic with all nodes by module and action
+    traffic: Arc<MutexP<TrafficStats>>,
+    /// upload rate limit
+    upload: Arc<MutexP<Limiter>>,
+    /// download rate limit
+    download: Arc<MutexP<Limiter>>,
 }
 
 impl Mgr {
-    /// constructor
-    pub fn new(mut config: Config, tokens_pairs: Vec<[u32; 2]>) -> Mgr {
+    /// constructor, `capabilities` being the protocols of upper layer modules
+    pub fn new(
+        mut config: Config,
+        tokens_pairs: Vec<[u32; 2]>,
+        mut capabilities: Vec<Capability>,
+    ) -> Mgr
+    {
+        // load node key, the local node id is derived from it
+        let key = match config.node_key_path {
+            Some(ref path) => {
+                NodeKey::load_or_create(path).unwrap_or_else(|e| {
+                    warn!(target: "p2p", "failed to load node key {:?}: {}", path, e);
+                    NodeKey::random()
+                })
+            }
+            None => NodeKey::random(),
+        };
+
         // load local node
-        let temp_local = TempNode::new_from_str(config.local_node);
+        let mut temp_local = TempNode::new_from_str(config.local_node);
+        if temp_local.id_pinned && temp_local.id[..] != key.id()[..] {
+            warn!(target: "p2p", "configured node id {} replaced by the id of the node key",
+                temp_local.get_id_string());
+        }
+        temp_local.id = key.id();
+        info!(target: "run", "          id: {}", temp_local.get_id_string());
         config.local_node = format!(
             "p2p://{}@{}",
             temp_local.get_id_string(),
@@ -128,11 +192,43 @@ impl Mgr {
         let mut id_set = HashSet::new();
         id_set.insert(temp_local.get_id_string());
 
-        // load seeds
+        // load reserved nodes, dialed ahead of seeds
+        let mut reserved = Vec::new();
+        for reserved_str in &config.reserved_nodes {
+            match TempNode::parse(reserved_str) {
+                Some(mut temp) => {
+                    info!(target: "run", "    reserved: {}", reserved_str);
+                    temp.if_seed = false;
+                    temp.if_reserved = true;
+                    reserved.push(temp);
+                }
+                None => warn!(target: "p2p", "invalid reserved node {}", reserved_str),
+            }
+        }
         let mut temp_queue = VecDeque::<TempNode>::with_capacity(TEMP_MAX);
-        for boot_node_str in config.boot_nodes.clone() {
-            info!(target: "run", "        seed: {}", &boot_node_str);
-            temp_queue.push_back(TempNode::new_from_str(boot_node_str.to_string()));
+        temp_queue.extend(reserved.iter().cloned());
+
+        // load seeds
+        let peer_store = PeerStore::new(config.peers_path.clone());
+        if !config.reserved_only {
+            let mut boot_addrs = Vec::new();
+            for boot_node_str in config.boot_nodes.clone() {
+                info!(target: "run", "        seed: {}", &boot_node_str);
+                let temp = TempNode::new_from_str(boot_node_str.to_string());
+                boot_addrs.push(temp.addr);
+                temp_queue.push_back(temp);
+            }
+
+            // load nodes known from previous runs
+            for temp in peer_store.seeds(TEMP_MAX) {
+                if temp_queue.len() >= TEMP_MAX {
+                    break;
+                }
+                if !boot_addrs.contains(&temp.addr) {
+                    temp_queue.push_back(temp);
+                }
+            }
+            info!(target: "p2p", "loaded {} known nodes", peer_store.len());
         }
 
         // parse token rules
@@ -149,6 +245,45 @@ impl Mgr {
             p2p_rule_base + Action::ACTIVENODESRES.value() as u32,
             p2p_rule_base + Action::ACTIVENODESREQ.value() as u32,
         );
+        tokens_rule.insert(
+            p2p_rule_base + Action::PONG.value() as u32,
+            p2p_rule_base + Action::PING.value() as u32,
+        );
+
+        capabilities.insert(
+            0,
+            Capability::new(
+                Module::P2P.value(),
+                PROTOCAL_VERSION,
+                vec![
+                    Action::HANDSHAKEREQ.value(),
+                    Action::HANDSHAKERES.value(),
+                    Action::PING.value(),
+                    Action::PONG.value(),
+                    Action::ACTIVENODESREQ.value(),
+                    Action::ACTIVENODESRES.value(),
+                ],
+            ),
+        );
+
+        let key = Arc::new(key);
+        let discovery = if config.discovery && !config.reserved_only {
+            let (ip, port) = config.get_ip_and_port();
+            let ip = ip.parse::<Ipv4Addr>().unwrap_or(Ipv4Addr::new(0, 0, 0, 0));
+            let boot_nodes = config
+                .boot_nodes
+                .iter()
+                .map(|boot_node_str| TempNode::new_from_str(boot_node_str.to_string()))
+                .collect();
+            let discovery = Discovery::new(key.clone(), ip, port as u16, boot_nodes);
+            Some(Arc::new(MutexP::new(discovery)))
+        } else {
+            None
+        };
+
+        let reputation = Reputation::new(config.ip_black_list.clone(), config.bans_path.clone());
+        let upload = Limiter::new(config.max_upload);
+        let download = Limiter::new(config.max_download);
 
         Mgr {
             shutdown_hooks: Arc::new(Mutex::new(Vec::new())),
@@ -158,6 +293,15 @@ impl Mgr {
             nodes: Arc::new(RwLockP::new(HashMap::new())),
             tokens_rule: Arc::new(tokens_rule),
             nodes_id: Arc::new(Mutex::new(id_set)),
+            key,
+            reputation: Arc::new(MutexP::new(reputation)),
+            peer_store: Arc::new(MutexP::new(peer_store)),
+            discovery,
+            reserved: Arc::new(reserved),
+            capabilities: Arc::new(capabilities),
+            traffic: Arc::new(MutexP::new(TrafficStats::new())),
+            upload: Arc::new(MutexP::new(upload)),
+            download: Arc::new(MutexP::new(download)),
         }
     }
 
@@ -207,6 +351,12 @@ impl Mgr {
         let ip;
         if let Some(node_lock) = nodes.read().get(&hash) {
             let node = node_lock.read();
+            // only send what the node agreed on in the handshake
+            if node.is_active() && !node.supports(cb.head.ctrl, cb.head.action) {
+                trace!(target: "p2p", "send: hash {} does not support ctrl/action {}/{}",
+                    hash, cb.head.ctrl, cb.head.action);
+                return false;
+            }
             ip = node.addr.get_ip();
             tx_send = Some(node.tx.clone());
         } else {
@@ -217,6 +367,8 @@ impl Mgr {
         if let Some(mut tx) = tx_send {
             let mut send_success = false;
             let route = cb.head.get_route();
+            let (ctrl, action) = (cb.head.ctrl, cb.head.action);
+            let size = HEADER_LENGTH + cb.body.len();
             match tx.try_send(cb) {
                 Ok(_) => {
                     send_success = true;
@@ -238,17 +390,21 @@ impl Mgr {
                     }
                 }
                 if let Some(node) = removed_node {
-                    self.disconnect(hash, node.get_id_string());
+                    self.disconnect(hash, node.held_id());
                 }
             } else {
                 if let Some(node_lock) = nodes.read().get(&hash) {
                     let mut node = node_lock.write();
                     node.tokens.insert(route);
+                    node.traffic.sent.add(size);
                 } else {
                     warn!(target:"p2p", "send: node not found hash {}", hash);
                     return false;
                 }
             }
+            if send_success {
+                self.traffic.lock().sent(ctrl, action, size);
+            }
             send_success
         } else {
             warn!(target:"p2p", "unreachable!!");
@@ -288,7 +444,7 @@ impl Mgr {
                     }
                 }
                 if index.len() > 0 {
-                    let mut removed_nodes: HashMap<u64, String> = HashMap::new();
+                    let mut removed_nodes: HashMap<u64, Option<String>> = HashMap::new();
                     {
                         let mut nodes_write = p2p_timeout.nodes.write();
                         for i in 0 .. index.len() {
@@ -296,16 +452,17 @@ impl Mgr {
                             match nodes_write.remove(&hash) {
                                 Some(node_lock) => {
                                     let mut node = node_lock.write();
+                                    p2p_timeout.reputation.lock().report(&node.addr.get_ip(), Report::Timeout);
                                     node.tx.close().unwrap();
-                                    removed_nodes.insert(hash, node.get_id_string());
+                                    removed_nodes.insert(hash, node.held_id());
                                     debug!(target: "p2p", "timeout hash/id/ip {}/{}/{}", &node.get_hash(), &node.get_id_string(), &node.addr.to_string());
                                 },
                                 None => {}
                             }
                         }
                     }
-                    for (hash, id_string) in removed_nodes {
-                        p2p_timeout.disconnect(hash, id_string);
+                    for (hash, id) in removed_nodes {
+                        p2p_timeout.disconnect(hash, id);
                     }
                 }
                 Ok(())
@@ -319,6 +476,24 @@ impl Mgr {
             shutdown_hooks.push(tx);
         }
 
+        // interval ping
+        let p2p_ping = self.clone();
+        let (tx, rx) = oneshot::channel::<()>();
+        executor.spawn(
+            Interval::new(Instant::now(), Duration::from_secs(INTERVAL_PING))
+                .for_each(move |_| {
+                    ping::send(p2p_ping.clone());
+                    Ok(())
+                })
+                .map_err(|err| error!(target: "p2p", "executor ping: {:?}", err))
+                .select(rx.map_err(|_| {}))
+                .map(|_| ())
+                .map_err(|_| ()),
+        );
+        if let Ok(mut shutdown_hooks) = self.shutdown_hooks.lock() {
+            shutdown_hooks.push(tx);
+        }
+
         // interval outbound
         let executor_outbound_0 = executor.clone();
         let p2p_outbound = self.clone();
@@ -343,6 +518,21 @@ impl Mgr {
                                     temp_node_opt = None;
                                 }
                             }
+                            // reserved nodes are never banned
+                            let ip = temp_node.addr.get_ip();
+                            if !temp_node.if_reserved
+                                && !p2p_outbound.is_reserved_ip(&ip)
+                                && p2p_outbound.reputation.lock().is_banned(&ip)
+                            {
+                                temp_node_opt = None;
+                            }
+                            // reserved nodes are dialed beyond the peer limits
+                            if !temp_node.if_reserved
+                                && (p2p_outbound.config.reserved_only
+                                    || !p2p_outbound.has_outbound_slot())
+                            {
+                                temp_node_opt = None;
+                            }
                             // store back if seed node immediately
                             if temp_node.if_seed {
                                 lock.push_back(temp_node);
@@ -383,6 +573,7 @@ impl Mgr {
 
                                     let p2p_outbound_1 = p2p_outbound_0.clone();
                                     let p2p_outbound_2 = p2p_outbound_0.clone();
+                                    let p2p_outbound_3 = p2p_outbound_0.clone();
 
                                     // config stream
                                     match config_stream(&ts){
@@ -396,14 +587,19 @@ impl Mgr {
                                     // construct node instance and store it
                                     let (mut tx, rx) = mpsc::channel(409600);
                                     let (mut tx_thread, rx_thread) = oneshot::channel::<()>();
+                                    let channel;
                                     if let Ok(ts_0) = ts.try_clone() {
-                                        let node = Node::new_outbound(
+                                        let mut node = Node::new_outbound(
                                             ts_0,
                                             tx,
                                             temp_node.id,
                                             temp_node.if_seed,
                                             tx_thread,
                                         );
+                                        node.id_pinned = temp_node.id_pinned;
+                                        node.if_reserved = temp_node.if_reserved;
+                                        node.score = p2p_outbound_0.reputation.lock().score(&node.addr.get_ip());
+                                        channel = node.channel.clone();
 
                                         let mut new_node = false;
                                         {
@@ -426,12 +622,21 @@ impl Mgr {
                                     }
 
                                     // binding io futures
-                                    let (sink, stream) = split_frame(ts);
+                                    let (sink, stream) = split_frame(ts, channel);
+                                    let download = p2p_outbound_0.download.clone();
                                     let read = stream.for_each(move |cb| {
+                                        let wait = throttle(&download, &cb);
                                         p2p_outbound_2.handle(hash.clone(), cb);
-                                        Ok(())
+                                        wait.map_err(|_| {
+                                            io::Error::new(io::ErrorKind::Other, "throttle")
+                                        })
+                                    })
+                                    .map_err(move |err| {
+                                        if err.kind() == io::ErrorKind::InvalidData {
+                                            p2p_outbound_3.report(hash, Report::MalformedFrame);
+                                        }
+                                        trace!(target: "p2p", "tcp outbound read: {:?}", err)
                                     })
-                                    .map_err(|err| trace!(target: "p2p", "tcp outbound read: {:?}", err))
                                     .select(rx_thread.map_err(|_| {}))
                                     .map(|_| ())
                                     .map_err(|_| ());
@@ -440,8 +645,10 @@ impl Mgr {
                                         Ok(())
                                     }));
 
+                                    let upload = p2p_outbound_0.upload.clone();
                                     let write = sink.send_all(
-                                        rx.map_err(|()| io::Error::new(io::ErrorKind::Other, "rx shouldn't have an error")),
+                                        rx.and_then(move |cb| throttle(&upload, &cb).map(|_| cb))
+                                            .map_err(|()| io::Error::new(io::ErrorKind::Other, "rx shouldn't have an error")),
                                     );
                                     executor_outbound_2.spawn(write.then(|_| { Ok(()) }));
 
@@ -453,7 +660,7 @@ impl Mgr {
                                 }
                             },
                             Err(_err) => {
-
+                                p2p_outbound_0.peer_store.lock().failed(&temp_node.addr.to_string());
                             }
                         }
                     }
@@ -469,17 +676,61 @@ impl Mgr {
             shutdown_hooks.push(tx);
         }
 
-        // interval active nodes
-        let p2p_active_nodes = self.clone();
+        match self.discovery {
+            Some(ref discovery) => self.run_discovery(&executor, discovery.clone(), &binding),
+            None => {
+                // interval active nodes
+                let p2p_active_nodes = self.clone();
+                let (tx, rx) = oneshot::channel::<()>();
+                executor.spawn(
+                    Interval::new(Instant::now(), Duration::from_secs(INTERVAL_ACTIVE_NODES))
+                        .for_each(move |_| {
+                            let p2p_active_nodes_0 = p2p_active_nodes.clone();
+                            active_nodes::send(p2p_active_nodes_0);
+                            Ok(())
+                        })
+                        .map_err(|err| error!(target: "p2p", "executor active nodes: {:?}", err))
+                        .select(rx.map_err(|_| {}))
+                        .map(|_| ())
+                        .map_err(|_| ()),
+                );
+                if let Ok(mut shutdown_hooks) = self.shutdown_hooks.lock() {
+                    shutdown_hooks.push(tx);
+                }
+            }
+        }
+
+        // interval reserved nodes
+        if !self.reserved.is_empty() {
+            let p2p_reserved = self.clone();
+            let (tx, rx) = oneshot::channel::<()>();
+            executor.spawn(
+                Interval::new(Instant::now(), Duration::from_secs(INTERVAL_RESERVED))
+                    .for_each(move |_| {
+                        p2p_reserved.connect_reserved();
+                        Ok(())
+                    })
+                    .map_err(|err| error!(target: "p2p", "executor reserved: {:?}", err))
+                    .select(rx.map_err(|_| {}))
+                    .map(|_| ())
+                    .map_err(|_| ()),
+            );
+            if let Ok(mut shutdown_hooks) = self.shutdown_hooks.lock() {
+                shutdown_hooks.push(tx);
+            }
+        }
+
+        // interval peer store
+        let p2p_peer_store = self.clone();
         let (tx, rx) = oneshot::channel::<()>();
         executor.spawn(
-            Interval::new(Instant::now(), Duration::from_secs(INTERVAL_ACTIVE_NODES))
+            Interval::new(Instant::now(), Duration::from_secs(INTERVAL_PEER_STORE))
                 .for_each(move |_| {
-                    let p2p_active_nodes_0 = p2p_active_nodes.clone();
-                    active_nodes::send(p2p_active_nodes_0);
+                    p2p_peer_store.save_peers();
+                    p2p_peer_store.reseed();
                     Ok(())
                 })
-                .map_err(|err| error!(target: "p2p", "executor active nodes: {:?}", err))
+                .map_err(|err| error!(target: "p2p", "executor peer store: {:?}", err))
                 .select(rx.map_err(|_| {}))
                 .map(|_| ())
                 .map_err(|_| ()),
@@ -499,11 +750,30 @@ impl Mgr {
             .for_each(move |ts: TcpStream| {
                 // counters
                 let p2p_inbound_1 = p2p_inbound.clone();
+                let p2p_inbound_2 = p2p_inbound.clone();
+
+                // refuse black listed and banned ips, other than reserved ones
+                let mut reserved = false;
+                if let Ok(addr) = ts.peer_addr() {
+                    let ip = IpAddr::parse(addr).get_ip();
+                    reserved = p2p_inbound.is_reserved_ip(&ip);
+                    if !reserved && p2p_inbound.reputation.lock().is_banned(&ip) {
+                        debug!(target: "p2p", "refused banned ip {}", ip);
+                        return Ok(());
+                    }
+                }
 
-                // TODO: black list check
-                if p2p_inbound.get_active_nodes_len() >= p2p_inbound.config.max_peers {
-                    debug!(target:"p2p", "max peers reached");
-                    return Ok(());
+                // reserved ips are accepted beyond the peer limits, provisionally until the
+                // handshake authenticates the node id
+                if !reserved {
+                    if p2p_inbound.config.reserved_only {
+                        debug!(target: "p2p", "refused node not reserved");
+                        return Ok(());
+                    }
+                    if !p2p_inbound.has_inbound_slot() {
+                        debug!(target:"p2p", "max peers reached");
+                        return Ok(());
+                    }
                 }
 
                 // config stream
@@ -519,13 +789,16 @@ impl Mgr {
                 let (tx_channel, rx_channel) = mpsc::channel(409600);
                 let (tx_thread, rx_thread) = oneshot::channel::<()>();
                 if let Ok(ts_0) = ts.try_clone() {
-                    let node = Node::new_inbound(
+                    let mut node = Node::new_inbound(
                         ts_0,
                         tx_channel,
                         false,
                         tx_thread,
                     );
+                    node.if_reserved = reserved;
+                    node.score = p2p_inbound.reputation.lock().score(&node.addr.get_ip());
                     let hash = node.get_hash();
+                    let channel = node.channel.clone();
 
                     let mut new_node = false;
                     {
@@ -544,19 +817,31 @@ impl Mgr {
                     }
 
                     // binding io futures
-                    let (sink, stream) = split_frame(ts);
+                    let (sink, stream) = split_frame(ts, channel);
+                    let download = p2p_inbound.download.clone();
+                    let upload = p2p_inbound.upload.clone();
                     let read = stream.for_each(move |cb| {
+                        let wait = throttle(&download, &cb);
                         p2p_inbound_1.handle(hash.clone(), cb);
-                        Ok(())
+                        wait.map_err(|_| io::Error::new(io::ErrorKind::Other, "throttle"))
                     })
-                        .map_err(|err| trace!(target: "p2p", "tcp inbound read: {:?}", err))
+                        .map_err(move |err| {
+                            if err.kind() == io::ErrorKind::InvalidData {
+                                p2p_inbound_2.report(hash, Report::MalformedFrame);
+                            }
+                            trace!(target: "p2p", "tcp inbound read: {:?}", err)
+                        })
                         .select(rx_thread.map_err(|_| {}))
                         .map(|_| ())
                         .map_err(|_| ());
                     executor_inbound_0.spawn(read.then(|_| { Ok(()) }));
-                    let write = sink.send_all(rx_channel.map_err(|()| {
-                        io::Error::new(io::ErrorKind::Other, "rx shouldn't have an error")
-                    }));
+                    let write = sink.send_all(
+                        rx_channel
+                            .and_then(move |cb| throttle(&upload, &cb).map(|_| cb))
+                            .map_err(|()| {
+                                io::Error::new(io::ErrorKind::Other, "rx shouldn't have an error")
+                            }),
+                    );
                     executor_inbound_1.spawn(write.then(|_| { Ok(()) }));
                 } else {
                     trace!(target: "p2p", "failed to clone TcpStream, stop connecting to {}", &ts.peer_addr().unwrap().to_string());
@@ -573,9 +858,251 @@ impl Mgr {
         }
     }
 
-    fn disconnect(&self, hash: u64, id: String) {
-        if let Ok(mut id_set) = self.nodes_id.lock() {
-            id_set.remove(&id);
+    /// bind the discovery socket and run the discovery on it
+    fn run_discovery(
+        &self,
+        executor: &TaskExecutor,
+        discovery: Arc<MutexP<Discovery>>,
+        binding: &SocketAddr,
+    )
+    {
+        let socket = match UdpSocket::bind(binding) {
+            Ok(socket) => socket,
+            Err(e) => {
+                warn!(target: "p2p", "discovery disabled, failed to bind udp {}: {}", binding, e);
+                return;
+            }
+        };
+        let (sink, stream) = UdpFramed::new(socket, DatagramCodec).split();
+        let (tx_udp, rx_udp) = mpsc::channel::<(Vec<u8>, SocketAddr)>(1024);
+
+        // read
+        let discovery_read = discovery.clone();
+        let mut tx_read = tx_udp.clone();
+        let (tx, rx) = oneshot::channel::<()>();
+        executor.spawn(
+            stream
+                .for_each(move |(packet, from)| {
+                    let mut discovery = discovery_read.lock();
+                    discovery.handle(&packet, from);
+                    send_datagrams(&mut tx_read, discovery.take_outbox());
+                    Ok(())
+                })
+                .map_err(|err| error!(target: "p2p", "executor discovery: {:?}", err))
+                .select(rx.map_err(|_| {}))
+                .map(|_| ())
+                .map_err(|_| ()),
+        );
+        if let Ok(mut shutdown_hooks) = self.shutdown_hooks.lock() {
+            shutdown_hooks.push(tx);
+        }
+
+        // write
+        let write = sink.send_all(rx_udp.map_err(|()| {
+            io::Error::new(io::ErrorKind::Other, "rx shouldn't have an error")
+        }));
+        executor.spawn(write.then(|_| Ok(())));
+
+        // interval discovery
+        let p2p_discovery = self.clone();
+        let mut tx_tick = tx_udp;
+        let (tx, rx) = oneshot::channel::<()>();
+        executor.spawn(
+            Interval::new(Instant::now(), Duration::from_secs(INTERVAL_DISCOVERY))
+                .for_each(move |_| {
+                    let (datagrams, discovered) = {
+                        let mut discovery = discovery.lock();
+                        discovery.tick();
+                        (discovery.take_outbox(), discovery.take_discovered())
+                    };
+                    send_datagrams(&mut tx_tick, datagrams);
+                    if !discovered.is_empty() {
+                        p2p_discovery.discovered(&discovered);
+                        if let Ok(mut temp) = p2p_discovery.temp.lock() {
+                            for node in discovered {
+                                if temp.len() >= TEMP_MAX {
+                                    break;
+                                }
+                                temp.push_back(node);
+                            }
+                        }
+                    }
+                    Ok(())
+                })
+                .map_err(|err| error!(target: "p2p", "executor discovery interval: {:?}", err))
+                .select(rx.map_err(|_| {}))
+                .map(|_| ())
+                .map_err(|_| ()),
+        );
+        if let Ok(mut shutdown_hooks) = self.shutdown_hooks.lock() {
+            shutdown_hooks.push(tx);
+        }
+    }
+
+    /// record an active node in the peer store
+    fn seen(&self, node: &Node) { self.peer_store.lock().seen(node); }
+
+    /// record nodes learned from other nodes in the peer store
+    fn discovered(&self, temps: &[TempNode]) {
+        let mut peer_store = self.peer_store.lock();
+        for temp in temps {
+            peer_store.discovered(temp);
+        }
+    }
+
+    /// refresh active nodes in the peer store and write it
+    fn save_peers(&self) {
+        let active_nodes = self.get_active_nodes();
+        let mut peer_store = self.peer_store.lock();
+        for node in &active_nodes {
+            peer_store.seen(node);
+        }
+        peer_store.save();
+    }
+
+    /// dial stored nodes again once no node is active, e.g. with boot nodes unreachable
+    fn reseed(&self) {
+        if self.config.reserved_only || self.get_active_nodes_len() > 0 {
+            return;
+        }
+        let seeds = self.peer_store.lock().seeds(TEMP_MAX);
+        if let Ok(mut temp) = self.temp.lock() {
+            for seed in seeds {
+                if temp.len() >= TEMP_MAX {
+                    break;
+                }
+                if !temp.iter().any(|queued| queued.addr == seed.addr) {
+                    temp.push_back(seed);
+                }
+            }
+        }
+    }
+
+    /// queue the reserved nodes which are neither connected nor queued
+    fn connect_reserved(&self) {
+        let disconnected: Vec<TempNode> = self
+            .reserved
+            .iter()
+            .filter(|temp| !self.is_connected(temp))
+            .cloned()
+            .collect();
+        if let Ok(mut temp) = self.temp.lock() {
+            for reserved in disconnected {
+                if !temp.iter().any(|queued| queued.addr == reserved.addr) {
+                    debug!(target: "p2p", "reconnect reserved node {}", reserved.addr.to_string());
+                    temp.push_front(reserved);
+                }
+            }
+        }
+    }
+
+    /// whether a node authenticated as `id` from `ip` is reserved,
+    /// reserved nodes configured without an id are matched by ip
+    fn is_reserved(&self, id: &[u8], ip: &str) -> bool {
+        self.reserved.iter().any(|temp| {
+            if temp.id_pinned {
+                temp.id[..] == id[..]
+            } else {
+                temp.addr.get_ip() == ip
+            }
+        })
+    }
+
+    /// whether `ip` is the one of a reserved node, only telling before the handshake
+    /// that the node may be reserved
+    fn is_reserved_ip(&self, ip: &str) -> bool {
+        self.reserved.iter().any(|temp| temp.addr.get_ip() == ip)
+    }
+
+    /// count nodes other than reserved nodes as (inbound, outbound)
+    fn slots(&self) -> (u32, u32) {
+        let mut inbound = 0;
+        let mut outbound = 0;
+        for node_lock in self.nodes.read().values() {
+            let node = node_lock.read();
+            if node.if_reserved {
+                continue;
+            }
+            match node.connection {
+                Connection::INBOUND => inbound += 1,
+                Connection::OUTBOUND => outbound += 1,
+            }
+        }
+        (inbound, outbound)
+    }
+
+    fn has_inbound_slot(&self) -> bool {
+        let (inbound, outbound) = self.slots();
+        inbound < self.config.max_inbound && inbound + outbound < self.config.max_peers
+    }
+
+    fn has_outbound_slot(&self) -> bool {
+        let (inbound, outbound) = self.slots();
+        outbound < self.config.max_outbound && inbound + outbound < self.config.max_peers
+    }
+
+    /// whether a node is connected at the address or with the id of `temp`
+    fn is_connected(&self, temp: &TempNode) -> bool {
+        let connected = self.nodes.read().values().any(|node_lock| {
+            let node = node_lock.read();
+            node.addr == temp.addr || node.real_addr == temp.addr
+        });
+        let known_id = match self.nodes_id.lock() {
+            Ok(id_set) => temp.id_pinned && id_set.contains(&temp.get_id_string()),
+            Err(_) => false,
+        };
+        connected || known_id
+    }
+
+    /// apply a report to the score of a node, dropping it once its ip is banned.
+    /// reserved nodes are trusted by configuration and never scored nor banned
+    pub fn report(&self, hash: u64, report: Report) {
+        let banned;
+        {
+            let nodes_read = self.nodes.read();
+            if let Some(node_lock) = nodes_read.get(&hash) {
+                let mut node = node_lock.write();
+                let ip = node.addr.get_ip();
+                if node.if_reserved || self.is_reserved_ip(&ip) {
+                    trace!(target: "p2p", "ignored report {:?} of reserved node hash/ip {}/{}",
+                        report, hash, ip);
+                    return;
+                }
+                let mut reputation = self.reputation.lock();
+                node.score = reputation.report(&ip, report);
+                banned = reputation.is_banned(&ip);
+                trace!(target: "p2p", "report {:?}: hash/ip/score {}/{}/{}",
+                    report, hash, ip, node.score);
+                if banned {
+                    warn!(target: "p2p", "banned node id/ip {}/{} for {:?}",
+                        node.get_id_string(), ip, report);
+                }
+            } else {
+                return;
+            }
+        }
+        if banned {
+            self.remove_node(hash);
+        }
+    }
+
+    /// drop node and close its connection
+    fn remove_node(&self, hash: u64) {
+        let removed = self.nodes.write().remove(&hash);
+        if let Some(node_lock) = removed {
+            let node = node_lock.into_inner();
+            let _ = node.ts.shutdown(Shutdown::Both);
+            let _ = node.shutdown_tcp_thread();
+            self.disconnect(hash, node.held_id());
+        }
+    }
+
+    /// release the id held by a dropped node and tell sync about it
+    fn disconnect(&self, hash: u64, id: Option<String>) {
+        if let Some(id) = id {
+            if let Ok(mut id_set) = self.nodes_id.lock() {
+                id_set.remove(&id);
+            }
         }
         if let Ok(lock) = self.callback.read() {
             if let Some(ref callback) = *lock {
@@ -629,6 +1156,9 @@ impl Mgr {
             }
         }
         nodes_write.clear();
+        drop(nodes_write);
+
+        self.peer_store.lock().save();
 
         info!(target: "p2p" , "p2p shutdown finished");
     }
@@ -663,6 +1193,17 @@ impl Mgr {
         }
     }
 
+    /// get random active node hash among nodes supporting an action,
+    /// preferring nodes with higher scores
+    pub fn get_preferred_active_node_hash(&self, module: u8, action: u8) -> Option<u64> {
+        let nodes: Vec<Node> = self
+            .get_active_nodes()
+            .into_iter()
+            .filter(|node| node.supports(module, action))
+            .collect();
+        pick_preferred(&nodes).map(|node| node.get_hash())
+    }
+
     /// get random active node
     pub fn get_random_active_node(&self, filter: &[u64]) -> Option<Node> {
         let active: Vec<Node> = self.get_active_nodes();
@@ -680,8 +1221,13 @@ impl Mgr {
         }
     }
 
-    /// get total nodes count
-    pub fn get_statics_info(&self) -> (usize, HashMap<u64, (String, String, String, &str)>) {
+    /// get traffic with all nodes as (module, action, traffic)
+    pub fn get_traffic(&self) -> Vec<(u8, u8, Traffic)> { self.traffic.lock().routes() }
+
+    /// get total nodes count and addr, revision, connection, seed flag and latency of active nodes
+    pub fn get_statics_info(
+        &self,
+    ) -> (usize, HashMap<u64, (String, String, String, &str, String)>) {
         let mut statics_info = HashMap::new();
         let nodes_read = self.nodes.read();
         let len = nodes_read.len();
@@ -698,6 +1244,10 @@ impl Mgr {
                             true => "y",
                             _ => " ",
                         },
+                        match node.latency_millis() {
+                            Some(latency) => format!("{}ms", latency),
+                            None => "-".to_owned(),
+                        },
                     ),
                 );
             }
@@ -744,30 +1294,119 @@ impl Mgr {
 
     pub fn get_local_node_info(&self) -> &String { &self.config.local_node }
 
+    pub fn get_local_id_and_binding(&self) -> (String, String) { self.config.get_id_and_binding() }
+
+    /// queue a node given as `p2p://<id>@<ip>:<port>` to be dialed next
+    pub fn add_node(&self, node_str: &str) -> Result<(), String> {
+        let mut temp = match TempNode::parse(node_str) {
+            Some(temp) => temp,
+            None => return Err(format!("invalid node {}", node_str)),
+        };
+        temp.if_seed = false;
+        if self.config.reserved_only {
+            return Err("only reserved nodes are dialed".to_owned());
+        }
+        let ip = temp.addr.get_ip();
+        if self.reputation.lock().is_banned(&ip) {
+            return Err(format!("ip {} is banned", ip));
+        }
+        if self.is_connected(&temp) {
+            return Err(format!("node {} is already connected", node_str));
+        }
+
+        self.peer_store.lock().discovered(&temp);
+        if let Ok(mut lock) = self.temp.lock() {
+            lock.retain(|queued| queued.addr != temp.addr);
+            lock.push_front(temp);
+        }
+        Ok(())
+    }
+
+    /// drop the nodes with the given id or address, returns whether any node was dropped
+    pub fn disconnect_node(&self, id_or_addr: &str) -> bool {
+        let hashes: Vec<u64> = self
+            .nodes
+            .read()
+            .iter()
+            .filter(|(_, node_lock)| {
+                let node = node_lock.read();
+                node.get_id_string() == id_or_addr
+                    || node.addr.to_string() == id_or_addr
+                    || node.real_addr.to_string() == id_or_addr
+            })
+            .map(|(hash, _)| *hash)
+            .collect();
+        for hash in &hashes {
+            info!(target: "p2p", "disconnect node hash {}", hash);
+            self.remove_node(*hash);
+        }
+        !hashes.is_empty()
+    }
+
+    /// ban an ip and drop its nodes
+    pub fn ban_ip(&self, ip: &str) {
+        self.reputation.lock().ban(ip);
+        let hashes: Vec<u64> = self
+            .nodes
+            .read()
+            .iter()
+            .filter(|(_, node_lock)| node_lock.read().addr.get_ip() == ip)
+            .map(|(hash, _)| *hash)
+            .collect();
+        for hash in hashes {
+            self.remove_node(hash);
+        }
+        warn!(target: "p2p", "banned ip {}", ip);
+    }
+
+    /// lift the ban of an ip, returns whether the ip was banned
+    pub fn unban_ip(&self, ip: &str) -> bool { self.reputation.lock().unban(ip) }
+
     /// messages with module code other than p2p module
     /// should flow into external handlers
     fn handle(&self, hash: u64, cb: ChannelBuffer) {
         // check body length
         if cb.head.len as usize != cb.body.len() {
             debug!(target: "p2p", "Length does not match!! hash/ver/ctrl/action {}/{}/{}/{}", hash, cb.head.ver, cb.head.ctrl, cb.head.action);
+            self.report(hash, Report::MalformedFrame);
             return;
         }
 
+        let size = HEADER_LENGTH + cb.body.len();
+        self.traffic.lock().received(cb.head.ctrl, cb.head.action, size);
+
         let p2p = self.clone();
         debug!(target: "p2p", "handle: hash/ver/ctrl/action/route {}/{}/{}/{}/{}", &hash, cb.head.ver, cb.head.ctrl, cb.head.action, cb.head.get_route());
         // verify if flag token has been set
         let mut pass = false;
+        let mut active = false;
+        let mut accepted = false;
         {
             let nodes_read = self.nodes.read();
             if let Some(node_lock) = nodes_read.get(&hash) {
                 let mut node = node_lock.write();
+                node.traffic.received.add(size);
                 let clear_token = cb.head.get_route();
                 pass = self.token_check(clear_token, &mut node);
+                active = node.is_active();
+                accepted = node.state == STATE::ACCEPTED;
             } else {
                 debug!(target: "p2p", "failed to get node with hash {}", hash);
             }
         }
 
+        // only the handshake may run before the connection is authenticated and encrypted
+        let handshake = cb.head.ctrl == Module::P2P.value()
+            && (cb.head.action == Action::HANDSHAKEREQ.value()
+                || cb.head.action == Action::HANDSHAKERES.value());
+        if accepted && !handshake {
+            active = handshake::confirm(&p2p, hash);
+        }
+        if !active && !handshake {
+            debug!(target: "p2p", "not authenticated: hash/ver/ctrl/action {}/{}/{}/{}", &hash, cb.head.ver, cb.head.ctrl, cb.head.action);
+            return;
+        }
+
         if pass {
             match Version::from(cb.head.ver) {
                 Version::V0 => {
@@ -776,6 +1415,8 @@ impl Mgr {
                             match Action::from(cb.head.action) {
                                 Action::HANDSHAKEREQ => handshake::receive_req(p2p, hash, cb),
                                 Action::HANDSHAKERES => handshake::receive_res(p2p, hash, cb),
+                                Action::PING => ping::receive_req(p2p, hash, cb),
+                                Action::PONG => ping::receive_res(p2p, hash, cb),
                                 Action::ACTIVENODESREQ => {
                                     active_nodes::receive_req(p2p, hash, cb.head.ver)
                                 }
@@ -813,14 +1454,48 @@ fn config_stream(stream: &TcpStream) -> Result<(), io::Error> {
     Ok(())
 }
 
-/// helper function for tokio io frame
+/// helper function delaying a message over a rate limit, p2p messages keeping the
+/// connection alive are not limited
+fn throttle(
+    limiter: &MutexP<Limiter>,
+    cb: &ChannelBuffer,
+) -> Box<Future<Item = (), Error = ()> + Send>
+{
+    if cb.head.ctrl == Module::P2P.value() {
+        return Box::new(future::ok(()));
+    }
+    match limiter.lock().reserve(HEADER_LENGTH + cb.body.len()) {
+        Some(at) => {
+            Box::new(
+                Delay::new(at).map_err(|err| error!(target: "p2p", "throttle: {:?}", err)),
+            )
+        }
+        None => Box::new(future::ok(())),
+    }
+}
+
+/// helper function queueing discovery packets to the udp socket
+fn send_datagrams(
+    tx: &mut mpsc::Sender<(Vec<u8>, SocketAddr)>,
+    datagrams: Vec<(Vec<u8>, SocketAddr)>,
+)
+{
+    for datagram in datagrams {
+        if let Err(err) = tx.try_send(datagram) {
+            trace!(target: "p2p", "discovery send: {}", err);
+        }
+    }
+}
+
+/// helper function for tokio io frame, encrypted once the handshake sets up the channel
 fn split_frame(
     socket: TcpStream,
+    channel: Arc<MutexP<SecureChannel>>,
 ) -> (
     stream::SplitSink<Framed<TcpStream, Codec>>,
     stream::SplitStream<Framed<TcpStream, Codec>>,
 ) {
-    Codec.framed(socket).split()
+    Codec::new(channel).framed(socket).split()
 }
 
 #[cfg(test)]
@@ -850,7 +1525,7 @@ mod tests {
             let flat_token_0: u32 = (0 << 16) + (0 << 8) + 0;
             let clear_token_0: u32 = (0 << 16) + (0 << 8) + 1;
             tokens_rules.push([flat_token_0, clear_token_0]);
-            let p2p = Mgr::new(Config::new(), tokens_rules);
+            let p2p = Mgr::new(Config::new(), tokens_rules, Vec::new());
 
             let (tx, _rx) = mpsc::channel(409600);
             let (tx_thread, _rx_thread) = oneshot::channel::<()>();
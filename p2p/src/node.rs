@@ -20,8 +20,8 @@
  ******************************************************************************/
 
 use std::fmt;
-use std::time::SystemTime;
-use std::net::SocketAddr;
+use std::time::{Duration, Instant, SystemTime};
+use std::net::{SocketAddr, SocketAddrV4};
 use std::sync::Arc;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::hash_set::HashSet;
@@ -35,6 +35,11 @@ use super::msg::*;
 use super::state::STATE;
 use futures::sync::oneshot::Sender;
 use std::sync::Mutex;
+use parking_lot::Mutex as MutexP;
+use secure::{Ephemeral, SecureChannel};
+use reputation::DEFAULT_SCORE;
+use capability::Capability;
+use traffic::Traffic;
 
 const EMPTY_ID: &str = "00000000-0000-0000-0000-000000000000";
 
@@ -73,6 +78,28 @@ pub struct Node {
     /// clear on incoming paired clear_token received
     pub tokens: HashSet<u32>,
     pub tx_thread: Arc<Mutex<Vec<Sender<()>>>>,
+
+    /// encryption state shared with the framed socket
+    pub channel: Arc<MutexP<SecureChannel>>,
+    /// ephemeral key of the handshake request sent to an outbound node
+    pub ephemeral: Option<Ephemeral>,
+    /// whether the id of an outbound node is known in advance and must match the
+    /// id authenticated by the handshake
+    pub id_pinned: bool,
+    /// reputation of the node ip, sync prefers nodes scoring higher
+    pub score: i32,
+    /// nonce and send time of the ping awaiting a pong
+    pub ping: Option<(u64, Instant)>,
+    /// pings sent since the last pong
+    pub missed_pings: u32,
+    /// smoothed ping round trip, unknown until the node answers a ping
+    pub latency: Option<Duration>,
+    /// reserved nodes are kept connected and not counted against the peer limits
+    pub if_reserved: bool,
+    /// protocols agreed on in the handshake
+    pub capabilities: Vec<Capability>,
+    /// messages and bytes exchanged with the node
+    pub traffic: Traffic,
 }
 
 impl Node {
@@ -107,6 +134,17 @@ impl Node {
 
             tokens: HashSet::new(),
             tx_thread: Arc::new(Mutex::new(tx_thread_vec)),
+
+            channel: Arc::new(MutexP::new(SecureChannel::default())),
+            ephemeral: None,
+            id_pinned: false,
+            score: DEFAULT_SCORE,
+            ping: None,
+            missed_pings: 0,
+            latency: None,
+            if_reserved: false,
+            capabilities: Vec::new(),
+            traffic: Traffic::default(),
         }
     }
 
@@ -142,6 +180,17 @@ impl Node {
 
             tokens: HashSet::new(),
             tx_thread: Arc::new(Mutex::new(tx_thread_vec)),
+
+            channel: Arc::new(MutexP::new(SecureChannel::default())),
+            ephemeral: None,
+            id_pinned: false,
+            score: DEFAULT_SCORE,
+            ping: None,
+            missed_pings: 0,
+            latency: None,
+            if_reserved: false,
+            capabilities: Vec::new(),
+            traffic: Traffic::default(),
         }
     }
 
@@ -159,6 +208,36 @@ impl Node {
 
     pub fn is_active(&self) -> bool { self.state == STATE::ACTIVE }
 
+    /// id the node holds among the connected ids, which it only does once active
+    pub fn held_id(&self) -> Option<String> {
+        if self.is_active() {
+            Some(self.get_id_string())
+        } else {
+            None
+        }
+    }
+
+    /// agreed version of a module
+    pub fn version(&self, module: u8) -> Option<u16> {
+        self.capabilities
+            .iter()
+            .find(|capability| capability.module == module)
+            .map(|capability| capability.version)
+    }
+
+    /// whether the node handles an action of a module at the agreed version
+    pub fn supports(&self, module: u8, action: u8) -> bool {
+        self.capabilities
+            .iter()
+            .any(|capability| capability.module == module && capability.actions.contains(&action))
+    }
+
+    /// smoothed ping round trip in milliseconds
+    pub fn latency_millis(&self) -> Option<u64> {
+        self.latency
+            .map(|latency| latency.as_secs() * 1000 + latency.subsec_millis() as u64)
+    }
+
     pub fn shutdown_tcp_thread(&self) -> Result<(), ()> {
         if let Ok(mut tx_thread_vec) = self.tx_thread.lock() {
             let mut result = Ok(());
@@ -250,6 +329,8 @@ pub struct TempNode {
     pub id: [u8; NODE_ID_LENGTH],
     pub addr: IpAddr,
     pub if_seed: bool,
+    pub id_pinned: bool,
+    pub if_reserved: bool,
 }
 
 impl TempNode {
@@ -259,6 +340,8 @@ impl TempNode {
             id: [b'0'; NODE_ID_LENGTH],
             addr: IpAddr::new(),
             if_seed: false,
+            id_pinned: false,
+            if_reserved: false,
         }
     }
 
@@ -269,6 +352,19 @@ impl TempNode {
 
     pub fn get_id_string(&self) -> String { String::from_utf8_lossy(&self.id).into() }
 
+    /// parse a node url like `p2p://<id>@<ip>:<port>`, None if malformed
+    pub fn parse(node_str: &str) -> Option<TempNode> {
+        if !node_str.is_ascii() || !node_str.starts_with("p2p://") {
+            return None;
+        }
+        let (_, rest) = node_str.split_at(PROTOCOL_LENGTH);
+        if rest.len() <= NODE_ID_LENGTH || !rest[NODE_ID_LENGTH..].starts_with('@') {
+            return None;
+        }
+        rest[NODE_ID_LENGTH + 1..].parse::<SocketAddrV4>().ok()?;
+        Some(TempNode::new_from_str(node_str.to_owned()))
+    }
+
     // construct node from seed config
     // constrait check
     // TODO: return Option<TempNode>
@@ -280,8 +376,10 @@ impl TempNode {
         let ip_str = addr_str_1_arr[0];
         let port_str = addr_str_1_arr[1];
 
+        // an empty id accepts whichever id the node authenticates with
         let mut id: [u8; NODE_ID_LENGTH] = [b'0'; NODE_ID_LENGTH];
-        if EMPTY_ID == id_str.to_string() {
+        let id_pinned = EMPTY_ID != id_str.to_string();
+        if !id_pinned {
             let uuid = Uuid::new_v4();
             id.copy_from_slice(uuid.hyphenated().to_string().as_bytes());
         } else {
@@ -297,6 +395,8 @@ impl TempNode {
             id,
             addr,
             if_seed: true,
+            id_pinned,
+            if_reserved: false,
         }
     }
 }
@@ -329,4 +429,18 @@ mod node_tests {
         let tn = TempNode::new_from_str(node_str);
         assert_eq!(tn.addr.to_string(), "0.0.0.0:30303".to_string());
     }
+
+    #[test]
+    fn test_parse() {
+        let tn = TempNode::parse("p2p://c33d1066-8c7e-496c-9c4e-c89318280274@10.0.0.1:30304")
+            .unwrap();
+        assert_eq!(tn.addr.to_string(), "10.0.0.1:30304".to_string());
+        assert_eq!(tn.get_id_string(), "c33d1066-8c7e-496c-9c4e-c89318280274");
+        assert!(tn.id_pinned);
+
+        assert!(TempNode::parse("c33d1066-8c7e-496c-9c4e-c89318280274@10.0.0.1:30304").is_none());
+        assert!(TempNode::parse("p2p://c33d1066@10.0.0.1:30304").is_none());
+        assert!(TempNode::parse("p2p://c33d1066-8c7e-496c-9c4e-c89318280274@10.0.0.1").is_none());
+        assert!(TempNode::parse("p2p://c33d1066-8c7e-496c-9c4e-c89318280274@host:30304").is_none());
+    }
 }
@@ -0,0 +1,286 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use bincode;
+use node::{convert_ip_string, Node, TempNode, NODE_ID_LENGTH};
+
+/// most nodes kept in the store
+const MAX_RECORDS: usize = 1024;
+/// nodes failing this many dials in a row are forgotten
+const MAX_FAILURES: u32 = 8;
+
+/// what is known about a node across restarts
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeerRecord {
+    pub id: String,
+    /// listening address as "ip:port"
+    pub addr: String,
+    /// seconds since unix epoch, 0 for nodes never connected to
+    pub last_seen: u64,
+    /// failed dials since the node was last seen
+    pub failures: u32,
+    /// revision and protocols announced by the node
+    pub capabilities: Vec<String>,
+}
+
+impl PeerRecord {
+    fn to_temp_node(&self) -> Option<TempNode> {
+        let mut frags = self.addr.split(':');
+        let (ip, port) = match (frags.next(), frags.next()) {
+            (Some(ip), Some(port)) => (ip, port.parse::<u32>().ok()?),
+            _ => return None,
+        };
+        if self.id.len() != NODE_ID_LENGTH {
+            return None;
+        }
+        let mut temp = TempNode::default();
+        temp.id.copy_from_slice(self.id.as_bytes());
+        temp.id_pinned = true;
+        temp.addr.ip = convert_ip_string(ip.to_owned());
+        temp.addr.port = port;
+        Some(temp)
+    }
+}
+
+/// Table of known nodes stored at `path`, seeding outbound connections at startup so
+/// that the node does not depend on its boot nodes only.
+pub struct PeerStore {
+    records: HashMap<String, PeerRecord>,
+    path: Option<PathBuf>,
+    dirty: bool,
+}
+
+impl PeerStore {
+    pub fn new(path: Option<PathBuf>) -> PeerStore {
+        let records = match path {
+            Some(ref path) => {
+                load(path).unwrap_or_else(|e| {
+                    warn!(target: "p2p", "failed to load peer store {:?}: {}", path, e);
+                    HashMap::new()
+                })
+            }
+            None => HashMap::new(),
+        };
+        PeerStore {
+            records,
+            path,
+            dirty: false,
+        }
+    }
+
+    pub fn len(&self) -> usize { self.records.len() }
+
+    pub fn get(&self, addr: &str) -> Option<&PeerRecord> { self.records.get(addr) }
+
+    /// record an active node
+    pub fn seen(&mut self, node: &Node) {
+        let addr = node.real_addr.to_string();
+        {
+            let record = self.records.entry(addr.clone()).or_insert_with(|| {
+                PeerRecord {
+                    id: String::new(),
+                    addr,
+                    last_seen: 0,
+                    failures: 0,
+                    capabilities: Vec::new(),
+                }
+            });
+            record.id = node.get_id_string();
+            record.last_seen = now();
+            record.failures = 0;
+            record.capabilities = vec![String::from_utf8_lossy(&node.revision).trim().to_owned()];
+            record
+                .capabilities
+                .extend(node.capabilities.iter().map(|capability| capability.to_string()));
+        }
+        self.dirty = true;
+        self.evict();
+    }
+
+    /// record a node learned from another node, if it is not known yet
+    pub fn discovered(&mut self, temp: &TempNode) {
+        let addr = temp.addr.to_string();
+        if self.records.contains_key(&addr) {
+            return;
+        }
+        self.records.insert(
+            addr.clone(),
+            PeerRecord {
+                id: temp.get_id_string(),
+                addr,
+                last_seen: 0,
+                failures: 0,
+                capabilities: Vec::new(),
+            },
+        );
+        self.dirty = true;
+        self.evict();
+    }
+
+    /// record a failed dial, forgetting nodes failing too often
+    pub fn failed(&mut self, addr: &str) {
+        let forget = match self.records.get_mut(addr) {
+            Some(record) => {
+                record.failures += 1;
+                record.failures >= MAX_FAILURES
+            }
+            None => return,
+        };
+        if forget {
+            self.records.remove(addr);
+        }
+        self.dirty = true;
+    }
+
+    /// nodes to dial, the ones seen most recently and failing least first
+    pub fn seeds(&self, count: usize) -> Vec<TempNode> {
+        let mut records: Vec<&PeerRecord> = self.records.values().collect();
+        records.sort_by(|a, b| {
+            a.failures
+                .cmp(&b.failures)
+                .then_with(|| b.last_seen.cmp(&a.last_seen))
+        });
+        records
+            .iter()
+            .filter_map(|record| record.to_temp_node())
+            .take(count)
+            .collect()
+    }
+
+    /// write the table if it changed since the last save
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        if let Some(ref path) = self.path {
+            let records: Vec<&PeerRecord> = self.records.values().collect();
+            match save(path, &records) {
+                Ok(_) => trace!(target: "p2p", "saved {} peers", records.len()),
+                Err(e) => warn!(target: "p2p", "failed to save peer store {:?}: {}", path, e),
+            }
+        }
+        self.dirty = false;
+    }
+
+    // drop the worst records beyond the capacity
+    fn evict(&mut self) {
+        if self.records.len() <= MAX_RECORDS {
+            return;
+        }
+        let mut records: Vec<(String, u32, u64)> = self
+            .records
+            .values()
+            .map(|record| (record.addr.clone(), record.failures, record.last_seen))
+            .collect();
+        records.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.2.cmp(&b.2)));
+        let excess = self.records.len() - MAX_RECORDS;
+        for &(ref addr, _, _) in records.iter().take(excess) {
+            self.records.remove(addr);
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+fn load(path: &Path) -> io::Result<HashMap<String, PeerRecord>> {
+    let mut records = HashMap::new();
+    if !path.exists() {
+        return Ok(records);
+    }
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+    let list: Vec<PeerRecord> = bincode::deserialize(&bytes)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    for record in list {
+        records.insert(record.addr.clone(), record);
+    }
+    Ok(records)
+}
+
+fn save(path: &Path, records: &[&PeerRecord]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let bytes = bincode::serialize(records)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+    File::create(path)?.write_all(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use rand::random;
+    use node::TempNode;
+    use super::*;
+
+    fn temp_node(id: &str, addr: &str) -> TempNode {
+        TempNode::new_from_str(format!("p2p://{}@{}", id, addr))
+    }
+
+    #[test]
+    fn test_seeds_order() {
+        let mut store = PeerStore::new(None);
+        store.discovered(&temp_node("c33d2207-729a-4584-86f1-e19ab97cf9ce", "10.0.0.1:30303"));
+        store.discovered(&temp_node("c33d302f-216b-47d4-ac44-5d8181b56e7e", "10.0.0.2:30303"));
+        store.failed("10.0.0.1:30303");
+        assert_eq!(store.len(), 2);
+
+        let seeds = store.seeds(2);
+        assert_eq!(seeds.len(), 2);
+        assert_eq!(seeds[0].addr.to_string(), "10.0.0.2:30303");
+        assert!(seeds[0].id_pinned);
+        assert_eq!(seeds[0].get_id_string(), "c33d302f-216b-47d4-ac44-5d8181b56e7e");
+
+        for _ in 1..MAX_FAILURES {
+            store.failed("10.0.0.1:30303");
+        }
+        assert_eq!(store.len(), 1);
+        assert!(store.get("10.0.0.1:30303").is_none());
+    }
+
+    #[test]
+    fn test_store_persists() {
+        let path = env::temp_dir().join(format!("p2p-peers-{}", random::<u64>()));
+        {
+            let mut store = PeerStore::new(Some(path.clone()));
+            store.discovered(&temp_node("c33d2207-729a-4584-86f1-e19ab97cf9ce", "10.0.0.1:30303"));
+            store.save();
+        }
+
+        let store = PeerStore::new(Some(path.clone()));
+        assert_eq!(store.len(), 1);
+        let record = store.get("10.0.0.1:30303").unwrap();
+        assert_eq!(record.id, "c33d2207-729a-4584-86f1-e19ab97cf9ce");
+        assert_eq!(record.last_seen, 0);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,293 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+use std::cmp;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use lru_cache::LruCache;
+use rand::random;
+use node::Node;
+
+/// score of an ip without any report
+pub const DEFAULT_SCORE: i32 = 0;
+/// highest score useful responses can earn
+pub const MAX_SCORE: i32 = 100;
+/// ips dropping to this score are banned
+pub const BAN_SCORE: i32 = -100;
+/// seconds a ban lasts
+pub const BAN_DURATION: u64 = 3600;
+
+const MAX_SCORES: usize = 4096;
+
+/// behaviour of a node affecting its score
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Report {
+    /// header failing validation
+    InvalidHeader,
+    /// bodies not matching the requested headers, or a block failing import
+    InvalidBody,
+    /// no message received within the node timeout
+    Timeout,
+    /// frame failing to decode or authenticate
+    MalformedFrame,
+    /// handshake from another network
+    WrongNetId,
+    /// handshake failing authentication
+    InvalidHandshake,
+    /// response carrying data put to use
+    UsefulResponse,
+    /// requests beyond the quota of the node
+    ExceededQuota,
+}
+
+impl Report {
+    pub fn value(&self) -> i32 {
+        match *self {
+            Report::InvalidHeader => -20,
+            Report::InvalidBody => -20,
+            Report::Timeout => -5,
+            Report::MalformedFrame => -50,
+            Report::WrongNetId => BAN_SCORE,
+            Report::InvalidHandshake => -50,
+            Report::UsefulResponse => 1,
+            Report::ExceededQuota => -10,
+        }
+    }
+}
+
+/// Scores and bans by ip, so that reconnecting does not reset the record of a node.
+/// Bans are stored at `path` to outlive restarts.
+pub struct Reputation {
+    scores: LruCache<String, i32>,
+    /// ban expiry as seconds since unix epoch by ip
+    bans: HashMap<String, u64>,
+    black_list: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl Reputation {
+    pub fn new(black_list: Vec<String>, path: Option<PathBuf>) -> Reputation {
+        let bans = match path {
+            Some(ref path) => {
+                load(path).unwrap_or_else(|e| {
+                    warn!(target: "p2p", "failed to load bans {:?}: {}", path, e);
+                    HashMap::new()
+                })
+            }
+            None => HashMap::new(),
+        };
+        Reputation {
+            scores: LruCache::new(MAX_SCORES),
+            bans,
+            black_list,
+            path,
+        }
+    }
+
+    pub fn score(&mut self, ip: &str) -> i32 {
+        self.scores.get_mut(ip).map_or(DEFAULT_SCORE, |score| *score)
+    }
+
+    /// Apply a report to the score of `ip`, banning it once the score drops to `BAN_SCORE`.
+    /// Returns the new score.
+    pub fn report(&mut self, ip: &str, report: Report) -> i32 {
+        let score = cmp::min(self.score(ip) + report.value(), MAX_SCORE);
+        if score <= BAN_SCORE {
+            // start over once the ban expires
+            self.scores.remove(ip);
+            self.ban(ip);
+        } else {
+            self.scores.insert(ip.to_owned(), score);
+        }
+        score
+    }
+
+    pub fn ban(&mut self, ip: &str) {
+        self.bans.insert(ip.to_owned(), now() + BAN_DURATION);
+        self.save();
+    }
+
+    /// Lift the ban of `ip` and reset its score. Black listed ips stay refused.
+    /// Returns whether `ip` was banned.
+    pub fn unban(&mut self, ip: &str) -> bool {
+        self.scores.remove(ip);
+        let banned = self.bans.remove(ip).is_some();
+        if banned {
+            self.save();
+        }
+        banned
+    }
+
+    pub fn is_banned(&mut self, ip: &str) -> bool {
+        if self.black_list.iter().any(|black| black == ip) {
+            return true;
+        }
+        match self.bans.get(ip).cloned() {
+            Some(expiry) if expiry > now() => true,
+            Some(_) => {
+                self.bans.remove(ip);
+                self.save();
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn save(&self) {
+        if let Some(ref path) = self.path {
+            if let Err(e) = save(path, &self.bans) {
+                warn!(target: "p2p", "failed to save bans {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Pick a random node among the better half of `nodes`, ranked by score and then by latency.
+pub fn pick_preferred(nodes: &[Node]) -> Option<Node> {
+    if nodes.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<&Node> = nodes.iter().collect();
+    sorted.sort_by_key(|node| rank(node.score, node.latency_millis()));
+    let preferred = (sorted.len() + 1) / 2;
+    Some(sorted[random::<usize>() % preferred].clone())
+}
+
+// higher scores first, nodes with unknown latency last among equal scores
+fn rank(score: i32, latency: Option<u64>) -> (cmp::Reverse<i32>, u64) {
+    (cmp::Reverse(score), latency.unwrap_or(u64::max_value()))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+// one "<ip> <expiry>" line per ban, expired bans are dropped
+fn load(path: &Path) -> io::Result<HashMap<String, u64>> {
+    let mut bans = HashMap::new();
+    if !path.exists() {
+        return Ok(bans);
+    }
+    let now = now();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        let mut fields = line.split_whitespace();
+        if let (Some(ip), Some(expiry)) = (fields.next(), fields.next()) {
+            match expiry.parse::<u64>() {
+                Ok(expiry) if expiry > now => {
+                    bans.insert(ip.to_owned(), expiry);
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(bans)
+}
+
+fn save(path: &Path, bans: &HashMap<String, u64>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = File::create(path)?;
+    for (ip, expiry) in bans {
+        writeln!(file, "{} {}", ip, expiry)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use super::*;
+
+    #[test]
+    fn test_report_and_ban() {
+        let mut reputation = Reputation::new(vec!["10.0.0.9".into()], None);
+        assert!(reputation.is_banned("10.0.0.9"));
+
+        let ip = "10.0.0.1";
+        for _ in 0..MAX_SCORE * 2 {
+            reputation.report(ip, Report::UsefulResponse);
+        }
+        assert_eq!(reputation.score(ip), MAX_SCORE);
+
+        reputation.report(ip, Report::InvalidHeader);
+        assert_eq!(reputation.score(ip), MAX_SCORE + Report::InvalidHeader.value());
+        assert!(!reputation.is_banned(ip));
+
+        reputation.report(ip, Report::WrongNetId);
+        assert!(!reputation.is_banned(ip));
+        for _ in 0..4 {
+            reputation.report(ip, Report::MalformedFrame);
+        }
+        assert!(reputation.is_banned(ip));
+        assert_eq!(reputation.score(ip), DEFAULT_SCORE);
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut ranks = vec![
+            rank(DEFAULT_SCORE, None),
+            rank(DEFAULT_SCORE, Some(80)),
+            rank(MAX_SCORE, None),
+            rank(DEFAULT_SCORE, Some(20)),
+            rank(BAN_SCORE + 1, Some(1)),
+        ];
+        ranks.sort();
+        assert_eq!(
+            ranks,
+            vec![
+                rank(MAX_SCORE, None),
+                rank(DEFAULT_SCORE, Some(20)),
+                rank(DEFAULT_SCORE, Some(80)),
+                rank(DEFAULT_SCORE, None),
+                rank(BAN_SCORE + 1, Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bans_persist() {
+        let path = env::temp_dir().join(format!("p2p-bans-{}", random::<u64>()));
+        {
+            let mut reputation = Reputation::new(Vec::new(), Some(path.clone()));
+            reputation.report("10.0.0.2", Report::WrongNetId);
+            assert!(reputation.is_banned("10.0.0.2"));
+        }
+
+        let mut reputation = Reputation::new(Vec::new(), Some(path.clone()));
+        assert!(reputation.is_banned("10.0.0.2"));
+        assert!(!reputation.is_banned("10.0.0.3"));
+        assert!(reputation.unban("10.0.0.2"));
+        assert!(!reputation.unban("10.0.0.2"));
+
+        let mut reputation = Reputation::new(Vec::new(), Some(path.clone()));
+        assert!(!reputation.is_banned("10.0.0.2"));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -40,6 +40,7 @@ pub enum Version {
     V0 = 0,
     V1 = 1,
     V2 = 2,
+    UNKNOWN = 0xffff,
 }
 
 impl Version {
@@ -48,6 +49,7 @@ impl Version {
             Version::V0 => 0u16,
             Version::V1 => 1u16,
             Version::V2 => 2u16,
+            Version::UNKNOWN => 0xffffu16,
         }
     }
 
@@ -55,7 +57,8 @@ impl Version {
         match value {
             0 => Version::V0,
             1 => Version::V1,
-            _ => Version::V2,
+            2 => Version::V2,
+            _ => Version::UNKNOWN,
         }
     }
 }
@@ -95,8 +98,8 @@ pub enum Action {
     DISCONNECT,
     HANDSHAKEREQ,
     HANDSHAKERES,
-    // PING,
-    // PONG,
+    PING,
+    PONG,
     ACTIVENODESREQ,
     ACTIVENODESRES,
     UNKNOWN,
@@ -108,8 +111,8 @@ impl Action {
             Action::DISCONNECT => 0u8,
             Action::HANDSHAKEREQ => 1u8,
             Action::HANDSHAKERES => 2u8,
-            // Action::PING => 3u8,
-            // Action::PONG => 4u8,
+            Action::PING => 3u8,
+            Action::PONG => 4u8,
             Action::ACTIVENODESREQ => 5u8,
             Action::ACTIVENODESRES => 6u8,
             Action::UNKNOWN => 255u8,
@@ -120,8 +123,8 @@ impl Action {
             0 => Action::DISCONNECT,
             1 => Action::HANDSHAKEREQ,
             2 => Action::HANDSHAKERES,
-            // 3 => Action::PING,
-            // 4 => Action::PONG,
+            3 => Action::PING,
+            4 => Action::PONG,
             5 => Action::ACTIVENODESREQ,
             6 => Action::ACTIVENODESRES,
             _ => Action::UNKNOWN,
@@ -212,7 +215,8 @@ mod tests {
         assert_eq!(Version::V0, Version::from(0));
         assert_eq!(Version::V1, Version::from(1));
         assert_eq!(Version::V2, Version::from(2));
-        assert_eq!(Version::V2, Version::from(255));
+        assert_eq!(Version::UNKNOWN, Version::from(3));
+        assert_eq!(Version::UNKNOWN, Version::from(255));
     }
 
     #[test]
@@ -249,6 +253,8 @@ mod tests {
         assert_eq!(Action::DISCONNECT.value(), 0);
         assert_eq!(Action::HANDSHAKEREQ.value(), 1);
         assert_eq!(Action::HANDSHAKERES.value(), 2);
+        assert_eq!(Action::PING.value(), 3);
+        assert_eq!(Action::PONG.value(), 4);
         assert_eq!(Action::ACTIVENODESREQ.value(), 5);
         assert_eq!(Action::ACTIVENODESRES.value(), 6);
         assert_eq!(Action::UNKNOWN.value(), 255);
@@ -258,6 +264,8 @@ mod tests {
     fn test_action_from() {
         assert_eq!(Action::HANDSHAKEREQ, Action::from(1));
         assert_eq!(Action::HANDSHAKERES, Action::from(2));
+        assert_eq!(Action::PING, Action::from(3));
+        assert_eq!(Action::PONG, Action::from(4));
         assert_eq!(Action::ACTIVENODESREQ, Action::from(5));
         assert_eq!(Action::ACTIVENODESRES, Action::from(6));
         assert_eq!(Action::UNKNOWN, Action::from(8));
@@ -0,0 +1,448 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Authenticated key exchange and frame encryption.
+//!
+//! Every node owns a long-lived ed25519 keypair and its node id is derived from the public
+//! key, so an id can only be claimed by the holder of the matching secret. Handshake
+//! requests and responses carry the sender's public key, a fresh x25519 ephemeral key and a
+//! signature over the ephemeral keys of the exchange. Both sides then derive one AES-256-CTR
+//! key and one HMAC-SHA256 key per direction from the ephemeral Diffie-Hellman secret.
+//!
+//! A request signs nothing chosen by the responder, so a captured request can be replayed.
+//! The replaying party cannot derive the session keys though, and the responder only
+//! trusts the node id once a first frame under those keys authenticates the initiator.
+//!
+//! Once the handshake completes every frame is sent as `len | ciphertext | mac`, where the
+//! mac also covers a per-direction sequence number. A frame failing authentication closes
+//! the connection.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use acore_bytes::to_hex;
+use byteorder::{BigEndian, ByteOrder};
+use bytes::BytesMut;
+use crypto::aessafe::AesSafe256Encryptor;
+use crypto::blockmodes::CtrMode;
+use crypto::curve25519::{curve25519, curve25519_base};
+use crypto::digest::Digest;
+use crypto::ed25519;
+use crypto::hkdf::{hkdf_expand, hkdf_extract};
+use crypto::hmac::Hmac;
+use crypto::mac::Mac;
+use crypto::sha2::Sha256;
+use crypto::symmetriccipher::SynchronousStreamCipher;
+use crypto::util::fixed_time_eq;
+use rand::random;
+
+use node::NODE_ID_LENGTH;
+
+pub const PUBLIC_KEY_LENGTH: usize = 32;
+pub const SIGNATURE_LENGTH: usize = 64;
+/// Length of the authentication data appended to handshake messages.
+pub const AUTH_LENGTH: usize = 2 * PUBLIC_KEY_LENGTH + SIGNATURE_LENGTH;
+
+const SEED_LENGTH: usize = 32;
+const KEY_LENGTH: usize = 32;
+const MAC_LENGTH: usize = 32;
+const LENGTH_PREFIX: usize = 4;
+const MAX_FRAME_LENGTH: usize = 64 * 1024 * 1024;
+
+const REQUEST_LABEL: &[u8] = b"aion-p2p-handshake-req";
+const RESPONSE_LABEL: &[u8] = b"aion-p2p-handshake-res";
+const SESSION_LABEL: &[u8] = b"aion-p2p-session";
+
+/// Long-lived identity key of the local node.
+pub struct NodeKey {
+    secret: [u8; 64],
+    public: [u8; PUBLIC_KEY_LENGTH],
+}
+
+impl NodeKey {
+    /// Generate a new key which is not persisted.
+    pub fn random() -> NodeKey {
+        let seed: [u8; SEED_LENGTH] = random();
+        NodeKey::from_seed(&seed)
+    }
+
+    /// Load the key stored at `path`, or generate and store a new one if there is none.
+    pub fn load_or_create(path: &Path) -> io::Result<NodeKey> {
+        if path.exists() {
+            let mut seed = Vec::new();
+            File::open(path)?.read_to_end(&mut seed)?;
+            if seed.len() != SEED_LENGTH {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "node key file is corrupted",
+                ));
+            }
+            return Ok(NodeKey::from_seed(&seed));
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let seed: [u8; SEED_LENGTH] = random();
+        create_private(path)?.write_all(&seed)?;
+        Ok(NodeKey::from_seed(&seed))
+    }
+
+    fn from_seed(seed: &[u8]) -> NodeKey {
+        let (secret, public) = ed25519::keypair(seed);
+        NodeKey {
+            secret,
+            public,
+        }
+    }
+
+    pub fn public(&self) -> &[u8; PUBLIC_KEY_LENGTH] { &self.public }
+
+    /// Node id derived from the public key.
+    pub fn id(&self) -> [u8; NODE_ID_LENGTH] { node_id(&self.public) }
+
+    pub fn sign(&self, message: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+        ed25519::signature(message, &self.secret)
+    }
+}
+
+#[cfg(unix)]
+fn create_private(path: &Path) -> io::Result<File> {
+    use std::fs::OpenOptions;
+    use std::os::unix::fs::OpenOptionsExt;
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+}
+
+#[cfg(not(unix))]
+fn create_private(path: &Path) -> io::Result<File> { File::create(path) }
+
+/// Sha256 hash of the given data.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.input(data);
+    let mut hash = [0u8; 32];
+    hasher.result(&mut hash);
+    hash
+}
+
+/// Node id of the given public key, formatted like a uuid: the first 16 bytes of its
+/// sha256 hash.
+pub fn node_id(public: &[u8]) -> [u8; NODE_ID_LENGTH] {
+    let hash = sha256(public);
+
+    let formatted = format!(
+        "{}-{}-{}-{}-{}",
+        to_hex(&hash[0..4]),
+        to_hex(&hash[4..6]),
+        to_hex(&hash[6..8]),
+        to_hex(&hash[8..10]),
+        to_hex(&hash[10..16])
+    );
+    let mut id = [0u8; NODE_ID_LENGTH];
+    id.copy_from_slice(formatted.as_bytes());
+    id
+}
+
+/// Ephemeral x25519 key of one side of a handshake.
+#[derive(Clone)]
+pub struct Ephemeral {
+    secret: [u8; KEY_LENGTH],
+    public: [u8; KEY_LENGTH],
+}
+
+impl Ephemeral {
+    pub fn new() -> Ephemeral {
+        let secret: [u8; KEY_LENGTH] = random();
+        Ephemeral {
+            public: curve25519_base(&secret),
+            secret,
+        }
+    }
+
+    pub fn public(&self) -> &[u8; KEY_LENGTH] { &self.public }
+
+    /// Derive the session keys from the peer's ephemeral key. Returns `None` if the peer
+    /// key is a low order point.
+    pub fn agree(&self, peer: &[u8], initiator: bool) -> Option<SessionKeys> {
+        let shared = curve25519(&self.secret, peer);
+        if shared == [0u8; KEY_LENGTH] {
+            return None;
+        }
+
+        let mut salt = Vec::with_capacity(2 * KEY_LENGTH);
+        if initiator {
+            salt.extend_from_slice(&self.public);
+            salt.extend_from_slice(peer);
+        } else {
+            salt.extend_from_slice(peer);
+            salt.extend_from_slice(&self.public);
+        }
+        let mut prk = [0u8; 32];
+        hkdf_extract(Sha256::new(), &salt, &shared, &mut prk);
+        let mut okm = [0u8; 4 * KEY_LENGTH];
+        hkdf_expand(Sha256::new(), &prk, SESSION_LABEL, &mut okm);
+
+        let (initiator_keys, responder_keys) = okm.split_at(2 * KEY_LENGTH);
+        let (egress, ingress) = if initiator {
+            (initiator_keys, responder_keys)
+        } else {
+            (responder_keys, initiator_keys)
+        };
+        Some(SessionKeys {
+            egress: FrameCipher::new(&egress[..KEY_LENGTH], &egress[KEY_LENGTH..]),
+            ingress: FrameCipher::new(&ingress[..KEY_LENGTH], &ingress[KEY_LENGTH..]),
+        })
+    }
+}
+
+/// Authentication data of a handshake request: `public | ephemeral | signature`. It can be
+/// replayed, the responder must wait for a first authenticated frame before trusting it.
+pub fn auth_request(key: &NodeKey, ephemeral: &Ephemeral) -> Vec<u8> {
+    let signature = key.sign(&transcript(REQUEST_LABEL, &[ephemeral.public()]));
+    auth(key, ephemeral, &signature)
+}
+
+/// Authentication data of a handshake response, also signing the initiator's ephemeral key.
+pub fn auth_response(key: &NodeKey, ephemeral: &Ephemeral, initiator: &[u8]) -> Vec<u8> {
+    let signature = key.sign(&transcript(RESPONSE_LABEL, &[initiator, ephemeral.public()]));
+    auth(key, ephemeral, &signature)
+}
+
+/// Check the authentication data of a handshake request. Returns the public key and the
+/// ephemeral key of the initiator.
+pub fn check_request(auth: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (public, ephemeral, signature) = split_auth(auth)?;
+    if ed25519::verify(&transcript(REQUEST_LABEL, &[ephemeral]), public, signature) {
+        Some((public, ephemeral))
+    } else {
+        None
+    }
+}
+
+/// Check the authentication data of a handshake response to a request sent with the
+/// `initiator` ephemeral key. Returns the public key and the ephemeral key of the responder.
+pub fn check_response<'a>(auth: &'a [u8], initiator: &[u8]) -> Option<(&'a [u8], &'a [u8])> {
+    let (public, ephemeral, signature) = split_auth(auth)?;
+    let message = transcript(RESPONSE_LABEL, &[initiator, ephemeral]);
+    if ed25519::verify(&message, public, signature) {
+        Some((public, ephemeral))
+    } else {
+        None
+    }
+}
+
+fn auth(key: &NodeKey, ephemeral: &Ephemeral, signature: &[u8]) -> Vec<u8> {
+    let mut auth = Vec::with_capacity(AUTH_LENGTH);
+    auth.extend_from_slice(key.public());
+    auth.extend_from_slice(ephemeral.public());
+    auth.extend_from_slice(signature);
+    auth
+}
+
+fn split_auth(auth: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    if auth.len() != AUTH_LENGTH {
+        return None;
+    }
+    let (public, rest) = auth.split_at(PUBLIC_KEY_LENGTH);
+    let (ephemeral, signature) = rest.split_at(KEY_LENGTH);
+    Some((public, ephemeral, signature))
+}
+
+fn transcript(label: &[u8], keys: &[&[u8]]) -> Vec<u8> {
+    let mut message = label.to_vec();
+    for key in keys {
+        message.extend_from_slice(key);
+    }
+    message
+}
+
+/// Keys of both directions of a connection.
+pub struct SessionKeys {
+    egress: FrameCipher,
+    ingress: FrameCipher,
+}
+
+/// Cipher and mac key protecting one direction of a connection.
+pub struct FrameCipher {
+    cipher: CtrMode<AesSafe256Encryptor>,
+    mac_key: [u8; KEY_LENGTH],
+    seq: u64,
+}
+
+impl FrameCipher {
+    fn new(cipher_key: &[u8], mac_key: &[u8]) -> FrameCipher {
+        let mut key = [0u8; KEY_LENGTH];
+        key.copy_from_slice(mac_key);
+        // keys are never reused across connections, so the counter can start at zero.
+        FrameCipher {
+            cipher: CtrMode::new(AesSafe256Encryptor::new(cipher_key), vec![0u8; 16]),
+            mac_key: key,
+            seq: 0,
+        }
+    }
+
+    fn mac(&self, prefix: &[u8], ciphertext: &[u8]) -> [u8; MAC_LENGTH] {
+        let mut seq = [0u8; 8];
+        BigEndian::write_u64(&mut seq, self.seq);
+        let mut hmac = Hmac::new(Sha256::new(), &self.mac_key);
+        hmac.input(&seq);
+        hmac.input(prefix);
+        hmac.input(ciphertext);
+        let mut mac = [0u8; MAC_LENGTH];
+        mac.copy_from_slice(hmac.result().code());
+        mac
+    }
+
+    /// Encrypt and authenticate a frame into `dst`.
+    pub fn seal(&mut self, plaintext: &[u8], dst: &mut BytesMut) {
+        let mut prefix = [0u8; LENGTH_PREFIX];
+        BigEndian::write_u32(&mut prefix, plaintext.len() as u32);
+        let mut ciphertext = vec![0u8; plaintext.len()];
+        SynchronousStreamCipher::process(&mut self.cipher, plaintext, &mut ciphertext);
+        let mac = self.mac(&prefix, &ciphertext);
+        self.seq += 1;
+
+        dst.reserve(LENGTH_PREFIX + ciphertext.len() + MAC_LENGTH);
+        dst.extend_from_slice(&prefix);
+        dst.extend_from_slice(&ciphertext);
+        dst.extend_from_slice(&mac);
+    }
+
+    /// Take one frame from `src` if it has been fully received, then check and decrypt it.
+    pub fn open(&mut self, src: &mut BytesMut) -> io::Result<Option<Vec<u8>>> {
+        if src.len() < LENGTH_PREFIX {
+            return Ok(None);
+        }
+        let len = BigEndian::read_u32(&src[..LENGTH_PREFIX]) as usize;
+        if len > MAX_FRAME_LENGTH {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too large"));
+        }
+        if src.len() < LENGTH_PREFIX + len + MAC_LENGTH {
+            return Ok(None);
+        }
+
+        let frame = src.split_to(LENGTH_PREFIX + len + MAC_LENGTH);
+        let (prefix, rest) = frame.split_at(LENGTH_PREFIX);
+        let (ciphertext, mac) = rest.split_at(len);
+        if !fixed_time_eq(&self.mac(prefix, ciphertext), mac) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame authentication failed",
+            ));
+        }
+        self.seq += 1;
+
+        let mut plaintext = vec![0u8; len];
+        SynchronousStreamCipher::process(&mut self.cipher, ciphertext, &mut plaintext);
+        Ok(Some(plaintext))
+    }
+}
+
+/// Encryption state of a connection, shared by both halves of its framed socket and the
+/// handshake handlers.
+#[derive(Default)]
+pub struct SecureChannel {
+    egress: Option<FrameCipher>,
+    ingress: Option<FrameCipher>,
+    // egress cipher of a responder, used once the handshake response is written.
+    pending_egress: Option<FrameCipher>,
+}
+
+impl SecureChannel {
+    /// Start encrypting with the session keys. The initiator switches both directions at
+    /// once; the responder still writes its handshake response in plain.
+    pub fn start(&mut self, keys: SessionKeys, initiator: bool) {
+        self.ingress = Some(keys.ingress);
+        if initiator {
+            self.egress = Some(keys.egress);
+        } else {
+            self.pending_egress = Some(keys.egress);
+        }
+    }
+
+    /// Switch the egress to encryption after the handshake response has been written.
+    pub fn handshake_written(&mut self) {
+        if let Some(egress) = self.pending_egress.take() {
+            self.egress = Some(egress);
+        }
+    }
+
+    pub fn egress(&mut self) -> Option<&mut FrameCipher> { self.egress.as_mut() }
+
+    pub fn ingress(&mut self) -> Option<&mut FrameCipher> { self.ingress.as_mut() }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use bytes::BytesMut;
+    use super::*;
+
+    #[test]
+    fn test_node_id() {
+        let key = NodeKey::random();
+        let id = String::from_utf8(key.id().to_vec()).unwrap();
+        assert_eq!(id.len(), NODE_ID_LENGTH);
+        assert_eq!(id.split('-').count(), 5);
+        assert_eq!(key.id(), node_id(key.public()));
+    }
+
+    #[test]
+    fn test_handshake_and_frames() {
+        let initiator_key = NodeKey::random();
+        let responder_key = NodeKey::random();
+        let initiator = Ephemeral::new();
+        let responder = Ephemeral::new();
+
+        let request = auth_request(&initiator_key, &initiator);
+        let (public, ephemeral) = check_request(&request).unwrap();
+        assert_eq!(public, &initiator_key.public()[..]);
+        let mut responder_keys = responder.agree(ephemeral, false).unwrap();
+
+        let response = auth_response(&responder_key, &responder, initiator.public());
+        let (public, ephemeral) = check_response(&response, initiator.public()).unwrap();
+        assert_eq!(public, &responder_key.public()[..]);
+        let mut initiator_keys = initiator.agree(ephemeral, true).unwrap();
+
+        // a response to another request does not verify
+        assert!(check_response(&response, Ephemeral::new().public()).is_none());
+
+        let mut wire = BytesMut::new();
+        initiator_keys.egress.seal(b"first", &mut wire);
+        initiator_keys.egress.seal(b"second", &mut wire);
+        let first = responder_keys.ingress.open(&mut wire).unwrap().unwrap();
+        let second = responder_keys.ingress.open(&mut wire).unwrap().unwrap();
+        assert_eq!(first, b"first".to_vec());
+        assert_eq!(second, b"second".to_vec());
+        assert!(wire.is_empty());
+
+        // a tampered frame is rejected
+        responder_keys.egress.seal(b"reply", &mut wire);
+        let last = wire.len() - 1;
+        wire[last] ^= 1;
+        assert!(initiator_keys.ingress.open(&mut wire).is_err());
+    }
+}
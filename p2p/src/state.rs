@@ -25,6 +25,8 @@
 pub enum STATE {
     CONNECTED,
     ACTIVE,
+    /// inbound handshake accepted, waiting for the first frame under the session keys
+    ACCEPTED,
 }
 
 impl STATE {
@@ -32,11 +34,13 @@ impl STATE {
         match self {
             STATE::CONNECTED => 0,
             STATE::ACTIVE => 1,
+            STATE::ACCEPTED => 2,
         }
     }
     pub fn from(value: usize) -> STATE {
         match value {
             1 => STATE::ACTIVE,
+            2 => STATE::ACCEPTED,
             _ => STATE::CONNECTED,
         }
     }
@@ -51,17 +55,20 @@ mod tests {
     fn equal() {
         assert_eq!(STATE::CONNECTED, STATE::CONNECTED);
         assert_eq!(STATE::ACTIVE, STATE::ACTIVE);
+        assert_eq!(STATE::ACCEPTED, STATE::ACCEPTED);
     }
 
     #[test]
     fn value() {
         assert_eq!(STATE::CONNECTED.value(), 0);
         assert_eq!(STATE::ACTIVE.value(), 1);
+        assert_eq!(STATE::ACCEPTED.value(), 2);
     }
 
     #[test]
     fn from() {
         assert_eq!(STATE::CONNECTED, STATE::from(0));
         assert_eq!(STATE::ACTIVE, STATE::from(1));
+        assert_eq!(STATE::ACCEPTED, STATE::from(2));
     }
 }
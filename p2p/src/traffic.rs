@@ -0,0 +1,159 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Messages and bytes carried, frame heads included.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Counter {
+    pub messages: u64,
+    pub bytes: u64,
+}
+
+impl Counter {
+    pub fn add(&mut self, bytes: usize) {
+        self.messages += 1;
+        self.bytes += bytes as u64;
+    }
+}
+
+/// Messages and bytes sent and received.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Traffic {
+    pub sent: Counter,
+    pub received: Counter,
+}
+
+/// Traffic with all nodes by module and action since start.
+pub struct TrafficStats {
+    routes: HashMap<(u8, u8), Traffic>,
+}
+
+impl TrafficStats {
+    pub fn new() -> TrafficStats {
+        TrafficStats {
+            routes: HashMap::new(),
+        }
+    }
+
+    pub fn sent(&mut self, ctrl: u8, action: u8, bytes: usize) {
+        self.routes
+            .entry((ctrl, action))
+            .or_insert_with(Traffic::default)
+            .sent
+            .add(bytes);
+    }
+
+    pub fn received(&mut self, ctrl: u8, action: u8, bytes: usize) {
+        self.routes
+            .entry((ctrl, action))
+            .or_insert_with(Traffic::default)
+            .received
+            .add(bytes);
+    }
+
+    /// traffic as (module, action, traffic) ordered by module and action
+    pub fn routes(&self) -> Vec<(u8, u8, Traffic)> {
+        let mut routes: Vec<(u8, u8, Traffic)> = self
+            .routes
+            .iter()
+            .map(|(&(ctrl, action), traffic)| (ctrl, action, *traffic))
+            .collect();
+        routes.sort_by_key(|&(ctrl, action, _)| (ctrl, action));
+        routes
+    }
+}
+
+/// Rate limit shared by the connections of one direction. Each message is let through once
+/// the bytes let through before it fit in the rate, so bursts are spread over time.
+pub struct Limiter {
+    /// bytes per second, not limited if not set
+    rate: Option<u64>,
+    /// time at which the bytes let through so far fit in the rate
+    next: Instant,
+}
+
+impl Limiter {
+    pub fn new(rate: Option<u64>) -> Limiter {
+        Limiter {
+            rate: rate.filter(|rate| *rate > 0),
+            next: Instant::now(),
+        }
+    }
+
+    /// account a message of `bytes`, returns the time to wait for if over the rate
+    pub fn reserve(&mut self, bytes: usize) -> Option<Instant> {
+        let rate = self.rate?;
+        let now = Instant::now();
+        if self.next < now {
+            self.next = now;
+        }
+        let at = self.next;
+        self.next += Duration::from_nanos(bytes as u64 * 1_000_000_000 / rate);
+        if at > now {
+            Some(at)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traffic_stats() {
+        let mut stats = TrafficStats::new();
+        stats.sent(1, 2, 100);
+        stats.sent(1, 2, 50);
+        stats.received(1, 3, 1000);
+        stats.received(0, 4, 8);
+
+        let routes = stats.routes();
+        assert_eq!(routes.len(), 3);
+        assert_eq!((routes[0].0, routes[0].1), (0, 4));
+        assert_eq!(routes[0].2.received, Counter { messages: 1, bytes: 8 });
+        assert_eq!((routes[1].0, routes[1].1), (1, 2));
+        assert_eq!(routes[1].2.sent, Counter { messages: 2, bytes: 150 });
+        assert_eq!(routes[1].2.received, Counter::default());
+        assert_eq!(routes[2].2.received, Counter { messages: 1, bytes: 1000 });
+    }
+
+    #[test]
+    fn test_limiter() {
+        let mut unlimited = Limiter::new(None);
+        assert_eq!(unlimited.reserve(1 << 30), None);
+        assert_eq!(unlimited.reserve(1 << 30), None);
+        let mut unlimited = Limiter::new(Some(0));
+        assert_eq!(unlimited.reserve(1 << 30), None);
+
+        let mut limiter = Limiter::new(Some(1000));
+        let start = Instant::now();
+        // the first message passes at once, the next ones wait for it to fit in the rate
+        assert_eq!(limiter.reserve(2000), None);
+        let at = limiter.reserve(500).expect("over the rate");
+        assert!(at >= start + Duration::from_secs(2));
+        let at_next = limiter.reserve(10).expect("over the rate");
+        assert!(at_next >= at + Duration::from_millis(500));
+    }
+}
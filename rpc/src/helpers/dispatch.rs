@@ -77,6 +77,9 @@ pub trait Dispatcher: Send + Sync + Clone {
 
     /// "Dispatch" a local transaction.
     fn dispatch_transaction(&self, signed_transaction: PendingTransaction) -> Result<H256>;
+
+    /// Find a local transaction still waiting in the queue.
+    fn local_transaction(&self, hash: &H256) -> Option<PendingTransaction>;
 }
 
 /// A dispatcher which uses references to a client and miner in order to sign
@@ -201,6 +204,10 @@ impl<C: MiningBlockChainClient, M: MinerService> Dispatcher for FullDispatcher<C
     fn dispatch_transaction(&self, signed_transaction: PendingTransaction) -> Result<H256> {
         Self::dispatch_transaction(&*self.client, &*self.miner, signed_transaction)
     }
+
+    fn local_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+        self.miner.local_transaction(hash)
+    }
 }
 
 /// Returns a eth_sign-compatible hash of data to sign.
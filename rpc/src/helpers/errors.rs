@@ -27,6 +27,7 @@ use std::fmt;
 
 use acore::account_provider::{SignError as AccountError};
 use acore::{Error as EthcoreError, CallError};
+use acore::light::Error as LightError;
 use jsonrpc_core::{futures, Error, ErrorCode, Value};
 use rlp::DecoderError;
 use acore::transaction::Error as TransactionError;
@@ -423,6 +424,26 @@ pub fn transaction_message(error: TransactionError) -> String {
             )
         }
         BeaconBanned => "Not yet forked, Beacon hash is banned.".into(),
+        InsufficientEffectiveGasPrice {
+            minimal,
+            got,
+        } => {
+            format!(
+                "Transaction gas price is too low for the energy it uses (minimal effective price: \
+                 {}, got: {}). Try to increase the gas price or decrease supplied gas.",
+                minimal, got
+            )
+        }
+        NonceTooFarInFuture {
+            maximal,
+            got,
+        } => {
+            format!(
+                "Transaction nonce is too far ahead of the account nonce (maximal distance: {}, \
+                 got: {}). Wait for the previous transactions to be mined.",
+                maximal, got
+            )
+        }
     }
 }
 
@@ -499,6 +520,17 @@ pub fn compilation_failed<T: Into<Option<String>>>(message: T) -> Error {
     }
 }
 
+pub fn light(error: LightError) -> Error {
+    match error {
+        LightError::Timeout => no_light_peers(),
+        LightError::UnknownBlock => unknown_block(),
+        LightError::Canceled => internal("on-demand sender cancelled", ""),
+        LightError::Execution(e) => execution(e),
+        LightError::NotSupported(what) => light_unimplemented(Some(what.into())),
+        e => fetch(e),
+    }
+}
+
 // on-demand sender cancelled.
 pub fn on_demand_cancel(_cancel: futures::sync::oneshot::Canceled) -> Error {
     internal("on-demand sender cancelled", "")
@@ -31,10 +31,12 @@ pub mod nonce;
 mod poll_filter;
 mod poll_manager;
 mod requests;
+mod subscribers;
 
 pub use self::dispatch::{Dispatcher, FullDispatcher};
 pub use self::poll_manager::PollManager;
 pub use self::poll_filter::{PollFilter, limit_logs};
+pub use self::subscribers::Subscribers;
 pub use self::requests::{
     TransactionRequest, FilledTransactionRequest, ConfirmationRequest, ConfirmationPayload,
     CallRequest,
@@ -0,0 +1,112 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! A map of subscribers.
+
+use std::collections::HashMap;
+use std::ops;
+
+use jsonrpc_macros::pubsub::{Sink, Subscriber};
+use jsonrpc_pubsub::SubscriptionId;
+
+/// Subscription id, unique within a `Subscribers` map.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct Id(u64);
+
+impl Id {
+    /// The id as passed to and from rpc clients.
+    pub fn as_string(&self) -> String { format!("{:#x}", self.0) }
+
+    fn parse(id: &str) -> Option<Id> {
+        if !id.starts_with("0x") {
+            return None;
+        }
+        u64::from_str_radix(&id[2..], 16).ok().map(Id)
+    }
+}
+
+/// Subscribers of a single subscription kind, keyed by their subscription id.
+pub struct Subscribers<T> {
+    next_id: u64,
+    subscriptions: HashMap<Id, T>,
+}
+
+impl<T> Default for Subscribers<T> {
+    fn default() -> Self {
+        Subscribers {
+            next_id: 0,
+            subscriptions: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Subscribers<T> {
+    fn next_id(&mut self) -> Id {
+        self.next_id += 1;
+        Id(self.next_id)
+    }
+
+    /// Removes subscription with given id and returns it (if any).
+    pub fn remove(&mut self, id: &SubscriptionId) -> Option<T> {
+        match *id {
+            SubscriptionId::String(ref id) => {
+                Id::parse(id).and_then(|id| self.subscriptions.remove(&id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Assigns an id to given subscriber and starts tracking what `track` makes of its sink.
+    pub fn push<S, F>(&mut self, sub: Subscriber<S>, track: F)
+    where F: FnOnce(Sink<S>) -> T {
+        let id = self.next_id();
+        if let Ok(sink) = sub.assign_id(SubscriptionId::String(id.as_string())) {
+            self.subscriptions.insert(id, track(sink));
+        }
+    }
+}
+
+impl<T> ops::Deref for Subscribers<T> {
+    type Target = HashMap<Id, T>;
+
+    fn deref(&self) -> &Self::Target { &self.subscriptions }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpc_pubsub::SubscriptionId;
+    use super::{Id, Subscribers};
+
+    #[test]
+    fn should_parse_ids() {
+        assert_eq!(Id::parse(&Id(0x2a).as_string()), Some(Id(0x2a)));
+        assert_eq!(Id::parse("2a"), None);
+        assert_eq!(Id::parse("0xzz"), None);
+    }
+
+    #[test]
+    fn should_not_remove_unknown_ids() {
+        let mut subscribers = Subscribers::<()>::default();
+        assert_eq!(subscribers.remove(&SubscriptionId::String("0x1".into())), None);
+        assert_eq!(subscribers.remove(&SubscriptionId::Number(1)), None);
+    }
+}
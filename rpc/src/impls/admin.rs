@@ -0,0 +1,91 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Admin rpc implementation.
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use jsonrpc_core::Result;
+use acore::sync::{ManageNetwork, SyncProvider};
+use version::version;
+
+use helpers::errors;
+use traits::Admin;
+use types::{NodeInfo, PeerInfo, RouteTraffic};
+
+fn parse_ip(ip: &str) -> Result<Ipv4Addr> {
+    ip.parse::<Ipv4Addr>().map_err(|e| errors::invalid_params("ip", e))
+}
+
+/// Admin rpc implementation.
+pub struct AdminClient<S: ?Sized, N: ?Sized> {
+    sync: Arc<S>,
+    net: Arc<N>,
+}
+
+impl<S: ?Sized, N: ?Sized> AdminClient<S, N>
+where
+    S: SyncProvider,
+    N: ManageNetwork,
+{
+    /// Creates new AdminClient.
+    pub fn new(sync: &Arc<S>, net: &Arc<N>) -> Self {
+        AdminClient {
+            sync: sync.clone(),
+            net: net.clone(),
+        }
+    }
+}
+
+impl<S: ?Sized, N: ?Sized> Admin for AdminClient<S, N>
+where
+    S: SyncProvider + 'static,
+    N: ManageNetwork + 'static,
+{
+    fn peers(&self) -> Result<Vec<PeerInfo>> {
+        Ok(self.sync.peers().into_iter().map(Into::into).collect())
+    }
+
+    fn node_info(&self) -> Result<NodeInfo> {
+        Ok(NodeInfo::new(self.sync.node_info(), version()))
+    }
+
+    fn traffic(&self) -> Result<Vec<RouteTraffic>> {
+        Ok(self.sync.traffic().into_iter().map(Into::into).collect())
+    }
+
+    fn add_peer(&self, node: String) -> Result<bool> {
+        self.net
+            .add_peer(&node)
+            .map(|_| true)
+            .map_err(|e| errors::invalid_params("node", e))
+    }
+
+    fn remove_peer(&self, id_or_addr: String) -> Result<bool> {
+        Ok(self.net.remove_peer(&id_or_addr))
+    }
+
+    fn ban_ip(&self, ip: String) -> Result<bool> {
+        self.net.ban_ip(parse_ip(&ip)?);
+        Ok(true)
+    }
+
+    fn unban_ip(&self, ip: String) -> Result<bool> { Ok(self.net.unban_ip(parse_ip(&ip)?)) }
+}
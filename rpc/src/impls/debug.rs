@@ -0,0 +1,87 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Debug api implementation.
+
+use std::sync::Arc;
+
+use acore::client::{BlockChainClient, CallAnalytics, TransactionId};
+use aion_types::H256;
+use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
+
+use helpers::{errors, fake_sign};
+use traits::Debug;
+use types::{BlockNumber, CallRequest, ExecutionTrace, TraceOptions};
+
+fn vm_tracing(options: Trailing<TraceOptions>) -> CallAnalytics {
+    CallAnalytics {
+        vm_tracing: Some(options.unwrap_or_default().into()),
+        ..Default::default()
+    }
+}
+
+/// Debug api implementation.
+pub struct DebugClient<C> {
+    client: Arc<C>,
+}
+
+impl<C> DebugClient<C> {
+    /// Creates new Debug client.
+    pub fn new(client: &Arc<C>) -> Self {
+        DebugClient {
+            client: client.clone(),
+        }
+    }
+}
+
+impl<C> Debug for DebugClient<C>
+where C: BlockChainClient + 'static
+{
+    fn trace_transaction(
+        &self,
+        transaction_hash: H256,
+        options: Trailing<TraceOptions>,
+    ) -> Result<ExecutionTrace>
+    {
+        self.client
+            .replay(TransactionId::Hash(transaction_hash), vm_tracing(options))
+            .map(ExecutionTrace::from)
+            .map_err(errors::call)
+    }
+
+    fn trace_call(
+        &self,
+        request: CallRequest,
+        block: BlockNumber,
+        options: Trailing<TraceOptions>,
+    ) -> Result<ExecutionTrace>
+    {
+        let request = CallRequest::into(request);
+        let signed = fake_sign::sign_call(request)?;
+
+        self.client
+            .call(&signed, vm_tracing(options), block.into())
+            .map(ExecutionTrace::from)
+            .map_err(errors::call)
+    }
+}
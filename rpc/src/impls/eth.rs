@@ -32,12 +32,14 @@ use aion_types::{H64, H128, U128, H256, U256, Address};
 use serde_json::{self, Value};
 use serde_json::map::Map;
 use dispatch::DynamicGasPrice;
+use blake2b::blake2b;
 
 use acore::sync::SyncProvider;
 use acore::account_provider::AccountProvider;
-use acore::client::{MiningBlockChainClient, BlockId, TransactionId};
+use acore::client::{MiningBlockChainClient, ProvingBlockChainClient, BlockId, TransactionId};
 use acore::filter::Filter as EthcoreFilter;
 use acore::header::{BlockNumber as EthBlockNumber, SealType};
+use acore::light::LightClient;
 use acore::log_entry::LogEntry;
 use acore::miner::MinerService;
 use acore::miner::external::ExternalMinerService;
@@ -46,7 +48,7 @@ use acore::transaction::SignedTransaction;
 use solidity::compile;
 
 use jsonrpc_core::{BoxFuture, Result};
-use jsonrpc_core::futures::future;
+use jsonrpc_core::futures::{future, Future};
 use jsonrpc_macros::Trailing;
 
 use helpers::{errors, limit_logs, fake_sign};
@@ -55,7 +57,8 @@ use helpers::accounts::unwrap_provider;
 use traits::Eth;
 use types::{
     Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, Transaction, CallRequest, Index,
-Filter, Log, Receipt, Work, Contract, ContractInfo, Abi, AbiIO , SyncInfo, /*AcitvePeerInfo, PbSyncInfo,
+    AccountProof, StorageProof,
+Filter, Log, Receipt, Work, Contract, ContractInfo, Abi, AbiIO , /*AcitvePeerInfo, PbSyncInfo,
                                                                            SimpleReceipt, SimpleReceiptLog,*/
 };
 
@@ -75,6 +78,7 @@ where
     miner: Arc<M>,
     external_miner: Arc<EM>,
     dynamic_gas_price: Option<DynamicGasPrice>,
+    light: Option<Arc<LightClient>>,
 }
 
 impl<C, S: ?Sized, M, EM> EthClient<C, S, M, EM>
@@ -92,6 +96,7 @@ where
         miner: &Arc<M>,
         em: &Arc<EM>,
         dynamic_gas_price: Option<DynamicGasPrice>,
+        light: &Option<Arc<LightClient>>,
     ) -> Self
     {
         EthClient {
@@ -101,6 +106,7 @@ where
             accounts: accounts.clone(),
             external_miner: em.clone(),
             dynamic_gas_price: dynamic_gas_price.clone(),
+            light: light.clone(),
         }
     }
 
@@ -246,7 +252,7 @@ where C: MiningBlockChainClient {
 
 impl<C, S: ?Sized, M, EM> Eth for EthClient<C, S, M, EM>
 where
-    C: MiningBlockChainClient + 'static,
+    C: MiningBlockChainClient + ProvingBlockChainClient + 'static,
     S: SyncProvider + 'static,
     M: MinerService + 'static,
     EM: ExternalMinerService + 'static,
@@ -258,24 +264,11 @@ where
 
     fn syncing(&self) -> Result<SyncStatus> {
         let status = self.sync.status();
-        let client = &self.client;
-
-        let chain_info = client.chain_info();
-        let current_block = chain_info.best_block_number;
-        let highest_block = status.highest_block_number.unwrap_or(0u64);
-
-        // refer to java's impl: AionImpl.java isSyncComplete.
-        if (current_block + 5) < highest_block {
-            let info = SyncInfo {
-                // to comply with java's impl, return hex string.
-                starting_block: format!("{:#x}", status.start_block_number),
-                current_block: format!("{:#x}", current_block),
-                highest_block: format!("{:#x}", highest_block),
-            };
-            Ok(SyncStatus::Info(info))
-        } else {
-            Ok(SyncStatus::None)
-        }
+        Ok(SyncStatus::new(
+            status.start_block_number,
+            self.client.chain_info().best_block_number,
+            status.highest_block_number.unwrap_or(0u64),
+        ))
     }
 
     fn author(&self) -> Result<H256> { Ok(H256::from(self.miner.author())) }
@@ -300,13 +293,22 @@ where
         Ok(accounts.into_iter().map(Into::into).collect::<Vec<H256>>())
     }
 
-    fn block_number(&self) -> Result<u64> { Ok(self.client.chain_info().best_block_number) }
+    fn block_number(&self) -> Result<u64> {
+        match self.light {
+            Some(ref light) => Ok(light.chain().best_block_number()),
+            None => Ok(self.client.chain_info().best_block_number),
+        }
+    }
 
     fn balance(&self, address: H256, num: Trailing<BlockNumber>) -> BoxFuture<U256> {
         let address = address.into();
 
         let id = num.unwrap_or_default();
 
+        if let Some(ref light) = self.light {
+            return Box::new(light.balance(address, id.into()).map_err(errors::light));
+        }
+
         try_bf!(check_known(&*self.client, id.clone()));
         let res = match self.client.balance(&address, id.into()) {
             Some(balance) => Ok(balance.into()),
@@ -325,6 +327,14 @@ where
     {
         let id = num.unwrap_or_default();
 
+        if let Some(ref light) = self.light {
+            return Box::new(
+                light
+                    .storage_at(address, H128::from(pos), id.into())
+                    .map_err(errors::light),
+            );
+        }
+
         try_bf!(check_known(&*self.client, id.clone()));
         let res = match self
             .client
@@ -337,6 +347,51 @@ where
         Box::new(future::done(res))
     }
 
+    fn proof(
+        &self,
+        address: Address,
+        keys: Vec<U128>,
+        num: Trailing<BlockNumber>,
+    ) -> BoxFuture<AccountProof>
+    {
+        let id = num.unwrap_or_default();
+
+        try_bf!(check_known(&*self.client, id.clone()));
+        let id: BlockId = id.into();
+        let account_key = blake2b(&address);
+        let (account_proof, account) = try_bf!(
+            self.client
+                .prove_account(account_key, id.clone())
+                .ok_or_else(errors::state_pruned)
+        );
+        let storage_proof = try_bf!(
+            keys.into_iter()
+                .map(|key| {
+                    self.client
+                        .prove_storage(account_key, blake2b(&H128::from(key)), id.clone())
+                        .map(|(proof, value)| {
+                            StorageProof {
+                                key: key,
+                                value: value.into(),
+                                proof: proof.into_iter().map(Bytes::new).collect(),
+                            }
+                        })
+                        .ok_or_else(errors::state_pruned)
+                })
+                .collect::<Result<Vec<_>>>()
+        );
+
+        Box::new(future::ok(AccountProof {
+            address: address,
+            account_proof: account_proof.into_iter().map(Bytes::new).collect(),
+            balance: account.balance,
+            code_hash: account.code_hash,
+            nonce: account.nonce,
+            storage_hash: account.storage_root,
+            storage_proof: storage_proof,
+        }))
+    }
+
     fn transaction_count(&self, address: Address, num: Trailing<BlockNumber>) -> BoxFuture<U256> {
         let res = match num.unwrap_or_default() {
             BlockNumber::Pending => {
@@ -436,6 +491,15 @@ where
     }
 
     fn transaction_receipt(&self, hash: H256) -> BoxFuture<Option<Receipt>> {
+        if let Some(ref light) = self.light {
+            return Box::new(
+                light
+                    .transaction_receipt(hash)
+                    .map(|receipt| receipt.map(Into::into))
+                    .map_err(errors::light),
+            );
+        }
+
         let receipt = self.client.transaction_receipt(TransactionId::Hash(hash));
         Box::new(future::ok(receipt.map(Into::into)))
     }
@@ -502,6 +566,15 @@ where
         let signed = try_bf!(fake_sign::sign_call(request));
 
         let num = num.unwrap_or_default();
+        if let Some(ref light) = self.light {
+            return Box::new(
+                light
+                    .call(signed, num.into())
+                    .map(|executed| executed.output.into())
+                    .map_err(errors::light),
+            );
+        }
+
         let result = self.client.call(&signed, Default::default(), num.into());
 
         Box::new(future::done(
@@ -0,0 +1,227 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Eth PUB-SUB rpc implementation.
+
+use std::sync::Arc;
+
+use acore::client::{BlockChainClient, BlockId, ChainNotify};
+use acore::filter::Filter as EthFilter;
+use acore::sync::SyncProvider;
+use aion_types::H256;
+use bytes::Bytes;
+use jsonrpc_core::Result;
+use jsonrpc_core::futures::{Future, Sink as FutureSink, Stream};
+use jsonrpc_core::futures::sync::mpsc;
+use jsonrpc_macros::Trailing;
+use jsonrpc_macros::pubsub::Subscriber;
+use jsonrpc_pubsub::SubscriptionId;
+use parking_lot::{Mutex, RwLock};
+use tokio::runtime::TaskExecutor;
+
+use helpers::{errors, Subscribers};
+use metadata::Metadata;
+use traits::EthPubSub;
+use types::{Filter, Header, Log, SyncStatus};
+use types::pubsub;
+
+/// Notifications of a subscriber, forwarded in order to its sink by a single task.
+type Client = mpsc::UnboundedSender<pubsub::Result>;
+
+/// What a subscriber is subscribed to.
+enum Subscription {
+    NewHeads,
+    Logs(Filter),
+    NewPendingTransactions,
+    Syncing,
+}
+
+/// Eth PubSub implementation.
+pub struct EthPubSubClient<C> {
+    handler: Arc<ChainNotificationHandler<C>>,
+    subscribers: Arc<RwLock<Subscribers<(Client, Subscription)>>>,
+}
+
+impl<C> EthPubSubClient<C> {
+    /// Creates new `EthPubSubClient`.
+    pub fn new(client: Arc<C>, sync: Arc<SyncProvider>, executor: TaskExecutor) -> Self {
+        let subscribers = Arc::new(RwLock::new(Subscribers::default()));
+
+        EthPubSubClient {
+            handler: Arc::new(ChainNotificationHandler {
+                client,
+                sync,
+                executor,
+                syncing: Mutex::new(false),
+                subscribers: subscribers.clone(),
+            }),
+            subscribers,
+        }
+    }
+
+    /// Returns a chain notification handler, to be registered with the client.
+    pub fn handler(&self) -> Arc<ChainNotificationHandler<C>> { self.handler.clone() }
+}
+
+/// PubSub Notification handler.
+pub struct ChainNotificationHandler<C> {
+    client: Arc<C>,
+    sync: Arc<SyncProvider>,
+    executor: TaskExecutor,
+    // whether the last status sent to sync subscribers was "syncing"
+    syncing: Mutex<bool>,
+    subscribers: Arc<RwLock<Subscribers<(Client, Subscription)>>>,
+}
+
+impl<C: BlockChainClient> ChainNotificationHandler<C> {
+    fn notify(&self, subscriber: &Client, result: pubsub::Result) {
+        // only fails once the forwarding task is done, the subscriber being gone
+        let _ = subscriber.unbounded_send(result);
+    }
+
+    fn sync_status(&self) -> SyncStatus {
+        let status = self.sync.status();
+        SyncStatus::new(
+            status.start_block_number,
+            self.client.chain_info().best_block_number,
+            status.highest_block_number.unwrap_or(0u64),
+        )
+    }
+}
+
+impl<C: BlockChainClient> ChainNotify for ChainNotificationHandler<C> {
+    fn new_blocks(
+        &self,
+        _imported: Vec<H256>,
+        _invalid: Vec<H256>,
+        enacted: Vec<H256>,
+        _retracted: Vec<H256>,
+        _sealed: Vec<H256>,
+        _proposed: Vec<Bytes>,
+        _duration: u64,
+    )
+    {
+        let subscribers = self.subscribers.read();
+        if enacted.is_empty() || subscribers.is_empty() {
+            return;
+        }
+
+        let headers: Vec<Header> = enacted
+            .iter()
+            .filter_map(|hash| self.client.block_header(BlockId::Hash(*hash)))
+            .map(|header| Header::from(&header))
+            .collect();
+
+        // only report changes between syncing and not syncing.
+        let sync_status = {
+            let status = self.sync_status();
+            let mut syncing = self.syncing.lock();
+            if *syncing == status.is_syncing() {
+                None
+            } else {
+                *syncing = status.is_syncing();
+                Some(status)
+            }
+        };
+
+        for &(ref subscriber, ref subscription) in subscribers.values() {
+            match *subscription {
+                Subscription::NewHeads => {
+                    for header in &headers {
+                        self.notify(subscriber, pubsub::Result::Header(header.clone()));
+                    }
+                }
+                Subscription::Logs(ref filter) => {
+                    // logs of retracted blocks are not reported.
+                    for hash in &enacted {
+                        let mut filter: EthFilter = filter.clone().into();
+                        filter.from_block = BlockId::Hash(*hash);
+                        filter.to_block = BlockId::Hash(*hash);
+                        for log in self.client.logs(filter) {
+                            self.notify(subscriber, pubsub::Result::Log(Log::from(log)));
+                        }
+                    }
+                }
+                Subscription::Syncing => {
+                    if let Some(ref status) = sync_status {
+                        self.notify(subscriber, pubsub::Result::SyncState(status.clone()));
+                    }
+                }
+                Subscription::NewPendingTransactions => {}
+            }
+        }
+    }
+
+    fn transactions_imported(&self, hashes: &[H256]) {
+        for &(ref subscriber, ref subscription) in self.subscribers.read().values() {
+            if let Subscription::NewPendingTransactions = *subscription {
+                for hash in hashes {
+                    self.notify(subscriber, pubsub::Result::TransactionHash(*hash));
+                }
+            }
+        }
+    }
+}
+
+impl<C: BlockChainClient + 'static> EthPubSub for EthPubSubClient<C> {
+    type Metadata = Metadata;
+
+    fn subscribe(
+        &self,
+        _meta: Metadata,
+        subscriber: Subscriber<pubsub::Result>,
+        kind: pubsub::Kind,
+        params: Trailing<pubsub::Params>,
+    )
+    {
+        let subscription = match (kind, params.unwrap_or_default()) {
+            (pubsub::Kind::NewHeads, pubsub::Params::None) => Subscription::NewHeads,
+            (pubsub::Kind::Logs, pubsub::Params::Logs(filter)) => Subscription::Logs(filter),
+            (pubsub::Kind::NewPendingTransactions, pubsub::Params::None) => {
+                Subscription::NewPendingTransactions
+            }
+            (pubsub::Kind::Syncing, pubsub::Params::None) => Subscription::Syncing,
+            (pubsub::Kind::Logs, _) => {
+                let _ = subscriber.reject(errors::invalid_params("logs", "Expected a filter."));
+                return;
+            }
+            (_, _) => {
+                let _ = subscriber.reject(errors::invalid_params("params", "Expected none."));
+                return;
+            }
+        };
+        let executor = &self.handler.executor;
+        self.subscribers.write().push(subscriber, |sink| {
+            let (client, notifications) = mpsc::unbounded();
+            executor.spawn(
+                sink.sink_map_err(|e| warn!(target: "rpc", "Unable to send notification: {:?}", e))
+                    .send_all(notifications.map(Ok))
+                    .map(|_| ()),
+            );
+            (client, subscription)
+        });
+    }
+
+    fn unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        Ok(self.subscribers.write().remove(&id).is_some())
+    }
+}
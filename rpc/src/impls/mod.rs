@@ -32,7 +32,10 @@ macro_rules! try_bf {
 
 #[macro_use]
 mod eth;
+mod admin;
+mod debug;
 mod eth_filter;
+mod eth_pubsub;
 mod net;
 mod personal;
 mod signing;
@@ -40,9 +43,14 @@ mod rpc;
 mod stratum;
 mod web3;
 mod ping;
+mod traces;
+mod txpool;
 
+pub use self::admin::AdminClient;
+pub use self::debug::DebugClient;
 pub use self::eth::EthClient;
 pub use self::eth_filter::EthFilterClient;
+pub use self::eth_pubsub::EthPubSubClient;
 pub use self::net::NetClient;
 pub use self::personal::PersonalClient;
 pub use self::signing::SigningClient;
@@ -50,3 +58,5 @@ pub use self::web3::Web3Client;
 pub use self::rpc::RpcClient;
 pub use self::stratum::StratumClient;
 pub use self::ping::PingClient;
+pub use self::traces::{TraceFilterLimits, TracesClient};
+pub use self::txpool::TxPoolClient;
@@ -24,10 +24,10 @@
 use std::sync::Arc;
 use bytes::ToPretty;
 use acore::account_provider::AccountProvider;
-use acore::transaction::PendingTransaction;
-use aion_types::{H256, H768, Address};
+use acore::transaction::{Action, PendingTransaction};
+use aion_types::{H256, H768, U256, Address};
 use jsonrpc_core::{BoxFuture, Result};
-use jsonrpc_core::futures::Future;
+use jsonrpc_core::futures::{future, Future};
 use helpers::errors;
 use helpers::dispatch::{self, Dispatcher, SignWith};
 use helpers::accounts::unwrap_provider;
@@ -210,4 +210,103 @@ impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
         );
         self.send_transaction(request, password)
     }
+
+    fn speed_up_transaction(
+        &self,
+        hash: H256,
+        gas_price: U256,
+        password: String,
+    ) -> BoxFuture<H256>
+    {
+        let pending_tx = match self.dispatcher.local_transaction(&hash) {
+            Some(pending_tx) => pending_tx,
+            None => {
+                return Box::new(future::err(errors::invalid_params(
+                    "hash",
+                    "No local transaction with this hash in the queue",
+                )));
+            }
+        };
+        let request = try_bf!(speed_up_request(&pending_tx, gas_price));
+        self.send_transaction(request, password)
+    }
+}
+
+/// Builds the request replacing `pending_tx` with the same transaction paying `gas_price`.
+fn speed_up_request(
+    pending_tx: &PendingTransaction,
+    gas_price: U256,
+) -> Result<TransactionRequest>
+{
+    if gas_price <= pending_tx.gas_price {
+        return Err(errors::invalid_params(
+            "gas_price",
+            format!(
+                "Gas price has to be higher than {} of the queued transaction",
+                pending_tx.gas_price
+            ),
+        ));
+    }
+    Ok(TransactionRequest {
+        from: Some(pending_tx.sender().clone()),
+        to: match pending_tx.action {
+            Action::Create => None,
+            Action::Call(ref address) => Some(address.clone()),
+        },
+        gas_price: Some(gas_price),
+        gas: Some(pending_tx.gas),
+        value: Some(pending_tx.value),
+        data: Some(pending_tx.data.clone().into()),
+        nonce: Some(pending_tx.nonce),
+        tx_type: Some(pending_tx.transaction_type),
+        condition: pending_tx.condition.clone().map(Into::into),
+        beacon: pending_tx.beacon,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use acore::transaction::{Action, PendingTransaction, Transaction, DEFAULT_TRANSACTION_TYPE};
+    use aion_types::{Address, U256};
+    use key::generate_keypair;
+    use super::speed_up_request;
+
+    fn pending_transaction(gas_price: U256) -> PendingTransaction {
+        let keypair = generate_keypair();
+        Transaction::new(
+            U256::from(7),
+            gas_price,
+            U256::from(21_000),
+            Action::Call(Address::from(1)),
+            U256::from(100),
+            vec![1, 2, 3],
+            DEFAULT_TRANSACTION_TYPE,
+            None,
+        )
+        .sign(keypair.secret())
+        .into()
+    }
+
+    #[test]
+    fn should_reject_speed_up_without_higher_gas_price() {
+        let pending_tx = pending_transaction(U256::from(10_000_000_000u64));
+
+        assert!(speed_up_request(&pending_tx, U256::from(10_000_000_000u64)).is_err());
+        assert!(speed_up_request(&pending_tx, U256::from(9_999_999_999u64)).is_err());
+    }
+
+    #[test]
+    fn should_replace_transaction_at_same_nonce() {
+        let pending_tx = pending_transaction(U256::from(10_000_000_000u64));
+
+        let request = speed_up_request(&pending_tx, U256::from(12_000_000_000u64)).unwrap();
+
+        assert_eq!(request.from, Some(pending_tx.sender().clone()));
+        assert_eq!(request.to, Some(Address::from(1)));
+        assert_eq!(request.nonce, Some(pending_tx.nonce));
+        assert_eq!(request.gas_price, Some(U256::from(12_000_000_000u64)));
+        assert_eq!(request.gas, Some(pending_tx.gas));
+        assert_eq!(request.value, Some(pending_tx.value));
+        assert_eq!(request.data, Some(vec![1, 2, 3].into()));
+    }
 }
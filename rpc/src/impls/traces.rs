@@ -0,0 +1,161 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Traces api implementation.
+
+use std::sync::Arc;
+
+use acore::client::{BlockChainClient, CallAnalytics, TransactionId};
+use acore::trace;
+use aion_types::H256;
+use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
+
+use helpers::{errors, fake_sign};
+use traits::Traces;
+use types::{BlockNumber, CallRequest, LocalizedTrace, TraceFilter, TraceResults};
+
+fn to_call_analytics(flags: &[String]) -> CallAnalytics {
+    CallAnalytics {
+        transaction_tracing: flags.iter().any(|flag| flag == "trace"),
+        state_diffing: flags.iter().any(|flag| flag == "stateDiff"),
+        ..Default::default()
+    }
+}
+
+/// Limits of a single `trace_filter` request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceFilterLimits {
+    /// Maximal number of blocks searched.
+    pub max_blocks: u64,
+    /// Maximal number of traces returned.
+    pub max_results: usize,
+}
+
+impl Default for TraceFilterLimits {
+    fn default() -> Self {
+        TraceFilterLimits {
+            max_blocks: 1_000,
+            max_results: 10_000,
+        }
+    }
+}
+
+/// Traces api implementation.
+pub struct TracesClient<C> {
+    client: Arc<C>,
+    limits: TraceFilterLimits,
+}
+
+impl<C> TracesClient<C> {
+    /// Creates new Traces client.
+    pub fn new(client: &Arc<C>, limits: TraceFilterLimits) -> Self {
+        TracesClient {
+            client: client.clone(),
+            limits,
+        }
+    }
+}
+
+impl<C> Traces for TracesClient<C>
+where C: BlockChainClient + 'static
+{
+    fn filter(&self, filter: TraceFilter) -> Result<Option<Vec<LocalizedTrace>>> {
+        let mut filter: trace::Filter = filter.into();
+
+        let from = self.client.block_number(filter.from_block.clone());
+        let to = self.client.block_number(filter.to_block.clone());
+        if let (Some(from), Some(to)) = (from, to) {
+            if to >= from && to - from >= self.limits.max_blocks {
+                return Err(errors::filter(&format!(
+                    "Block range is limited to {} blocks",
+                    self.limits.max_blocks
+                )));
+            }
+        }
+
+        // Ask for one trace more than allowed to tell a full page from a truncated one.
+        let max_results = self.limits.max_results;
+        let capped = filter.count.map_or(true, |count| count > max_results);
+        if capped {
+            filter.count = Some(max_results.saturating_add(1));
+        }
+
+        let traces = match self.client.filter_traces(filter) {
+            Some(traces) => traces,
+            None => return Ok(None),
+        };
+        if capped && traces.len() > max_results {
+            return Err(errors::filter(&format!(
+                "Number of traces is limited to {}, use `after` and `count` to page through them",
+                max_results
+            )));
+        }
+        Ok(Some(traces.into_iter().map(LocalizedTrace::from).collect()))
+    }
+
+    fn block_traces(&self, block_number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>> {
+        Ok(self
+            .client
+            .block_traces(block_number.into())
+            .map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
+    }
+
+    fn transaction_traces(&self, transaction_hash: H256) -> Result<Option<Vec<LocalizedTrace>>> {
+        Ok(self
+            .client
+            .transaction_traces(TransactionId::Hash(transaction_hash))
+            .map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
+    }
+
+    fn call(
+        &self,
+        request: CallRequest,
+        flags: Vec<String>,
+        block: Trailing<BlockNumber>,
+    ) -> Result<TraceResults>
+    {
+        let request = CallRequest::into(request);
+        let signed = fake_sign::sign_call(request)?;
+
+        let block = block.unwrap_or_default();
+        self.client
+            .call(&signed, to_call_analytics(&flags), block.into())
+            .map(TraceResults::from)
+            .map_err(errors::call)
+    }
+
+    fn replay_transaction(
+        &self,
+        transaction_hash: H256,
+        flags: Vec<String>,
+    ) -> Result<TraceResults>
+    {
+        self.client
+            .replay(
+                TransactionId::Hash(transaction_hash),
+                to_call_analytics(&flags),
+            )
+            .map(TraceResults::from)
+            .map_err(errors::call)
+    }
+}
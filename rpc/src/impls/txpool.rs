@@ -0,0 +1,91 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Transaction pool rpc implementation.
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use jsonrpc_core::Result;
+use acore::miner::MinerService;
+use aion_types::H256;
+
+use traits::TxPool;
+use types::{LocalTransactionStatus, Transaction, TxPoolContent, TxPoolStatus};
+
+/// Transaction pool rpc implementation.
+pub struct TxPoolClient<M: ?Sized> {
+    miner: Arc<M>,
+}
+
+impl<M: ?Sized> TxPoolClient<M>
+where M: MinerService
+{
+    /// Creates new TxPoolClient.
+    pub fn new(miner: &Arc<M>) -> Self {
+        TxPoolClient {
+            miner: miner.clone(),
+        }
+    }
+
+    fn transactions(&self) -> (Vec<Transaction>, Vec<Transaction>) {
+        let pending = self
+            .miner
+            .pending_transactions()
+            .into_iter()
+            .map(Transaction::from_pending)
+            .collect();
+        let future = self
+            .miner
+            .future_transactions()
+            .into_iter()
+            .map(Transaction::from_pending)
+            .collect();
+        (pending, future)
+    }
+}
+
+impl<M: ?Sized> TxPool for TxPoolClient<M>
+where M: MinerService + 'static
+{
+    fn status(&self) -> Result<TxPoolStatus> { Ok(self.miner.status().into()) }
+
+    fn content(&self) -> Result<TxPoolContent<Transaction>> {
+        let (pending, future) = self.transactions();
+        Ok(TxPoolContent::new(pending, future))
+    }
+
+    fn inspect(&self) -> Result<TxPoolContent<String>> {
+        let (pending, future) = self.transactions();
+        Ok(TxPoolContent::inspect(pending, future))
+    }
+
+    fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>> {
+        Ok(self
+            .miner
+            .local_transactions()
+            .into_iter()
+            .map(|(hash, status)| (hash, status.into()))
+            .collect())
+    }
+
+    fn cancel_transaction(&self, hash: H256) -> Result<bool> {
+        Ok(self.miner.cancel_transaction(&hash))
+    }
+}
@@ -0,0 +1,59 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Admin rpc interface.
+use jsonrpc_core::Result;
+
+use types::{NodeInfo, PeerInfo, RouteTraffic};
+
+build_rpc_trait! {
+    /// Admin rpc interface, managing peers at runtime.
+    pub trait Admin {
+        /// Returns the active peers.
+        #[rpc(name = "admin_peers")]
+        fn peers(&self) -> Result<Vec<PeerInfo>>;
+
+        /// Returns the local node.
+        #[rpc(name = "admin_nodeInfo")]
+        fn node_info(&self) -> Result<NodeInfo>;
+
+        /// Returns the traffic with all peers by protocol message since start.
+        #[rpc(name = "admin_traffic")]
+        fn traffic(&self) -> Result<Vec<RouteTraffic>>;
+
+        /// Dials the node given as `p2p://<id>@<ip>:<port>`.
+        #[rpc(name = "admin_addPeer")]
+        fn add_peer(&self, String) -> Result<bool>;
+
+        /// Disconnects the peer with the given node id or address.
+        /// Returns false if no such peer is active.
+        #[rpc(name = "admin_removePeer")]
+        fn remove_peer(&self, String) -> Result<bool>;
+
+        /// Bans an ip and disconnects its peers.
+        #[rpc(name = "admin_banIp")]
+        fn ban_ip(&self, String) -> Result<bool>;
+
+        /// Lifts the ban of an ip. Returns false if the ip was not banned.
+        #[rpc(name = "admin_unbanIp")]
+        fn unban_ip(&self, String) -> Result<bool>;
+    }
+}
@@ -0,0 +1,50 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Debug rpc interface.
+use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
+use aion_types::H256;
+
+use types::{BlockNumber, CallRequest, ExecutionTrace, TraceOptions};
+
+build_rpc_trait! {
+    /// Debug rpc interface.
+    pub trait Debug {
+        /// Executes the given transaction again on the state it was originally executed on
+        /// and returns every instruction executed by the FastVM, with memory and storage
+        /// if the options ask for them. Avm transactions report their internal
+        /// transactions and committed state changes instead.
+        #[rpc(name = "debug_traceTransaction")]
+        fn trace_transaction(&self, H256, Trailing<TraceOptions>) -> Result<ExecutionTrace>;
+
+        /// Executes the given call on the state of the given block and returns every
+        /// instruction executed by the FastVM, or the avm trace for avm calls.
+        #[rpc(name = "debug_traceCall")]
+        fn trace_call(
+            &self,
+            CallRequest,
+            BlockNumber,
+            Trailing<TraceOptions>,
+        ) -> Result<ExecutionTrace>;
+    }
+}
@@ -28,7 +28,7 @@ use jsonrpc_macros::Trailing;
 use aion_types::{H64, H256, U256, U128, H128, Address};
 
 use types::{Block, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index};
-use types::{Log, Receipt, SyncStatus, Transaction, Work, Contract};
+use types::{Log, Receipt, SyncStatus, Transaction, Work, Contract, AccountProof};
 
 build_rpc_trait! {
     /// Eth rpc interface.
@@ -74,6 +74,11 @@ build_rpc_trait! {
         #[rpc(name = "eth_getStorageAt")]
         fn storage_at(&self, Address, U128, Trailing<BlockNumber>) -> BoxFuture<H128>;
 
+        /// Returns the merkle proof of the given account and of the given keys of its storage.
+        /// Storage proofs are checked against the storage root of FastVM contracts.
+        #[rpc(name = "eth_getProof")]
+        fn proof(&self, Address, Vec<U128>, Trailing<BlockNumber>) -> BoxFuture<AccountProof>;
+
         /// Returns block with given hash.
         #[rpc(name = "eth_getBlockByHash")]
         fn block_by_hash(&self, H256, bool) -> BoxFuture<Option<Block>>;
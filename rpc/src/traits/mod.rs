@@ -22,20 +22,30 @@
 
 //! Ethereum rpc interfaces.
 
+pub mod admin;
 pub mod web3;
+pub mod debug;
 pub mod eth;
 pub mod stratum;
+pub mod eth_pubsub;
 pub mod eth_signing;
 pub mod net;
 pub mod personal;
 pub mod rpc;
 pub mod ping;
+pub mod traces;
+pub mod txpool;
 
+pub use self::admin::Admin;
 pub use self::web3::Web3;
+pub use self::debug::Debug;
 pub use self::eth::{Eth, EthFilter};
 pub use self::stratum::Stratum;
+pub use self::eth_pubsub::EthPubSub;
 pub use self::eth_signing::EthSigning;
 pub use self::net::Net;
 pub use self::personal::Personal;
 pub use self::rpc::Rpc;
 pub use self::ping::Ping;
+pub use self::traces::Traces;
+pub use self::txpool::TxPool;
@@ -22,7 +22,7 @@
 
 //! Personal rpc interface.
 use jsonrpc_core::{BoxFuture, Result};
-use aion_types::{H256, H768, Address};
+use aion_types::{H256, H768, U256, Address};
 
 use types::{Bytes, TransactionRequest, RichRawTransaction as RpcRichRawTransaction};
 
@@ -73,5 +73,10 @@ build_rpc_trait! {
         #[rpc(name = "personal_signAndSendTransaction")]
         fn sign_and_send_transaction(&self, TransactionRequest, String) -> BoxFuture<H256>;
 
+        /// Re-signs a queued local transaction at the same nonce with a higher nrg price and
+        /// sends it to replace the original. Returns the hash of the replacement.
+        #[rpc(name = "personal_speedUpTransaction")]
+        fn speed_up_transaction(&self, H256, U256, String) -> BoxFuture<H256>;
+
     }
 }
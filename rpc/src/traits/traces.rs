@@ -0,0 +1,56 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Traces rpc interface.
+use jsonrpc_core::Result;
+use jsonrpc_macros::Trailing;
+use aion_types::H256;
+
+use types::{BlockNumber, CallRequest, LocalizedTrace, TraceFilter, TraceResults};
+
+build_rpc_trait! {
+    /// Traces rpc interface.
+    pub trait Traces {
+        /// Returns traces matching given filter.
+        /// Large results have to be paged through with `after` and `count`.
+        #[rpc(name = "trace_filter")]
+        fn filter(&self, TraceFilter) -> Result<Option<Vec<LocalizedTrace>>>;
+
+        /// Returns all traces produced at given block.
+        #[rpc(name = "trace_block")]
+        fn block_traces(&self, BlockNumber) -> Result<Option<Vec<LocalizedTrace>>>;
+
+        /// Returns all traces of given transaction.
+        #[rpc(name = "trace_transaction")]
+        fn transaction_traces(&self, H256) -> Result<Option<Vec<LocalizedTrace>>>;
+
+        /// Executes the given call and returns the requested traces of it.
+        /// Supported trace types are "trace" and "stateDiff".
+        #[rpc(name = "trace_call")]
+        fn call(&self, CallRequest, Vec<String>, Trailing<BlockNumber>) -> Result<TraceResults>;
+
+        /// Executes the given transaction again on the state it was originally executed on
+        /// and returns the requested traces of it.
+        #[rpc(name = "trace_replayTransaction")]
+        fn replay_transaction(&self, H256, Vec<String>) -> Result<TraceResults>;
+    }
+}
@@ -0,0 +1,56 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Transaction pool rpc interface.
+use std::collections::BTreeMap;
+
+use jsonrpc_core::Result;
+use aion_types::H256;
+
+use types::{LocalTransactionStatus, Transaction, TxPoolContent, TxPoolStatus};
+
+build_rpc_trait! {
+    /// Transaction pool rpc interface, inspecting the transaction queue.
+    pub trait TxPool {
+        /// Returns the number of pending and future transactions.
+        #[rpc(name = "txpool_status")]
+        fn status(&self) -> Result<TxPoolStatus>;
+
+        /// Returns the pending and future transactions grouped by sender and nonce.
+        #[rpc(name = "txpool_content")]
+        fn content(&self) -> Result<TxPoolContent<Transaction>>;
+
+        /// Returns a one line summary of the pending and future transactions
+        /// grouped by sender and nonce.
+        #[rpc(name = "txpool_inspect")]
+        fn inspect(&self) -> Result<TxPoolContent<String>>;
+
+        /// Returns the status of the transactions submitted to this node,
+        /// including the recently mined or dropped ones.
+        #[rpc(name = "txpool_localTransactions")]
+        fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>>;
+
+        /// Removes a transaction submitted to this node from the queue.
+        /// Returns false if it is no longer queued.
+        #[rpc(name = "txpool_cancelTransaction")]
+        fn cancel_transaction(&self, H256) -> Result<bool>;
+    }
+}
@@ -0,0 +1,183 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Admin types.
+
+use acore::sync::{
+    LocalNodeInfo, PeerInfo as SyncPeerInfo, RouteTraffic as SyncRouteTraffic, TrafficInfo,
+};
+use aion_types::{H256, U256};
+
+/// Active peer
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PeerInfo {
+    /// Node id
+    pub id: String,
+    /// Remote address
+    pub address: String,
+    /// Client revision
+    pub revision: String,
+    /// "inbound" or "outbound"
+    pub direction: String,
+    /// Best block number announced by the peer
+    #[serde(rename = "bestBlockNumber")]
+    pub best_block_number: u64,
+    /// Best block hash announced by the peer
+    #[serde(rename = "bestBlockHash")]
+    pub best_block_hash: H256,
+    /// Total difficulty announced by the peer
+    #[serde(rename = "totalDifficulty")]
+    pub total_difficulty: U256,
+    /// Ping round trip in milliseconds, null until the peer answers a ping
+    pub latency: Option<u64>,
+    /// Reputation of the peer ip
+    pub score: i32,
+    /// Traffic with the peer
+    pub traffic: Traffic,
+}
+
+impl From<SyncPeerInfo> for PeerInfo {
+    fn from(peer: SyncPeerInfo) -> Self {
+        PeerInfo {
+            id: peer.id,
+            address: peer.addr,
+            revision: peer.revision,
+            direction: peer.direction,
+            best_block_number: peer.best_block_number,
+            best_block_hash: peer.best_block_hash,
+            total_difficulty: peer.total_difficulty,
+            latency: peer.latency,
+            score: peer.score,
+            traffic: peer.traffic.into(),
+        }
+    }
+}
+
+/// Messages and bytes sent and received
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Traffic {
+    /// Messages sent
+    #[serde(rename = "messagesSent")]
+    pub messages_sent: u64,
+    /// Bytes sent
+    #[serde(rename = "bytesSent")]
+    pub bytes_sent: u64,
+    /// Messages received
+    #[serde(rename = "messagesReceived")]
+    pub messages_received: u64,
+    /// Bytes received
+    #[serde(rename = "bytesReceived")]
+    pub bytes_received: u64,
+}
+
+impl From<TrafficInfo> for Traffic {
+    fn from(traffic: TrafficInfo) -> Self {
+        Traffic {
+            messages_sent: traffic.messages_sent,
+            bytes_sent: traffic.bytes_sent,
+            messages_received: traffic.messages_received,
+            bytes_received: traffic.bytes_received,
+        }
+    }
+}
+
+/// Traffic with all peers of a protocol message
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RouteTraffic {
+    /// Protocol module, "p2p" or "sync"
+    pub module: String,
+    /// Action code within the module
+    pub action: u8,
+    /// Traffic of the message
+    pub traffic: Traffic,
+}
+
+impl From<SyncRouteTraffic> for RouteTraffic {
+    fn from(route: SyncRouteTraffic) -> Self {
+        RouteTraffic {
+            module: route.module,
+            action: route.action,
+            traffic: route.traffic.into(),
+        }
+    }
+}
+
+/// Local node
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct NodeInfo {
+    /// Node id
+    pub id: String,
+    /// Node url to add the node as peer
+    pub node: String,
+    /// Listening address
+    #[serde(rename = "listenAddress")]
+    pub listen_address: String,
+    /// Network id
+    #[serde(rename = "networkId")]
+    pub network_id: u32,
+    /// Client version
+    pub version: String,
+}
+
+impl NodeInfo {
+    /// Creates the node info of the local node running `version`.
+    pub fn new(node: LocalNodeInfo, version: String) -> Self {
+        NodeInfo {
+            id: node.id,
+            node: node.node,
+            listen_address: node.binding,
+            network_id: node.network_id,
+            version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PeerInfo, Traffic};
+    use serde_json;
+
+    #[test]
+    fn peer_info_serialization() {
+        let peer = PeerInfo {
+            id: "c33d1066-8c7e-496c-9c4e-c89318280274".into(),
+            address: "10.0.0.1:30303".into(),
+            revision: "r-0.1.0".into(),
+            direction: "outbound".into(),
+            best_block_number: 16,
+            best_block_hash: 5.into(),
+            total_difficulty: 32.into(),
+            latency: None,
+            score: 1,
+            traffic: Traffic {
+                messages_sent: 3,
+                bytes_sent: 120,
+                messages_received: 2,
+                bytes_received: 4096,
+            },
+        };
+        let serialized = serde_json::to_string(&peer).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"id":"c33d1066-8c7e-496c-9c4e-c89318280274","address":"10.0.0.1:30303","revision":"r-0.1.0","direction":"outbound","bestBlockNumber":16,"bestBlockHash":"0x0000000000000000000000000000000000000000000000000000000000000005","totalDifficulty":"0x20","latency":null,"score":1,"traffic":{"messagesSent":3,"bytesSent":120,"messagesReceived":2,"bytesReceived":4096}}"#
+        );
+    }
+}
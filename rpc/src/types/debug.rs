@@ -0,0 +1,457 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Debug types.
+
+use std::collections::BTreeMap;
+
+use acore::client::Executed;
+use acore::trace;
+use aion_types::{Address, H128, H256, U256};
+
+use types::Bytes;
+
+/// Size of a vm word, memory is reported as a list of words.
+const WORD_SIZE: usize = 16;
+
+/// Returns the mnemonic of a FastVM opcode.
+fn op_name(op: u8) -> String {
+    let name = match op {
+        0x00 => "STOP",
+        0x01 => "ADD",
+        0x02 => "MUL",
+        0x03 => "SUB",
+        0x04 => "DIV",
+        0x05 => "SDIV",
+        0x06 => "MOD",
+        0x07 => "SMOD",
+        0x08 => "ADDMOD",
+        0x09 => "MULMOD",
+        0x0a => "EXP",
+        0x0b => "SIGNEXTEND",
+        0x10 => "LT",
+        0x11 => "GT",
+        0x12 => "SLT",
+        0x13 => "SGT",
+        0x14 => "EQ",
+        0x15 => "ISZERO",
+        0x16 => "AND",
+        0x17 => "OR",
+        0x18 => "XOR",
+        0x19 => "NOT",
+        0x1a => "BYTE",
+        0x20 => "SHA3",
+        0x30 => "ADDRESS",
+        0x31 => "BALANCE",
+        0x32 => "ORIGIN",
+        0x33 => "CALLER",
+        0x34 => "CALLVALUE",
+        0x35 => "CALLDATALOAD",
+        0x36 => "CALLDATASIZE",
+        0x37 => "CALLDATACOPY",
+        0x38 => "CODESIZE",
+        0x39 => "CODECOPY",
+        0x3a => "GASPRICE",
+        0x3b => "EXTCODESIZE",
+        0x3c => "EXTCODECOPY",
+        0x3d => "RETURNDATASIZE",
+        0x3e => "RETURNDATACOPY",
+        0x40 => "BLOCKHASH",
+        0x41 => "COINBASE",
+        0x42 => "TIMESTAMP",
+        0x43 => "NUMBER",
+        0x44 => "DIFFICULTY",
+        0x45 => "GASLIMIT",
+        0x50 => "POP",
+        0x51 => "MLOAD",
+        0x52 => "MSTORE",
+        0x53 => "MSTORE8",
+        0x54 => "SLOAD",
+        0x55 => "SSTORE",
+        0x56 => "JUMP",
+        0x57 => "JUMPI",
+        0x58 => "PC",
+        0x59 => "MSIZE",
+        0x5a => "GAS",
+        0x5b => "JUMPDEST",
+        0x60...0x7f => return format!("PUSH{}", op - 0x5f),
+        0x80...0x8f => return format!("DUP{}", op - 0x7f),
+        0x90...0x9f => return format!("SWAP{}", op - 0x8f),
+        0xa0...0xa4 => return format!("LOG{}", op - 0xa0),
+        0xb0...0xbf => return format!("DUP{}", op - 0x9f),
+        0xc0...0xcf => return format!("SWAP{}", op - 0xaf),
+        0xf0 => "CREATE",
+        0xf1 => "CALL",
+        0xf2 => "CALLCODE",
+        0xf3 => "RETURN",
+        0xf4 => "DELEGATECALL",
+        0xfa => "STATICCALL",
+        0xfd => "REVERT",
+        0xff => "SELFDESTRUCT",
+        _ => return format!("opcode {:#04x} not defined", op),
+    };
+    name.into()
+}
+
+/// What a trace records beside the executed instructions and their stack.
+#[derive(Debug, Default, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TraceOptions {
+    /// Record the memory of the call frame at each instruction
+    #[serde(rename = "enableMemory", default)]
+    pub enable_memory: bool,
+    /// Record the storage written by the executing account at each instruction
+    #[serde(rename = "enableStorage", default)]
+    pub enable_storage: bool,
+}
+
+impl Into<trace::VMTraceOptions> for TraceOptions {
+    fn into(self) -> trace::VMTraceOptions {
+        trace::VMTraceOptions {
+            memory: self.enable_memory,
+            storage: self.enable_storage,
+        }
+    }
+}
+
+/// A single instruction executed by the vm.
+#[derive(Debug, Serialize)]
+pub struct StructLog {
+    /// Offset of the instruction in the code
+    pub pc: usize,
+    /// Instruction mnemonic
+    pub op: String,
+    /// Gas available before the instruction
+    pub gas: U256,
+    /// Static gas cost of the instruction
+    #[serde(rename = "gasCost")]
+    pub gas_cost: U256,
+    /// Call depth, starting at 1
+    pub depth: usize,
+    /// Stack items read by the instruction, top first
+    pub stack: Vec<H128>,
+    /// Memory of the call frame, in words
+    pub memory: Vec<Bytes>,
+    /// Storage written by the executing account so far
+    pub storage: BTreeMap<H128, H128>,
+}
+
+impl From<trace::StructLog> for StructLog {
+    fn from(s: trace::StructLog) -> Self {
+        StructLog {
+            pc: s.pc,
+            op: op_name(s.op),
+            gas: s.gas,
+            gas_cost: s.gas_cost,
+            depth: s.depth,
+            stack: s.stack,
+            memory: s
+                .memory
+                .chunks(WORD_SIZE)
+                .map(|word| Bytes::new(word.to_vec()))
+                .collect(),
+            storage: s.storage,
+        }
+    }
+}
+
+/// A call or create made inside the avm.
+#[derive(Debug, Serialize)]
+pub struct AvmInternalTransaction {
+    /// Either `call` or `create`
+    #[serde(rename = "type")]
+    pub tx_type: String,
+    /// Sender
+    pub from: Address,
+    /// Recipient, null for creates
+    pub to: Option<Address>,
+    /// Sender nonce
+    pub nonce: U256,
+    /// Transferred value
+    pub value: U256,
+    /// Energy limit
+    pub gas: U256,
+    /// Input data
+    pub input: Bytes,
+    /// Whether the avm rejected the transaction
+    pub rejected: bool,
+}
+
+impl From<trace::AvmInternalTransaction> for AvmInternalTransaction {
+    fn from(t: trace::AvmInternalTransaction) -> Self {
+        AvmInternalTransaction {
+            tx_type: match t.is_create {
+                true => "create",
+                false => "call",
+            }
+            .into(),
+            from: t.sender,
+            to: t.destination,
+            nonce: t.nonce,
+            value: t.value,
+            gas: t.energy_limit.into(),
+            input: t.data.into(),
+            rejected: t.is_rejected,
+        }
+    }
+}
+
+/// A state change committed by an avm transaction.
+#[derive(Debug, Serialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum AvmOperation {
+    /// Account creation
+    CreateAccount {
+        /// Created account
+        address: Address,
+    },
+    /// Code deployment
+    PutCode {
+        /// Account receiving the code
+        address: Address,
+        /// Deployed code
+        code: Bytes,
+    },
+    /// Storage write
+    PutStorage {
+        /// Account owning the storage
+        address: Address,
+        /// Storage key
+        key: Bytes,
+        /// Stored value
+        value: Bytes,
+    },
+    /// Storage removal
+    RemoveStorage {
+        /// Account owning the storage
+        address: Address,
+        /// Storage key
+        key: Bytes,
+    },
+    /// Balance increase
+    IncreaseBalance {
+        /// Credited account
+        address: Address,
+        /// Amount
+        value: U256,
+    },
+    /// Balance decrease
+    DecreaseBalance {
+        /// Debited account
+        address: Address,
+        /// Amount
+        value: U256,
+    },
+    /// Log emission
+    Log {
+        /// Emitting contract
+        address: Address,
+        /// Topics
+        topics: Vec<H256>,
+        /// Data
+        data: Bytes,
+    },
+}
+
+impl From<trace::AvmOperation> for AvmOperation {
+    fn from(o: trace::AvmOperation) -> Self {
+        match o {
+            trace::AvmOperation::CreateAccount(address) => {
+                AvmOperation::CreateAccount {
+                    address,
+                }
+            }
+            trace::AvmOperation::PutCode(address, code) => {
+                AvmOperation::PutCode {
+                    address,
+                    code: code.into(),
+                }
+            }
+            trace::AvmOperation::PutStorage(address, key, value) => {
+                AvmOperation::PutStorage {
+                    address,
+                    key: key.into(),
+                    value: value.into(),
+                }
+            }
+            trace::AvmOperation::RemoveStorage(address, key) => {
+                AvmOperation::RemoveStorage {
+                    address,
+                    key: key.into(),
+                }
+            }
+            trace::AvmOperation::IncreaseBalance(address, value) => {
+                AvmOperation::IncreaseBalance {
+                    address,
+                    value,
+                }
+            }
+            trace::AvmOperation::DecreaseBalance(address, value) => {
+                AvmOperation::DecreaseBalance {
+                    address,
+                    value,
+                }
+            }
+            trace::AvmOperation::Log(log) => {
+                AvmOperation::Log {
+                    address: log.address,
+                    topics: log.topics,
+                    data: log.data.into(),
+                }
+            }
+        }
+    }
+}
+
+/// Trace of an avm transaction.
+#[derive(Debug, Serialize)]
+pub struct AvmTrace {
+    /// Calls and creates made inside the avm
+    #[serde(rename = "internalTransactions")]
+    pub internal_transactions: Vec<AvmInternalTransaction>,
+    /// State changes, in commit order
+    pub operations: Vec<AvmOperation>,
+}
+
+impl From<trace::AvmTrace> for AvmTrace {
+    fn from(t: trace::AvmTrace) -> Self {
+        AvmTrace {
+            internal_transactions: t
+                .internal_transactions
+                .into_iter()
+                .map(AvmInternalTransaction::from)
+                .collect(),
+            operations: t.operations.into_iter().map(AvmOperation::from).collect(),
+        }
+    }
+}
+
+/// Instruction trace of an execution.
+#[derive(Debug, Serialize)]
+pub struct ExecutionTrace {
+    /// Gas used by the execution
+    pub gas: U256,
+    /// Whether the execution failed
+    pub failed: bool,
+    /// Output of the execution
+    #[serde(rename = "returnValue")]
+    pub return_value: Bytes,
+    /// Executed instructions
+    #[serde(rename = "structLogs")]
+    pub struct_logs: Vec<StructLog>,
+    /// Whether instructions past the limits of a trace were left out
+    pub truncated: bool,
+    /// State changes and internal transactions, for avm transactions
+    #[serde(rename = "avmTrace", skip_serializing_if = "Option::is_none")]
+    pub avm_trace: Option<AvmTrace>,
+}
+
+impl From<Executed> for ExecutionTrace {
+    fn from(e: Executed) -> Self {
+        ExecutionTrace {
+            gas: e.gas_used,
+            failed: !e.exception.is_empty(),
+            return_value: e.output.into(),
+            struct_logs: e.struct_logs.into_iter().map(StructLog::from).collect(),
+            truncated: e.struct_logs_truncated,
+            avm_trace: e.avm_trace.map(AvmTrace::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use std::collections::BTreeMap;
+    use acore::trace;
+    use acore::log_entry::LogEntry;
+    use aion_types::{Address, H128};
+    use super::{op_name, AvmOperation, StructLog, TraceOptions};
+
+    #[test]
+    fn op_names() {
+        assert_eq!(op_name(0x01), "ADD");
+        assert_eq!(op_name(0x60), "PUSH1");
+        assert_eq!(op_name(0x7f), "PUSH32");
+        assert_eq!(op_name(0xb0), "DUP17");
+        assert_eq!(op_name(0xcf), "SWAP32");
+        assert_eq!(op_name(0xa4), "LOG4");
+        assert_eq!(op_name(0x0c), "opcode 0x0c not defined");
+    }
+
+    #[test]
+    fn trace_options_deserialization() {
+        let options: TraceOptions = serde_json::from_str(r#"{"enableMemory":true}"#).unwrap();
+        assert_eq!(
+            options,
+            TraceOptions {
+                enable_memory: true,
+                enable_storage: false,
+            }
+        );
+        let options: TraceOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(options, TraceOptions::default());
+    }
+
+    #[test]
+    fn struct_log_serialization() {
+        let mut storage = BTreeMap::new();
+        storage.insert(H128::from(1), H128::from(2));
+        let log = StructLog::from(trace::StructLog {
+            pc: 3,
+            op: 0x55,
+            gas: 100.into(),
+            gas_cost: 5000.into(),
+            depth: 1,
+            stack: vec![H128::from(1), H128::from(2)],
+            memory: vec![0; 20],
+            storage,
+        });
+        let serialized = serde_json::to_string(&log).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"pc":3,"op":"SSTORE","gas":"0x64","gasCost":"0x1388","depth":1,"stack":["0x00000000000000000000000000000001","0x00000000000000000000000000000002"],"memory":["0x00000000000000000000000000000000","0x0000000000"],"storage":{"0x00000000000000000000000000000001":"0x00000000000000000000000000000002"}}"#
+        );
+    }
+
+    #[test]
+    fn avm_operation_serialization() {
+        let put = AvmOperation::from(trace::AvmOperation::PutStorage(
+            Address::from(1),
+            vec![1],
+            vec![2],
+        ));
+        assert_eq!(
+            serde_json::to_string(&put).unwrap(),
+            r#"{"op":"putStorage","address":"0x0000000000000000000000000000000000000000000000000000000000000001","key":"0x01","value":"0x02"}"#
+        );
+        let log = AvmOperation::from(trace::AvmOperation::Log(LogEntry {
+            address: Address::from(2),
+            topics: vec![],
+            data: vec![3],
+        }));
+        assert_eq!(
+            serde_json::to_string(&log).unwrap(),
+            r#"{"op":"log","address":"0x0000000000000000000000000000000000000000000000000000000000000002","topics":[],"data":"0x03"}"#
+        );
+    }
+}
@@ -22,6 +22,7 @@
 
 //! RPC types
 
+mod admin;
 mod stratum_header;
 mod template_param;
 mod block;
@@ -30,21 +31,27 @@ mod bytes;
 mod call_request;
 mod confirmations;
 mod contract;
+mod debug;
 mod filter;
 mod histogram;
 mod index;
 mod log;
 mod node_kind;
+mod proof;
 mod provenance;
+pub mod pubsub;
 mod receipt;
 mod rpc_settings;
 mod secretstore;
 mod sync;
+mod trace;
 mod transaction;
 mod transaction_request;
 mod transaction_condition;
+mod txpool;
 mod mining;
 
+pub use self::admin::{NodeInfo, PeerInfo, RouteTraffic, Traffic};
 pub use self::bytes::Bytes;
 pub use self::block::{Block, BlockTransactions, Header};
 pub use self::block_number::BlockNumber;
@@ -56,11 +63,15 @@ pub use self::confirmations::{
     TransactionModification, SignRequest, DecryptRequest,
 };
 pub use self::contract::{Contract, ContractInfo, Abi, AbiIO};
+pub use self::debug::{
+    AvmInternalTransaction, AvmOperation, AvmTrace, ExecutionTrace, StructLog, TraceOptions,
+};
 pub use self::filter::{Filter, FilterChanges};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
 pub use self::log::Log;
 pub use self::node_kind::{NodeKind, Availability, Capability};
+pub use self::proof::{AccountProof, StorageProof};
 pub use self::provenance::Origin;
 pub use self::receipt::{Receipt, SimpleReceipt, SimpleReceiptLog};
 pub use self::rpc_settings::RpcSettings;
@@ -69,7 +80,9 @@ pub use self::sync::{
 SyncStatus, SyncInfo, /* Peers, PeerInfo, PeerNetworkInfo, TransactionStats, ChainStatus,
                       AcitvePeerInfo, PbSyncInfo,*/
 };
+pub use self::trace::{LocalizedTrace, TraceFilter, TraceResults};
 pub use self::transaction::{Transaction, RichRawTransaction};
 pub use self::transaction_request::TransactionRequest;
 pub use self::transaction_condition::TransactionCondition;
+pub use self::txpool::{LocalTransactionStatus, TxPoolContent, TxPoolStatus};
 pub use self::mining::{Work, Info, AddressValidation, MiningInfo, MinerStats};
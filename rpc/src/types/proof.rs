@@ -0,0 +1,90 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Merkle proof types.
+
+use aion_types::{Address, H256, U128, U256};
+
+use types::Bytes;
+
+/// Merkle proof of a storage entry.
+#[derive(Debug, Serialize)]
+pub struct StorageProof {
+    /// Storage key
+    pub key: U128,
+    /// Stored value
+    pub value: Bytes,
+    /// Trie nodes from the storage root to the entry
+    pub proof: Vec<Bytes>,
+}
+
+/// Merkle proof of an account and some of its storage.
+#[derive(Debug, Serialize)]
+pub struct AccountProof {
+    /// Account address
+    pub address: Address,
+    /// Trie nodes from the state root to the account
+    #[serde(rename = "accountProof")]
+    pub account_proof: Vec<Bytes>,
+    /// Balance
+    pub balance: U256,
+    /// Hash of the code
+    #[serde(rename = "codeHash")]
+    pub code_hash: H256,
+    /// Nonce
+    pub nonce: U256,
+    /// Root of the storage trie
+    #[serde(rename = "storageHash")]
+    pub storage_hash: H256,
+    /// Proofs of the requested storage keys
+    #[serde(rename = "storageProof")]
+    pub storage_proof: Vec<StorageProof>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use aion_types::Address;
+    use super::{AccountProof, StorageProof};
+
+    #[test]
+    fn account_proof_serialization() {
+        let proof = AccountProof {
+            address: Address::from(1),
+            account_proof: vec![vec![0xc0].into()],
+            balance: 5.into(),
+            code_hash: 2.into(),
+            nonce: 1.into(),
+            storage_hash: 3.into(),
+            storage_proof: vec![StorageProof {
+                key: 4.into(),
+                value: vec![0x45].into(),
+                proof: vec![],
+            }],
+        };
+        let serialized = serde_json::to_string(&proof).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"address":"0x0000000000000000000000000000000000000000000000000000000000000001","accountProof":["0xc0"],"balance":"0x5","codeHash":"0x0000000000000000000000000000000000000000000000000000000000000002","nonce":"0x1","storageHash":"0x0000000000000000000000000000000000000000000000000000000000000003","storageProof":[{"key":"0x4","value":"0x45","proof":[]}]}"#
+        );
+    }
+}
@@ -0,0 +1,161 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Pub-Sub types.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use serde_json::{Value, from_value};
+use aion_types::H256;
+
+use types::{Filter, Header, Log, SyncStatus};
+
+/// Subscription result.
+#[derive(Debug)]
+pub enum Result {
+    /// New block header.
+    Header(Header),
+    /// Log
+    Log(Log),
+    /// Transaction hash
+    TransactionHash(H256),
+    /// Sync status
+    SyncState(SyncStatus),
+}
+
+impl Serialize for Result {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where S: Serializer {
+        match *self {
+            Result::Header(ref header) => header.serialize(serializer),
+            Result::Log(ref log) => log.serialize(serializer),
+            Result::TransactionHash(ref hash) => hash.serialize(serializer),
+            Result::SyncState(ref sync) => sync.serialize(serializer),
+        }
+    }
+}
+
+/// Subscription kind.
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum Kind {
+    /// New block headers subscription.
+    #[serde(rename = "newHeads")]
+    NewHeads,
+    /// Logs subscription.
+    #[serde(rename = "logs")]
+    Logs,
+    /// New Pending Transactions subscription.
+    #[serde(rename = "newPendingTransactions")]
+    NewPendingTransactions,
+    /// Node syncing status subscription.
+    #[serde(rename = "syncing")]
+    Syncing,
+}
+
+/// Subscription params.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum Params {
+    /// Logs filter.
+    Logs(Filter),
+    /// No parameters passed.
+    None,
+}
+
+impl Default for Params {
+    fn default() -> Self { Params::None }
+}
+
+impl<'a> Deserialize<'a> for Params {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Params, D::Error>
+    where D: Deserializer<'a> {
+        let v: Value = Deserialize::deserialize(deserializer)?;
+
+        if v.is_null() {
+            return Ok(Params::None);
+        }
+
+        from_value(v.clone())
+            .map(Params::Logs)
+            .map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use types::{BlockNumber, Filter, SyncStatus};
+    use super::{Kind, Params, Result};
+
+    #[test]
+    fn should_deserialize_kind() {
+        assert_eq!(
+            serde_json::from_str::<Kind>(r#""newHeads""#).unwrap(),
+            Kind::NewHeads
+        );
+        assert_eq!(
+            serde_json::from_str::<Kind>(r#""logs""#).unwrap(),
+            Kind::Logs
+        );
+        assert_eq!(
+            serde_json::from_str::<Kind>(r#""newPendingTransactions""#).unwrap(),
+            Kind::NewPendingTransactions
+        );
+        assert_eq!(
+            serde_json::from_str::<Kind>(r#""syncing""#).unwrap(),
+            Kind::Syncing
+        );
+        assert!(serde_json::from_str::<Kind>(r#""unknown""#).is_err());
+    }
+
+    #[test]
+    fn should_deserialize_logs() {
+        let none = serde_json::from_str::<Params>(r#"null"#).unwrap();
+        assert_eq!(none, Params::None);
+
+        let logs = serde_json::from_str::<Params>(r#"{"fromBlock": "latest"}"#).unwrap();
+        assert_eq!(
+            logs,
+            Params::Logs(Filter {
+                from_block: Some(BlockNumber::Latest),
+                to_block: None,
+                address: None,
+                topics: None,
+                limit: None,
+            })
+        );
+
+        assert!(serde_json::from_str::<Params>(r#"{"unknown": 1}"#).is_err());
+    }
+
+    #[test]
+    fn should_serialize_results() {
+        let hash = Result::TransactionHash(5.into());
+        assert_eq!(
+            serde_json::to_string(&hash).unwrap(),
+            r#""0x0000000000000000000000000000000000000000000000000000000000000005""#
+        );
+
+        let sync = Result::SyncState(SyncStatus::None);
+        assert_eq!(serde_json::to_string(&sync).unwrap(), "false");
+    }
+}
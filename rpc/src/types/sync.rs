@@ -25,7 +25,7 @@ use serde::{Serialize, Serializer};
 //use aion_types::{U256, H512};
 
 /// Sync info
-#[derive(Default, Debug, Serialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, PartialEq)]
 pub struct SyncInfo {
     /// Starting block, hex representation
     #[serde(rename = "startingBlock")]
@@ -76,7 +76,7 @@ pub struct SyncInfo {
 //}
 
 /// Sync status
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SyncStatus {
     /// Info when syncing
     Info(SyncInfo),
@@ -84,6 +84,31 @@ pub enum SyncStatus {
     None,
 }
 
+impl SyncStatus {
+    /// Sync status of a node at `current_block` that has seen `highest_block` on the network.
+    pub fn new(starting_block: u64, current_block: u64, highest_block: u64) -> Self {
+        // refer to java's impl: AionImpl.java isSyncComplete.
+        if (current_block + 5) < highest_block {
+            SyncStatus::Info(SyncInfo {
+                // to comply with java's impl, return hex string.
+                starting_block: format!("{:#x}", starting_block),
+                current_block: format!("{:#x}", current_block),
+                highest_block: format!("{:#x}", highest_block),
+            })
+        } else {
+            SyncStatus::None
+        }
+    }
+
+    /// Whether the node is still catching up with the network.
+    pub fn is_syncing(&self) -> bool {
+        match *self {
+            SyncStatus::Info(_) => true,
+            SyncStatus::None => false,
+        }
+    }
+}
+
 ///// Active peer infomation
 //#[derive(Default, Debug, Serialize)]
 //pub struct AcitvePeerInfo {
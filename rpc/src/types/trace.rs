@@ -0,0 +1,538 @@
+/*******************************************************************************
+ * Copyright (c) 2015-2018 Parity Technologies (UK) Ltd.
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Trace types.
+
+use std::collections::BTreeMap;
+
+use serde::ser::{Serialize, Serializer, SerializeStruct};
+use acore::account_diff;
+use acore::client::{BlockId, Executed};
+use acore::state_diff;
+use acore::trace::{self, CallType, FlatTrace};
+use aion_types::{H256, U256, Address};
+
+use types::{BlockNumber, Bytes};
+
+/// Call action.
+#[derive(Debug, Serialize)]
+pub struct Call {
+    /// Sender
+    pub from: Address,
+    /// Recipient
+    pub to: Address,
+    /// Transferred value
+    pub value: U256,
+    /// Gas
+    pub gas: U256,
+    /// Input data
+    pub input: Bytes,
+    /// The type of the call
+    #[serde(rename = "callType")]
+    pub call_type: String,
+}
+
+impl From<trace::Call> for Call {
+    fn from(c: trace::Call) -> Self {
+        Call {
+            from: c.from,
+            to: c.to,
+            value: c.value,
+            gas: c.gas,
+            input: c.input.into(),
+            call_type: match c.call_type {
+                CallType::None => "none",
+                CallType::Call => "call",
+                CallType::CallCode => "callcode",
+                CallType::DelegateCall => "delegatecall",
+                CallType::StaticCall => "staticcall",
+                CallType::BulkBalance => "bulkbalance",
+            }
+            .into(),
+        }
+    }
+}
+
+/// Create action.
+#[derive(Debug, Serialize)]
+pub struct Create {
+    /// Sender
+    pub from: Address,
+    /// Value
+    pub value: U256,
+    /// Gas
+    pub gas: U256,
+    /// Initialization code
+    pub init: Bytes,
+}
+
+impl From<trace::Create> for Create {
+    fn from(c: trace::Create) -> Self {
+        Create {
+            from: c.from,
+            value: c.value,
+            gas: c.gas,
+            init: c.init.into(),
+        }
+    }
+}
+
+/// Action
+#[derive(Debug)]
+pub enum Action {
+    /// Call
+    Call(Call),
+    /// Create
+    Create(Create),
+}
+
+impl From<trace::Action> for Action {
+    fn from(c: trace::Action) -> Self {
+        match c {
+            trace::Action::Call(call) => Action::Call(call.into()),
+            trace::Action::Create(create) => Action::Create(create.into()),
+        }
+    }
+}
+
+/// Call result.
+#[derive(Debug, Serialize)]
+pub struct CallResult {
+    /// Gas used
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U256,
+    /// Output bytes
+    pub output: Bytes,
+}
+
+impl From<trace::CallResult> for CallResult {
+    fn from(c: trace::CallResult) -> Self {
+        CallResult {
+            gas_used: c.gas_used,
+            output: c.output.into(),
+        }
+    }
+}
+
+/// Create result.
+#[derive(Debug, Serialize)]
+pub struct CreateResult {
+    /// Gas used
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U256,
+    /// Code
+    pub code: Bytes,
+    /// Assigned address
+    pub address: Address,
+}
+
+impl From<trace::CreateResult> for CreateResult {
+    fn from(c: trace::CreateResult) -> Self {
+        CreateResult {
+            gas_used: c.gas_used,
+            code: c.code.into(),
+            address: c.address,
+        }
+    }
+}
+
+/// Response
+#[derive(Debug)]
+pub enum Res {
+    /// Call
+    Call(CallResult),
+    /// Create
+    Create(CreateResult),
+    /// Call failure
+    FailedCall(String),
+    /// Creation failure
+    FailedCreate(String),
+    /// None
+    None,
+}
+
+impl From<trace::Res> for Res {
+    fn from(t: trace::Res) -> Self {
+        match t {
+            trace::Res::Call(call) => Res::Call(call.into()),
+            trace::Res::Create(create) => Res::Create(create.into()),
+            trace::Res::FailedCall(error) => Res::FailedCall(error.to_string()),
+            trace::Res::FailedCreate(error) => Res::FailedCreate(error.to_string()),
+            trace::Res::None => Res::None,
+        }
+    }
+}
+
+fn serialize_frame<S>(struc: &mut S, action: &Action, result: &Res) -> Result<(), S::Error>
+where S: SerializeStruct {
+    match *action {
+        Action::Call(ref call) => {
+            struc.serialize_field("type", "call")?;
+            struc.serialize_field("action", call)?;
+        }
+        Action::Create(ref create) => {
+            struc.serialize_field("type", "create")?;
+            struc.serialize_field("action", create)?;
+        }
+    }
+
+    match *result {
+        Res::Call(ref call) => struc.serialize_field("result", call),
+        Res::Create(ref create) => struc.serialize_field("result", create),
+        Res::FailedCall(ref error) | Res::FailedCreate(ref error) => {
+            struc.serialize_field("error", error)
+        }
+        Res::None => struc.serialize_field("result", &None as &Option<CallResult>),
+    }
+}
+
+/// Trace with its position in the chain.
+#[derive(Debug)]
+pub struct LocalizedTrace {
+    /// Action
+    pub action: Action,
+    /// Result
+    pub result: Res,
+    /// Trace address
+    pub trace_address: Vec<usize>,
+    /// Subtraces
+    pub subtraces: usize,
+    /// Transaction position
+    pub transaction_position: usize,
+    /// Transaction hash
+    pub transaction_hash: H256,
+    /// Block Number
+    pub block_number: u64,
+    /// Block Hash
+    pub block_hash: H256,
+}
+
+impl Serialize for LocalizedTrace {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        let mut struc = serializer.serialize_struct("LocalizedTrace", 9)?;
+        serialize_frame(&mut struc, &self.action, &self.result)?;
+        struc.serialize_field("traceAddress", &self.trace_address)?;
+        struc.serialize_field("subtraces", &self.subtraces)?;
+        struc.serialize_field("transactionPosition", &self.transaction_position)?;
+        struc.serialize_field("transactionHash", &self.transaction_hash)?;
+        struc.serialize_field("blockNumber", &self.block_number)?;
+        struc.serialize_field("blockHash", &self.block_hash)?;
+        struc.end()
+    }
+}
+
+impl From<trace::LocalizedTrace> for LocalizedTrace {
+    fn from(t: trace::LocalizedTrace) -> Self {
+        LocalizedTrace {
+            action: t.action.into(),
+            result: t.result.into(),
+            trace_address: t.trace_address,
+            subtraces: t.subtraces,
+            transaction_position: t.transaction_number,
+            transaction_hash: t.transaction_hash,
+            block_number: t.block_number,
+            block_hash: t.block_hash,
+        }
+    }
+}
+
+/// Trace of a replayed transaction, without its position in the chain.
+#[derive(Debug)]
+pub struct Trace {
+    /// Trace address
+    pub trace_address: Vec<usize>,
+    /// Subtraces
+    pub subtraces: usize,
+    /// Action
+    pub action: Action,
+    /// Result
+    pub result: Res,
+}
+
+impl Serialize for Trace {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        let mut struc = serializer.serialize_struct("Trace", 5)?;
+        serialize_frame(&mut struc, &self.action, &self.result)?;
+        struc.serialize_field("traceAddress", &self.trace_address)?;
+        struc.serialize_field("subtraces", &self.subtraces)?;
+        struc.end()
+    }
+}
+
+impl From<FlatTrace> for Trace {
+    fn from(t: FlatTrace) -> Self {
+        Trace {
+            trace_address: t.trace_address.into_iter().collect(),
+            subtraces: t.subtraces,
+            action: t.action.into(),
+            result: t.result.into(),
+        }
+    }
+}
+
+/// Aux type for Diff::Changed.
+#[derive(Debug, Serialize)]
+pub struct ChangedType<T>
+where T: Serialize
+{
+    /// Value before the change.
+    from: T,
+    /// Value after the change.
+    to: T,
+}
+
+/// Serde-friendly `Diff` shadow.
+#[derive(Debug, Serialize)]
+pub enum Diff<T>
+where T: Serialize
+{
+    /// Unchanged.
+    #[serde(rename = "=")]
+    Same,
+    /// Added.
+    #[serde(rename = "+")]
+    Born(T),
+    /// Removed.
+    #[serde(rename = "-")]
+    Died(T),
+    /// Changed.
+    #[serde(rename = "*")]
+    Changed(ChangedType<T>),
+}
+
+impl<T, U> From<account_diff::Diff<T>> for Diff<U>
+where
+    T: Eq,
+    U: Serialize + From<T>,
+{
+    fn from(c: account_diff::Diff<T>) -> Self {
+        match c {
+            account_diff::Diff::Same => Diff::Same,
+            account_diff::Diff::Born(t) => Diff::Born(t.into()),
+            account_diff::Diff::Died(t) => Diff::Died(t.into()),
+            account_diff::Diff::Changed(t, u) => {
+                Diff::Changed(ChangedType {
+                    from: t.into(),
+                    to: u.into(),
+                })
+            }
+        }
+    }
+}
+
+/// Serde-friendly `AccountDiff` shadow.
+#[derive(Debug, Serialize)]
+pub struct AccountDiff {
+    /// Balance change.
+    pub balance: Diff<U256>,
+    /// Nonce change.
+    pub nonce: Diff<U256>,
+    /// Code change.
+    pub code: Diff<Bytes>,
+    /// Changed storage entries.
+    pub storage: BTreeMap<Bytes, Diff<Bytes>>,
+}
+
+impl From<account_diff::AccountDiff> for AccountDiff {
+    fn from(c: account_diff::AccountDiff) -> Self {
+        AccountDiff {
+            balance: c.balance.into(),
+            nonce: c.nonce.into(),
+            code: c.code.into(),
+            storage: c
+                .storage
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        }
+    }
+}
+
+/// Serde-friendly `StateDiff` shadow.
+#[derive(Debug, Serialize)]
+pub struct StateDiff(BTreeMap<Address, AccountDiff>);
+
+impl From<state_diff::StateDiff> for StateDiff {
+    fn from(c: state_diff::StateDiff) -> Self {
+        StateDiff(
+            c.raw
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Result of replaying a transaction.
+#[derive(Debug, Serialize)]
+pub struct TraceResults {
+    /// The output of the call/create
+    pub output: Bytes,
+    /// The transaction trace.
+    pub trace: Vec<Trace>,
+    /// The state diff, if requested.
+    #[serde(rename = "stateDiff")]
+    pub state_diff: Option<StateDiff>,
+}
+
+impl From<Executed> for TraceResults {
+    fn from(t: Executed) -> Self {
+        TraceResults {
+            output: t.output.into(),
+            trace: t.trace.into_iter().map(Into::into).collect(),
+            state_diff: t.state_diff.map(Into::into),
+        }
+    }
+}
+
+/// Trace filter
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TraceFilter {
+    /// From block
+    #[serde(rename = "fromBlock")]
+    pub from_block: Option<BlockNumber>,
+    /// To block
+    #[serde(rename = "toBlock")]
+    pub to_block: Option<BlockNumber>,
+    /// From address
+    #[serde(rename = "fromAddress")]
+    pub from_address: Option<Vec<Address>>,
+    /// To address
+    #[serde(rename = "toAddress")]
+    pub to_address: Option<Vec<Address>>,
+    /// Number of matching traces to skip
+    pub after: Option<usize>,
+    /// Maximal number of traces to return
+    pub count: Option<usize>,
+}
+
+impl Into<trace::Filter> for TraceFilter {
+    fn into(self) -> trace::Filter {
+        trace::Filter {
+            from_block: self.from_block.map_or_else(|| BlockId::Latest, Into::into),
+            to_block: self.to_block.map_or_else(|| BlockId::Latest, Into::into),
+            from_address: self.from_address.unwrap_or_else(Vec::new),
+            to_address: self.to_address.unwrap_or_else(Vec::new),
+            after: self.after,
+            count: self.count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use acore::client::BlockId;
+    use acore::trace;
+    use aion_types::Address;
+    use types::BlockNumber;
+    use std::collections::BTreeMap;
+    use super::{
+        AccountDiff, CallResult, ChangedType, Diff, LocalizedTrace, Res, StateDiff, TraceFilter,
+        Action, Call,
+    };
+
+    #[test]
+    fn trace_filter_deserialization() {
+        let s = r#"{
+            "fromBlock": "0x1",
+            "toBlock": "latest",
+            "toAddress": ["0x0000000000000000000000000000000000000000000000000000000000000002"],
+            "after": 2,
+            "count": 3
+        }"#;
+        let filter: TraceFilter = serde_json::from_str(s).unwrap();
+        assert_eq!(filter.from_block, Some(BlockNumber::Num(1)));
+        assert_eq!(filter.to_block, Some(BlockNumber::Latest));
+        assert_eq!(filter.from_address, None);
+
+        let filter: trace::Filter = filter.into();
+        assert_eq!(filter.from_block, BlockId::Number(1));
+        assert_eq!(filter.to_block, BlockId::Latest);
+        assert!(filter.from_address.is_empty());
+        assert_eq!(filter.to_address, vec![Address::from(2)]);
+        assert_eq!(filter.after, Some(2));
+        assert_eq!(filter.count, Some(3));
+    }
+
+    #[test]
+    fn localized_trace_serialization() {
+        let t = LocalizedTrace {
+            action: Action::Call(Call {
+                from: Address::from(4),
+                to: Address::from(5),
+                value: 6.into(),
+                gas: 7.into(),
+                input: vec![0x12, 0x34].into(),
+                call_type: "call".into(),
+            }),
+            result: Res::Call(CallResult {
+                gas_used: 8.into(),
+                output: vec![0x56, 0x78].into(),
+            }),
+            trace_address: vec![10],
+            subtraces: 1,
+            transaction_position: 11,
+            transaction_hash: 12.into(),
+            block_number: 13,
+            block_hash: 14.into(),
+        };
+        let serialized = serde_json::to_string(&t).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"type":"call","action":{"from":"0x0000000000000000000000000000000000000000000000000000000000000004","to":"0x0000000000000000000000000000000000000000000000000000000000000005","value":"0x6","gas":"0x7","input":"0x1234","callType":"call"},"result":{"gasUsed":"0x8","output":"0x5678"},"traceAddress":[10],"subtraces":1,"transactionPosition":11,"transactionHash":"0x000000000000000000000000000000000000000000000000000000000000000c","blockNumber":13,"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000e"}"#
+        );
+    }
+
+    #[test]
+    fn state_diff_serialization() {
+        let mut storage = BTreeMap::new();
+        storage.insert(
+            vec![0x01].into(),
+            Diff::Changed(ChangedType {
+                from: vec![0x02].into(),
+                to: vec![0x03].into(),
+            }),
+        );
+        let mut accounts = BTreeMap::new();
+        accounts.insert(
+            Address::from(1),
+            AccountDiff {
+                balance: Diff::Changed(ChangedType {
+                    from: 4.into(),
+                    to: 5.into(),
+                }),
+                nonce: Diff::Same,
+                code: Diff::Born(vec![0x06].into()),
+                storage: storage,
+            },
+        );
+        let serialized = serde_json::to_string(&StateDiff(accounts)).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"0x0000000000000000000000000000000000000000000000000000000000000001":{"balance":{"*":{"from":"0x4","to":"0x5"}},"nonce":"=","code":{"+":"0x06"},"storage":{"0x01":{"*":{"from":"0x02","to":"0x03"}}}}}"#
+        );
+    }
+}
@@ -0,0 +1,171 @@
+/*******************************************************************************
+ * Copyright (c) 2018-2019 Aion foundation.
+ *
+ *     This file is part of the aion network project.
+ *
+ *     The aion network project is free software: you can redistribute it
+ *     and/or modify it under the terms of the GNU General Public License
+ *     as published by the Free Software Foundation, either version 3 of
+ *     the License, or any later version.
+ *
+ *     The aion network project is distributed in the hope that it will
+ *     be useful, but WITHOUT ANY WARRANTY; without even the implied
+ *     warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ *     See the GNU General Public License for more details.
+ *
+ *     You should have received a copy of the GNU General Public License
+ *     along with the aion network project source files.
+ *     If not, see <https://www.gnu.org/licenses/>.
+ *
+ ******************************************************************************/
+
+//! Transaction pool types.
+
+use std::collections::BTreeMap;
+
+use acore::miner::{LocalTransactionStatus as MinerLocalTransactionStatus, MinerStatus};
+use aion_types::H256;
+
+use types::Transaction;
+
+/// Number of transactions in the queue
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TxPoolStatus {
+    /// Transactions ready to be included in a block
+    pub pending: u64,
+    /// Transactions waiting for transactions with lower nonces
+    pub future: u64,
+}
+
+impl From<MinerStatus> for TxPoolStatus {
+    fn from(status: MinerStatus) -> Self {
+        TxPoolStatus {
+            pending: status.transactions_in_pending_queue as u64,
+            future: status.transactions_in_future_queue as u64,
+        }
+    }
+}
+
+/// Transactions of the queue grouped by sender and nonce
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TxPoolContent<T> {
+    /// Transactions ready to be included in a block
+    pub pending: BTreeMap<H256, BTreeMap<u64, T>>,
+    /// Transactions waiting for transactions with lower nonces
+    pub future: BTreeMap<H256, BTreeMap<u64, T>>,
+}
+
+impl TxPoolContent<Transaction> {
+    /// Groups the pending and future transactions.
+    pub fn new(pending: Vec<Transaction>, future: Vec<Transaction>) -> Self {
+        TxPoolContent {
+            pending: group(pending, |transaction| transaction),
+            future: group(future, |transaction| transaction),
+        }
+    }
+}
+
+impl TxPoolContent<String> {
+    /// Groups the pending and future transactions as one line summaries,
+    /// `<to>: <value> + <nrg> nrg × <nrg price>`.
+    pub fn inspect(pending: Vec<Transaction>, future: Vec<Transaction>) -> Self {
+        TxPoolContent {
+            pending: group(pending, summary),
+            future: group(future, summary),
+        }
+    }
+}
+
+fn group<T, F>(transactions: Vec<Transaction>, f: F) -> BTreeMap<H256, BTreeMap<u64, T>>
+where F: Fn(Transaction) -> T {
+    let mut grouped = BTreeMap::new();
+    for transaction in transactions {
+        grouped
+            .entry(transaction.from)
+            .or_insert_with(BTreeMap::new)
+            .insert(transaction.nonce.low_u64(), f(transaction));
+    }
+    grouped
+}
+
+fn summary(transaction: Transaction) -> String {
+    let to = match transaction.to {
+        Some(to) => format!("{:?}", to),
+        None => "contract creation".into(),
+    };
+    format!(
+        "{}: {} + {} nrg × {}",
+        to, transaction.value, transaction.gas, transaction.gas_price
+    )
+}
+
+/// Status of a transaction submitted to this node
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LocalTransactionStatus {
+    /// In the pending part of the queue
+    Pending,
+    /// In the future part of the queue
+    Future,
+    /// Included in a block
+    Mined,
+    /// Dropped because of the queue limit
+    Dropped,
+    /// Replaced by a transaction with a higher nrg price
+    Replaced,
+    /// Never accepted to the queue
+    Rejected,
+    /// Invalid
+    Invalid,
+    /// Canceled
+    Canceled,
+}
+
+impl From<MinerLocalTransactionStatus> for LocalTransactionStatus {
+    fn from(status: MinerLocalTransactionStatus) -> Self {
+        match status {
+            MinerLocalTransactionStatus::Pending => LocalTransactionStatus::Pending,
+            MinerLocalTransactionStatus::Future => LocalTransactionStatus::Future,
+            MinerLocalTransactionStatus::Mined => LocalTransactionStatus::Mined,
+            MinerLocalTransactionStatus::Dropped => LocalTransactionStatus::Dropped,
+            MinerLocalTransactionStatus::Replaced => LocalTransactionStatus::Replaced,
+            MinerLocalTransactionStatus::Rejected => LocalTransactionStatus::Rejected,
+            MinerLocalTransactionStatus::Invalid => LocalTransactionStatus::Invalid,
+            MinerLocalTransactionStatus::Canceled => LocalTransactionStatus::Canceled,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocalTransactionStatus, TxPoolContent};
+    use serde_json;
+    use types::Transaction;
+
+    #[test]
+    fn txpool_content_serialization() {
+        let mut first = Transaction::default();
+        first.from = 1.into();
+        first.nonce = 2.into();
+        let mut second = Transaction::default();
+        second.from = 1.into();
+        second.nonce = 1.into();
+        second.to = Some(3.into());
+        second.value = 10.into();
+        second.gas = 21000.into();
+        second.gas_price = 5.into();
+
+        let content = TxPoolContent::inspect(vec![first, second], vec![]);
+        let serialized = serde_json::to_string(&content).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"pending":{"0x0000000000000000000000000000000000000000000000000000000000000001":{"1":"0x0000000000000000000000000000000000000000000000000000000000000003: 10 + 21000 nrg × 5","2":"contract creation: 0 + 0 nrg × 0"}},"future":{}}"#
+        );
+    }
+
+    #[test]
+    fn local_transaction_status_serialization() {
+        let serialized = serde_json::to_string(&LocalTransactionStatus::Mined).unwrap();
+        assert_eq!(serialized, r#""mined""#);
+    }
+}
@@ -138,6 +138,30 @@ impl Directories {
         dir
     }
 
+    /// Get the p2p node key path
+    pub fn node_key_path(&self) -> PathBuf {
+        let mut dir = Path::new(&self.base).to_path_buf();
+        dir.push("network");
+        dir.push("node.key");
+        dir
+    }
+
+    /// Get the p2p banned ips path
+    pub fn bans_path(&self) -> PathBuf {
+        let mut dir = Path::new(&self.base).to_path_buf();
+        dir.push("network");
+        dir.push("bans");
+        dir
+    }
+
+    /// Get the p2p known nodes path
+    pub fn peers_path(&self) -> PathBuf {
+        let mut dir = Path::new(&self.base).to_path_buf();
+        dir.push("network");
+        dir.push("peers");
+        dir
+    }
+
     /// Get the keys path
     pub fn keys_path(&self, spec_name: &str) -> PathBuf {
         let mut dir = PathBuf::from(&self.keys);
@@ -182,6 +206,9 @@ impl DatabaseDirectories {
         self.db_root_path().join(pruning.as_internal_name_str())
     }
 
+    /// Path to the local snapshot, served to peers and used by the `snapshot` command
+    pub fn snapshot_path(&self) -> PathBuf { self.db_root_path().join("snapshot") }
+
     /// Get user defauls path
     pub fn user_defaults_path(&self) -> PathBuf { self.spec_root_path().join("user_defaults") }
 }
@@ -6,6 +6,7 @@ package org.aion.avm.jni;
 // import org.aion.vm.api.interfaces.ResultCode;
 import org.aion.types.TransactionResult;
 import org.aion.types.TransactionStatus;
+import org.aion.types.InternalTransaction;
 
 import java.util.List;
 import java.util.Optional;
@@ -39,6 +40,24 @@ public class TransactionResultHelper {
         }
     }
 
+    /**
+     * Encodes a call or create made from inside the avm, so that the kernel can
+     * trace it.
+     */
+    private static byte[] encodeInternalTransaction(InternalTransaction tx) {
+        NativeEncoder enc = new NativeEncoder();
+        enc.encodeByte((byte) (tx.isCreate ? 1 : 0));
+        enc.encodeByte((byte) (tx.isRejected ? 1 : 0));
+        enc.encodeBytes(tx.sender.toByteArray());
+        enc.encodeBytes(tx.destination == null ? new byte[0] : tx.destination.toByteArray());
+        enc.encodeBytes(tx.senderNonce.toByteArray());
+        enc.encodeBytes(tx.value.toByteArray());
+        enc.encodeBytes(tx.copyOfData());
+        enc.encodeLong(tx.energyLimit);
+
+        return enc.toByteArray();
+    }
+
     public static byte[] encodeTransactionResult(TransactionResult result) {
         NativeEncoder enc = new NativeEncoder();
 
@@ -50,6 +69,12 @@ public class TransactionResultHelper {
         enc.encodeBytes(output.orElse(new byte[0]));
         enc.encodeLong(result.energyUsed);
 
+        // Encode internal transactions
+        enc.encodeInt(result.internalTransactions.size());
+        for (InternalTransaction tx: result.internalTransactions) {
+            enc.encodeBytes(encodeInternalTransaction(tx));
+        }
+
         return enc.toByteArray();
     }
 }
@@ -138,9 +138,12 @@ public class NativeTransactionExecutor {
      *
      * @param handle reserved pointer for the client
      * @param txs    serialized list of transaction contexts, using the Native Codec
+     * @param is_local    whether the transactions are local calls which must not be committed
+     * @param is_tracing  whether the transactions are traced; logs and state changes of local
+     *                    calls are only reported to the kernel when tracing
      * @return serialized list of transaction result, using the Native Codec
      */
-    public static byte[] execute(long handle, byte[] txs, boolean is_local) {
+    public static byte[] execute(long handle, byte[] txs, boolean is_local, boolean is_tracing) {
         if (Constants.DEBUG) {
             System.out.println("JNI V2");
         }
@@ -185,10 +188,9 @@ public class NativeTransactionExecutor {
                 //TODO: get VM kernel interface generated during execution; then update substates
                 IExternalState transactionKernel = futures[i].getExternalState();
                 
-                byte[] state_root;
-                if (is_local) {
-                    state_root = kernel.sendSignal(1);
-                } else {
+                // logs of local calls are only reported when tracing, so that replayed
+                // transactions can be traced
+                if (!is_local || is_tracing) {
                     for (Log log: r.logs) {
                         NativeEncoder logEncoder = new NativeEncoder();
                         logEncoder.encodeBytes(log.copyOfAddress());
@@ -200,6 +202,14 @@ public class NativeTransactionExecutor {
                         logEncoder.encodeBytes(log.copyOfData());
                         kernel.addLog(logEncoder.toByteArray(), i);
                     }
+                }
+
+                byte[] state_root;
+                if (is_local && !is_tracing) {
+                    state_root = kernel.sendSignal(1);
+                } else {
+                    // traced local calls run on a throwaway state; their writes are committed
+                    // so that the tracer sees them and later replayed transactions build on them
                     transactionKernel.commitTo(kernel);
                     // 0: should commit state; and return state root
                     state_root = kernel.sendSignal(0);
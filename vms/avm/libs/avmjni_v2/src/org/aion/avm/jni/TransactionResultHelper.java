@@ -36,6 +36,24 @@ public class TransactionResultHelper {
         }
     }
 
+    /**
+     * Encodes a call or create made from inside the avm, so that the kernel can
+     * trace it.
+     */
+    private static byte[] encodeInternalTransaction(InternalTransaction tx) {
+        NativeEncoder enc = new NativeEncoder();
+        enc.encodeByte((byte) (tx.isCreate ? 1 : 0));
+        enc.encodeByte((byte) (tx.isRejected ? 1 : 0));
+        enc.encodeBytes(tx.sender.toByteArray());
+        enc.encodeBytes(tx.destination == null ? new byte[0] : tx.destination.toByteArray());
+        enc.encodeBytes(tx.senderNonce.toByteArray());
+        enc.encodeBytes(tx.value.toByteArray());
+        enc.encodeBytes(tx.copyOfData());
+        enc.encodeLong(tx.energyLimit);
+
+        return enc.toByteArray();
+    }
+
     public static byte[] encodeTransactionResult(TransactionResult result) {
         NativeEncoder enc = new NativeEncoder();
 
@@ -47,6 +65,12 @@ public class TransactionResultHelper {
         enc.encodeBytes(output.orElse(new byte[0]));
         enc.encodeLong(result.energyUsed);
 
+        // Encode internal transactions
+        enc.encodeInt(result.internalTransactions.size());
+        for (InternalTransaction tx: result.internalTransactions) {
+            enc.encodeBytes(encodeInternalTransaction(tx));
+        }
+
         // Encode invokable internal transactions
         for (InternalTransaction tx: result.internalTransactions) {
             if (tx.copyOfInvokableHash() != null) {
@@ -68,7 +68,8 @@ public class AvmVersion {
             String root_path,
             long handle,
             byte[] txs,
-            boolean is_local)
+            boolean is_local,
+            boolean is_tracing)
     {
         try {
             if (version == 0) {
@@ -91,7 +92,7 @@ public class AvmVersion {
                 }
                 callMethod.setAccessible(true);
 
-                return (byte[])callMethod.invoke(null, handle, txs, is_local);
+                return (byte[])callMethod.invoke(null, handle, txs, is_local, is_tracing);
             } else {
                 if (resource_v1 != null) {
                     System.out.println("AvmVersion: close v1");
@@ -113,7 +114,7 @@ public class AvmVersion {
                 }
                 callMethod.setAccessible(true);
 
-                return (byte[])callMethod.invoke(null, handle, txs, is_local);
+                return (byte[])callMethod.invoke(null, handle, txs, is_local, is_tracing);
             }
         } catch (Exception e) {
             e.printStackTrace();
@@ -4,7 +4,7 @@ public interface IExecutor {
     /*
         execute avm version 1
      */
-    public byte[] execute(long handle, byte[] txs, boolean is_local);
+    public byte[] execute(long handle, byte[] txs, boolean is_local, boolean is_tracing);
     /*
         Execute avm version 2
      */
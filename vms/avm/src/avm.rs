@@ -208,6 +208,7 @@ impl AVM {
         version: i32,
         transactions: &Vec<TransactionContext>,
         is_local: bool,
+        is_tracing: bool,
     ) -> Result<Vec<TransactionResult>, &'static str>
     {
         trace!(target: "vm", "start rust jvm executor");
@@ -248,6 +249,7 @@ impl AVM {
                     .expect("Failed to create new byte array in JVM"),
             ),
             Value::Boolean(is_local),
+            Value::Boolean(is_tracing),
         ];
 
         trace!(target: "vm", "rust jvm call_static");
@@ -1,5 +1,5 @@
 use std::fmt;
-use aion_types::{Address,H256};
+use aion_types::{Address, H256, U256};
 use super::ExecStatus;
 
 #[derive(Debug)]
@@ -97,12 +97,54 @@ impl TransactionContext {
     pub fn tx_hash(&self) -> &Vec<u8> { return &self.transaction_hash; }
 }
 
+/// A call or create made from inside the avm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InternalTransaction {
+    pub is_create: bool,
+    pub is_rejected: bool,
+    pub sender: Address,
+    /// `None` for creates
+    pub destination: Option<Address>,
+    pub nonce: U256,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub energy_limit: u64,
+}
+
+impl InternalTransaction {
+    pub fn new(bytes: Vec<u8>) -> Result<InternalTransaction, &'static str> {
+        let mut decoder = NativeDecoder::new(&bytes);
+        let is_create = decoder.decode_byte()? != 0;
+        let is_rejected = decoder.decode_byte()? != 0;
+        let sender = decoder.decode_bytes()?;
+        let destination = decoder.decode_bytes()?;
+        let nonce = decoder.decode_big_integer()?;
+        let value = decoder.decode_big_integer()?;
+        let data = decoder.decode_bytes()?;
+        let energy_limit = decoder.decode_long()?;
+        Ok(InternalTransaction {
+            is_create,
+            is_rejected,
+            sender: sender.as_slice().into(),
+            destination: match destination.is_empty() {
+                true => None,
+                false => Some(destination.as_slice().into()),
+            },
+            nonce,
+            value,
+            data,
+            energy_limit,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TransactionResult {
     pub status: u32,
     pub return_data: Vec<u8>,
     pub energy_used: u64,
     pub state_root: H256,
+    pub internal_transactions: Vec<InternalTransaction>,
     pub invokable_hashes: Vec<H256>,
 }
 
@@ -112,6 +154,10 @@ impl TransactionResult {
         let status = decoder.decode_int()?;
         let return_data = decoder.decode_bytes()?;
         let energy_used = decoder.decode_long()?;
+        let mut internal_transactions = Vec::new();
+        for _i in 0..decoder.decode_int()? {
+            internal_transactions.push(InternalTransaction::new(decoder.decode_bytes()?)?);
+        }
         let mut invokable_hashes = Vec::new();
         loop {
             match decoder.decode_bytes() {
@@ -124,6 +170,7 @@ impl TransactionResult {
             return_data,
             energy_used,
             state_root: state_root.as_slice().into(),
+            internal_transactions,
             invokable_hashes,
         })
     }
@@ -256,6 +303,16 @@ impl NativeDecoder {
         }
     }
 
+    /// Decodes a non-negative java `BigInteger`, encoded as its two's-complement bytes.
+    pub fn decode_big_integer(&mut self) -> Result<U256, &'static str> {
+        let bytes = self.decode_bytes()?;
+        let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+        match bytes.len() - start <= 32 {
+            true => Ok(U256::from(&bytes[start..])),
+            false => Err("Big integer out of range"),
+        }
+    }
+
     pub fn require(&self, n: usize) -> bool { self.bytes.len() - self.index >= n }
 }
 
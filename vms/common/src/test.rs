@@ -1,5 +1,5 @@
 use std::{u16, u32, u64, u8};
-use avm::{NativeEncoder, NativeDecoder};
+use avm::{InternalTransaction, NativeEncoder, NativeDecoder};
 
 use super::EnvInfo;
 
@@ -31,6 +31,30 @@ pub fn test_codec() {
     assert_eq!("test".as_bytes().to_vec(), decoder.decode_bytes().unwrap());
 }
 
+#[test]
+fn test_decode_internal_transaction() {
+    let mut encoder = NativeEncoder::new();
+    encoder.encode_byte(1);
+    encoder.encode_byte(0);
+    encoder.encode_bytes(&vec![0xa0u8; 32]);
+    encoder.encode_bytes(&Vec::new());
+    encoder.encode_bytes(&vec![0x07]);
+    // a sign byte precedes values with the top bit set
+    encoder.encode_bytes(&vec![0x00, 0xff]);
+    encoder.encode_bytes(&"init".as_bytes().to_vec());
+    encoder.encode_long(2_000_000);
+
+    let tx = InternalTransaction::new(encoder.to_bytes()).unwrap();
+    assert!(tx.is_create);
+    assert!(!tx.is_rejected);
+    assert_eq!(tx.sender, [0xa0u8; 32].into());
+    assert_eq!(tx.destination, None);
+    assert_eq!(tx.nonce, 7.into());
+    assert_eq!(tx.value, 255.into());
+    assert_eq!(tx.data, "init".as_bytes().to_vec());
+    assert_eq!(tx.energy_limit, 2_000_000);
+}
+
 #[test]
 fn it_can_be_created_as_default() {
     let default_env_info = EnvInfo::default();
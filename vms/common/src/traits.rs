@@ -23,6 +23,7 @@ use std::sync::Arc;
 use bytes::Bytes;
 use aion_types::{ H128, U256, H256, Address };
 use super::{ EnvInfo, ExecutionResult, CallType };
+use avm::InternalTransaction;
 
 /// Externalities interface for EVMs
 pub trait Ext {
@@ -119,6 +120,23 @@ pub trait Ext {
     {
     }
 
+    /// Whether the VM should report every instruction through `trace_step`.
+    fn is_step_tracing(&self) -> bool { false }
+
+    /// Trace an instruction about to be executed, with the stack items it reads (top first)
+    /// and the current memory.
+    fn trace_step(
+        &mut self,
+        _pc: usize,
+        _instruction: u8,
+        _gas: U256,
+        _gas_cost: U256,
+        _stack: &[H128],
+        _memory: &[u8],
+    )
+    {
+    }
+
     /// Save code to newly created contract.
     fn save_code(&mut self, code: Bytes);
 
@@ -168,6 +186,12 @@ pub trait Ext {
 
     fn avm_log(&mut self, address: &Address, topics: Vec<H256>, data: Bytes, idx: i32);
 
+    /// Trace the calls and creates made inside the avm by the transaction at `idx`.
+    fn trace_avm_internal_transactions(&mut self, _idx: i32, _txs: &[InternalTransaction]) {}
+
+    /// Whether the transactions are traced. Logs of local calls are only reported when tracing.
+    fn is_tracing(&self) -> bool { false }
+
     fn get_transformed_code(&self, address: &Address) -> Option<Arc<Bytes>>;
 
     fn save_transformed_code(&mut self, address: &Address, code: Bytes);
@@ -275,6 +275,26 @@ extern "C" {
     }
   }
 
+  /**
+   * evm_step_fn
+   */
+  static void step(struct evm_context *context,
+            int64_t pc,
+            int32_t opcode,
+            int64_t gas,
+            int64_t gas_cost,
+            const struct evm_word *stack,
+            size_t stack_size,
+            const uint8_t *memory,
+            size_t memory_size)
+  {
+    (void)context;
+
+    if (NULL != evm_cbs_p->step) {
+        evm_cbs_p->step(gbl_cb_obj, pc, opcode, gas, gas_cost, stack, stack_size, memory, memory_size);
+    }
+  }
+
   static const struct evm_context_fn_table ctx_fn_table = {
     account_exists,
     get_storage,
@@ -285,7 +305,8 @@ extern "C" {
     call,
     get_tx_context,
     get_block_hash,
-    log
+    log,
+    step
   };
 
   struct evm_context vm_context = { &ctx_fn_table };
@@ -447,6 +468,7 @@ void register_callback(void (*func)())
   register_cb_fn(get_tx_context_cb, get_tx_context);
   register_cb_fn(get_blockhash_cb, get_blockhash);
   register_cb_fn(log_cb, log);
+  register_cb_fn(step_cb, step);
 #if __cplusplus
 }
 #endif
\ No newline at end of file
@@ -66,6 +66,12 @@ struct result_info {
                          size_t data_size,
                          const struct evm_word topics[],
                          size_t topics_count);
+  typedef void (*step_cb)(const void *obj, int64_t pc, int32_t opcode,
+                          int64_t gas, int64_t gas_cost,
+                          const struct evm_word *stack,
+                          size_t stack_size,
+                          const uint8_t *memory,
+                          size_t memory_size);
 
   typedef struct EvmCBS {
     exists_cb exists;
@@ -78,6 +84,7 @@ struct result_info {
     get_tx_context_cb get_tx_context;
     get_blockhash_cb get_blockhash;
     log_cb log;
+    step_cb step;
   } EVM_CBS;
 
   extern void do_keccak(uint8_t const *data, uint64_t size, uint8_t *o_hash);
@@ -67,7 +67,8 @@ enum evm_call_kind {
 
 /// The flags for ::evm_message.
 enum evm_flags {
-    EVM_STATIC = 1        ///< Static call mode.
+    EVM_STATIC = 1,       ///< Static call mode.
+    EVM_TRACE = 2         ///< Report every executed instruction to the Host.
 };
 
 /// The message describing an EVM call,
@@ -378,6 +379,30 @@ typedef void (*evm_call_fn)(struct evm_result* result,
                             struct evm_context* context,
                             const struct evm_message* msg);
 
+/// Step callback function.
+///
+/// This callback function is used by an EVM compiled in ::EVM_TRACE mode to
+/// report an instruction before it is executed.
+/// @param context      The pointer to the Host execution context.
+///                     @see ::evm_context.
+/// @param pc           The offset of the instruction in the code.
+/// @param opcode       The instruction opcode.
+/// @param gas          The gas available before the instruction.
+/// @param gas_cost     The static gas cost of the instruction.
+/// @param stack        The stack items read by the instruction, top first.
+/// @param stack_size   The number of the stack items.
+/// @param memory       The pointer to the memory of the current call.
+/// @param memory_size  The size of the memory in bytes.
+typedef void (*evm_step_fn)(struct evm_context* context,
+                            int64_t pc,
+                            int32_t opcode,
+                            int64_t gas,
+                            int64_t gas_cost,
+                            const struct evm_word* stack,
+                            size_t stack_size,
+                            const uint8_t* memory,
+                            size_t memory_size);
+
 /// The context interface.
 ///
 /// The set of all callback functions expected by EVM instances. This is C
@@ -395,6 +420,7 @@ struct evm_context_fn_table {
     evm_get_tx_context_fn get_tx_context;
     evm_get_block_hash_fn get_block_hash;
     evm_log_fn log;
+    evm_step_fn step;
 };
 
 
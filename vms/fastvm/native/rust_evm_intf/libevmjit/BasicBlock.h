@@ -34,6 +34,9 @@ public:
 	/// @param _index Index of value to be swaped. Must be > 0.
 	void swap(size_t _index);
 
+	/// Returns _index'th value from top (counting from 0) without removing it
+	llvm::Value* peek(size_t _index) { return get(_index); }
+
 	ssize_t size() const { return static_cast<ssize_t>(m_local.size()) - m_globalPops; }
 	ssize_t minSize() const { return m_minSize; }
 	ssize_t maxSize() const { return m_maxSize; }
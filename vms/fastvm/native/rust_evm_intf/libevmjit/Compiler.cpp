@@ -47,10 +47,11 @@ namespace jit
 
 static const auto c_destIdxLabel = "destIdx";
 
-Compiler::Compiler(Options const& _options, evm_revision _rev, bool _staticCall, llvm::LLVMContext& _llvmContext):
+Compiler::Compiler(Options const& _options, evm_revision _rev, bool _staticCall, bool _traceSteps, llvm::LLVMContext& _llvmContext):
 	m_options(_options),
 	m_rev(_rev),
 	m_staticCall(_staticCall),
+	m_traceSteps(_traceSteps),
 	m_builder(_llvmContext)
 {
 	Type::init(m_builder.getContext());
@@ -322,6 +323,17 @@ void Compiler::compileBasicBlock(BasicBlock& _basicBlock, RuntimeManager& _runti
 	{
 		auto inst = Instruction(*it);
 
+		if (m_traceSteps)
+		{
+			llvm::SmallVector<llvm::Value*, 34> items;
+			for (size_t i = 0; i < stackInputs(inst); ++i)
+				items.push_back(stack.peek(i));
+
+			auto pc = static_cast<int64_t>(_basicBlock.firstInstrIdx() + (it - _basicBlock.begin()));
+			auto step = _ext.step(pc, inst, _gasMeter.getStepCost(inst), items);
+			_gasMeter.registerStep(step, 3);
+		}
+
 		_gasMeter.count(inst);
 
 		switch (inst)
@@ -23,7 +23,7 @@ public:
 		bool dumpCFG = false;
 	};
 
-	Compiler(Options const& _options, evm_revision _rev, bool _staticCall, llvm::LLVMContext& _llvmContext);
+	Compiler(Options const& _options, evm_revision _rev, bool _staticCall, bool _traceSteps, llvm::LLVMContext& _llvmContext);
 
 	std::unique_ptr<llvm::Module> compile(code_iterator _begin, code_iterator _end, std::string const& _id);
 
@@ -47,6 +47,9 @@ private:
 
 	bool const m_staticCall = false;
 
+	/// Report every instruction to the host before executing it.
+	bool const m_traceSteps = false;
+
 	/// Helper class for generating IR
 	IRBuilder m_builder;
 
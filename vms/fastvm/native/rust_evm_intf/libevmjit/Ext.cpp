@@ -177,6 +177,23 @@ llvm::Function* getLogFunc(llvm::Module* _module)
 	return func;
 }
 
+llvm::Function* getStepFunc(llvm::Module* _module)
+{
+	static const auto funcName = "evm.step";
+	auto func = _module->getFunction(funcName);
+	if (!func)
+	{
+		auto i32 = llvm::IntegerType::getInt32Ty(_module->getContext());
+		auto fty = llvm::FunctionType::get(Type::Void, {Type::EnvPtr, Type::Size, i32, Type::Gas, Type::Gas, Type::WordPtr, Type::Size, Type::BytePtr, Type::Size}, false);
+		func = llvm::Function::Create(fty, llvm::Function::ExternalLinkage, funcName, _module);
+		func->addAttribute(6, llvm::Attribute::ReadOnly);
+		func->addAttribute(6, llvm::Attribute::NoCapture);
+		func->addAttribute(8, llvm::Attribute::ReadOnly);
+		func->addAttribute(8, llvm::Attribute::NoCapture);
+	}
+	return func;
+}
+
 llvm::Function* getCallFunc(llvm::Module* _module)
 {
 	static const auto funcName = "call";
@@ -473,6 +490,34 @@ void Ext::log(llvm::Value* _memIdx, llvm::Value* _numBytes, llvm::ArrayRef<llvm:
 	});
 }
 
+llvm::CallInst* Ext::step(int64_t _pc, evmjit::Instruction _inst, int64_t _cost, llvm::ArrayRef<llvm::Value*> _stack)
+{
+	if (!m_stepStack)
+	{
+		InsertPointGuard g{m_builder};
+		auto& entryBB = getMainFunction()->front();
+		m_builder.SetInsertPoint(&entryBB, entryBB.begin());
+		m_stepStack = m_builder.CreateAlloca(Type::Word, m_builder.getInt32(34), "step.stack");
+	}
+
+	for (size_t i = 0; i < _stack.size(); ++i)
+	{
+		auto item = Endianness::toBE(m_builder, _stack[i]);
+		auto p = m_builder.CreateConstGEP1_32(m_stepStack, static_cast<unsigned>(i));
+		m_builder.CreateStore(item, p);
+	}
+
+	auto memData = m_memoryMan.getData();
+	auto memSize = m_builder.CreateTrunc(m_memoryMan.getSize(), Type::Size, "step.msize");
+
+	auto func = getStepFunc(getModule());
+	return m_builder.CreateCall(func, {
+		getRuntimeManager().getEnvPtr(), m_builder.getInt64(_pc), m_builder.getInt32(static_cast<uint32_t>(_inst)),
+		getRuntimeManager().getGas(), m_builder.getInt64(_cost),
+		m_stepStack, m_builder.getInt64(_stack.size()), memData, memSize
+	});
+}
+
 llvm::Value* Ext::call(int _kind,
 					   llvm::Value* _gas,
 					   llvm::Value* _addr,
@@ -4,6 +4,7 @@
 
 #include "JIT.h"
 #include "CompilerHelper.h"
+#include "Instruction.h"
 
 namespace dev
 {
@@ -60,6 +61,10 @@ public:
 	void log(llvm::Value* _memIdx, llvm::Value* _numBytes, llvm::ArrayRef<llvm::Value*> _topics);
 	void selfdestruct(llvm::Value* _beneficiary);
 
+	/// Report the instruction at _pc to the host before it is executed.
+	/// The returned call takes the gas available as its 4th argument.
+	llvm::CallInst* step(int64_t _pc, evmjit::Instruction _inst, int64_t _cost, llvm::ArrayRef<llvm::Value*> _stack);
+
 	llvm::Value* call(int _kind,
 	                  llvm::Value* _gas,
 	                  llvm::Value* _addr,
@@ -80,6 +85,9 @@ private:
 	/// TODO: Merge this memory with args allocas.
 	llvm::Value* m_topics = nullptr;
 
+	/// Memory for stack items reported by a step, up to 34 (SWAP32)
+	llvm::Value* m_stepStack = nullptr;
+
 	llvm::CallInst* createCall(EnvFunc _funcId, std::initializer_list<llvm::Value*> const& _args);
 
 	llvm::Value* createCABICall(llvm::Function* _func,
@@ -142,11 +142,32 @@ void GasMeter::giveBack(llvm::Value* _gas)
 	m_runtimeManager.setGas(m_builder.CreateAdd(m_runtimeManager.getGas(), _gas));
 }
 
+void GasMeter::registerStep(llvm::CallInst* _step, unsigned _gasArg)
+{
+	// A step before the gas check of the cost-block sees the gas not charged yet
+	if (m_checkCall)
+		m_steps.emplace_back(_step, _gasArg, m_blockCost);
+}
+
 void GasMeter::commitCostBlock()
 {
 	// If any uncommited block
 	if (m_checkCall)
 	{
+		// The whole cost-block is charged up front, give the rest of it back to the steps
+		for (auto& step: m_steps)
+		{
+			auto call = std::get<0>(step);
+			auto gasArg = std::get<1>(step);
+			auto pending = m_blockCost - std::get<2>(step);
+			if (pending != 0)
+			{
+				auto gas = llvm::BinaryOperator::CreateNSWAdd(call->getArgOperand(gasArg), m_builder.getInt64(pending), "step.gas", call);
+				call->setArgOperand(gasArg, gas);
+			}
+		}
+		m_steps.clear();
+
 		if (m_blockCost == 0) // Do not check 0
 		{
 			m_checkCall->eraseFromParent(); // Remove the gas check call
@@ -1,5 +1,8 @@
 #pragma once
 
+#include <tuple>
+#include <vector>
+
 #include <evm.h>
 #include "CompilerHelper.h"
 #include "Instruction.h"
@@ -48,14 +51,22 @@ public:
 	/// Count addional gas cost for memory copy
 	void countCopy(llvm::Value* _copyWords);
 
-private:
+	/// Register a step trace call made before the instruction is counted.
+	/// The gas argument is patched to include the part of the current cost-block not spent yet.
+	void registerStep(llvm::CallInst* _step, unsigned _gasArg);
+
 	int64_t getStepCost(Instruction inst) const;
 
+private:
+
 	/// Cumulative gas cost of a block of instructions
 	/// @TODO Handle overflow
 	int64_t m_blockCost = 0;
 
 	llvm::CallInst* m_checkCall = nullptr;
+
+	/// Step trace calls of the current cost-block with the block cost counted before each of them
+	std::vector<std::tuple<llvm::CallInst*, unsigned, int64_t>> m_steps;
 	llvm::Function* m_gasCheckFunc = nullptr;
 
 	RuntimeManager& m_runtimeManager;
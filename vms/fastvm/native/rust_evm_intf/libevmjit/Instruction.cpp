@@ -37,5 +37,86 @@ void skipPushData(code_iterator& _curr, code_iterator _end)
 	for (decltype(numBytes) i = 0; i < numBytes && _curr < _end; ++i, ++_curr) {}
 }
 
+size_t stackInputs(Instruction _inst)
+{
+	auto const op = static_cast<size_t>(_inst);
+
+	if (_inst >= Instruction::DUP1 && _inst <= Instruction::DUP16)
+		return op - static_cast<size_t>(Instruction::DUP1) + 1;
+	if (_inst >= Instruction::DUP17 && _inst <= Instruction::DUP32)
+		return op - static_cast<size_t>(Instruction::DUP17) + 17;
+	if (_inst >= Instruction::SWAP1 && _inst <= Instruction::SWAP16)
+		return op - static_cast<size_t>(Instruction::SWAP1) + 2;
+	if (_inst >= Instruction::SWAP17 && _inst <= Instruction::SWAP32)
+		return op - static_cast<size_t>(Instruction::SWAP17) + 18;
+	if (_inst >= Instruction::LOG0 && _inst <= Instruction::LOG4)
+		return 2 + 2 * (op - static_cast<size_t>(Instruction::LOG0));
+
+	switch (_inst)
+	{
+	case Instruction::ISZERO:
+	case Instruction::NOT:
+	case Instruction::CALLDATALOAD:
+	case Instruction::BLOCKHASH:
+	case Instruction::POP:
+	case Instruction::MLOAD:
+	case Instruction::SLOAD:
+	case Instruction::JUMP:
+		return 1;
+
+	case Instruction::ADD:
+	case Instruction::MUL:
+	case Instruction::SUB:
+	case Instruction::DIV:
+	case Instruction::SDIV:
+	case Instruction::MOD:
+	case Instruction::SMOD:
+	case Instruction::EXP:
+	case Instruction::SIGNEXTEND:
+	case Instruction::LT:
+	case Instruction::GT:
+	case Instruction::SLT:
+	case Instruction::SGT:
+	case Instruction::EQ:
+	case Instruction::AND:
+	case Instruction::OR:
+	case Instruction::XOR:
+	case Instruction::BYTE:
+	case Instruction::SHA3:
+	case Instruction::BALANCE:
+	case Instruction::EXTCODESIZE:
+	case Instruction::MSTORE:
+	case Instruction::MSTORE8:
+	case Instruction::SSTORE:
+	case Instruction::JUMPI:
+	case Instruction::RETURN:
+	case Instruction::REVERT:
+	case Instruction::SELFDESTRUCT:
+		return 2;
+
+	case Instruction::ADDMOD:
+	case Instruction::MULMOD:
+	case Instruction::CALLDATACOPY:
+	case Instruction::CODECOPY:
+	case Instruction::RETURNDATACOPY:
+	case Instruction::CREATE:
+		return 3;
+
+	case Instruction::EXTCODECOPY:
+		return 5;
+
+	case Instruction::DELEGATECALL:
+	case Instruction::STATICCALL:
+		return 7;
+
+	case Instruction::CALL:
+	case Instruction::CALLCODE:
+		return 8;
+
+	default:
+		return 0;
+	}
+}
+
 }
 }
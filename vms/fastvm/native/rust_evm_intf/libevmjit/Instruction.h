@@ -1,5 +1,7 @@
 #pragma once
 
+#include <cstddef>
+
 #include "Common.h"
 
 namespace llvm
@@ -206,6 +208,10 @@ llvm::APInt readPushData(code_iterator& _curr, code_iterator _end);
 /// @param _curr is updated and points the last real byte skipped
 void skipPushData(code_iterator& _curr, code_iterator _end);
 
+/// Returns the number of stack items read by the instruction, counting
+/// 256-bit operands (addresses) as two items
+size_t stackInputs(Instruction _inst);
+
 #define ANY_PUSH	  PUSH1:  \
 	case Instruction::PUSH2:  \
 	case Instruction::PUSH3:  \
@@ -85,6 +85,8 @@ std::string makeCodeId(evm_hash codeHash, evm_revision rev, uint32_t flags)
 	str.push_back(toChar(rev));
 	if (flags & EVM_STATIC)
 		str.push_back('S');
+	if (flags & EVM_TRACE)
+		str.push_back('T');
 	return str;
 }
 
@@ -158,7 +160,7 @@ public:
 	CodeMapEntry getExecFunc(std::string const& _codeIdentifier);
 	void mapExecFunc(std::string const& _codeIdentifier, ExecFunc _funcAddr);
 
-	ExecFunc compile(evm_revision _rev, bool _staticCall, byte const* _code, uint64_t _codeSize, std::string const& _codeIdentifier);
+	ExecFunc compile(evm_revision _rev, bool _staticCall, bool _traceSteps, byte const* _code, uint64_t _codeSize, std::string const& _codeIdentifier);
 
 	evm_context_fn_table const* host = nullptr;
 
@@ -256,6 +258,7 @@ class SymbolResolver : public llvm::SectionMemoryManager
 			.Case("evm.get_tx_context", reinterpret_cast<uint64_t>(jit.host->get_tx_context))
 			.Case("evm.blockhash", reinterpret_cast<uint64_t>(jit.host->get_block_hash))
 			.Case("evm.log", reinterpret_cast<uint64_t>(jit.host->log))
+			.Case("evm.step", reinterpret_cast<uint64_t>(jit.host->step))
 			.Default(0);
 		if (addr)
 			return {addr, llvm::JITSymbolFlags::Exported};
@@ -319,7 +322,7 @@ void JITImpl::mapExecFunc(std::string const& _codeIdentifier, ExecFunc _funcAddr
     m_codeMap[_codeIdentifier].func = _funcAddr;
 }
 
-ExecFunc JITImpl::compile(evm_revision _rev, bool _staticCall, byte const* _code, uint64_t _codeSize,
+ExecFunc JITImpl::compile(evm_revision _rev, bool _staticCall, bool _traceSteps, byte const* _code, uint64_t _codeSize,
 	std::string const& _codeIdentifier)
 {
 	// reset engine.
@@ -336,7 +339,7 @@ ExecFunc JITImpl::compile(evm_revision _rev, bool _staticCall, byte const* _code
 		//listener->stateChanged(ExecState::Compilation);
 		assert(_code || !_codeSize);
 		//TODO: Can the Compiler be stateless?
-		module = Compiler({}, _rev, _staticCall, getLLVMContext()).compile(_code, _code + _codeSize, _codeIdentifier);
+		module = Compiler({}, _rev, _staticCall, _traceSteps, getLLVMContext()).compile(_code, _code + _codeSize, _codeIdentifier);
 
 		if (g_optimize)
 		{
@@ -462,7 +465,8 @@ static evm_result execute(evm_instance* instance, evm_context* context, evm_revi
             std::cerr << "EVMJIT Compile " << codeIdentifier << " (" << codeEntry.hits << ")\n";
 
         const bool staticCall = (msg->flags & EVM_STATIC) != 0;
-        func = jit.compile(rev, staticCall, ctx.code(), ctx.codeSize(), codeIdentifier);
+        const bool traceSteps = (msg->flags & EVM_TRACE) != 0;
+        func = jit.compile(rev, staticCall, traceSteps, ctx.code(), ctx.codeSize(), codeIdentifier);
         if (!func)
         {
             result.status_code = EVM_INTERNAL_ERROR;
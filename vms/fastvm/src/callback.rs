@@ -19,16 +19,17 @@
  *
  ******************************************************************************/
 
-use std::{mem, slice};
+use std::{cmp, mem, slice};
 use std::ops::Deref;
 use std::convert::Into;
 use std::clone::Clone;
 use libc;
 use basetypes::{EvmMessage, constants};
 use ffi::{EvmResult, EvmStatusCode};
+use context::execution_flags;
 use types::CallType;
 use types::traits::Ext;
-use aion_types::{U128, H256, U256, Address};
+use aion_types::{U128, H128, H256, U256, Address};
 
 #[derive(Debug)]
 #[repr(C)]
@@ -201,7 +202,7 @@ pub extern fn call(obj: *mut libc::c_void, info: *mut u8, msg: *const u8) -> *co
         _ => panic!("Call type does not exist"),
     };
 
-    let static_flag = evm_msg.flags == 1 || evm_msg.kind == 4;
+    let static_flag = (evm_msg.flags & execution_flags::STATIC) != 0 || evm_msg.kind == 4;
 
     // Address in different call types are handled in VM
     let sender_address: Address = U256::from(&evm_msg.caller).into();
@@ -272,6 +273,44 @@ pub extern fn get_tx_context(_obj: *mut libc::c_void, _result: *mut u8) {
     debug!(target: "vm", "I'm get_tx_context foo");
 }
 
+#[no_mangle]
+// 11 - step, only called for code compiled in trace mode
+pub extern fn step(
+    obj: *mut libc::c_void,
+    pc: i64,
+    opcode: i32,
+    gas: i64,
+    gas_cost: i64,
+    stack: *const EvmWord,
+    stack_size: usize,
+    memory: *const u8,
+    memory_size: usize,
+)
+{
+    let ext: &mut Box<Ext> = unsafe { mem::transmute(obj) };
+    let stack: Vec<H128> = match stack_size {
+        0 => Vec::new(),
+        _ => {
+            unsafe { slice::from_raw_parts(stack, stack_size) }
+                .iter()
+                .map(|word| (**word).into())
+                .collect()
+        }
+    };
+    let memory: &[u8] = match memory.is_null() || memory_size == 0 {
+        true => &[],
+        false => unsafe { slice::from_raw_parts(memory, memory_size) },
+    };
+    ext.trace_step(
+        pc as usize,
+        opcode as u8,
+        U256::from(cmp::max(gas, 0) as u64),
+        U256::from(cmp::max(gas_cost, 0) as u64),
+        &stack,
+        memory,
+    );
+}
+
 #[no_mangle]
 pub extern fn test_fn() {
     info!(target: "vm", "I'm the callback test foo");
@@ -316,6 +355,19 @@ extern {
     pub fn register_get_blockhash_fn(
         func: extern fn(obj: *mut libc::c_void, number: u64) -> HashValue,
     );
+    pub fn register_step_fn(
+        func: extern fn(
+            obj: *mut libc::c_void,
+            pc: i64,
+            opcode: i32,
+            gas: i64,
+            gas_cost: i64,
+            stack: *const EvmWord,
+            stack_size: usize,
+            memory: *const u8,
+            memory_size: usize,
+        ),
+    );
 }
 
 pub fn register_cbs() {
@@ -333,5 +385,6 @@ pub fn register_cbs() {
         register_get_tx_context_fn(get_tx_context);
         register_get_blockhash_fn(get_blockhash);
         register_log_fn(vm_log);
+        register_step_fn(step);
     };
 }
@@ -221,3 +221,9 @@ pub mod execution_kind {
     pub const DELEGATECALL: i32 = 1;
     pub const CALL: i32 = 0;
 }
+
+// execution flags, this definition from FVM
+pub mod execution_flags {
+    pub const STATIC: i32 = 1;
+    pub const TRACE: i32 = 2;
+}
@@ -27,7 +27,7 @@ use avm::AVM;
 use fastvm::ffi::EvmStatusCode;
 use fastvm::core::FastVM;
 use fastvm::basetypes::{constants::GAS_CODE_DEPOSIT, DataWord};
-use fastvm::context::{execution_kind, ExecutionContext, TransactionResult};
+use fastvm::context::{execution_flags, execution_kind, ExecutionContext, TransactionResult};
 use types::{ExecutionResult, ExecStatus, CallType, ReturnData, ActionParams, ActionValue};
 use types::traits::Ext;
 use types::avm::{TransactionContext as AVMTxContext, AvmStatusCode};
@@ -129,7 +129,10 @@ impl Factory for FastVMFactory {
             CallType::DelegateCall => execution_kind::DELEGATECALL,
             _ => execution_kind::CALL,
         };
-        let flags: i32 = params.static_flag.into();
+        let mut flags: i32 = params.static_flag.into();
+        if ext.is_step_tracing() {
+            flags |= execution_flags::TRACE;
+        }
 
         let mut ctx = ExecutionContext::new(
             tx_hash,
@@ -304,10 +307,17 @@ impl Factory for AVMFactory {
             ))
         }
 
+        let is_tracing = ext.is_tracing();
         let inst = &mut self.instance;
-        let ext_ptr: *mut ::libc::c_void = unsafe { ::std::mem::transmute(Box::new(ext)) };
-
-        let mut res = inst.execute(ext_ptr as i64, version, &avm_tx_contexts, is_local);
+        let ext_ptr: *mut ::libc::c_void = unsafe { ::std::mem::transmute(Box::new(&mut *ext)) };
+
+        let mut res = inst.execute(
+            ext_ptr as i64,
+            version,
+            &avm_tx_contexts,
+            is_local,
+            is_tracing,
+        );
 
         let mut exec_results = Vec::new();
 
@@ -324,6 +334,7 @@ impl Factory for AVMFactory {
                     U256::from(avm_tx_contexts[index].energy_limit - result.energy_used);
                 let return_data = result.return_data;
                 debug!(target: "vm", "tx: {:?}, avm status code = {:?}, gas left = {:?}", index, status_code, gas_left);
+                ext.trace_avm_internal_transactions(index as i32, &result.internal_transactions);
                 exec_results.push(ExecutionResult {
                     gas_left: gas_left.into(),
                     status_code: status_code.clone().into(),
@@ -39,3 +39,4 @@ pub use fastvm::vm::{self, Error};
 pub use fastvm::basetypes::constants;
 // export vm related types from vms module
 pub use types::*;
+pub use types::avm::InternalTransaction as AvmInternalTransaction;